@@ -156,6 +156,10 @@ pub fn get_host_and_port(args: Vec<String>) -> std::io::Result<(String, String)>
     Ok((host, port))
 }
 
+pub fn batch_uri(database: &str, schema: &str, name: &str) -> String {
+    format!("/{}/{}/{}/batch", database, schema, name)
+}
+
 pub fn ns_uri(database: &str, schema: &str, name: &str) -> String {
     format!("/{}/{}/{}", database, schema, name)
 }
@@ -0,0 +1,469 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// BackupManifest class
+////////////////////////////////////////////////////////////////////
+
+use crate::errors::throw;
+use crate::errors::Errors::Exact;
+use crate::machine::Machine;
+use crate::namespace_leases::NamespaceLease;
+use crate::namespaces::Namespace;
+use crate::page_cache::PageCache;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use shared_lib::cnv_error;
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// the name of the manifest file found within a backup directory
+pub const BACKUP_MANIFEST: &str = "manifest.json";
+
+/// the current backup format version
+pub const BACKUP_FORMAT_VERSION: u16 = 1;
+
+/// Represents a file captured by a backup
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BackupFile {
+    /// the path of the file; relative to both the backup directory and `$OXIDE_HOME`
+    pub path: String,
+    pub size: u64,
+    pub checksum: u32,
+}
+
+/// Represents the manifest of a backup: the namespaces of a database (`database`),
+/// schema (`database.schema`) or table (`database.schema.name`) and the checksums of their files.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub version: u16,
+    pub created_time: i64,
+    pub scope: String,
+    pub namespaces: Vec<String>,
+    pub files: Vec<BackupFile>,
+}
+
+impl BackupManifest {
+    ////////////////////////////////////////////////////////////////
+    // static methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Copies the table, BLOB, configuration and journal files of the given database,
+    /// schema or table to the given directory (replacing any previous backup within it).
+    /// The leases of the namespaces are held while the files are copied, which keeps other
+    /// processes from writing to them; and cached writes are flushed beforehand, and held off
+    /// while the files are copied. NOTE: writes by this process that bypass the page cache
+    /// (e.g. BLOBs, the replication log, change feeds and configurations) are not held off;
+    /// so the backup is only consistent if this process isn't writing to the namespaces.
+    pub fn backup(scope: &str, path: &str) -> std::io::Result<Self> {
+        let components = Self::parse_scope(scope)?;
+        let namespaces = Self::find_namespaces(&components)?;
+        if namespaces.is_empty() {
+            return throw(Exact(format!("Nothing to back up: {scope} does not exist")));
+        }
+        let mut acquired = Vec::new();
+        let result = Self::acquire_all(&namespaces, &mut acquired)
+            .and_then(|_| Self::copy_namespaces(&components, &namespaces, path));
+        for ns in acquired.iter() {
+            NamespaceLease::release(ns);
+        }
+        result
+    }
+
+    /// Loads the manifest of the backup found within the given directory
+    pub fn load(path: &str) -> std::io::Result<Self> {
+        let manifest_string = fs::read_to_string(Path::new(path).join(BACKUP_MANIFEST))?;
+        let manifest = serde_json::from_str::<Self>(&manifest_string).map_err(|e| cnv_error!(e))?;
+        if manifest.version > BACKUP_FORMAT_VERSION {
+            return throw(Exact(format!("{path} uses backup format version {}, but only versions up to {} are supported",
+                                       manifest.version, BACKUP_FORMAT_VERSION)));
+        }
+        Ok(manifest)
+    }
+
+    /// Restores the backup found within the given directory; optionally renaming the
+    /// database, schema or table backed up (e.g. "securities.nyse" to "archive.nyse_2024").
+    /// Each restored namespace replaces any existing namespace of the same name; which
+    /// fails if the namespace is leased by another process. NOTE: tables of the replaced
+    /// namespaces that are open (e.g. by this process) still refer to the replaced files,
+    /// and must be reopened.
+    pub fn restore(path: &str, target: Option<&str>) -> std::io::Result<Self> {
+        let manifest = Self::load(path)?;
+        manifest.verify(path)?;
+        let scope = Self::parse_scope(&manifest.scope)?;
+        let target = match target {
+            Some(target) => Self::parse_scope(target)?,
+            None => scope.clone()
+        };
+        if target.len() != scope.len() {
+            return throw(Exact(format!("Cannot restore {} as {}", manifest.scope, target.join("."))));
+        }
+        let home = Machine::oxide_home();
+        for ns_id in &manifest.namespaces {
+            let source = Namespace::parse(ns_id)?;
+            let dest = Self::rename(&source, &scope, &target);
+            let (source_path, dest_path) = (Self::get_relative_path(&source), Self::get_relative_path(&dest));
+            Self::recover_namespace(&format!("{home}/{dest_path}"))?;
+            let staging_path = format!("{home}/{dest_path}.restore");
+            if Path::new(&staging_path).exists() {
+                fs::remove_dir_all(&staging_path)?;
+            }
+            fs::create_dir_all(&staging_path)?;
+            for file in manifest.files.iter().filter(|f| f.path.starts_with(&format!("{source_path}/"))) {
+                let file_name = &file.path[source_path.len() + 1..];
+                let file_name = match file_name.strip_prefix(&format!("{}.", source.name)) {
+                    Some(extension) if !file_name.contains('/') => format!("{}.{extension}", dest.name),
+                    _ => file_name.to_string()
+                };
+                Self::copy_file(&format!("{path}/{}", file.path), Some(&format!("{staging_path}/{file_name}")))?;
+            }
+            let is_held = NamespaceLease::is_held(&dest);
+            NamespaceLease::acquire(&dest)?;
+            let result = Self::replace_namespace(&home, &dest_path, &staging_path);
+            if !is_held {
+                NamespaceLease::release(&dest);
+            }
+            result?;
+        }
+        Ok(manifest)
+    }
+
+    /// Acquires the leases of the given namespaces; recording those not already held by this process
+    fn acquire_all(namespaces: &[Namespace], acquired: &mut Vec<Namespace>) -> std::io::Result<()> {
+        for ns in namespaces {
+            if !NamespaceLease::is_held(ns) {
+                NamespaceLease::acquire(ns)?;
+                acquired.push(ns.to_owned());
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies a file (or merely reads it if no destination is given); returning its size and checksum
+    fn copy_file(source: &str, dest: Option<&str>) -> std::io::Result<(u64, u32)> {
+        let mut reader = File::open(source)?;
+        let mut writer = match dest {
+            Some(dest) => {
+                if let Some(parent) = Path::new(dest).parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                Some(File::create(dest)?)
+            }
+            None => None
+        };
+        let mut hasher = crc32fast::Hasher::new();
+        let mut buffer = vec![0u8; 65536];
+        let mut size = 0;
+        loop {
+            let n = reader.read(&mut buffer)?;
+            if n == 0 { break; }
+            hasher.update(&buffer[..n]);
+            if let Some(writer) = writer.as_mut() {
+                writer.write_all(&buffer[..n])?;
+            }
+            size += n as u64;
+        }
+        if let Some(writer) = writer {
+            writer.sync_all()?;
+        }
+        Ok((size, hasher.finalize()))
+    }
+
+    /// Copies the files of the given namespaces to the given directory; writing the backup's manifest
+    fn copy_namespaces(
+        components: &[String],
+        namespaces: &[Namespace],
+        path: &str,
+    ) -> std::io::Result<Self> {
+        let home = Machine::oxide_home();
+        let files = PageCache::shared().snapshot(|| {
+            let mut files = Vec::new();
+            for ns in namespaces {
+                for file_path in Self::list_files(&home, &Self::get_relative_path(ns))? {
+                    let (size, checksum) = Self::copy_file(
+                        &format!("{home}/{file_path}"), Some(&format!("{path}/{file_path}")))?;
+                    files.push(BackupFile { path: file_path, size, checksum });
+                }
+            }
+            Ok(files)
+        })?;
+        let manifest = Self {
+            version: BACKUP_FORMAT_VERSION,
+            created_time: Local::now().timestamp_millis(),
+            scope: components.join("."),
+            namespaces: namespaces.iter().map(|ns| ns.id()).collect(),
+            files,
+        };
+        fs::write(Path::new(path).join(BACKUP_MANIFEST), serde_json::to_string_pretty(&manifest)?)?;
+        Ok(manifest)
+    }
+
+    /// Returns the namespaces within the given database, schema or table;
+    /// a journaled table's events (e.g. "stocks_events") are included with it.
+    fn find_namespaces(scope: &Vec<String>) -> std::io::Result<Vec<Namespace>> {
+        let root = format!("{}/ns/{}", Machine::oxide_home(), scope[0]);
+        let schemas = match scope.get(1) {
+            Some(schema) => vec![schema.to_owned()],
+            None => Self::list_directories(&root)?
+        };
+        let mut namespaces = Vec::new();
+        for schema in schemas {
+            for name in Self::list_directories(&format!("{root}/{schema}"))? {
+                // skip the directories left behind by an interrupted restore (e.g. "stocks.old")
+                if name.contains('.') { continue; }
+                let is_included = match scope.get(2) {
+                    Some(table) => name == *table || name == format!("{table}_events"),
+                    None => true
+                };
+                if is_included {
+                    namespaces.push(Namespace::new(scope[0].as_str(), schema.as_str(), name.as_str()));
+                }
+            }
+        }
+        Ok(namespaces)
+    }
+
+    /// Returns the path of a namespace's directory relative to `$OXIDE_HOME`
+    fn get_relative_path(ns: &Namespace) -> String {
+        format!("ns/{}/{}/{}", ns.database, ns.schema, ns.name)
+    }
+
+    /// Returns the (sorted) names of the subdirectories of the given directory
+    fn list_directories(path: &str) -> std::io::Result<Vec<String>> {
+        if !Path::new(path).is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut names = Vec::new();
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                names.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Returns the (sorted) paths of the files within the given directory (and its subdirectories);
+    /// excluding lock files (which namespaces created by earlier versions contain), as they
+    /// belong to the process holding the lease of a namespace.
+    fn list_files(home: &str, relative_path: &str) -> std::io::Result<Vec<String>> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(format!("{home}/{relative_path}"))? {
+            let entry = entry?;
+            let path = format!("{relative_path}/{}", entry.file_name().to_string_lossy());
+            if entry.file_type()?.is_dir() {
+                paths.extend(Self::list_files(home, &path)?);
+            } else if !path.ends_with(".lock") {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Parses a database, schema (`database.schema`) or table (`database.schema.name`)
+    fn parse_scope(scope: &str) -> std::io::Result<Vec<String>> {
+        let components = scope.split('.').map(|s| s.trim().to_string()).collect::<Vec<_>>();
+        if components.len() > 3 || components.iter().any(|s| s.is_empty() || s.contains('/')) {
+            return throw(Exact(format!("'{scope}' is not a valid database, schema or namespace")));
+        }
+        Ok(components)
+    }
+
+    /// Recovers the namespace at the given path from an interrupted restore: the replaced
+    /// namespace (`path.old`) is moved back if the restored one never took its place;
+    /// otherwise it's removed.
+    fn recover_namespace(path: &str) -> std::io::Result<()> {
+        let old_path = format!("{path}.old");
+        if Path::new(&old_path).exists() {
+            if Path::new(path).exists() {
+                fs::remove_dir_all(&old_path)?;
+            } else {
+                fs::rename(&old_path, path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Translates a namespace from the scope of a backup to the scope it's restored into
+    fn rename(ns: &Namespace, scope: &Vec<String>, target: &Vec<String>) -> Namespace {
+        let schema = target.get(1).unwrap_or(&ns.schema);
+        let name = match (scope.get(2), target.get(2)) {
+            (Some(old_name), Some(new_name)) => format!("{new_name}{}", &ns.name[old_name.len()..]),
+            _ => ns.name.to_owned()
+        };
+        Namespace::new(target[0].as_str(), schema.as_str(), name.as_str())
+    }
+
+    /// Replaces the namespace at the given path (relative to `$OXIDE_HOME`) with a staged copy:
+    /// the existing namespace is moved aside, the staged copy is moved into place, and only
+    /// then is the existing namespace removed; so that a crash never loses both copies.
+    /// The cached pages of the replaced files are discarded.
+    fn replace_namespace(home: &str, relative_path: &str, staging_path: &str) -> std::io::Result<()> {
+        let path = format!("{home}/{relative_path}");
+        let old_path = format!("{path}.old");
+        let mut file_ids = Vec::new();
+        if Path::new(&path).exists() {
+            for file_path in Self::list_files(home, relative_path)? {
+                file_ids.push(PageCache::get_file_id(&File::open(format!("{home}/{file_path}"))?)?);
+            }
+            fs::rename(&path, &old_path)?;
+        }
+        fs::rename(staging_path, &path)?;
+        for id in file_ids {
+            PageCache::shared().invalidate(id)?;
+        }
+        if Path::new(&old_path).exists() {
+            fs::remove_dir_all(&old_path)?;
+        }
+        Ok(())
+    }
+
+    ////////////////////////////////////////////////////////////////
+    // instance methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Ensures the files of the backup within the given directory match this manifest
+    pub fn verify(&self, path: &str) -> std::io::Result<()> {
+        for file in &self.files {
+            let (size, checksum) = Self::copy_file(&format!("{path}/{}", file.path), None)?;
+            if size != file.size || checksum != file.checksum {
+                return throw(Exact(format!("{path}/{} does not match the backup manifest", file.path)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_row_collection::FileRowCollection;
+    use crate::row_collection::RowCollection;
+    use crate::testdata::{make_quote, make_quote_parameters};
+    use std::fs::OpenOptions;
+    use std::os::unix::io::AsRawFd;
+
+    #[test]
+    fn test_backup_and_restore() {
+        let path = format!("{}/backups/backup_restore", Machine::oxide_home());
+        let _ = fs::remove_dir_all(&path);
+        let ns = Namespace::parse("backups.restore.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_rows(vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "VERY_LONG_SYMBOL", "NYSE", 23.66),
+        ]);
+
+        // back up the schema (including the unflushed writes)
+        let manifest = BackupManifest::backup("backups.restore", &path).unwrap();
+        assert_eq!(manifest.namespaces, vec!["backups.restore.stocks"]);
+        assert_eq!(manifest.files.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), vec![
+            "ns/backups/restore/stocks/stocks.json",
+            "ns/backups/restore/stocks/stocks.table",
+            "ns/backups/restore/stocks/stocks.table.blob",
+        ]);
+        assert_eq!(BackupManifest::load(&path).unwrap(), manifest);
+
+        // damage the table, then restore it
+        frc.delete_row(0);
+        frc.flush().unwrap();
+        drop(frc);
+        BackupManifest::restore(&path, None).unwrap();
+        assert!(NamespaceLease::is_held(&ns));
+        let frc = FileRowCollection::open(&ns).unwrap();
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "VERY_LONG_SYMBOL", "NYSE", 23.66),
+        ]);
+
+        // restore the schema under a different name
+        BackupManifest::restore(&path, Some("backups.restored")).unwrap();
+        let frc = FileRowCollection::open(&Namespace::parse("backups.restored.stocks").unwrap()).unwrap();
+        assert_eq!(frc.read_one(1).unwrap(), Some(make_quote(1, "VERY_LONG_SYMBOL", "NYSE", 23.66)));
+        assert!(BackupManifest::restore(&path, Some("backups.restored.stocks")).is_err());
+    }
+
+    #[test]
+    fn test_restore_renamed_table() {
+        let path = format!("{}/backups/restore_renamed", Machine::oxide_home());
+        let _ = fs::remove_dir_all(&path);
+        let ns = Namespace::parse("backups.rename.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "BIZ", "NYSE", 9.775));
+        BackupManifest::backup(&ns.id(), &path).unwrap();
+
+        // the table's files are renamed along with it
+        BackupManifest::restore(&path, Some("backups.rename.quotes")).unwrap();
+        let ns = Namespace::parse("backups.rename.quotes").unwrap();
+        assert!(fs::exists(ns.get_table_file_path()).unwrap());
+        let frc = FileRowCollection::open(&ns).unwrap();
+        assert_eq!(frc.read_one(0).unwrap(), Some(make_quote(0, "BIZ", "NYSE", 9.775)));
+    }
+
+    #[test]
+    fn test_restore_damaged_backup() {
+        let path = format!("{}/backups/restore_damaged", Machine::oxide_home());
+        let _ = fs::remove_dir_all(&path);
+        let ns = Namespace::parse("backups.damaged.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "JET", "NASDAQ", 32.12));
+        let manifest = BackupManifest::backup(&ns.id(), &path).unwrap();
+
+        // a backup whose files do not match its manifest is not restored
+        let table_path = format!("{path}/{}", manifest.files[1].path);
+        let mut bytes = fs::read(&table_path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xFF;
+        fs::write(&table_path, bytes).unwrap();
+        assert!(BackupManifest::restore(&path, None).is_err());
+        assert!(BackupManifest::backup("backups.missing", &format!("{path}_missing")).is_err());
+    }
+
+    #[test]
+    fn test_backup_while_leased_elsewhere() {
+        let path = format!("{}/backups/backup_leased", Machine::oxide_home());
+        let _ = fs::remove_dir_all(&path);
+        let ns = Namespace::parse("backups.leased.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "GOTO", "OTC", 0.1442));
+        drop(frc);
+
+        // another process holds the lease (i.e. via a separate file description)
+        NamespaceLease::release(&ns);
+        let other = OpenOptions::new().create(true).truncate(false).read(true).write(true)
+            .open(NamespaceLease::get_lock_path(&ns)).unwrap();
+        assert_eq!(unsafe { libc::flock(other.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) }, 0);
+        assert!(BackupManifest::backup(&ns.id(), &path).is_err());
+
+        // once the other process lets go, the lease is only held for the backup
+        drop(other);
+        BackupManifest::backup(&ns.id(), &path).unwrap();
+        assert!(!NamespaceLease::is_held(&ns));
+    }
+
+    #[test]
+    fn test_restore_after_interrupted_restore() {
+        let path = format!("{}/backups/restore_interrupted", Machine::oxide_home());
+        let _ = fs::remove_dir_all(&path);
+        let ns = Namespace::parse("backups.interrupted.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_row(make_quote(0, "KNOW", "NYSE", 52.77));
+        BackupManifest::backup(&ns.id(), &path).unwrap();
+        drop(frc);
+
+        // a restore was interrupted after the existing namespace was moved aside
+        let root_path = ns.get_root_path();
+        let root_path = root_path.trim_end_matches('/');
+        fs::rename(root_path, format!("{root_path}.old")).unwrap();
+        assert_eq!(BackupManifest::find_namespaces(&vec!["backups".into(), "interrupted".into()]).unwrap(), vec![]);
+
+        // the next restore recovers the namespace, then replaces it
+        BackupManifest::restore(&path, None).unwrap();
+        assert!(!fs::exists(format!("{root_path}.old")).unwrap());
+        let frc = FileRowCollection::open(&ns).unwrap();
+        assert_eq!(frc.read_one(0).unwrap(), Some(make_quote(0, "KNOW", "NYSE", 52.77)));
+    }
+}
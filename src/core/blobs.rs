@@ -3,25 +3,45 @@
 // BLOBStore class
 ////////////////////////////////////////////////////////////////////
 
+use crate::checksums::{append_checksum, is_intact, CHECKSUM_LEN};
 use crate::columns::Column;
+use crate::encryption::EncryptionKey;
+use crate::errors::throw;
+use crate::errors::Errors::Exact;
 use crate::field;
 use crate::namespaces::Namespace;
+use crate::page_cache::{FileId, PageCache};
 use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{Null, Undefined};
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use shared_lib::fail;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
 use std::fs::{File, OpenOptions};
-use std::os::unix::fs::FileExt;
-use std::sync::Arc;
+use std::os::unix::fs::{FileExt, MetadataExt};
+use std::sync::{Arc, Mutex, OnceLock};
 
 /// BLOB Store
 #[derive(Clone)]
 pub struct BLOBStore {
+    encryption: Option<EncryptionKey>,
     file: Arc<File>,
+    free_space: SharedFreeSpaceMap,
+    is_checksummed: bool,
     path: String,
 }
 
+/// Maps the offsets of the free (reusable) allocations to their sizes
+type FreeSpaceMap = BTreeMap<u64, u64>;
+
+/// A free space map shared by all handles of a BLOB file (loaded on first use)
+type SharedFreeSpaceMap = Arc<Mutex<Option<FreeSpaceMap>>>;
+
+/// the free space maps of the BLOB files opened by this process; all handles of a file
+/// share its map, so that they never hand out the same allocation (or rescan the file).
+static FREE_SPACE_MAPS: OnceLock<Mutex<HashMap<FileId, (String, SharedFreeSpaceMap)>>> = OnceLock::new();
+
 impl BLOBStore {
 
     ////////////////////////////////////////////////////////////////
@@ -46,29 +66,79 @@ impl BLOBStore {
             OpenOptions::new().read(true).write(true).create(true).open(path)?
         } else { OpenOptions::new().read(true).write(true).open(path)? };
         Ok(Self {
+            encryption: None,
+            free_space: Self::get_shared_free_space(&file, path)?,
             file: Arc::new(file),
+            is_checksummed: true,
             path: path.to_string(),
         })
     }
 
+    /// Returns the free space map shared by all handles of the given file
+    fn get_shared_free_space(file: &File, path: &str) -> std::io::Result<SharedFreeSpaceMap> {
+        let id = PageCache::get_file_id(file)?;
+        let mut maps = match FREE_SPACE_MAPS.get_or_init(|| Mutex::new(HashMap::new())).lock() {
+            Ok(maps) => maps,
+            Err(err) => return throw(Exact(err.to_string()))
+        };
+        if let Some((_, map)) = maps.get(&id) {
+            return Ok(map.clone());
+        }
+        // forget the unused maps of files that have since been removed or replaced
+        maps.retain(|id, (path, map)| Arc::strong_count(map) > 1 ||
+            matches!(fs::metadata(path.as_str()), Ok(md) if (md.dev(), md.ino()) == *id));
+        let map = SharedFreeSpaceMap::default();
+        maps.insert(id, (path.to_string(), map.clone()));
+        Ok(map)
+    }
+
+    /// Determines whether the BLOBs within this store end with a checksum of their (sealed) bytes;
+    /// they do, unless the store belongs to a table written by an earlier version (see [crate::table_header]).
+    pub fn with_checksums(self, is_checksummed: bool) -> Self {
+        Self { is_checksummed, ..self }
+    }
+
+    /// Enables encryption at rest for the BLOBs within this store
+    pub fn with_encryption(self, key: EncryptionKey) -> Self {
+        Self {
+            encryption: Some(key),
+            ..self
+        }
+    }
+
     ////////////////////////////////////////////////////////////////
     // instance methods
     ////////////////////////////////////////////////////////////////
 
-    /// Encodes a binary field
+    /// Encodes a binary field; values too large for the field, and all values of
+    /// variable-length columns are stored as BLOBs; `previous` is the offset of the
+    /// BLOB currently referenced by the cell (if any), which is reused or released as needed.
+    /// `aad` identifies the owning cell, and is authenticated along with encrypted BLOBs.
     pub fn encode_field(
         &self,
         column: &Column,
         value: &TypedValue,
+        previous: Option<u64>,
+        aad: &[u8],
     ) -> std::io::Result<Vec<u8>> {
         let (data_type, fixed_size) = (column.get_data_type(), column.get_fixed_size());
+        let is_variable_length = data_type.is_variable_length();
         let buffer = data_type.encode(value)?;
         let mut encoded = Vec::with_capacity(fixed_size);
-        if buffer.len() <= fixed_size {
+        if is_variable_length && matches!(value, Null | Undefined) {
+            // a null variable-length value is an inactive field without a BLOB
+            if let Some(offset) = previous { self.free(offset)?; }
+            encoded.push(0u8);
+        } else if !is_variable_length && buffer.len() < fixed_size {
+            // the field metadata occupies the first byte of the fixed size
+            if let Some(offset) = previous { self.free(offset)?; }
             encoded.push(field::ACTIVE_MASK);
             encoded.extend(buffer);
         } else {
-            let key = self.insert(value)?;
+            let key = match previous {
+                Some(offset) => self.update(offset, value, aad)?,
+                None => self.insert(value, aad)?
+            };
             encoded.push(field::ACTIVE_MASK | field::EXTERNAL_MASK);
             encoded.extend(key.offset.to_be_bytes());
         }
@@ -76,41 +146,196 @@ impl BLOBStore {
         Ok(encoded)
     }
 
-    pub fn insert<T>(&self, item: T) -> std::io::Result<BLOBCellMetadata>
+    pub fn insert<T>(&self, item: T, aad: &[u8]) -> std::io::Result<BLOBCellMetadata>
     where
         T: serde::ser::Serialize,
     {
         match bincode::serialize(&item) {
-            Ok(bytes) => self.insert_blob(bytes),
+            Ok(bytes) => self.insert_blob(bytes, aad),
             Err(err) => fail(err.to_string())
         }
     }
 
-    pub fn insert_blob(&self, bytes: Vec<u8>) -> std::io::Result<BLOBCellMetadata> {
-        let limit = self.file.metadata()?.len();
-        let offset = limit;
-        let header = BLOBCellMetadata {
-            offset,
-            allocated: Self::compute_allocated_size(bytes.len()),
-            used: (HEADER_LEN + bytes.len()) as u64,
+    pub fn insert_blob(&self, bytes: Vec<u8>, aad: &[u8]) -> std::io::Result<BLOBCellMetadata> {
+        let bytes = self.seal(bytes, aad)?;
+        self.allocate(self.frame(bytes))
+    }
+
+    /// Copies the (sealed) BLOB at the offset into another store
+    pub fn copy_blob(&self, offset: u64, target: &BLOBStore) -> std::io::Result<BLOBCellMetadata> {
+        let (_, bytes) = self.read_sealed_blob(offset)?;
+        target.allocate(target.frame(bytes))
+    }
+
+    /// Releases the allocation at the offset, so that it can be reused;
+    /// adjacent free allocations are merged, and free space at the end
+    /// of the file is truncated.
+    pub fn free(&self, offset: u64) -> std::io::Result<()> {
+        self.with_free_space(|map| {
+            let header = self.read_header(offset)?;
+            if header.is_free() { return Ok(()); }
+            let (mut offset, mut allocated) = (header.offset, header.allocated);
+            if let Some(next_allocated) = map.remove(&(offset + allocated)) {
+                if self.is_free_allocation(offset + allocated, next_allocated)? {
+                    allocated += next_allocated;
+                }
+            }
+            if let Some((&prev_offset, &prev_allocated)) = map.range(..offset).next_back() {
+                if prev_offset + prev_allocated == offset && self.is_free_allocation(prev_offset, prev_allocated)? {
+                    map.remove(&prev_offset);
+                    offset = prev_offset;
+                    allocated += prev_allocated;
+                }
+            }
+            if offset + allocated >= self.file.metadata()?.len() {
+                self.file.set_len(offset)
+            } else {
+                map.insert(offset, allocated);
+                self.write_header(&BLOBCellMetadata::new(offset, allocated, 0))
+            }
+        })
+    }
+
+    /// Returns the free (reusable) allocations as a list of (offset, size) tuples
+    pub fn get_free_space(&self) -> std::io::Result<Vec<(u64, u64)>> {
+        self.with_free_space(|map| Ok(map.iter().map(|(a, b)| (*a, *b)).collect()))
+    }
+
+    /// Returns the size (in bytes) of the underlying file
+    pub fn len(&self) -> std::io::Result<u64> {
+        Ok(self.file.metadata()?.len())
+    }
+
+    /// Discards the free space map (e.g. after the file has been replaced by
+    /// a vacuumed copy), so that the file is rescanned upon next use.
+    pub fn invalidate(&self) -> std::io::Result<()> {
+        match self.free_space.lock() {
+            Ok(mut guard) => {
+                *guard = None;
+                Ok(())
+            }
+            Err(err) => throw(Exact(err.to_string()))
+        }
+    }
+
+    /// Flushes the contents of the underlying file to disk
+    pub fn sync(&self) -> std::io::Result<()> {
+        self.file.sync_all()
+    }
+
+    /// Truncates the store; e.g. prior to being used as a vacuum target
+    pub fn truncate(&self) -> std::io::Result<()> {
+        self.with_free_space(|map| {
+            map.clear();
+            self.file.set_len(0)
+        })
+    }
+
+    /// Appends the checksum of the given (sealed) bytes to them; if enabled
+    fn frame(&self, mut bytes: Vec<u8>) -> Vec<u8> {
+        if self.is_checksummed { append_checksum(&mut bytes); }
+        bytes
+    }
+
+    /// Writes the (sealed and framed) bytes into a free allocation that can contain
+    /// them (using the best fit) or at the end of the file.
+    fn allocate(&self, bytes: Vec<u8>) -> std::io::Result<BLOBCellMetadata> {
+        let used = (HEADER_LEN + bytes.len()) as u64;
+        let allocated = Self::compute_allocated_size(bytes.len());
+        let header = self.with_free_space(|map| {
+            // find the smallest free allocation that fits; a map that disagrees
+            // with the file (e.g. it was modified by another process) is rescanned.
+            let find_best_fit = |map: &FreeSpaceMap| map.iter()
+                .filter(|(_, size)| **size >= used)
+                .min_by_key(|(_, size)| **size)
+                .map(|(offset, size)| (*offset, *size));
+            let mut best_fit = find_best_fit(map);
+            if let Some((offset, size)) = best_fit {
+                if !self.is_free_allocation(offset, size)? {
+                    *map = self.scan_free_space()?;
+                    best_fit = find_best_fit(map);
+                }
+            }
+            match best_fit {
+                Some((offset, size)) => {
+                    map.remove(&offset);
+                    // split the allocation if the remainder is worth keeping
+                    if size >= allocated + MIN_SPLIT_LEN {
+                        let remainder = BLOBCellMetadata::new(offset + allocated, size - allocated, 0);
+                        self.write_header(&remainder)?;
+                        map.insert(remainder.offset, remainder.allocated);
+                        Ok(BLOBCellMetadata::new(offset, allocated, used))
+                    } else {
+                        Ok(BLOBCellMetadata::new(offset, size, used))
+                    }
+                }
+                None => {
+                    let offset = self.file.metadata()?.len();
+                    self.file.set_len(offset + allocated)?;
+                    Ok(BLOBCellMetadata::new(offset, allocated, used))
+                }
+            }
+        })?;
+        self.write_header(&header)?;
+        let _ = self.file.write_at(&bytes, header.offset + HEADER_LEN as u64)?;
+        Ok(header)
+    }
+
+    /// Indicates whether the file contains a free allocation of the given size at the offset
+    fn is_free_allocation(&self, offset: u64, allocated: u64) -> std::io::Result<bool> {
+        if offset + HEADER_LEN as u64 > self.len()? { return Ok(false); }
+        let header = self.read_header(offset)?;
+        Ok(header.offset == offset && header.is_free() && header.allocated == allocated)
+    }
+
+    /// Scans the allocations of the store for free ones
+    fn scan_free_space(&self) -> std::io::Result<FreeSpaceMap> {
+        let (mut map, limit, mut offset) = (FreeSpaceMap::new(), self.file.metadata()?.len(), 0u64);
+        while offset + HEADER_LEN as u64 <= limit {
+            let header = self.read_header(offset)?;
+            if header.offset != offset || header.allocated < HEADER_LEN as u64 {
+                return throw(Exact(format!("{}: corrupted BLOB header at offset {offset}", self.path)));
+            }
+            if header.is_free() { map.insert(header.offset, header.allocated); }
+            offset += header.allocated;
+        }
+        Ok(map)
+    }
+
+    /// Executes the function against the free space map (loaded on first use)
+    fn with_free_space<F, A>(&self, f: F) -> std::io::Result<A>
+    where
+        F: FnOnce(&mut FreeSpaceMap) -> std::io::Result<A>,
+    {
+        let mut guard = match self.free_space.lock() {
+            Ok(guard) => guard,
+            Err(err) => return throw(Exact(err.to_string()))
         };
-        match bincode::serialize(&header) {
+        if guard.is_none() {
+            *guard = Some(self.scan_free_space()?);
+        }
+        match guard.as_mut() {
+            Some(map) => f(map),
+            None => throw(Exact("Free space map is not available".into()))
+        }
+    }
+
+    fn write_header(&self, header: &BLOBCellMetadata) -> std::io::Result<()> {
+        match bincode::serialize(header) {
             Ok(header_bytes) => {
-                self.file.set_len(limit + header.allocated)?;
                 let _ = self.file.write_at(&header_bytes, header.offset)?;
-                let _ = self.file.write_at(&bytes, header.offset + header_bytes.len() as u64)?;
-                Ok(header)
+                Ok(())
             }
             Err(err) => fail(err.to_string())
         }
     }
 
     /// Reads an object of type [T] from the blob store
-    pub fn read<T>(&self, offset: u64) -> std::io::Result<(BLOBCellMetadata, T)>
+    pub fn read<T>(&self, offset: u64, aad: &[u8]) -> std::io::Result<(BLOBCellMetadata, T)>
     where
         T: serde::de::DeserializeOwned,
     {
-        let (header, bytes) = self.read_blob(offset)?;
+        let (header, bytes) = self.read_blob(offset, aad)?;
         match bincode::deserialize(&bytes) {
             Ok(item) => Ok((header, item)),
             Err(err) => fail(err.to_string())
@@ -118,13 +343,30 @@ impl BLOBStore {
     }
 
     /// Reads a raw blob of data from the blob store
-    pub fn read_blob(&self, offset: u64) -> std::io::Result<(BLOBCellMetadata, Vec<u8>)> {
+    pub fn read_blob(&self, offset: u64, aad: &[u8]) -> std::io::Result<(BLOBCellMetadata, Vec<u8>)> {
+        let (header, buffer) = self.read_sealed_blob(offset)?;
+        Ok((header, self.unseal(buffer, aad)?))
+    }
+
+    /// Reads a raw blob of (possibly encrypted) data from the blob store
+    fn read_sealed_blob(&self, offset: u64) -> std::io::Result<(BLOBCellMetadata, Vec<u8>)> {
         // first, read the header
         let header = self.read_header(offset)?;
+        if header.is_free() {
+            return throw(Exact(format!("{}: no BLOB found at offset {offset}", self.path)));
+        }
 
         // next, read the byes indicated within the header
-        let mut buffer: Vec<u8> = vec![0u8; header.used as usize];
+        let mut buffer: Vec<u8> = vec![0u8; header.used.saturating_sub(HEADER_LEN as u64) as usize];
         let _ = self.file.read_at(&mut buffer, offset + HEADER_LEN as u64)?;
+
+        // finally, verify and remove the checksum
+        if self.is_checksummed {
+            if !is_intact(&buffer) {
+                return throw(Exact(format!("{}: checksum mismatch in the BLOB at offset {offset}", self.path)));
+            }
+            buffer.truncate(buffer.len() - CHECKSUM_LEN);
+        }
         Ok((header, buffer))
     }
 
@@ -142,44 +384,80 @@ impl BLOBStore {
         &self,
         offset: u64,
         item: T,
+        aad: &[u8],
     ) -> std::io::Result<BLOBCellMetadata>
     where
         T: serde::ser::Serialize,
     {
         match bincode::serialize(&item) {
-            Ok(bytes) => self.update_blob(offset, bytes),
+            Ok(bytes) => self.update_blob(offset, bytes, aad),
             Err(err) => fail(err.to_string())
         }
     }
 
+    /// Updates the BLOB at the offset in place when the new bytes fit within
+    /// its allocation; otherwise the allocation is released and the bytes are
+    /// written elsewhere. The returned header indicates the (new) location.
     pub fn update_blob(
         &self,
         offset: u64,
         bytes: Vec<u8>,
+        aad: &[u8],
     ) -> std::io::Result<BLOBCellMetadata> {
         // read the header
         let header = self.read_header(offset)?;
+        let bytes = self.frame(self.seal(bytes, aad)?);
+        let used = (HEADER_LEN + bytes.len()) as u64;
+        if header.is_free() || used > header.allocated {
+            self.free(offset)?;
+            return self.allocate(bytes);
+        }
 
-        // create a new header with the new amount used
-        let mut new_header = header.clone();
-        new_header.used = bytes.len() as u64;
-        assert!(new_header.used <= new_header.allocated);
+        // update the header with the new amount used, then the data
+        let new_header = BLOBCellMetadata::new(header.offset, header.allocated, used);
+        self.write_header(&new_header)?;
+        let _ = self.file.write_at(&bytes, header.offset + HEADER_LEN as u64)?;
+        Ok(new_header)
+    }
 
-        // serialize the new header
-        match bincode::serialize(&new_header) {
-            Ok(header_bytes) => {
-                // update the data
-                let _ = self.file.write_at(&header_bytes, header.offset)?;
-                let _ = self.file.write_at(&bytes, header.offset + header_bytes.len() as u64)?;
-                Ok(header)
+    /// Encrypts the bytes of a BLOB (if encryption is enabled); the sealed
+    /// bytes are prefixed by their length (u64) as the allocation may contain slack
+    fn seal(&self, bytes: Vec<u8>, aad: &[u8]) -> std::io::Result<Vec<u8>> {
+        match &self.encryption {
+            Some(key) => {
+                let sealed = key.encrypt(&bytes, aad)?;
+                let mut framed = Vec::with_capacity(8 + sealed.len());
+                framed.extend((sealed.len() as u64).to_be_bytes());
+                framed.extend(sealed);
+                Ok(framed)
             }
-            Err(err) => fail(err.to_string())
+            None => Ok(bytes)
+        }
+    }
+
+    /// Decrypts the bytes of a BLOB (if encryption is enabled)
+    fn unseal(&self, bytes: Vec<u8>, aad: &[u8]) -> std::io::Result<Vec<u8>> {
+        match &self.encryption {
+            Some(key) if bytes.len() >= 8 => {
+                let mut len_bytes = [0u8; 8];
+                len_bytes.copy_from_slice(&bytes[0..8]);
+                let len = u64::from_be_bytes(len_bytes) as usize;
+                if 8 + len > bytes.len() {
+                    return throw(Exact("Encrypted BLOB is truncated".into()));
+                }
+                key.decrypt(&bytes[8..(8 + len)], aad)
+            }
+            Some(_) => throw(Exact("Encrypted BLOB is truncated".into())),
+            None => Ok(bytes)
         }
     }
 }
 
 pub const HEADER_LEN: usize = 24;
 
+/// The smallest remainder for which a reused allocation is split
+const MIN_SPLIT_LEN: u64 = 2 * HEADER_LEN as u64;
+
 /// BLOB Store: Cell Metadata
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct BLOBCellMetadata {
@@ -200,16 +478,25 @@ impl BLOBCellMetadata {
             used,
         }
     }
+
+    /// Indicates whether the allocation has been released
+    pub fn is_free(&self) -> bool {
+        self.used == 0
+    }
 }
 
 
 /// Unit tests
 #[cfg(test)]
 mod tests {
-    use crate::blobs::BLOBStore;
+    use crate::blobs::{BLOBStore, HEADER_LEN};
+    use crate::checksums::CHECKSUM_LEN;
+    use crate::columns::Column;
+    use crate::data_types::DataType::StringType;
+    use crate::field::FieldMetadata;
     use crate::namespaces::Namespace;
     use crate::typed_values::TypedValue;
-    use crate::typed_values::TypedValue::StringValue;
+    use crate::typed_values::TypedValue::{Null, StringValue};
 
     #[test]
     fn test_crud() {
@@ -218,23 +505,136 @@ mod tests {
         let bs = BLOBStore::open(&ns).unwrap();
 
         // insert a new blob
-        let key = bs.insert(StringValue("Hello World".into())).unwrap();
+        let key = bs.insert(StringValue("Hello World".into()), &[]).unwrap();
         println!("key0: {:?}", key);
 
         // read back the value by the key
-        let (key1, value) = bs.read::<TypedValue>(key.offset).unwrap();
+        let (key1, value) = bs.read::<TypedValue>(key.offset, &[]).unwrap();
         println!("key1: {:?}", key1);
         println!("value1: {:?}", value);
         assert_eq!(value, StringValue("Hello World".into()));
 
         // next, update the value
-        let key2 = bs.update(key1.offset, StringValue("Goodbye World".into())).unwrap();
+        let key2 = bs.update(key1.offset, StringValue("Goodbye World".into()), &[]).unwrap();
         println!("key2: {:?}", key2);
 
         // read back the value by the key
-        let (key3, value) = bs.read::<TypedValue>(key2.offset).unwrap();
+        let (key3, value) = bs.read::<TypedValue>(key2.offset, &[]).unwrap();
         println!("key3: {:?}", key3);
         println!("value3: {:?}", value);
         assert_eq!(value, StringValue("Goodbye World".into()));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_update_in_place_or_relocate() {
+        let bs = BLOBStore::open(&Namespace::new("blobs", "update", "data")).unwrap();
+        bs.truncate().unwrap();
+
+        // a value that fits within the allocation is updated in place
+        let key0 = bs.insert(StringValue("Hello World".into()), &[]).unwrap();
+        let key1 = bs.update(key0.offset, StringValue("Hello".into()), &[]).unwrap();
+        assert_eq!(key1.offset, key0.offset);
+        assert_eq!(bs.read::<TypedValue>(key1.offset, &[]).unwrap().1, StringValue("Hello".into()));
+
+        // a value that doesn't fit is relocated, and its allocation is released
+        let key2 = bs.insert(StringValue("Guard".into()), &[]).unwrap();
+        let key3 = bs.update(key1.offset, StringValue("Hello World! Hello World!".into()), &[]).unwrap();
+        assert_eq!(key3.offset, key2.offset + key2.allocated);
+        assert_eq!(bs.get_free_space().unwrap(), vec![(key0.offset, key0.allocated)]);
+        assert!(bs.read::<TypedValue>(key0.offset, &[]).is_err());
+    }
+
+    #[test]
+    fn test_checksums() {
+        let ns = Namespace::new("blobs", "checksums", "data");
+        let bs = BLOBStore::open(&ns).unwrap();
+        bs.truncate().unwrap();
+        let key0 = bs.insert(StringValue("Hello World".into()), &[]).unwrap();
+        assert_eq!(bs.read::<TypedValue>(key0.offset, &[]).unwrap().1, StringValue("Hello World".into()));
+
+        // a flipped bit is detected
+        let path = ns.get_blob_file_path();
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[HEADER_LEN + 10] ^= 0b0000_0100;
+        std::fs::write(&path, bytes).unwrap();
+        let err = bs.read::<TypedValue>(key0.offset, &[]).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"));
+
+        // BLOBs written by an earlier version have no checksums
+        let legacy = bs.clone().with_checksums(false);
+        let key1 = legacy.insert(StringValue("Hello World".into()), &[]).unwrap();
+        assert_eq!(key1.used + CHECKSUM_LEN as u64, key0.used);
+        assert_eq!(legacy.read::<TypedValue>(key1.offset, &[]).unwrap().1, StringValue("Hello World".into()));
+    }
+
+    #[test]
+    fn test_free_space_reuse() {
+        let bs = BLOBStore::open(&Namespace::new("blobs", "reuse", "data")).unwrap();
+        bs.truncate().unwrap();
+        let keys = (0..4)
+            .map(|n| bs.insert(StringValue(format!("The quick brown fox #{n}")), &[]).unwrap())
+            .collect::<Vec<_>>();
+
+        // adjacent free allocations are merged
+        bs.free(keys[1].offset).unwrap();
+        bs.free(keys[2].offset).unwrap();
+        assert_eq!(bs.get_free_space().unwrap(), vec![
+            (keys[1].offset, keys[1].allocated + keys[2].allocated)
+        ]);
+
+        // a new BLOB reuses (and splits) the free allocation
+        let key = bs.insert(StringValue("Jumped".into()), &[]).unwrap();
+        assert_eq!(key.offset, keys[1].offset);
+        assert_eq!(bs.get_free_space().unwrap(), vec![
+            (key.offset + key.allocated, keys[1].allocated + keys[2].allocated - key.allocated)
+        ]);
+
+        // freeing the last BLOB truncates the file
+        bs.free(keys[3].offset).unwrap();
+        assert_eq!(bs.len().unwrap(), key.offset + key.allocated);
+        assert_eq!(bs.get_free_space().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_free_space_is_shared_between_handles() {
+        let ns = Namespace::new("blobs", "shared", "data");
+        let bs0 = BLOBStore::open(&ns).unwrap();
+        bs0.truncate().unwrap();
+        let keys = (0..3)
+            .map(|n| bs0.insert(StringValue(format!("The quick brown fox #{n}")), &[]).unwrap())
+            .collect::<Vec<_>>();
+        bs0.free(keys[1].offset).unwrap();
+
+        // another handle of the same file sees the free allocation, and
+        // once it reuses the allocation, it is no longer offered to the first handle
+        let bs1 = BLOBStore::open(&ns).unwrap();
+        assert_eq!(bs1.get_free_space().unwrap(), vec![(keys[1].offset, keys[1].allocated)]);
+        let key = bs1.insert(StringValue("Jumped".into()), &[]).unwrap();
+        assert_eq!(key.offset, keys[1].offset);
+        assert_eq!(bs0.get_free_space().unwrap(), vec![]);
+        assert_eq!(bs0.insert(StringValue("Over".into()), &[]).unwrap().offset, keys[2].offset + keys[2].allocated);
+    }
+
+    #[test]
+    fn test_encode_field_at_capacity() {
+        let bs = BLOBStore::open(&Namespace::new("blobs", "capacity", "data")).unwrap();
+        let column = Column::new("symbol", StringType(8), Null, 0);
+        let fixed_size = column.get_fixed_size();
+
+        // a value that fits beside the field metadata is stored inline
+        let value = StringValue("ABCDEFGH".into());
+        assert_eq!(column.get_data_type().encode(&value).unwrap().len(), fixed_size - 1);
+        let encoded = bs.encode_field(&column, &value, None, &[]).unwrap();
+        assert_eq!(encoded.len(), fixed_size);
+        assert_eq!(FieldMetadata::decode(encoded[0]).is_external, false);
+        assert_eq!(column.get_data_type().decode_field_value(&encoded, 0), value);
+
+        // a value exactly as long as the field would lose its last byte inline, so it's stored externally
+        let value = StringValue("ABCDEFGHI".into());
+        assert_eq!(column.get_data_type().encode(&value).unwrap().len(), fixed_size);
+        let encoded = bs.encode_field(&column, &value, None, &[]).unwrap();
+        assert!(FieldMetadata::decode(encoded[0]).is_external);
+        let offset = u64::from_be_bytes(encoded[1..9].try_into().unwrap());
+        assert_eq!(bs.read::<TypedValue>(offset, &[]).unwrap().1, value);
+    }
+}
@@ -149,13 +149,13 @@ impl ByteCodeCompiler {
         result.unwrap_or_else(|err| f(err))
     }
 
-    pub fn decode_row_id(buffer: &Vec<u8>, offset: usize) -> usize {
+    pub fn decode_row_id(buffer: &[u8], offset: usize) -> usize {
         let mut id_array = [0u8; 8];
         id_array.copy_from_slice(&buffer[offset..(offset + 8)]);
         usize::from_be_bytes(id_array)
     }
 
-    pub fn decode_string(buffer: &Vec<u8>, offset: usize, max_size: usize) -> String {
+    pub fn decode_string(buffer: &[u8], offset: usize, max_size: usize) -> String {
         let a: usize = offset + size_of::<usize>();
         let b: usize = a + max_size;
         let mut data: &[u8] = &buffer[a..b];
@@ -168,32 +168,32 @@ impl ByteCodeCompiler {
         }
     }
 
-    pub fn decode_u8<A>(buffer: &Vec<u8>, offset: usize, f: fn(u8) -> A) -> A {
+    pub fn decode_u8<A>(buffer: &[u8], offset: usize, f: fn(u8) -> A) -> A {
         f(buffer[offset])
     }
 
-    pub fn decode_u8x2<A>(buffer: &Vec<u8>, offset: usize, f: fn([u8; 2]) -> A) -> A {
+    pub fn decode_u8x2<A>(buffer: &[u8], offset: usize, f: fn([u8; 2]) -> A) -> A {
         let mut scratch = [0; 2];
         let limit = offset + scratch.len();
         scratch.copy_from_slice(&buffer[offset..limit]);
         f(scratch)
     }
 
-    pub fn decode_u8x4<A>(buffer: &Vec<u8>, offset: usize, f: fn([u8; 4]) -> A) -> A {
+    pub fn decode_u8x4<A>(buffer: &[u8], offset: usize, f: fn([u8; 4]) -> A) -> A {
         let mut scratch = [0; 4];
         let limit = offset + scratch.len();
         scratch.copy_from_slice(&buffer[offset..limit]);
         f(scratch)
     }
 
-    pub fn decode_u8x8<A>(buffer: &Vec<u8>, offset: usize, f: fn([u8; 8]) -> A) -> A {
+    pub fn decode_u8x8<A>(buffer: &[u8], offset: usize, f: fn([u8; 8]) -> A) -> A {
         let mut scratch = [0; 8];
         let limit = offset + scratch.len();
         scratch.copy_from_slice(&buffer[offset..limit]);
         f(scratch)
     }
 
-    pub fn decode_u8x16<A>(buffer: &Vec<u8>, offset: usize, f: fn([u8; 16]) -> A) -> A {
+    pub fn decode_u8x16<A>(buffer: &[u8], offset: usize, f: fn([u8; 16]) -> A) -> A {
         let mut scratch = [0; 16];
         let limit = offset + scratch.len();
         scratch.copy_from_slice(&buffer[offset..limit]);
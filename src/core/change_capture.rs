@@ -0,0 +1,439 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// change capture module
+////////////////////////////////////////////////////////////////////
+
+use crate::byte_code_compiler::ByteCodeCompiler;
+use crate::columns::Column;
+use crate::compiler::Compiler;
+use crate::encryption::EncryptionKey;
+use crate::errors::throw;
+use crate::errors::Errors::Exact;
+use crate::expression::Expression;
+use crate::expression::Expression::{FunctionCall, Literal};
+use crate::expression::TableOptions;
+use crate::machine::Machine;
+use crate::namespaces::Namespace;
+use crate::object_config::{ObjectConfig, TriggerConfig};
+use crate::replication::ReplicationLog;
+use crate::structures::Structures::Hard;
+use crate::structures::{HardStructure, Row};
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{ErrorValue, Null, Structured, Undefined};
+use actix::{Actor, AsyncContext, StreamHandler};
+use actix_web_actors::ws;
+use chrono::Local;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::fs::OpenOptions;
+use std::time::Duration;
+
+/// the maximum number of changes transmitted in a single batch
+const BATCH_SIZE: usize = 1000;
+
+/// how often the server checks the change feeds for new changes
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Represents the kind of change made to a row
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum ChangeKind {
+    Append,
+    Delete,
+    Update,
+}
+
+impl ChangeKind {
+    /// Parses a change kind (e.g. "update")
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code {
+            "append" => Some(ChangeKind::Append),
+            "delete" => Some(ChangeKind::Delete),
+            "update" => Some(ChangeKind::Update),
+            _ => None
+        }
+    }
+
+    pub fn to_code(&self) -> String {
+        match self {
+            ChangeKind::Append => "append".into(),
+            ChangeKind::Delete => "delete".into(),
+            ChangeKind::Update => "update".into(),
+        }
+    }
+}
+
+/// Represents a change made to a row of a table
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub kind: ChangeKind,
+    pub id: usize,
+    pub old: Option<Row>,
+    pub new: Option<Row>,
+    pub created_time: i64,
+}
+
+impl ChangeEvent {
+    /// Describes the change made to a row given its (active) states before and after
+    /// a write; or [None] if the row was left unchanged.
+    pub fn new(id: usize, old: Option<Row>, new: Option<Row>) -> Option<Self> {
+        let kind = match (&old, &new) {
+            (None, Some(..)) => ChangeKind::Append,
+            (Some(..), None) => ChangeKind::Delete,
+            (Some(a), Some(b)) if a != b => ChangeKind::Update,
+            _ => return None
+        };
+        Some(Self { kind, id, old, new, created_time: Local::now().timestamp_millis() })
+    }
+}
+
+/// Represents a message exchanged between a server and the subscribers of its change feeds
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ChangeMessage {
+    /// (client -> server) subscribes to the changes of a table starting at a sequence number
+    Subscribe { ns: Namespace, since: u64 },
+    /// (server -> client) a batch of changes and their sequence numbers;
+    /// `next` is the sequence number to resume from
+    Changes { ns: Namespace, changes: Vec<(u64, ChangeEvent)>, next: u64 },
+    /// (server -> client) a subscription could not be honored
+    Failure { ns: Namespace, message: String },
+}
+
+impl ChangeMessage {
+    pub fn decode(bytes: &[u8]) -> std::io::Result<Self> {
+        ByteCodeCompiler::unwrap_as_result(bincode::deserialize(bytes))
+    }
+
+    pub fn encode(&self) -> std::io::Result<Vec<u8>> {
+        ByteCodeCompiler::unwrap_as_result(bincode::serialize(self))
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+// ChangeFeed class
+////////////////////////////////////////////////////////////////////
+
+/// Tailable log of the [ChangeEvent]s of a table created with the `change_capture` option.
+/// It shares the format of the [ReplicationLog]; so the byte offset of each change
+/// serves as its sequence number.
+#[derive(Clone)]
+pub struct ChangeFeed {
+    log: ReplicationLog,
+}
+
+impl ChangeFeed {
+    /// Creates (or truncates) the change feed of a table
+    pub fn create(ns: &Namespace) -> std::io::Result<Self> {
+        let path = Self::get_feed_file_path(&ns.get_table_file_path());
+        fs::create_dir_all(ns.get_root_path())?;
+        OpenOptions::new().create(true).truncate(true).write(true).open(&path)?;
+        Self::open_file(path.as_str())
+    }
+
+    /// Returns the path of the change feed for the given table file
+    pub fn get_feed_file_path(table_path: &str) -> String {
+        format!("{}.changes", table_path)
+    }
+
+    /// Opens the change feed of a table created with the `change_capture` option
+    pub fn open(ns: &Namespace) -> std::io::Result<Self> {
+        let options = ObjectConfig::load(ns)?.get_options();
+        if !options.contains(&TableOptions::ChangeCapture) {
+            return throw(Exact(format!("{} does not capture changes", ns)));
+        }
+        let feed = Self::open_file(Self::get_feed_file_path(&ns.get_table_file_path()).as_str())?;
+        Ok(if options.contains(&TableOptions::Encrypted) {
+            feed.with_encryption(EncryptionKey::load()?)
+        } else { feed })
+    }
+
+    /// Opens (or creates) a change feed by file
+    pub fn open_file(path: &str) -> std::io::Result<Self> {
+        Ok(Self { log: ReplicationLog::open_file(path)? })
+    }
+
+    /// Enables encryption at rest for the changes of this feed
+    pub fn with_encryption(self, key: EncryptionKey) -> Self {
+        Self { log: self.log.with_encryption(key) }
+    }
+
+    /// Appends a change to the feed; returning its sequence number
+    pub fn append(&self, event: &ChangeEvent) -> std::io::Result<u64> {
+        self.log.append_entry(event)
+    }
+
+    /// Reads up to `limit` changes starting at the sequence number `since`;
+    /// returning the changes (and their sequence numbers) and the sequence number to resume from.
+    pub fn read_since(&self, since: u64, limit: usize) -> std::io::Result<(Vec<(u64, ChangeEvent)>, u64)> {
+        self.log.read_entries_since(since, limit)
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+// Trigger class
+////////////////////////////////////////////////////////////////////
+
+/// Represents a compiled trigger of a table
+#[derive(Clone, Debug)]
+pub struct Trigger {
+    config: TriggerConfig,
+    fx: Expression,
+}
+
+impl Trigger {
+    /// Compiles the function of a trigger configuration
+    pub fn compile(config: &TriggerConfig) -> std::io::Result<Self> {
+        Ok(Self { config: config.to_owned(), fx: Compiler::build(config.get_code())? })
+    }
+
+    /// Invokes the trigger function with the old and new states (or null) of the changed row;
+    /// returning [Undefined] if the trigger does not observe the kind of change.
+    /// Trigger functions are evaluated within a new platform [Machine].
+    pub fn fire(&self, columns: &Vec<Column>, event: &ChangeEvent) -> TypedValue {
+        if !self.config.get_kinds().contains(&event.kind) {
+            return Undefined;
+        }
+        let to_value = |row: &Option<Row>| match row {
+            Some(row) => Structured(Hard(HardStructure::from_columns_and_row(columns, row))),
+            None => Null
+        };
+        let call = FunctionCall {
+            fx: Box::new(self.fx.to_owned()),
+            args: vec![Literal(to_value(&event.old)), Literal(to_value(&event.new))],
+        };
+        match Machine::new_platform().evaluate(&call) {
+            Ok((_, result)) => result,
+            Err(err) => ErrorValue(Exact(format!("Trigger {} failed: {}", self.config.get_name(), err)))
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+// ChangeFeedServer class
+////////////////////////////////////////////////////////////////////
+
+/// Oxide Change Feed Server; streams the changes of tables to a websocket subscriber
+pub struct ChangeFeedServer {
+    subscriptions: Vec<(Namespace, ChangeFeed, u64)>,
+}
+
+impl ChangeFeedServer {
+    pub fn new() -> Self {
+        Self { subscriptions: Vec::new() }
+    }
+
+    /// transmits any new changes to the subscriber
+    fn publish(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        for (ns, feed, since) in self.subscriptions.iter_mut() {
+            match feed.read_since(*since, BATCH_SIZE) {
+                Ok((changes, _)) if changes.is_empty() => {}
+                Ok((changes, next)) => {
+                    *since = next;
+                    Self::transmit(ctx, ChangeMessage::Changes { ns: ns.to_owned(), changes, next })
+                }
+                Err(err) => error!("change feed of {ns} failed: {err}")
+            }
+        }
+    }
+
+    fn subscribe(&mut self, ctx: &mut ws::WebsocketContext<Self>, ns: Namespace, since: u64) {
+        match ChangeFeed::open(&ns) {
+            Ok(feed) => {
+                info!("streaming the changes of {ns} from sequence {since}");
+                self.subscriptions.push((ns, feed, since));
+            }
+            Err(err) =>
+                Self::transmit(ctx, ChangeMessage::Failure { ns, message: err.to_string() })
+        }
+    }
+
+    fn transmit(ctx: &mut ws::WebsocketContext<Self>, message: ChangeMessage) {
+        match message.encode() {
+            Ok(bytes) => ctx.binary(bytes),
+            Err(err) => error!("{}", err)
+        }
+    }
+}
+
+impl Actor for ChangeFeedServer {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(POLL_INTERVAL, |server, ctx| server.publish(ctx));
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for ChangeFeedServer {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Err(err) => error!("{}", err),
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Pong(msg)) => ctx.ping(&msg),
+            Ok(ws::Message::Binary(bytes)) =>
+                match ChangeMessage::decode(&bytes) {
+                    Ok(ChangeMessage::Subscribe { ns, since }) => self.subscribe(ctx, ns, since),
+                    Ok(other) => error!("Unexpected change feed message: {:?}", other),
+                    Err(err) => error!("{}", err)
+                }
+            Ok(ws::Message::Close(reason)) => {
+                info!("change feed channel closed [{:?}]", reason);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_row_collection::FileRowCollection;
+    use crate::interpreter::Interpreter;
+    use crate::numbers::Numbers::{Ack, RowsAffected};
+    use crate::row_collection::RowCollection;
+    use crate::testdata::{make_quote, start_test_server};
+    use crate::typed_values::TypedValue::Number;
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::connect_async;
+    use tokio_tungstenite::tungstenite::Message;
+
+    #[test]
+    fn test_change_event_kinds() {
+        let (a, b) = (make_quote(0, "ABC", "AMEX", 11.77), make_quote(0, "ABC", "AMEX", 11.88));
+        assert_eq!(ChangeEvent::new(0, None, Some(a.clone())).map(|e| e.kind), Some(ChangeKind::Append));
+        assert_eq!(ChangeEvent::new(0, Some(a.clone()), Some(b.clone())).map(|e| e.kind), Some(ChangeKind::Update));
+        assert_eq!(ChangeEvent::new(0, Some(b.clone()), None).map(|e| e.kind), Some(ChangeKind::Delete));
+        assert_eq!(ChangeEvent::new(0, Some(a.clone()), Some(a)), None);
+        assert_eq!(ChangeEvent::new(0, None, None), None);
+    }
+
+    #[test]
+    fn test_feed_captures_changes() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(Number(Ack), interpreter.evaluate(r#"
+            create table ns("change_capture.feed.stocks") (
+                symbol: String(8), exchange: String(8), last_sale: f64
+            ) with change_capture
+        "#).unwrap());
+        assert_eq!(Number(RowsAffected(2)), interpreter.evaluate(r#"
+            [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+             { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }] ~> ns("change_capture.feed.stocks")
+        "#).unwrap());
+        interpreter.evaluate(r#"
+            update ns("change_capture.feed.stocks") via { last_sale: 11.88 } where symbol == "ABC"
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            delete from ns("change_capture.feed.stocks") where symbol == "UNO"
+        "#).unwrap();
+
+        // the feed lists each change along with its sequence number
+        let feed = ChangeFeed::open(&Namespace::new("change_capture", "feed", "stocks")).unwrap();
+        let (changes, next) = feed.read_since(0, BATCH_SIZE).unwrap();
+        assert_eq!(changes.iter().map(|(_, e)| (e.kind, e.id)).collect::<Vec<_>>(), vec![
+            (ChangeKind::Append, 0),
+            (ChangeKind::Append, 1),
+            (ChangeKind::Update, 0),
+            (ChangeKind::Delete, 1),
+        ]);
+        assert_eq!(changes[2].1.old, Some(make_quote(0, "ABC", "AMEX", 11.77)));
+        assert_eq!(changes[2].1.new, Some(make_quote(0, "ABC", "AMEX", 11.88)));
+
+        // the feed can be tailed from any sequence number
+        let (seq, _) = changes[2];
+        assert_eq!(feed.read_since(seq, BATCH_SIZE).unwrap(), (changes[2..].to_vec(), next));
+        assert_eq!(feed.read_since(next, BATCH_SIZE).unwrap(), (vec![], next));
+    }
+
+    #[test]
+    fn test_triggers() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            table(symbol: String(8), exchange: String(8), last_sale: f64)
+                ~> ns("change_capture.triggers.audit")
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            create table ns("change_capture.triggers.stocks") (
+                symbol: String(8), exchange: String(8), last_sale: f64
+            )
+        "#).unwrap();
+        assert_eq!(Number(Ack), interpreter.evaluate(r#"
+            create trigger audit on ns("change_capture.triggers.stocks") after update, delete
+                fn(old, new) => [old] ~> ns("change_capture.triggers.audit")
+        "#).unwrap());
+
+        // appends are not observed by the trigger; but updates and deletes are
+        interpreter.evaluate(r#"
+            [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+             { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }] ~> ns("change_capture.triggers.stocks")
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            update ns("change_capture.triggers.stocks") via { last_sale: 11.88 } where symbol == "ABC"
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            delete from ns("change_capture.triggers.stocks") where symbol == "UNO"
+        "#).unwrap();
+        let audit = FileRowCollection::open(&Namespace::new("change_capture", "triggers", "audit")).unwrap();
+        assert_eq!(audit.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+        ]);
+    }
+
+    #[test]
+    fn test_failing_trigger_does_not_fail_the_write() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("change_capture.failing_trigger.stocks") (
+                symbol: String(8), exchange: String(8), last_sale: f64
+            )
+        "#).unwrap();
+        assert_eq!(Number(Ack), interpreter.evaluate(r#"
+            create trigger broken on ns("change_capture.failing_trigger.stocks") after append
+                fn(old, new) => no_such_function(new)
+        "#).unwrap());
+
+        // the trigger fails after the row was written; so the write succeeds
+        assert_eq!(Number(RowsAffected(1)), interpreter.evaluate(r#"
+            [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 }] ~> ns("change_capture.failing_trigger.stocks")
+        "#).unwrap());
+        let stocks = FileRowCollection::open(&Namespace::new("change_capture", "failing_trigger", "stocks")).unwrap();
+        assert_eq!(stocks.read_active_rows().unwrap(), vec![make_quote(0, "ABC", "AMEX", 11.77)]);
+    }
+
+    #[actix::test]
+    async fn test_change_feed_subscription() {
+        let port = 8032;
+        start_test_server(port);
+        let ns = Namespace::new("change_capture", "subscription", "stocks");
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("change_capture.subscription.stocks") (
+                symbol: String(8), exchange: String(8), last_sale: f64
+            ) with change_capture
+        "#).unwrap();
+
+        // subscribe to the change feed, then make some changes
+        let (ws_stream, _) = connect_async(format!("ws://0.0.0.0:{port}/changes")).await.unwrap();
+        let (mut write, mut read) = ws_stream.split();
+        let message = ChangeMessage::Subscribe { ns: ns.to_owned(), since: 0 };
+        write.send(Message::Binary(message.encode().unwrap())).await.unwrap();
+        interpreter.evaluate(r#"
+            [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 }] ~> ns("change_capture.subscription.stocks")
+        "#).unwrap();
+
+        // the changes are streamed to the subscriber
+        match read.next().await {
+            Some(Ok(Message::Binary(bytes))) =>
+                match ChangeMessage::decode(&bytes).unwrap() {
+                    ChangeMessage::Changes { ns: changed_ns, changes, .. } => {
+                        assert_eq!(changed_ns, ns);
+                        assert_eq!(changes.len(), 1);
+                        assert_eq!(changes[0].1.kind, ChangeKind::Append);
+                        assert_eq!(changes[0].1.new, Some(make_quote(0, "ABC", "AMEX", 11.77)));
+                    }
+                    other => panic!("Unexpected message: {:?}", other)
+                }
+            other => panic!("Unexpected message: {:?}", other)
+        }
+    }
+}
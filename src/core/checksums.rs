@@ -0,0 +1,91 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// checksums module
+////////////////////////////////////////////////////////////////////
+
+use crate::errors::Errors::ChecksumMismatch;
+use crate::errors::throw;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// the length of the checksum stored at the end of each record
+pub const CHECKSUM_LEN: usize = 4;
+
+/// the environment variable used to configure the [ChecksumPolicy] ("fail" or "warn")
+const CHECKSUM_POLICY_ENV_VAR: &str = "OXIDE_CHECKSUM_POLICY";
+
+/// Determines how records whose checksums do not match are handled upon read
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum ChecksumPolicy {
+    Fail,
+    Warn,
+}
+
+impl ChecksumPolicy {
+    /// Returns the policy configured via the environment; failing by default
+    pub fn load() -> Self {
+        match env::var(CHECKSUM_POLICY_ENV_VAR) {
+            Ok(policy) if policy.eq_ignore_ascii_case("warn") => ChecksumPolicy::Warn,
+            _ => ChecksumPolicy::Fail
+        }
+    }
+
+    /// Applies this policy to the damaged record of the given row ID
+    pub fn enforce(&self, path: &str, id: usize) -> std::io::Result<()> {
+        match self {
+            ChecksumPolicy::Fail => throw(ChecksumMismatch(path.to_string(), id)),
+            ChecksumPolicy::Warn => {
+                warn!("{}", ChecksumMismatch(path.to_string(), id));
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Appends the checksum of the given record to it
+pub fn append_checksum(record: &mut Vec<u8>) {
+    let checksum = crc32fast::hash(record);
+    record.extend(checksum.to_be_bytes());
+}
+
+/// Indicates whether the trailing checksum of the given (stored) record matches its contents
+pub fn is_intact(record: &[u8]) -> bool {
+    if record.len() < CHECKSUM_LEN { return false; }
+    let (payload, checksum) = record.split_at(record.len() - CHECKSUM_LEN);
+    let expected = crc32fast::hash(payload).to_be_bytes();
+    checksum == expected
+}
+
+/// Indicates whether the given bytes were never written (i.e. all zeros)
+pub fn is_zeroed(bytes: &[u8]) -> bool {
+    bytes.iter().all(|&b| b == 0)
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_then_verify() {
+        let mut record = b"\x80\x00\x00\x00\x00\x00\x00\x00\x01Hello World".to_vec();
+        append_checksum(&mut record);
+        assert_eq!(record.len(), 20 + CHECKSUM_LEN);
+        assert!(is_intact(&record));
+
+        // a flipped bit is detected
+        record[12] ^= 0b0000_0100;
+        assert!(!is_intact(&record));
+
+        // never-written (zeroed) records are not intact
+        assert!(!is_intact(&[0u8; 24]));
+        assert!(is_zeroed(&[0u8; 24]));
+    }
+
+    #[test]
+    fn test_enforce() {
+        assert!(ChecksumPolicy::Fail.enforce("stocks.table", 1).is_err());
+        assert!(ChecksumPolicy::Warn.enforce("stocks.table", 1).is_ok());
+    }
+}
@@ -4,19 +4,23 @@
 ////////////////////////////////////////////////////////////////////
 
 use crate::byte_code_compiler::ByteCodeCompiler;
+use crate::change_capture::ChangeKind;
+use crate::constraints::{Constraint, ForeignKey, ReferentialAction};
 use crate::data_types::DataType;
 use crate::data_types::DataType::{Indeterminate, VaryingType};
 use crate::errors::throw;
 use crate::errors::Errors::{ExactNear, Syntax, TypeMismatch};
 use crate::errors::TypeMismatchErrors::{CodeBlockExpected, ParameterExpected, VariableExpected};
 use crate::expression::Conditions::*;
-use crate::expression::CreationEntity::{IndexEntity, TableEntity, TableFnEntity};
+use crate::expression::CreationEntity::{IndexEntity, SequenceEntity, TableEntity, TableFnEntity, TriggerEntity};
 use crate::expression::DatabaseOps::{Mutation, Queryable};
 use crate::expression::Expression::*;
 use crate::expression::MutateTarget::TableTarget;
 use crate::expression::Mutations::{Create, Declare, Drop, IntoNs, Undelete};
 use crate::expression::Queryables::Select;
 use crate::expression::*;
+use crate::hybrid_row_collection::HybridPolicy;
+use crate::namespaces::Namespace;
 use crate::numbers::Numbers::*;
 use crate::parameter::Parameter;
 use crate::structures::HardStructure;
@@ -297,6 +301,7 @@ impl Compiler {
                 "[-]" => self.parse_expression_1a(nts, |e| Directive(Directives::MustNotAck(e))),
                 "[~]" => self.parse_expression_1a(nts, |e| Directive(Directives::MustIgnoreAck(e))),
                 "Ack" => Ok((ACK, nts)),
+                "alter" => self.parse_keyword_alter(nts),
                 "append" => self.parse_keyword_append(nts),
                 "create" => self.parse_keyword_create(nts),
                 "delete" => self.parse_keyword_delete(nts),
@@ -319,12 +324,14 @@ impl Compiler {
                 "limit" => throw(ExactNear("`from` is expected before `limit`: from stocks limit 5".into(), nts.current())),
                 "mod" => self.parse_keyword_mod(nts),
                 "NaN" => Ok((Literal(Number(NaNValue)), nts)),
+                "nextval" => self.parse_expression_1a(nts, NextVal),
                 "ns" => self.parse_expression_1a(nts, Ns),
                 "null" => Ok((NULL, nts)),
                 "overwrite" => self.parse_keyword_overwrite(nts),
                 "PATCH" => self.parse_keyword_http(ts),
                 "POST" => self.parse_keyword_http(ts),
                 "PUT" => self.parse_keyword_http(ts),
+                "rename" => self.parse_keyword_rename(nts),
                 "Scenario" => self.parse_keyword_scenario(nts),
                 "select" => self.parse_keyword_select(nts),
                 "Struct" => self.parse_keyword_struct(nts),
@@ -341,15 +348,62 @@ impl Compiler {
         } else { fail("Unexpected end of input") }
     }
 
+    /// Alters the columns of a table
+    /// ex: alter table ns("a.b.stocks") add column market: String(8) = "NYSE"
+    /// ex: alter table ns("a.b.stocks") alter column last_sale: f32
+    /// ex: alter table ns("a.b.stocks") drop column market
+    /// ex: alter table ns("a.b.stocks") rename column market to exchange
+    fn parse_keyword_alter(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        let (table, ts) = self.compile_next(ts.expect("table")?)?;
+        let (alteration, ts) = match ts.next() {
+            (Some(Atom { text, .. }), ats) => match text.as_str() {
+                "add" => {
+                    let (param, ts) = self.expect_parameter(ats.expect("column")?)?;
+                    (Alterations::AddColumn(param), ts)
+                }
+                "alter" => {
+                    let (param, ts) = self.expect_parameter(ats.expect("column")?)?;
+                    if param.get_data_type() == VaryingType(vec![]) {
+                        return throw(ExactNear("A column type was expected".into(), ts.current()));
+                    }
+                    (Alterations::AlterColumn(param), ts)
+                }
+                "drop" => {
+                    let (name, ts) = self.expect_column_name(ats.expect("column")?)?;
+                    (Alterations::DropColumn(name), ts)
+                }
+                "rename" => {
+                    let (from, ts) = self.expect_column_name(ats.expect("column")?)?;
+                    let (to, ts) = self.expect_column_name(ts.expect("to")?)?;
+                    (Alterations::RenameColumn(from, to), ts)
+                }
+                _ => return throw(ExactNear("Expected add, alter, drop or rename".into(), ts.current()))
+            }
+            (_, ts) => return throw(ExactNear("Expected add, alter, drop or rename".into(), ts.current()))
+        };
+        Ok((DatabaseOp(Mutation(Mutations::Alter { path: Box::new(table), alteration })), ts))
+    }
+
     /// Appends a new row to a table
     /// ex: append stocks select symbol: "ABC", exchange: "NYSE", last_sale: 0.1008
+    /// ex: append stocks from quotes with bulk
     fn parse_keyword_append(
         &mut self,
         ts: TokenSlice,
     ) -> std::io::Result<(Expression, TokenSlice)> {
         let (table, ts) = self.compile_next(ts)?;
         let (source, ts) = self.compile_next(ts)?;
-        Ok((DatabaseOp(Mutation(Mutations::Append { path: Box::new(table), source: Box::new(source) })), ts))
+        let (is_bulk, ts) = if ts.is("with") {
+            (true, ts.skip().expect("bulk")?)
+        } else { (false, ts) };
+        Ok((DatabaseOp(Mutation(Mutations::Append {
+            path: Box::new(table),
+            source: Box::new(source),
+            is_bulk,
+        })), ts))
     }
 
     /// Creates a database object (e.g., table or index)
@@ -360,7 +414,10 @@ impl Compiler {
         if let (Some(t), ts) = ts.next() {
             match t.get_raw_value().as_str() {
                 "index" => self.parse_keyword_create_index(ts),
+                "sequence" => self.parse_keyword_create_sequence(ts),
                 "table" => self.parse_keyword_create_table(ts),
+                "temp" => self.parse_keyword_create_temp_table(ts.expect("table")?),
+                "trigger" => self.parse_keyword_create_trigger(ts),
                 name => throw(ExactNear(format!("Syntax error: expect type identifier, got '{}'", name), ts.current()))
             }
         } else { fail("Unexpected end of input") }
@@ -382,6 +439,30 @@ impl Compiler {
         }
     }
 
+    /// Parses a sequence creation expression
+    /// e.g: create sequence ns("finance.trades.trade_ids") start with 1000 increment by 10
+    fn parse_keyword_create_sequence(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        let (sequence, mut ts) = self.compile_next(ts)?;
+        let (mut start, mut increment) = (None, None);
+        if ts.is("start") {
+            let (expr, sts) = self.compile_next(ts.skip().expect("with")?)?;
+            start = Some(Box::new(expr));
+            ts = sts;
+        }
+        if ts.is("increment") {
+            let (expr, its) = self.compile_next(ts.skip().expect("by")?)?;
+            increment = Some(Box::new(expr));
+            ts = its;
+        }
+        Ok((DatabaseOp(Mutation(Create {
+            path: Box::new(sequence),
+            entity: SequenceEntity { start, increment },
+        })), ts))
+    }
+
     /// Parses a table creation expression
     fn parse_keyword_create_table(
         &mut self,
@@ -396,7 +477,8 @@ impl Compiler {
                 path: Box::new(table),
                 entity: TableFnEntity { fx: Box::from(fx) },
             })), ts))
-        } else if let (Parameters(columns), ts) = self.expect_parameters(ts.to_owned())? {
+        } else if ts.is("(") {
+            let (columns, constraints, ts) = self.expect_column_definitions(ts)?;
             // from { symbol: "ABC", exchange: "NYSE", last_sale: 67.89 }
             let (from, ts) =
                 if ts.is("from") {
@@ -409,13 +491,76 @@ impl Compiler {
             let (options, ts) = self.parse_table_options(ts)?;
             Ok((DatabaseOp(Mutation(Create {
                 path: Box::new(table),
-                entity: TableEntity { columns, from, options },
+                entity: TableEntity { columns, constraints, from, options },
             })), ts))
         } else {
             throw(ExactNear("Expected column definitions".into(), ts.current()))
         }
     }
 
+    /// Parses a temporary table creation expression
+    /// e.g: create temp table scratch (symbol: String(8), last_sale: f64)
+    fn parse_keyword_create_temp_table(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        // the name is bound within the session (rather than evaluated), so `scratch (..)` isn't a call
+        let (name, ts) = match ts.next() {
+            (Some(Atom { text, .. }), ts) => (text, ts),
+            (_, ts) => return throw(ExactNear("Temporary table name expected".into(), ts.current()))
+        };
+        if ts.isnt("(") {
+            return throw(ExactNear("Expected column definitions".into(), ts.current()));
+        }
+        let (columns, constraints, ts) = self.expect_column_definitions(ts)?;
+        let (from, ts) =
+            if ts.is("from") {
+                let (from, ts) = self.compile_next(ts.expect("from")?)?;
+                (Some(Box::new(from)), ts)
+            } else {
+                (None, ts)
+            };
+        let (mut options, ts) = self.parse_table_options(ts)?;
+        options.push(TableOptions::Temporary);
+        Ok((DatabaseOp(Mutation(Create {
+            path: Box::new(Variable(name)),
+            entity: TableEntity { columns, constraints, from, options },
+        })), ts))
+    }
+
+    /// Parses a trigger definition
+    /// e.g: create trigger audit on stocks after update, delete fn(old, new) => ...
+    fn parse_keyword_create_trigger(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        let (name, ts) = match ts.next() {
+            (Some(Atom { text, .. }), ts) => (text, ts),
+            (_, ts) => return throw(ExactNear("Trigger name expected".into(), ts.current()))
+        };
+        let (table, ts) = self.compile_next(ts.expect("on")?)?;
+        let mut ts = ts.expect("after")?;
+        let mut kinds = vec![];
+        loop {
+            match ts.next() {
+                (Some(Atom { text, .. }), tts) if ChangeKind::from_code(&text).is_some() => {
+                    kinds.extend(ChangeKind::from_code(&text));
+                    if tts.isnt(",") {
+                        ts = tts;
+                        break;
+                    }
+                    ts = tts.expect(",")?;
+                }
+                (_, tts) => return throw(ExactNear("Expected append, delete or update".into(), tts.current()))
+            }
+        }
+        let (fx, ts) = self.expect_function_parameters_and_body(None, ts.expect("fn")?)?;
+        Ok((DatabaseOp(Mutation(Create {
+            path: Box::new(table),
+            entity: TriggerEntity { name, kinds, fx: Box::new(fx) },
+        })), ts))
+    }
+
     /// Parses the table options
     /// e.g: create table ns("a.b.c") (
     ///     symbol: String(8), exchange: String(8), last_sale: f64
@@ -429,18 +574,48 @@ impl Compiler {
         while ts.is("with") {
             let (_, tts) = ts.next();
             ts = tts;
-            println!("ts: {:?}", ts.next());
             match ts.next() {
                 (Some(Atom { text, .. }), tts) =>
                     match text.as_str() {
+                        "change_capture" => {
+                            options.push(TableOptions::ChangeCapture);
+                            ts = tts;
+                        }
+                        "encrypted" => {
+                            options.push(TableOptions::Encrypted);
+                            ts = tts;
+                        }
+                        "hybrid" => {
+                            let (option, tts) = self.parse_hybrid(tts)?;
+                            options.push(option);
+                            ts = tts;
+                        }
                         "journaling" => {
                             options.push(TableOptions::Journaling);
                             ts = tts;
                         }
-                        _ => return throw(ExactNear("Expected journaling".into(), tts.current()))
+                        "memory_mapped" => {
+                            options.push(TableOptions::MemoryMapped);
+                            ts = tts;
+                        }
+                        "partitioned" => {
+                            let (columns, tts) = self.parse_partition_columns(tts)?;
+                            options.push(TableOptions::Partitioned(columns));
+                            ts = tts;
+                        }
+                        "replicated" => {
+                            options.push(TableOptions::Replicated);
+                            ts = tts;
+                        }
+                        "ttl" => {
+                            let (option, tts) = self.parse_ttl(tts)?;
+                            options.push(option);
+                            ts = tts;
+                        }
+                        _ => return throw(ExactNear("Expected change_capture, encrypted, hybrid, journaling, memory_mapped, partitioned, replicated or ttl".into(), tts.current()))
                     }
                 (Some(tok), _ts) =>
-                    return throw(ExactNear("Expected journaling".into(), tok)),
+                    return throw(ExactNear("Expected change_capture, encrypted, hybrid, journaling, memory_mapped, partitioned, replicated or ttl".into(), tok)),
                 (None, ts) =>
                     return throw(ExactNear("Unexpected end of input".into(), ts.current())),
             }
@@ -448,6 +623,66 @@ impl Compiler {
         Ok((options, ts))
     }
 
+    /// Parses the in-memory capacity (and optional residency policy) of a hybrid table
+    /// e.g: with hybrid(1000, lru)
+    fn parse_hybrid(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(TableOptions, TokenSlice)> {
+        let (capacity, ts) = self.compile_next(ts.expect("(")?)?;
+        let capacity = match capacity {
+            Literal(Number(n)) if n.to_i64() > 0 => n.to_i64() as usize,
+            _ => return throw(ExactNear("Capacity expected (e.g. 1000)".into(), ts.current()))
+        };
+        let (policy, ts) = if ts.is(",") {
+            match ts.expect(",")?.next() {
+                (Some(Atom { text, .. }), tts) => (HybridPolicy::from_code(text.as_str())?, tts),
+                (_, tts) => return throw(ExactNear("Expected positional, lru or recent_appends".into(), tts.current()))
+            }
+        } else { (HybridPolicy::Positional, ts) };
+        Ok((TableOptions::Hybrid(capacity, policy), ts.expect(")")?))
+    }
+
+    /// Parses the time-to-live of a table's rows; the duration is either
+    /// a string (e.g. "30m") or a number of milliseconds.
+    /// e.g: with ttl("30m", updated_time)
+    fn parse_ttl(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(TableOptions, TokenSlice)> {
+        let (duration, ts) = self.compile_next(ts.expect("(")?)?;
+        let millis = match duration {
+            Literal(StringValue(text)) => TableOptions::parse_duration(text.as_str())?,
+            Literal(Number(n)) if n.to_i64() > 0 => n.to_i64(),
+            _ => return throw(ExactNear("Duration expected (e.g. \"30m\")".into(), ts.current()))
+        };
+        let (column, ts) = self.expect_column_name(ts.expect(",")?)?;
+        Ok((TableOptions::Ttl(millis, column), ts.expect(")")?))
+    }
+
+    /// Parses the partition columns of a table
+    /// e.g: with partitioned(exchange)
+    fn parse_partition_columns(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Vec<String>, TokenSlice)> {
+        let mut columns = vec![];
+        let mut ts = ts.expect("(")?;
+        while !ts.is(")") {
+            match ts.next() {
+                (Some(Atom { text, .. }), tts) => {
+                    columns.push(text);
+                    ts = if tts.is(",") { tts.expect(",")? } else { tts };
+                }
+                (Some(tok), _) =>
+                    return throw(ExactNear("Expected a partition column name".into(), tok)),
+                (None, ts) =>
+                    return throw(ExactNear("Unexpected end of input".into(), ts.current())),
+            }
+        }
+        Ok((columns, ts.expect(")")?))
+    }
+
     /// SQL Delete statement.
     /// ex: delete from stocks where last_sale > 1.00
     fn parse_keyword_delete(
@@ -710,6 +945,17 @@ impl Compiler {
         })), ts))
     }
 
+    /// Renames (or moves) a table to another namespace
+    /// ex: rename table ns("a.b.stocks") to ns("a.archive.stocks_2025")
+    fn parse_keyword_rename(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Expression, TokenSlice)> {
+        let (table, ts) = self.compile_next(ts.expect("table")?)?;
+        let (target, ts) = self.compile_next(ts.expect("to")?)?;
+        Ok((DatabaseOp(Mutation(Mutations::Rename { path: Box::new(table), target: Box::new(target) })), ts))
+    }
+
     /// Builds a language model from a SELECT statement:
     /// ex: select sum(last_sale) from stocks group by exchange
     fn parse_keyword_select(&mut self, ts: TokenSlice) -> std::io::Result<(Expression, TokenSlice)> {
@@ -758,7 +1004,8 @@ impl Compiler {
         } else {
             false
         };
-        if let (Parameters(params), ts) = self.expect_parameters(ts.to_owned())? {
+        if ts.is("(") {
+            let (params, constraints, ts) = self.expect_column_definitions(ts)?;
             // from { symbol: "ABC", exchange: "NYSE", last_sale: 67.89 }
             let (from, ts) = if ts.is("from") {
                 let ts = ts.expect("from")?;
@@ -768,9 +1015,9 @@ impl Compiler {
                 (None, ts)
             };
             if is_function {
-                Ok((DatabaseOp(Mutation(Declare(TableEntity { columns: params, from, options: vec![] }))), ts))
+                Ok((DatabaseOp(Mutation(Declare(TableEntity { columns: params, constraints, from, options: vec![] }))), ts))
             } else {
-                Ok((DatabaseOp(Mutation(Declare(TableEntity { columns: params, from, options: vec![] }))), ts))
+                Ok((DatabaseOp(Mutation(Declare(TableEntity { columns: params, constraints, from, options: vec![] }))), ts))
             }
         } else {
             throw(ExactNear("Expected column definitions".into(), ts.current()))
@@ -914,6 +1161,10 @@ impl Compiler {
         ts: TokenSlice,
     ) -> std::io::Result<(Expression, TokenSlice)> {
         match ts.to_owned() {
+            t if t.is("as") => {
+                let (expr, ts) = self.compile_next(ts.skip().expect("of")?)?;
+                self.parse_queryable(DatabaseOp(Queryable(Queryables::AsOf { from: Box::new(host), moment: Box::new(expr) })), ts)
+            }
             t if t.is("limit") => {
                 let (expr, ts) = self.compile_next(ts.skip())?;
                 self.parse_queryable(DatabaseOp(Queryable(Queryables::Limit { from: Box::new(host), limit: Box::new(expr) })), ts)
@@ -1093,6 +1344,94 @@ impl Compiler {
         Ok((Parameters(parameters), ts.expect(")")?))
     }
 
+    /// Expects column definitions along with the constraints declared upon them
+    /// ex: (symbol: String(8) not null unique, last_sale: f64 check(last_sale >= 0.0))
+    fn expect_column_definitions(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(Vec<Parameter>, Vec<Constraint>, TokenSlice)> {
+        let mut columns = Vec::new();
+        let mut constraints = Vec::new();
+        let mut ts = ts.expect("(")?;
+        let mut is_done = ts.is(")");
+        while !is_done {
+            // get the next column
+            let (column, mut ats) = self.expect_parameter(ts.to_owned())?;
+            let name = column.get_name().to_string();
+            columns.push(column);
+
+            // get the column's constraints
+            loop {
+                if ats.is("not") {
+                    ats = ats.skip().expect("null")?;
+                    constraints.push(Constraint::NotNull(name.to_owned()));
+                } else if ats.is("unique") {
+                    ats = ats.skip();
+                    constraints.push(Constraint::Unique(vec![name.to_owned()]));
+                } else if ats.is("check") {
+                    let (condition, cts) = self.expect_parentheses(ats.skip().expect("(")?)?;
+                    constraints.push(Constraint::Check(name.to_owned(), Box::new(condition)));
+                    ats = cts;
+                } else if ats.is("identity") {
+                    ats = ats.skip();
+                    constraints.push(Constraint::Identity(name.to_owned()));
+                } else if ats.is("references") {
+                    let (fk, rts) = self.expect_foreign_key(&name, ats.skip())?;
+                    constraints.push(Constraint::References(fk));
+                    ats = rts;
+                } else { break }
+            }
+
+            // are we done yet?
+            is_done = ats.is(")");
+            ts = if !is_done { ats.expect(",")? } else { ats };
+        }
+        Ok((columns, constraints, ts.expect(")")?))
+    }
+
+    /// Expects a foreign key reference (following the "references" keyword)
+    /// ex: ns("finance.accounts.accounts")(id) on delete cascade
+    fn expect_foreign_key(
+        &mut self,
+        column: &str,
+        ts: TokenSlice,
+    ) -> std::io::Result<(ForeignKey, TokenSlice)> {
+        // get the referenced table: ns("finance.accounts.accounts")
+        let (parent, ts) = match self.expect_parentheses(ts.expect("ns")?.expect("(")?)? {
+            (Literal(StringValue(path)), ts) => (Namespace::parse(path.as_str())?, ts),
+            (_, ts) => return throw(ExactNear("Namespace string expected".into(), ts.current()))
+        };
+
+        // get the referenced column: (id)
+        let (parent_column, ts) = self.expect_column_name(ts.expect("(")?)?;
+        let mut ts = ts.expect(")")?;
+
+        // get the delete action: on delete [cascade | restrict | set null]
+        let mut on_delete = ReferentialAction::Restrict;
+        if ts.is("on") {
+            ts = ts.skip().expect("delete")?;
+            (on_delete, ts) = match ts.next() {
+                (Some(Atom { text, .. }), ats) if text == "cascade" => (ReferentialAction::Cascade, ats),
+                (Some(Atom { text, .. }), ats) if text == "restrict" => (ReferentialAction::Restrict, ats),
+                (Some(Atom { text, .. }), ats) if text == "set" => (ReferentialAction::SetNull, ats.expect("null")?),
+                (_, ats) => return throw(ExactNear("cascade, restrict or set null expected".into(), ats.current()))
+            };
+        }
+        Ok((ForeignKey::new(column, parent, parent_column.as_str(), on_delete), ts))
+    }
+
+    /// Expects a column name
+    /// ex: last_sale
+    fn expect_column_name(
+        &mut self,
+        ts: TokenSlice,
+    ) -> std::io::Result<(String, TokenSlice)> {
+        match ts.next() {
+            (Some(Atom { text: name, .. } | Backticks { text: name, .. }), ts) => Ok((name, ts)),
+            (_, ats) => throw(ExactNear("Column name expected".into(), ats.current()))
+        }
+    }
+
     /// Expects a single function parameter
     /// ex: a: i32
     fn expect_parameter(
@@ -1280,7 +1619,9 @@ mod tests {
 
         #[test]
         fn test_build_save_load() {
-            let src_path = "stocks.oxc";
+            let src_path = format!("{}/stocks.oxc", Machine::oxide_home());
+            let src_path = src_path.as_str();
+            std::fs::create_dir_all(Machine::oxide_home()).unwrap();
 
             // compile and save as a binary
             Compiler::build_and_save(src_path, r#"
@@ -2097,18 +2438,67 @@ mod tests {
         use crate::compiler::Compiler;
         use crate::data_types::DataType::{NumberType, StringType};
         use crate::expression::Conditions::{Between, Betwixt, Equal, GreaterOrEqual, LessOrEqual, LessThan, Like};
-        use crate::expression::CreationEntity::{IndexEntity, TableEntity};
+        use crate::expression::CreationEntity::{IndexEntity, SequenceEntity, TableEntity, TriggerEntity};
         use crate::expression::DatabaseOps::{Mutation, Queryable};
         use crate::expression::Expression::{ArrayExpression, Condition, DatabaseOp, From, StructureExpression, Literal, Ns, Variable, Via};
         use crate::expression::MutateTarget::TableTarget;
         use crate::expression::Mutations::{Create, Declare, Drop, IntoNs};
+        use crate::expression::TableOptions;
         use crate::expression::TableOptions::Journaling;
-        use crate::expression::{Mutations, Queryables};
-        use crate::number_kind::NumberKind::F64Kind;
+        use crate::expression::{Alterations, Mutations, Queryables};
+        use crate::number_kind::NumberKind::{F32Kind, F64Kind};
         use crate::numbers::Numbers::{F64Value, I64Value};
         use crate::parameter::Parameter;
         use crate::typed_values::TypedValue::{Number, StringValue};
 
+        #[test]
+        fn test_alter_table_add_column() {
+            let opcodes = Compiler::build(r#"
+                alter table ns("compiler.alter.stocks") add column market: String(8) = "NYSE"
+            "#).unwrap();
+            assert_eq!(opcodes, DatabaseOp(Mutation(Mutations::Alter {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.alter.stocks".to_string()))))),
+                alteration: Alterations::AddColumn(
+                    Parameter::with_default("market", StringType(8), StringValue("NYSE".into()))),
+            })))
+        }
+
+        #[test]
+        fn test_alter_table_alter_column() {
+            let opcodes = Compiler::build(r#"
+                alter table ns("compiler.alter.stocks") alter column last_sale: f32
+            "#).unwrap();
+            assert_eq!(opcodes, DatabaseOp(Mutation(Mutations::Alter {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.alter.stocks".to_string()))))),
+                alteration: Alterations::AlterColumn(Parameter::new("last_sale", NumberType(F32Kind))),
+            })));
+
+            // a column type is required
+            assert!(Compiler::build(r#"
+                alter table ns("compiler.alter.stocks") alter column last_sale
+            "#).is_err())
+        }
+
+        #[test]
+        fn test_alter_table_drop_and_rename_column() {
+            let path = Box::new(Ns(Box::new(Literal(StringValue("compiler.alter.stocks".to_string())))));
+            let opcodes = Compiler::build(r#"
+                alter table ns("compiler.alter.stocks") drop column exchange
+            "#).unwrap();
+            assert_eq!(opcodes, DatabaseOp(Mutation(Mutations::Alter {
+                path: path.clone(),
+                alteration: Alterations::DropColumn("exchange".into()),
+            })));
+
+            let opcodes = Compiler::build(r#"
+                alter table ns("compiler.alter.stocks") rename column exchange to market
+            "#).unwrap();
+            assert_eq!(opcodes, DatabaseOp(Mutation(Mutations::Alter {
+                path,
+                alteration: Alterations::RenameColumn("exchange".into(), "market".into()),
+            })))
+        }
+
         #[test]
         fn test_append_from_json_literal() {
             let opcodes = Compiler::build(r#"
@@ -2122,6 +2512,7 @@ mod tests {
                     ("exchange".into(), Literal(StringValue("NYSE".into()))),
                     ("last_sale".into(), Literal(Number(F64Value(0.1008)))),
                 ])))),
+                is_bulk: false,
             })))
         }
 
@@ -2156,6 +2547,7 @@ mod tests {
                         ]),
                     ]))
                 )),
+                is_bulk: false,
             })))
         }
 
@@ -2167,9 +2559,24 @@ mod tests {
             assert_eq!(opcodes, DatabaseOp(Mutation(Mutations::Append {
                 path: Box::new(Ns(Box::new(Literal(StringValue("compiler.append.stocks".to_string()))))),
                 source: Box::new(From(Box::new(Variable("stocks".into())))),
+                is_bulk: false,
             })))
         }
 
+        #[test]
+        fn test_append_with_bulk() {
+            let code = Compiler::build(r#"
+                append ns("compiler.bulk.stocks") from stocks with bulk
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Mutations::Append {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.bulk.stocks".to_string()))))),
+                source: Box::new(From(Box::new(Variable("stocks".into())))),
+                is_bulk: true,
+            })));
+            assert_eq!(code.to_code(), r#"append ns("compiler.bulk.stocks") from stocks with bulk"#);
+            assert_eq!(Compiler::build(code.to_code().as_str()).unwrap(), code);
+        }
+
         #[test]
         fn test_between() {
             assert_eq!(
@@ -2225,6 +2632,7 @@ mod tests {
                         Parameter::with_default("exchange", StringType(8), StringValue("NYSE".into())),
                         Parameter::with_default("last_sale", NumberType(F64Kind), Number(F64Value(23.54))),
                     ],
+                    constraints: vec![],
                     from: None,
                     options: vec![],
                 },
@@ -2257,6 +2665,7 @@ mod tests {
                         Parameter::new("exchange", StringType(8)),
                         Parameter::new("last_sale", NumberType(F64Kind)),
                     ],
+                    constraints: vec![],
                     from: None,
                     options: vec![
                         Journaling
@@ -2265,6 +2674,82 @@ mod tests {
             })))
         }
 
+        #[test]
+        fn test_create_table_with_encryption_in_namespace() {
+            let model = Compiler::build(r#"
+                create table ns("compiler.encrypted.stocks") (
+                   symbol: String(8), exchange: String(8), last_sale: f64
+                ) with encrypted
+            "#).unwrap();
+            assert_eq!(model, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.encrypted.stocks".into()))))),
+                entity: TableEntity {
+                    columns: vec![
+                        Parameter::new("symbol", StringType(8)),
+                        Parameter::new("exchange", StringType(8)),
+                        Parameter::new("last_sale", NumberType(F64Kind)),
+                    ],
+                    constraints: vec![],
+                    from: None,
+                    options: vec![
+                        TableOptions::Encrypted
+                    ],
+                }
+            })))
+        }
+
+        #[test]
+        fn test_create_table_with_partitions_in_namespace() {
+            let model = Compiler::build(r#"
+                create table ns("compiler.partitioned.stocks") (
+                   symbol: String(8), exchange: String(8), last_sale: f64
+                ) with partitioned(exchange, symbol)
+            "#).unwrap();
+            assert_eq!(model, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.partitioned.stocks".into()))))),
+                entity: TableEntity {
+                    columns: vec![
+                        Parameter::new("symbol", StringType(8)),
+                        Parameter::new("exchange", StringType(8)),
+                        Parameter::new("last_sale", NumberType(F64Kind)),
+                    ],
+                    constraints: vec![],
+                    from: None,
+                    options: vec![
+                        TableOptions::Partitioned(vec!["exchange".into(), "symbol".into()])
+                    ],
+                }
+            })));
+            assert_eq!(
+                model.to_code(),
+                r#"create table ns("compiler.partitioned.stocks") (symbol: String(8), exchange: String(8), last_sale: f64) with partitioned(exchange, symbol)"#)
+        }
+
+        #[test]
+        fn test_create_table_memory_mapped_in_namespace() {
+            let model = Compiler::build(r#"
+                create table ns("compiler.mapped.stocks") (
+                   symbol: String(8), exchange: String(8), last_sale: f64
+                ) with memory_mapped
+            "#).unwrap();
+            assert_eq!(model, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.mapped.stocks".into()))))),
+                entity: TableEntity {
+                    columns: vec![
+                        Parameter::new("symbol", StringType(8)),
+                        Parameter::new("exchange", StringType(8)),
+                        Parameter::new("last_sale", NumberType(F64Kind)),
+                    ],
+                    constraints: vec![],
+                    from: None,
+                    options: vec![TableOptions::MemoryMapped],
+                }
+            })));
+            assert_eq!(
+                model.to_code(),
+                r#"create table ns("compiler.mapped.stocks") (symbol: String(8), exchange: String(8), last_sale: f64) with memory_mapped"#)
+        }
+
         #[test]
         fn test_declare_table() {
             let model = Compiler::build(r#"
@@ -2279,6 +2764,7 @@ mod tests {
                     Parameter::new("exchange", StringType(8)),
                     Parameter::new("last_sale", NumberType(F64Kind)),
                 ],
+                constraints: vec![],
                 from: None,
                 options: vec![],
             }))));
@@ -2333,6 +2819,157 @@ mod tests {
             assert_eq!(opcodes, From(Box::new(Variable("stocks".into()))));
         }
 
+        #[test]
+        fn test_from_as_of_where() {
+            let opcodes = Compiler::build(r#"
+                from stocks as of 5 where last_sale >= 1.0
+            "#).unwrap();
+            assert_eq!(opcodes, DatabaseOp(Queryable(Queryables::Where {
+                from: Box::new(
+                    DatabaseOp(Queryable(Queryables::AsOf {
+                        from: Box::new(From(Box::new(Variable("stocks".into())))),
+                        moment: Box::new(Literal(Number(I64Value(5)))),
+                    }))),
+                condition: GreaterOrEqual(
+                    Box::new(Variable("last_sale".into())),
+                    Box::new(Literal(Number(F64Value(1.0)))),
+                ),
+            })
+            ));
+        }
+
+        #[test]
+        fn test_create_table_with_constraints() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.constraints.stocks") (
+                    symbol: String(8) not null unique,
+                    exchange: String(8),
+                    last_sale: f64 check(last_sale >= 0.0)
+                )
+            "#).unwrap();
+            assert_eq!(
+                code.to_code(),
+                r#"create table ns("compiler.constraints.stocks") (symbol: String(8) not null unique, exchange: String(8), last_sale: f64 check(last_sale >= 0.0))"#
+            );
+            assert_eq!(Compiler::build(code.to_code().as_str()).unwrap(), code);
+        }
+
+        #[test]
+        fn test_create_table_with_foreign_keys() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.fk.trades") (
+                    account_id: i64 not null references ns("compiler.fk.accounts")(id) on delete set null,
+                    broker_id: i64 references ns("compiler.fk.brokers")(id) on delete restrict,
+                    symbol: String(8)
+                )
+            "#).unwrap();
+            assert_eq!(
+                code.to_code(),
+                r#"create table ns("compiler.fk.trades") (account_id: i64 not null references ns("compiler.fk.accounts")(id) on delete set null, broker_id: i64 references ns("compiler.fk.brokers")(id), symbol: String(8))"#
+            );
+            assert_eq!(Compiler::build(code.to_code().as_str()).unwrap(), code);
+        }
+
+        #[test]
+        fn test_create_sequence() {
+            let code = Compiler::build(r#"
+                create sequence ns("compiler.sequence.trade_ids") start with 1000 increment by 10
+            "#).unwrap();
+            assert_eq!(code, DatabaseOp(Mutation(Create {
+                path: Box::new(Ns(Box::new(Literal(StringValue("compiler.sequence.trade_ids".into()))))),
+                entity: SequenceEntity {
+                    start: Some(Box::new(Literal(Number(I64Value(1000))))),
+                    increment: Some(Box::new(Literal(Number(I64Value(10))))),
+                },
+            })));
+            assert_eq!(
+                code.to_code(),
+                r#"create sequence ns("compiler.sequence.trade_ids") start with 1000 increment by 10"#
+            );
+            let code = Compiler::build(r#"
+                nextval(ns("compiler.sequence.trade_ids"))
+            "#).unwrap();
+            assert_eq!(code.to_code(), r#"nextval(ns("compiler.sequence.trade_ids"))"#);
+        }
+
+        #[test]
+        fn test_create_table_with_identity() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.identity.stocks") (id: i64 identity, symbol: String(8))
+            "#).unwrap();
+            assert_eq!(
+                code.to_code(),
+                r#"create table ns("compiler.identity.stocks") (id: i64 identity, symbol: String(8))"#
+            );
+            assert_eq!(Compiler::build(code.to_code().as_str()).unwrap(), code);
+        }
+
+        #[test]
+        fn test_create_table_with_ttl() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.ttl.quotes") (symbol: String(8), updated_time: Date)
+                    with ttl("90m", updated_time)
+            "#).unwrap();
+            assert_eq!(
+                code.to_code(),
+                r#"create table ns("compiler.ttl.quotes") (symbol: String(8), updated_time: Date) with ttl("90m", updated_time)"#
+            );
+            let code = Compiler::build(r#"
+                create table ns("compiler.ttl.quotes") (symbol: String(8), updated_time: Date)
+                    with ttl(86400000, updated_time)
+            "#).unwrap();
+            assert!(code.to_code().ends_with(r#"with ttl("1d", updated_time)"#));
+            assert!(Compiler::build(r#"
+                create table ns("compiler.ttl.quotes") (updated_time: Date) with ttl("5y", updated_time)
+            "#).is_err());
+        }
+
+        #[test]
+        fn test_create_temp_table() {
+            let code = Compiler::build(r#"
+                create temp table scratch (symbol: String(8), last_sale: f64) with journaling
+            "#).unwrap();
+            assert_eq!(
+                code.to_code(),
+                r#"create temp table scratch (symbol: String(8), last_sale: f64) with journaling"#
+            );
+            assert!(Compiler::build(r#"create temp sequence ids"#).is_err());
+        }
+
+        #[test]
+        fn test_create_table_with_hybrid() {
+            let code = Compiler::build(r#"
+                create table ns("compiler.hybrid.quotes") (symbol: String(8), last_sale: f64)
+                    with hybrid(1000)
+            "#).unwrap();
+            assert_eq!(
+                code.to_code(),
+                r#"create table ns("compiler.hybrid.quotes") (symbol: String(8), last_sale: f64) with hybrid(1000)"#
+            );
+            for policy in ["lru", "recent_appends"] {
+                let code = Compiler::build(format!(r#"
+                    create table ns("compiler.hybrid.quotes") (symbol: String(8)) with hybrid(50, {})
+                "#, policy).as_str()).unwrap();
+                assert!(code.to_code().ends_with(format!("with hybrid(50, {})", policy).as_str()));
+                assert_eq!(Compiler::build(code.to_code().as_str()).unwrap(), code);
+            }
+            assert!(Compiler::build(r#"
+                create table ns("compiler.hybrid.quotes") (symbol: String(8)) with hybrid(50, fifo)
+            "#).is_err());
+        }
+
+        #[test]
+        fn test_create_trigger() {
+            let code = Compiler::build(r#"
+                create trigger audit on stocks after update, delete fn(old, new) => old
+            "#).unwrap();
+            assert_eq!(
+                code.to_code(),
+                "create trigger audit on stocks after update, delete fn(old, new) => old"
+            );
+            assert!(matches!(code, DatabaseOp(Mutation(Create { entity: TriggerEntity { .. }, .. }))));
+        }
+
         #[test]
         fn test_from_where_limit() {
             let opcodes = Compiler::build(r#"
@@ -2393,6 +3030,18 @@ mod tests {
             })))
         }
 
+        #[test]
+        fn test_rename_table() {
+            let model = Compiler::build(r#"
+                rename table ns("a.b.stocks") to ns("a.archive.stocks_2025")
+            "#).unwrap();
+            assert_eq!(model, DatabaseOp(Mutation(Mutations::Rename {
+                path: Box::new(Ns(Box::new(Literal(StringValue("a.b.stocks".into()))))),
+                target: Box::new(Ns(Box::new(Literal(StringValue("a.archive.stocks_2025".into()))))),
+            })));
+            assert_eq!(model.to_code(), r#"rename table ns("a.b.stocks") to ns("a.archive.stocks_2025")"#);
+        }
+
         #[test]
         fn test_select_from_variable() {
             let opcodes = Compiler::build(r#"
@@ -0,0 +1,1201 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// constraints module
+////////////////////////////////////////////////////////////////////
+
+use crate::columns::Column;
+use crate::data_types::DataType::NumberType;
+use crate::dataframe::Dataframe;
+use crate::errors::Errors::{ConstraintViolation, Exact};
+use crate::errors::{throw, Errors};
+use crate::expression::Expression;
+use crate::field::FieldMetadata;
+use crate::machine::Machine;
+use crate::named_sequences::NamedSequence;
+use crate::namespace_leases::NamespaceLease;
+use crate::namespaces::Namespace;
+use crate::numbers::Numbers::I64Value;
+use crate::object_config::ObjectConfig;
+use crate::page_cache::{FileId, PageCache};
+use crate::row_collection::RowCollection;
+use crate::structures::Row;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{Boolean, ErrorValue, NamespaceValue, Null, Number, Undefined};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// the maximum number of key indices retained for the checking of foreign keys
+const KEY_INDEX_CAPACITY: usize = 64;
+
+/// the maximum number of unique indices retained for the checking of unique constraints
+const UNIQUE_INDEX_CAPACITY: usize = 64;
+
+/// the identity, generation, length and modification time (seconds and nanoseconds) of a file
+pub type FileVersion = (FileId, u64, u64, i64, i64);
+
+/// the key indices of referenced (and referencing) columns by table and column
+static KEY_INDICES: OnceLock<Mutex<BTreeMap<(Namespace, String), KeyIndex>>> = OnceLock::new();
+
+/// the unique indices of tables by table and (indexed) columns
+type UniqueIndices = BTreeMap<(Namespace, Vec<String>), UniqueIndex>;
+static UNIQUE_INDICES: OnceLock<Mutex<UniqueIndices>> = OnceLock::new();
+
+/// Represents a constraint declared upon the column(s) of a table
+/// ex: create table stocks (symbol: String(8) not null unique, last_sale: f64 check(last_sale >= 0.0))
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum Constraint {
+    /// the (non-null) values of the column must satisfy a condition
+    Check(String, Box<Expression>),
+    /// the values of the column are drawn from the table's own sequence (when not provided)
+    Identity(String),
+    /// the values of the column must not be null
+    NotNull(String),
+    /// the (non-null) values of the column must exist within a column of another table
+    References(ForeignKey),
+    /// the (combined) values of the columns must be distinct across the active rows
+    Unique(Vec<String>),
+}
+
+impl Constraint {
+    /// Returns the names of the columns governed by this constraint
+    pub fn get_column_names(&self) -> Vec<String> {
+        match self {
+            Constraint::Check(name, _) | Constraint::Identity(name) | Constraint::NotNull(name) =>
+                vec![name.to_owned()],
+            Constraint::References(fk) => vec![fk.column.to_owned()],
+            Constraint::Unique(names) => names.to_owned(),
+        }
+    }
+
+    /// Returns true, if this constraint governs the specified column
+    pub fn is_declared_on(&self, name: &str) -> bool {
+        self.get_column_names().iter().any(|n| n == name)
+    }
+
+    /// Decompiles the constraint as it would appear within a column definition
+    pub fn to_code(&self) -> String {
+        match self {
+            Constraint::Check(_, condition) => format!("check({})", condition.to_code()),
+            Constraint::Identity(..) => "identity".into(),
+            Constraint::NotNull(..) => "not null".into(),
+            Constraint::References(fk) => fk.to_code(),
+            Constraint::Unique(..) => "unique".into(),
+        }
+    }
+
+    /// Returns a copy of this constraint with the specified column renamed
+    pub fn with_renamed_column(self, old_name: &str, new_name: &str) -> Self {
+        let rename = |name: String| if name == old_name { new_name.to_string() } else { name };
+        match self {
+            Constraint::Check(name, condition) => Constraint::Check(rename(name), condition),
+            Constraint::Identity(name) => Constraint::Identity(rename(name)),
+            Constraint::NotNull(name) => Constraint::NotNull(rename(name)),
+            Constraint::References(fk) => Constraint::References(ForeignKey { column: rename(fk.column), ..fk }),
+            Constraint::Unique(names) => Constraint::Unique(names.into_iter().map(rename).collect()),
+        }
+    }
+
+    /// Returns a copy of this constraint with references to the specified table redirected
+    pub fn with_renamed_table(self, old_ns: &Namespace, new_ns: &Namespace) -> Self {
+        match self {
+            Constraint::References(fk) if fk.parent == *old_ns =>
+                Constraint::References(ForeignKey { parent: new_ns.to_owned(), ..fk }),
+            constraint => constraint
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+// Constraints class
+////////////////////////////////////////////////////////////////////
+
+/// Enforces the constraints (and column defaults) of a table upon the rows written to it
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct Constraints {
+    table: String,
+    constraints: Vec<Constraint>,
+}
+
+impl Constraints {
+    /// Returns the constraints of a table; or [None] if the table neither declares
+    /// constraints nor column defaults.
+    pub fn build(
+        table: &str,
+        columns: &Vec<Column>,
+        constraints: Vec<Constraint>,
+    ) -> Option<Self> {
+        // foreign keys are enforced by the mutations of the related tables (see [ForeignKeys])
+        let constraints = constraints.into_iter()
+            .filter(|c| !matches!(c, Constraint::References(..)))
+            .collect::<Vec<_>>();
+        let has_defaults = columns.iter()
+            .any(|c| !matches!(c.get_default_value(), Null | Undefined));
+        if constraints.is_empty() && !has_defaults {
+            return None;
+        }
+        Some(Self { table: table.to_string(), constraints })
+    }
+
+    /// Returns the constraints of a table (including its unique indices) given its configuration
+    pub fn from_config(ns: &Namespace, cfg: &ObjectConfig, columns: &Vec<Column>) -> Option<Self> {
+        let mut constraints = cfg.get_constraints();
+        for index in cfg.get_indices().iter().filter(|index| index.is_unique()) {
+            let constraint = Constraint::Unique(index.get_indexed_column_names().to_owned());
+            if !constraints.contains(&constraint) { constraints.push(constraint) }
+        }
+        Self::build(ns.get_full_name().as_str(), columns, constraints)
+    }
+
+    /// Applies the column defaults to a row about to be written to the specified position;
+    /// returning the resultant row or the first constraint it violates.
+    pub fn validate_row(
+        &self,
+        rc: &(impl RowCollection + ?Sized),
+        id: usize,
+        row: Row,
+    ) -> Result<Row, Errors> {
+        let row = self.apply_defaults(rc, row)?;
+        for constraint in self.constraints.iter() {
+            self.verify(rc, id, &row, constraint)?;
+        }
+        Ok(row)
+    }
+
+    /// Applies the column defaults to a batch of rows about to be appended at the specified
+    /// position; uniqueness is verified once for the entire batch (rather than once per row).
+    pub fn validate_rows(
+        &self,
+        rc: &(impl RowCollection + ?Sized),
+        start: usize,
+        rows: Vec<Row>,
+    ) -> Result<Vec<Row>, Errors> {
+        let mut validated = Vec::with_capacity(rows.len());
+        for (n, row) in rows.into_iter().enumerate() {
+            let row = self.apply_defaults(rc, row.with_row_id(start + n))?;
+            for constraint in self.constraints.iter() {
+                if !matches!(constraint, Constraint::Unique(..)) {
+                    self.verify(rc, start + n, &row, constraint)?;
+                }
+            }
+            validated.push(row);
+        }
+        for constraint in self.constraints.iter() {
+            if let Constraint::Unique(names) = constraint {
+                self.verify_unique_batch(rc, &validated, names, constraint)?;
+            }
+        }
+        Ok(validated)
+    }
+
+    /// Applies the column defaults (and identities) to a row
+    fn apply_defaults(
+        &self,
+        rc: &(impl RowCollection + ?Sized),
+        row: Row,
+    ) -> Result<Row, Errors> {
+        let columns = rc.get_columns();
+        let values = row.get_values().iter().zip(columns.iter())
+            .map(|(value, column)| match (value, column.get_default_value()) {
+                (Null | Undefined, Null | Undefined) => value.to_owned(),
+                (Null | Undefined, default_value) => default_value,
+                (value, _) => value.to_owned(),
+            })
+            .collect::<Vec<_>>();
+        let mut row = Row::new(row.get_id(), values);
+        for constraint in self.constraints.iter() {
+            if let Constraint::Identity(name) = constraint {
+                self.apply_identity(rc, &mut row, name)?;
+            }
+        }
+        Ok(row)
+    }
+
+    /// Applies the column default to a value about to be written to the specified field;
+    /// returning the resultant value or the first constraint it violates.
+    pub fn validate_field(
+        &self,
+        rc: &(impl RowCollection + ?Sized),
+        id: usize,
+        column_id: usize,
+        value: TypedValue,
+    ) -> Result<TypedValue, Errors> {
+        let columns = rc.get_columns();
+        let mut values = match rc.read_one(id) {
+            Ok(Some(row)) => row.get_values(),
+            Ok(None) => columns.iter().map(|_| Null).collect(),
+            Err(err) => return Err(Errors::Exact(err.to_string()))
+        };
+        if column_id < values.len() {
+            values[column_id] = value;
+        }
+        let row = self.validate_row(rc, id, Row::new(id, values))?;
+        Ok(row[column_id].to_owned())
+    }
+
+    /// Draws the next identity of a column for a row lacking one. The identities of a
+    /// namespaced table are durable; those of a declared table continue from its largest one.
+    fn apply_identity(
+        &self,
+        rc: &(impl RowCollection + ?Sized),
+        row: &mut Row,
+        name: &str,
+    ) -> Result<(), Errors> {
+        let columns = rc.get_columns();
+        let index = match columns.iter().position(|c| c.get_name() == name) {
+            Some(index) if matches!(row[index], Null | Undefined) => index,
+            _ => return Ok(())
+        };
+        let value = match Namespace::parse(self.table.as_str()) {
+            Ok(ns) => NamedSequence::next_identity(&ns, name)
+                .map_err(|err| Errors::Exact(err.to_string()))?,
+            Err(..) => rc.read_active_rows()
+                .map_err(|err| Errors::Exact(err.to_string()))?
+                .iter()
+                .filter_map(|other| match &other[index] {
+                    Number(n) => Some(n.to_i64()),
+                    _ => None
+                })
+                .max()
+                .unwrap_or(0) + 1,
+        };
+        let mut values = row.get_values();
+        values[index] = match columns[index].get_data_type() {
+            NumberType(kind) => Number(kind.convert(&I64Value(value))),
+            _ => Number(I64Value(value)),
+        };
+        *row = Row::new(row.get_id(), values);
+        Ok(())
+    }
+
+    /// Verifies a unique constraint against a batch of rows; the keys of the existing rows are
+    /// looked up within the unique index of a namespaced table, or else gathered with a single scan.
+    fn verify_unique_batch(
+        &self,
+        rc: &(impl RowCollection + ?Sized),
+        rows: &Vec<Row>,
+        names: &Vec<String>,
+        constraint: &Constraint,
+    ) -> Result<(), Errors> {
+        let columns = rc.get_columns();
+        let keys = rows.iter()
+            .map(|row| UniqueIndex::get_key(columns, names, row))
+            .collect::<Vec<_>>();
+        let existing = match Namespace::parse(self.table.as_str()) {
+            Ok(ns) => UniqueIndex::with_index(&ns, names, rc, |index| keys.iter().flatten()
+                .filter(|key| index.ids.contains_key(*key))
+                .cloned()
+                .collect::<HashSet<_>>()),
+            Err(..) => rc.read_active_rows().map(|rows| rows.iter()
+                .filter_map(|row| UniqueIndex::get_key(columns, names, row))
+                .collect::<HashSet<_>>()),
+        }.map_err(|err| Errors::Exact(err.to_string()))?;
+        let mut batch = HashSet::new();
+        for (row, key) in rows.iter().zip(keys) {
+            if let Some(key) = key {
+                if existing.contains(&key) || !batch.insert(key) {
+                    return Err(ConstraintViolation(
+                        self.table.to_owned(), names.join(", "), constraint.to_code(),
+                        Self::get_key_value(columns, names, row).to_code()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the value of the key of a row (e.g. a tuple for a composite key)
+    fn get_key_value(columns: &[Column], names: &[String], row: &Row) -> TypedValue {
+        let values = names.iter()
+            .filter_map(|name| columns.iter().position(|c| c.get_name() == name))
+            .map(|index| row[index].to_owned())
+            .collect::<Vec<_>>();
+        match values.as_slice() {
+            [value] => value.to_owned(),
+            values => TypedValue::TupleValue(values.to_vec()),
+        }
+    }
+
+    /// Returns the version of a namespaced table declaring unique constraints (see [UniqueIndex::get_version]);
+    /// which, taken prior to a write, allows the write to be recorded within the table's unique indices
+    /// (see [Constraints::index_rows]).
+    pub fn get_version(&self) -> Option<Vec<FileVersion>> {
+        if !self.constraints.iter().any(|c| matches!(c, Constraint::Unique(..))) {
+            return None;
+        }
+        UniqueIndex::get_version(&Namespace::parse(self.table.as_str()).ok()?).ok()
+    }
+
+    /// Records the rows just written to a namespaced table within its unique indices (see [UniqueIndex]);
+    /// given the version of the table prior to the write (see [Constraints::get_version]).
+    pub fn index_rows(
+        &self,
+        rc: &(impl RowCollection + ?Sized),
+        before: Option<Vec<FileVersion>>,
+        rows: &[Row],
+    ) {
+        let (ns, before) = match (Namespace::parse(self.table.as_str()), before) {
+            (Ok(ns), Some(before)) => (ns, before),
+            _ => return
+        };
+        for constraint in self.constraints.iter() {
+            if let Constraint::Unique(names) = constraint {
+                let keys = rows.iter()
+                    .map(|row| (row.get_id(), UniqueIndex::get_key(rc.get_columns(), names, row)))
+                    .collect::<Vec<_>>();
+                UniqueIndex::record(&ns, names, &before, keys);
+            }
+        }
+    }
+
+    /// Verifies a single constraint against a row about to be written to the specified position
+    fn verify(
+        &self,
+        rc: &(impl RowCollection + ?Sized),
+        id: usize,
+        row: &Row,
+        constraint: &Constraint,
+    ) -> Result<(), Errors> {
+        let columns = rc.get_columns();
+        let value_of = |name: &str| columns.iter().position(|c| c.get_name() == name)
+            .map(|index| row[index].to_owned())
+            .unwrap_or(Undefined);
+        let violation = |name: &str, value: &TypedValue| Err(ConstraintViolation(
+            self.table.to_owned(), name.to_string(), constraint.to_code(), value.to_code()));
+        match constraint {
+            Constraint::Check(name, condition) => {
+                let value = value_of(name);
+                if matches!(value, Null | Undefined) { return Ok(()); }
+                match Machine::new_platform().with_row(columns, row).evaluate(condition) {
+                    Ok((_, Boolean(true))) => Ok(()),
+                    _ => violation(name, &value)
+                }
+            }
+            Constraint::Identity(..) | Constraint::References(..) => Ok(()),
+            Constraint::NotNull(name) => match value_of(name) {
+                value @ (Null | Undefined) => violation(name, &value),
+                _ => Ok(())
+            }
+            Constraint::Unique(names) => {
+                let key = match UniqueIndex::get_key(columns, names, row) {
+                    Some(key) => key,
+                    None => return Ok(())
+                };
+                let found = match Namespace::parse(self.table.as_str()) {
+                    // namespaced tables are verified via their unique index
+                    Ok(ns) => UniqueIndex::find(&ns, names, rc, &key),
+                    // others (e.g. declared tables) are scanned
+                    Err(..) => rc.read_active_rows().map(|rows| rows.iter()
+                        .find(|other| UniqueIndex::get_key(columns, names, other).as_ref() == Some(&key))
+                        .map(|other| other.get_id())),
+                }.map_err(|err| Errors::Exact(err.to_string()))?;
+                match found {
+                    Some(other_id) if other_id != id =>
+                        violation(names.join(", ").as_str(), &Self::get_key_value(columns, names, row)),
+                    _ => Ok(())
+                }
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+// ForeignKey class
+////////////////////////////////////////////////////////////////////
+
+/// Represents the action taken upon the referencing rows of a deleted (parent) row
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum ReferentialAction {
+    /// the referencing rows are deleted as well
+    Cascade,
+    /// the deletion is rejected
+    Restrict,
+    /// the referencing columns are set to null
+    SetNull,
+}
+
+impl ReferentialAction {
+    pub fn to_code(&self) -> String {
+        match self {
+            ReferentialAction::Cascade => "cascade".into(),
+            ReferentialAction::Restrict => "restrict".into(),
+            ReferentialAction::SetNull => "set null".into(),
+        }
+    }
+}
+
+/// Represents a reference from a column of a (child) table to a column of a (parent) table
+/// ex: account_id: i64 references ns("finance.accounts.accounts")(id) on delete cascade
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct ForeignKey {
+    column: String,
+    parent: Namespace,
+    parent_column: String,
+    on_delete: ReferentialAction,
+}
+
+impl ForeignKey {
+    pub fn new(
+        column: &str,
+        parent: Namespace,
+        parent_column: &str,
+        on_delete: ReferentialAction,
+    ) -> Self {
+        Self { column: column.into(), parent, parent_column: parent_column.into(), on_delete }
+    }
+
+    pub fn to_code(&self) -> String {
+        let on_delete = match self.on_delete {
+            ReferentialAction::Restrict => String::new(),
+            action => format!(" on delete {}", action.to_code())
+        };
+        format!("references ns(\"{}\")({}){}", self.parent.get_full_name(), self.parent_column, on_delete)
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+// ForeignKeys class
+////////////////////////////////////////////////////////////////////
+
+/// Enforces the referential integrity between a table and the tables related to it;
+/// i.e. the tables it references and the tables referencing it (its dependents).
+#[derive(Clone, Debug)]
+pub struct ForeignKeys {
+    ns: Namespace,
+    columns: Vec<Column>,
+    references: Vec<ForeignKey>,
+    dependents: Vec<(Namespace, ForeignKey)>,
+}
+
+impl ForeignKeys {
+    /// Loads the foreign keys of a table, and those of the tables referencing it
+    pub fn load(ns: &Namespace) -> std::io::Result<Self> {
+        let cfg = ObjectConfig::load(ns)?;
+        let references = cfg.get_constraints().into_iter()
+            .filter_map(|c| match c {
+                Constraint::References(fk) => Some(fk),
+                _ => None
+            })
+            .collect();
+        let mut dependents = Vec::new();
+        for child_ns in cfg.get_dependents() {
+            for constraint in ObjectConfig::load(&child_ns)?.get_constraints() {
+                match constraint {
+                    Constraint::References(fk) if fk.parent == *ns =>
+                        dependents.push((child_ns.to_owned(), fk)),
+                    _ => {}
+                }
+            }
+        }
+        Ok(Self {
+            ns: ns.to_owned(),
+            columns: Column::from_parameters(&cfg.get_columns()),
+            references,
+            dependents,
+        })
+    }
+
+    /// Loads the foreign keys of the table referenced by a namespace value;
+    /// returning [None] for other values, or tables neither referencing nor referenced.
+    pub fn load_value(table: &TypedValue) -> std::io::Result<Option<Self>> {
+        match table {
+            NamespaceValue(ns) if Path::new(&ns.get_config_file_path()).exists() => {
+                let fks = Self::load(ns)?;
+                Ok(if fks.references.is_empty() && fks.dependents.is_empty() { None } else { Some(fks) })
+            }
+            _ => Ok(None)
+        }
+    }
+
+    /// Registers a (child) table as a dependent of each table referenced by its constraints
+    pub fn register(ns: &Namespace, constraints: &Vec<Constraint>) -> std::io::Result<()> {
+        // verify all references before any referenced table is changed
+        let mut parents = Vec::new();
+        for constraint in constraints {
+            if let Constraint::References(fk) = constraint {
+                let cfg = ObjectConfig::load(&fk.parent)
+                    .or_else(|_| throw(Exact(format!("Referenced table {} not found", fk.parent))))?;
+                if !cfg.get_columns().iter().any(|p| p.get_name() == fk.parent_column) {
+                    return throw(Exact(format!("Column '{}' not found in {}", fk.parent_column, fk.parent)));
+                }
+                if !Self::is_key(&cfg, &fk.parent_column) {
+                    return throw(Exact(format!("Column '{}' of {} must be unique or indexed to be referenced",
+                                               fk.parent_column, fk.parent)));
+                }
+                parents.push(&fk.parent);
+            }
+        }
+        for parent in parents {
+            let cfg = ObjectConfig::load(parent)?;
+            let mut dependents = cfg.get_dependents();
+            if !dependents.contains(ns) {
+                dependents.push(ns.to_owned());
+                cfg.with_dependents(dependents).save(parent)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Redirects the relationships of a renamed table (i.e. the references of its dependents,
+    /// and the dependents of the tables it references) from its old namespace to its new one.
+    pub fn rename(old_ns: &Namespace, new_ns: &Namespace) -> std::io::Result<()> {
+        let cfg = ObjectConfig::load(new_ns)?;
+        let rename = |ns: Namespace| if ns == *old_ns { new_ns.to_owned() } else { ns };
+        for child_ns in cfg.get_dependents().into_iter().filter(|ns| ns != old_ns) {
+            let child_cfg = ObjectConfig::load(&child_ns)?;
+            let constraints = child_cfg.get_constraints().into_iter()
+                .map(|c| c.with_renamed_table(old_ns, new_ns))
+                .collect();
+            child_cfg.with_constraints(constraints).save(&child_ns)?;
+        }
+        for constraint in cfg.get_constraints() {
+            match constraint {
+                Constraint::References(fk) if fk.parent != *old_ns => {
+                    let parent_cfg = ObjectConfig::load(&fk.parent)?;
+                    let dependents = parent_cfg.get_dependents().into_iter().map(rename).collect();
+                    parent_cfg.with_dependents(dependents).save(&fk.parent)?;
+                }
+                _ => {}
+            }
+        }
+
+        // a table may also reference itself
+        let constraints = cfg.get_constraints().into_iter()
+            .map(|c| c.with_renamed_table(old_ns, new_ns))
+            .collect();
+        let dependents = cfg.get_dependents().into_iter().map(rename).collect();
+        cfg.with_constraints(constraints).with_dependents(dependents).save(new_ns)
+    }
+
+    /// Returns the keys (i.e. the distinct values of the referenced column) of each referenced table
+    pub fn get_parent_keys(&self) -> std::io::Result<Vec<Arc<BTreeSet<String>>>> {
+        self.references.iter().map(|fk| KeyIndex::get_keys(&fk.parent, &fk.parent_column)).collect()
+    }
+
+    /// Indicates whether a column is a key of a table (i.e. it is unique or indexed)
+    fn is_key(cfg: &ObjectConfig, column: &str) -> bool {
+        let columns = vec![column.to_string()];
+        cfg.get_indices().iter().any(|index| index.get_indexed_column_names() == &columns)
+            || cfg.get_constraints().iter().any(|c| match c {
+            Constraint::Identity(name) => name == column,
+            Constraint::Unique(names) => names == &columns,
+            _ => false
+        })
+    }
+
+    /// Verifies that each (non-null) reference of the given row exists within the referenced table
+    pub fn verify_row(&self, parent_keys: &Vec<Arc<BTreeSet<String>>>, row: &Row) -> std::io::Result<()> {
+        for (fk, keys) in self.references.iter().zip(parent_keys.iter()) {
+            if let Some(index) = self.columns.iter().position(|c| c.get_name() == fk.column) {
+                let value = &row[index];
+                if !matches!(value, Null | Undefined) && !keys.contains(&value.to_code()) {
+                    return throw(ConstraintViolation(self.ns.get_full_name(), fk.column.to_owned(),
+                                                     fk.to_code(), value.to_code()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Verifies that each (non-null) reference of the given rows exists within the referenced tables
+    pub fn verify_rows(&self, rows: &Vec<Row>) -> std::io::Result<()> {
+        if self.references.is_empty() { return Ok(()); }
+        let parent_keys = self.get_parent_keys()?;
+        rows.iter().try_for_each(|row| self.verify_row(&parent_keys, row))
+    }
+
+    /// Verifies an updated row; its references must exist within the referenced tables,
+    /// and its referenced (key) values may only change while no dependent row refers to them.
+    pub fn verify_update(
+        &self,
+        parent_keys: &Vec<Arc<BTreeSet<String>>>,
+        old_row: &Row,
+        new_row: &Row,
+    ) -> std::io::Result<()> {
+        self.verify_row(parent_keys, new_row)?;
+        for (child_ns, fk) in self.dependents.iter() {
+            if let Some(index) = self.columns.iter().position(|c| c.get_name() == fk.parent_column) {
+                if old_row[index] != new_row[index] && !matches!(old_row[index], Null | Undefined)
+                    && KeyIndex::get_keys(child_ns, &fk.column)?.contains(&old_row[index].to_code()) {
+                    return throw(ConstraintViolation(child_ns.get_full_name(), fk.column.to_owned(),
+                                                     fk.to_code(), old_row[index].to_code()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the delete actions of the dependent tables for the rows about to be deleted
+    /// from this table; all restrictions are verified before any dependent row is changed.
+    pub fn delete_dependents(&self, rows: &Vec<Row>) -> std::io::Result<()> {
+        let mut actions = Vec::new();
+        for (child_ns, fk) in self.dependents.iter() {
+            let index = match self.columns.iter().position(|c| c.get_name() == fk.parent_column) {
+                Some(index) => index,
+                None => continue
+            };
+            let keys = rows.iter()
+                .map(|row| &row[index])
+                .filter(|value| !matches!(value, Null | Undefined))
+                .map(|value| value.to_code())
+                .collect::<BTreeSet<_>>();
+            let child = Dataframe::open(child_ns)?;
+            let child_index = match child.get_columns().iter().position(|c| c.get_name() == fk.column) {
+                Some(index) => index,
+                None => continue
+            };
+            let mut child_rows = Vec::new();
+            for id in child.get_indices()? {
+                if let Some(row) = child.read_one(id)? {
+                    if keys.contains(&row[child_index].to_code()) { child_rows.push(row) }
+                }
+            }
+            match (fk.on_delete, child_rows.first()) {
+                (_, None) => {}
+                (ReferentialAction::Restrict, Some(row)) =>
+                    return throw(ConstraintViolation(child_ns.get_full_name(), fk.column.to_owned(),
+                                                     fk.to_code(), row[child_index].to_code())),
+                (action, Some(..)) => actions.push((child_ns, child, child_index, action, child_rows)),
+            }
+        }
+        for (child_ns, mut child, child_index, action, child_rows) in actions {
+            if action == ReferentialAction::Cascade {
+                Self::load(child_ns)?.delete_dependents(&child_rows)?;
+            }
+            for row in child_rows {
+                // null fixed-length values are written as inactive fields
+                let result = match action {
+                    ReferentialAction::Cascade => child.delete_row(row.get_id()),
+                    _ if child.get_columns()[child_index].get_data_type().is_variable_length() =>
+                        child.overwrite_field(row.get_id(), child_index, Null),
+                    _ => child.overwrite_field_metadata(row.get_id(), child_index, FieldMetadata::new(false)),
+                };
+                if let ErrorValue(err) = result { return throw(err); }
+            }
+        }
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+// KeyIndex class
+////////////////////////////////////////////////////////////////////
+
+/// Represents the distinct (non-null) values of a column of a table; the index is retained
+/// (see [KEY_INDICES]) and reused for as long as the files of the table are unchanged.
+#[derive(Clone, Debug)]
+struct KeyIndex {
+    keys: Arc<BTreeSet<String>>,
+    version: Vec<FileVersion>,
+    tick: u64,
+}
+
+impl KeyIndex {
+    /// Returns the keys of a column of a table; rebuilding its index if the table has changed
+    fn get_keys(ns: &Namespace, column: &str) -> std::io::Result<Arc<BTreeSet<String>>> {
+        let key = (ns.to_owned(), column.to_string());
+        let version = Self::get_version(ns)?;
+        let mut indices = Self::lock()?;
+        let tick = indices.values().map(|index| index.tick).max().unwrap_or(0) + 1;
+        if let Some(index) = indices.get_mut(&key) {
+            if index.version == version {
+                index.tick = tick;
+                return Ok(index.keys.clone());
+            }
+        }
+        drop(indices);
+
+        let (version, keys) = Self::collect_keys(ns, column)?;
+        let keys = Arc::new(keys);
+        let mut indices = Self::lock()?;
+        while indices.len() >= KEY_INDEX_CAPACITY && !indices.contains_key(&key) {
+            match indices.iter().min_by_key(|(_, index)| index.tick).map(|(key, _)| key.to_owned()) {
+                Some(lru) => { indices.remove(&lru); }
+                None => break
+            }
+        }
+        indices.insert(key, Self { keys: keys.clone(), version, tick });
+        Ok(keys)
+    }
+
+    /// Collects the distinct (non-null) values of a column of a table, along with the version
+    /// of the table they were collected from. The version is taken once the table is open
+    /// (as opening it discards its cached pages), but before its rows are read; so writes
+    /// made meanwhile cause the index to be rebuilt upon its next use.
+    fn collect_keys(ns: &Namespace, column: &str) -> std::io::Result<(Vec<FileVersion>, BTreeSet<String>)> {
+        let df = Dataframe::open(ns)?;
+        let version = Self::get_version(ns)?;
+        let index = match df.get_columns().iter().position(|c| c.get_name() == column) {
+            Some(index) => index,
+            None => return throw(Exact(format!("Column '{}' not found in {}", column, ns)))
+        };
+        let mut keys = BTreeSet::new();
+        for id in df.get_indices()? {
+            if let Some(row) = df.read_one(id)? {
+                if !matches!(row[index], Null | Undefined) { keys.insert(row[index].to_code()); }
+            }
+        }
+        Ok((version, keys))
+    }
+
+    /// Identifies the state of the files of a table (e.g. its table, BLOB and partition files)
+    /// by the number of modifications made to each of them (see [PageCache::get_generation]);
+    /// as well as their lengths and modification times (e.g. for changes made by other processes).
+    fn get_version(ns: &Namespace) -> std::io::Result<Vec<FileVersion>> {
+        let mut version = Vec::new();
+        let mut dirs = vec![PathBuf::from(ns.get_root_path())];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(dir)? {
+                // files may be removed meanwhile (e.g. staging files)
+                let (path, metadata) = match entry.and_then(|entry| Ok((entry.path(), entry.metadata()?))) {
+                    Ok(found) => found,
+                    Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(err) => return Err(err)
+                };
+                if metadata.is_dir() {
+                    dirs.push(path);
+                } else {
+                    let id = (metadata.dev(), metadata.ino());
+                    let generation = PageCache::shared().get_generation(id)?;
+                    version.push((id, generation, metadata.len(), metadata.mtime(), metadata.mtime_nsec()));
+                }
+            }
+        }
+        version.sort();
+        Ok(version)
+    }
+
+    fn lock() -> std::io::Result<std::sync::MutexGuard<'static, BTreeMap<(Namespace, String), KeyIndex>>> {
+        KEY_INDICES.get_or_init(|| Mutex::new(BTreeMap::new())).lock()
+            .or_else(|err| throw(Exact(err.to_string())))
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+// UniqueIndex class
+////////////////////////////////////////////////////////////////////
+
+/// Represents the unique index of a table (see [HashIndexConfig]); i.e. the ID of the row holding
+/// each (non-null) key of the indexed columns. Like a [KeyIndex], the index is retained
+/// (see [UNIQUE_INDICES]) and reused for as long as the files of the table are unchanged.
+/// The writes validated by the table's [Constraints] are recorded within the index as they are made
+/// (see [Constraints::index_rows]); any other change (e.g. a deletion) causes it to be rebuilt upon its next use.
+/// NOTE: as with the verification of uniqueness itself, writes to a table are assumed to be serialized.
+///
+/// [HashIndexConfig]: crate::object_config::HashIndexConfig
+#[derive(Clone, Debug)]
+struct UniqueIndex {
+    ids: HashMap<Vec<String>, usize>,
+    keys: HashMap<usize, Vec<String>>,
+    version: Vec<FileVersion>,
+    tick: u64,
+}
+
+impl UniqueIndex {
+    /// Returns the ID of the row holding a key of the unique index of a table
+    fn find(
+        ns: &Namespace,
+        names: &[String],
+        rc: &(impl RowCollection + ?Sized),
+        key: &[String],
+    ) -> std::io::Result<Option<usize>> {
+        Self::with_index(ns, names, rc, |index| index.ids.get(key).cloned())
+    }
+
+    /// Identifies the state of the files of a table (see [KeyIndex::get_version]). While this process
+    /// holds the lease of the table's namespace, no other process can modify its files; so only the
+    /// modifications made through the page cache are considered (e.g. writing cached pages back,
+    /// which alters the lengths and modification times of the files, leaves the index intact).
+    fn get_version(ns: &Namespace) -> std::io::Result<Vec<FileVersion>> {
+        let version = KeyIndex::get_version(ns)?;
+        Ok(match NamespaceLease::is_held(ns) {
+            true => version.into_iter().map(|(id, generation, ..)| (id, generation, 0, 0, 0)).collect(),
+            false => version
+        })
+    }
+
+    /// Returns the key of a row within the unique index of the given columns;
+    /// or [None] if any of its values is null.
+    fn get_key(columns: &[Column], names: &[String], row: &Row) -> Option<Vec<String>> {
+        names.iter()
+            .map(|name| match columns.iter().position(|c| c.get_name() == name).map(|index| &row[index]) {
+                None | Some(Null | Undefined) => None,
+                Some(value) => Some(value.to_code()),
+            })
+            .collect()
+    }
+
+    /// (Re)assigns the key of a row
+    fn insert(&mut self, id: usize, key: Option<Vec<String>>) {
+        if let Some(old_key) = self.keys.remove(&id) {
+            if self.ids.get(&old_key) == Some(&id) { self.ids.remove(&old_key); }
+        }
+        if let Some(key) = key {
+            self.ids.insert(key.to_owned(), id);
+            self.keys.insert(id, key);
+        }
+    }
+
+    /// Records the keys of the rows written to a table within its unique index; provided the index
+    /// reflected the table as of the given (prior) version. Otherwise, the index is left as is
+    /// (i.e. to be rebuilt upon its next use).
+    fn record(ns: &Namespace, names: &[String], before: &Vec<FileVersion>, keys: Vec<(usize, Option<Vec<String>>)>) {
+        let version = match Self::get_version(ns) {
+            Ok(version) => version,
+            Err(..) => return
+        };
+        let mut indices = match Self::lock() {
+            Ok(indices) => indices,
+            Err(..) => return
+        };
+        if let Some(index) = indices.get_mut(&(ns.to_owned(), names.to_vec())) {
+            if index.version == *before {
+                for (id, key) in keys { index.insert(id, key) }
+                index.version = version;
+            }
+        }
+    }
+
+    /// Passes the unique index of the columns of a table to the given function; rebuilding
+    /// the index from the active rows of the table if the table has changed. The version is
+    /// taken before the rows are read; so writes made meanwhile cause the index to be rebuilt
+    /// upon its next use.
+    fn with_index<A>(
+        ns: &Namespace,
+        names: &[String],
+        rc: &(impl RowCollection + ?Sized),
+        f: impl FnOnce(&Self) -> A,
+    ) -> std::io::Result<A> {
+        let key = (ns.to_owned(), names.to_vec());
+        let version = Self::get_version(ns)?;
+        let mut indices = Self::lock()?;
+        let tick = indices.values().map(|index| index.tick).max().unwrap_or(0) + 1;
+        if let Some(index) = indices.get_mut(&key) {
+            if index.version == version {
+                index.tick = tick;
+                return Ok(f(index));
+            }
+        }
+        drop(indices);
+
+        let mut index = Self { ids: HashMap::new(), keys: HashMap::new(), version, tick };
+        for row in rc.read_active_rows()? {
+            index.insert(row.get_id(), Self::get_key(rc.get_columns(), names, &row));
+        }
+        let result = f(&index);
+        let mut indices = Self::lock()?;
+        while indices.len() >= UNIQUE_INDEX_CAPACITY && !indices.contains_key(&key) {
+            match indices.iter().min_by_key(|(_, index)| index.tick).map(|(key, _)| key.to_owned()) {
+                Some(lru) => { indices.remove(&lru); }
+                None => break
+            }
+        }
+        indices.insert(key, index);
+        Ok(result)
+    }
+
+    fn lock() -> std::io::Result<std::sync::MutexGuard<'static, UniqueIndices>> {
+        UNIQUE_INDICES.get_or_init(|| Mutex::new(BTreeMap::new())).lock()
+            .or_else(|err| throw(Exact(err.to_string())))
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+    use crate::model_row_collection::ModelRowCollection;
+    use crate::numbers::Numbers::F64Value;
+    use crate::testdata::{make_quote, make_quote_columns};
+    use crate::typed_values::TypedValue::{Number, StringValue};
+
+    #[test]
+    fn test_validate_row() {
+        let columns = make_quote_columns();
+        let constraints = Constraints::build("stocks", &columns, vec![
+            Constraint::NotNull("symbol".into()),
+            Constraint::Unique(vec!["symbol".into(), "exchange".into()]),
+        ]).unwrap();
+        let mrc = ModelRowCollection::from_columns_and_rows(&columns, &vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+        ]);
+        assert_eq!(
+            constraints.validate_row(&mrc, 1, make_quote(1, "ABC", "NYSE", 11.88)),
+            Ok(make_quote(1, "ABC", "NYSE", 11.88)));
+        assert_eq!(
+            constraints.validate_row(&mrc, 0, make_quote(0, "ABC", "AMEX", 11.88)),
+            Ok(make_quote(0, "ABC", "AMEX", 11.88)));
+        assert_eq!(
+            constraints.validate_row(&mrc, 1, make_quote(1, "ABC", "AMEX", 11.88)),
+            Err(ConstraintViolation("stocks".into(), "symbol, exchange".into(), "unique".into(), r#"("ABC", "AMEX")"#.into())));
+        assert_eq!(
+            constraints.validate_row(&mrc, 1, Row::new(1, vec![Null, StringValue("AMEX".into()), Number(F64Value(11.88))])),
+            Err(ConstraintViolation("stocks".into(), "symbol".into(), "not null".into(), "null".into())));
+    }
+
+    #[test]
+    fn test_validate_rows() {
+        let columns = make_quote_columns();
+        let constraints = Constraints::build("stocks", &columns, vec![
+            Constraint::Unique(vec!["symbol".into()]),
+        ]).unwrap();
+        let mrc = ModelRowCollection::from_columns_and_rows(&columns, &vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+        ]);
+        assert_eq!(
+            constraints.validate_rows(&mrc, 1, vec![
+                make_quote(0, "UNO", "OTC", 0.2456),
+                make_quote(0, "BIZ", "NYSE", 23.66),
+            ]),
+            Ok(vec![
+                make_quote(1, "UNO", "OTC", 0.2456),
+                make_quote(2, "BIZ", "NYSE", 23.66),
+            ]));
+
+        // duplicates are detected against the table and within the batch itself
+        assert_eq!(
+            constraints.validate_rows(&mrc, 1, vec![make_quote(0, "ABC", "NYSE", 1.0)]),
+            Err(ConstraintViolation("stocks".into(), "symbol".into(), "unique".into(), r#""ABC""#.into())));
+        assert_eq!(
+            constraints.validate_rows(&mrc, 1, vec![
+                make_quote(0, "UNO", "OTC", 0.2456),
+                make_quote(0, "UNO", "NYSE", 0.25),
+            ]),
+            Err(ConstraintViolation("stocks".into(), "symbol".into(), "unique".into(), r#""UNO""#.into())));
+    }
+
+    #[test]
+    fn test_constraints_on_disk() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("constraints.disk.stocks") (
+                symbol: String(8) not null unique,
+                exchange: String(8) = "NYSE",
+                last_sale: f64 check(last_sale >= 0.0)
+            )
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+             { symbol: "UNO", last_sale: 0.2456 }] ~> ns("constraints.disk.stocks")
+        "#).unwrap();
+
+        // the default value was applied to the missing exchange
+        assert_eq!(interpreter.evaluate(r#"
+            ns("constraints.disk.stocks")
+        "#).unwrap().to_table().unwrap().read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "NYSE", 0.2456),
+        ]);
+
+        // violations are rejected
+        for (code, message) in [
+            (r#"[{ symbol: "ABC", exchange: "OTC", last_sale: 1.0 }]"#,
+             r#"constraints.disk.stocks.symbol violates unique: "ABC""#),
+            (r#"[{ exchange: "OTC", last_sale: 1.0 }]"#,
+             r#"constraints.disk.stocks.symbol violates not null: undefined"#),
+            (r#"[{ symbol: "BIZ", exchange: "OTC", last_sale: -1.0 }]"#,
+             r#"constraints.disk.stocks.last_sale violates check(last_sale >= 0.0): -1"#),
+        ] {
+            let result = interpreter.evaluate(format!(r#"{code} ~> ns("constraints.disk.stocks")"#).as_str());
+            assert_eq!(result.map_err(|e| e.to_string()), Err(message.to_string()));
+        }
+        let result = interpreter.evaluate(r#"
+            update ns("constraints.disk.stocks") via { symbol: "ABC" } where symbol == "UNO"
+        "#);
+        assert_eq!(result.map_err(|e| e.to_string()),
+                   Err(r#"constraints.disk.stocks.symbol violates unique: "ABC""#.to_string()));
+    }
+
+    #[test]
+    fn test_foreign_keys() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("constraints.fk.accounts") (id: i64 unique, name: String(16))
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            create table ns("constraints.fk.trades") (
+                account_id: i64 references ns("constraints.fk.accounts")(id) on delete cascade,
+                symbol: String(8)
+            )
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            create table ns("constraints.fk.notes") (
+                account_id: i64 references ns("constraints.fk.accounts")(id) on delete set null,
+                note: String(32)
+            )
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ id: 1, name: "Alice" }, { id: 2, name: "Bob" }, { id: 3, name: "Carol" }]
+                ~> ns("constraints.fk.accounts")
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ account_id: 1, symbol: "ABC" }, { account_id: 2, symbol: "UNO" }]
+                ~> ns("constraints.fk.trades")
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ account_id: 2, note: "VIP" }] ~> ns("constraints.fk.notes")
+        "#).unwrap();
+
+        // orphaned references are rejected
+        let result = interpreter.evaluate(r#"
+            [{ account_id: 9, symbol: "BIZ" }] ~> ns("constraints.fk.trades")
+        "#);
+        assert_eq!(result.map_err(|e| e.to_string()), Err(
+            r#"constraints.fk.trades.account_id violates references ns("constraints.fk.accounts")(id) on delete cascade: 9"#.into()));
+        let result = interpreter.evaluate(r#"
+            update ns("constraints.fk.trades") via { account_id: 7 } where symbol == "ABC"
+        "#);
+        assert_eq!(result.map_err(|e| e.to_string()), Err(
+            r#"constraints.fk.trades.account_id violates references ns("constraints.fk.accounts")(id) on delete cascade: 7"#.into()));
+
+        // referenced keys can't be changed
+        let result = interpreter.evaluate(r#"
+            update ns("constraints.fk.accounts") via { id: 5 } where name == "Alice"
+        "#);
+        assert_eq!(result.map_err(|e| e.to_string()), Err(
+            r#"constraints.fk.trades.account_id violates references ns("constraints.fk.accounts")(id) on delete cascade: 1"#.into()));
+
+        // deleting a referenced account cascades to trades and nullifies notes
+        interpreter.evaluate(r#"
+            delete from ns("constraints.fk.accounts") where id == 2
+        "#).unwrap();
+        let trades = interpreter.evaluate(r#"
+            ns("constraints.fk.trades")
+        "#).unwrap().to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(trades.len(), 1);
+        assert_eq!(trades[0][1], StringValue("ABC".into()));
+        let notes = interpreter.evaluate(r#"
+            ns("constraints.fk.notes")
+        "#).unwrap().to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(notes.len(), 1);
+        assert_eq!(notes[0][0], Null);
+
+        // unreferenced accounts can be deleted freely
+        interpreter.evaluate(r#"
+            delete from ns("constraints.fk.accounts") where id == 3
+        "#).unwrap();
+        let accounts = interpreter.evaluate(r#"
+            ns("constraints.fk.accounts")
+        "#).unwrap().to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(accounts.len(), 1);
+    }
+
+    #[test]
+    fn test_foreign_key_restrict() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("constraints.restrict.accounts") (id: i64 unique, name: String(16))
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            create table ns("constraints.restrict.trades") (
+                account_id: i64 references ns("constraints.restrict.accounts")(id),
+                symbol: String(8)
+            )
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ id: 1, name: "Alice" }] ~> ns("constraints.restrict.accounts")
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ account_id: 1, symbol: "ABC" }] ~> ns("constraints.restrict.trades")
+        "#).unwrap();
+        let result = interpreter.evaluate(r#"
+            delete from ns("constraints.restrict.accounts") where id == 1
+        "#);
+        assert_eq!(result.map_err(|e| e.to_string()), Err(
+            r#"constraints.restrict.trades.account_id violates references ns("constraints.restrict.accounts")(id): 1"#.into()));
+        let accounts = interpreter.evaluate(r#"
+            ns("constraints.restrict.accounts")
+        "#).unwrap().to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(accounts.len(), 1);
+
+        // a reference to a missing table is rejected
+        let result = interpreter.evaluate(r#"
+            create table ns("constraints.restrict.orders") (
+                account_id: i64 references ns("constraints.restrict.missing")(id)
+            )
+        "#);
+        assert!(result.is_err());
+
+        // a referenced column must be a key (i.e. unique or indexed) of its table
+        let result = interpreter.evaluate(r#"
+            create table ns("constraints.restrict.notes") (
+                account_name: String(16) references ns("constraints.restrict.accounts")(name)
+            )
+        "#);
+        assert_eq!(result.map_err(|e| e.to_string()), Err(
+            "Column 'name' of constraints.restrict.accounts must be unique or indexed to be referenced".into()));
+    }
+
+    #[test]
+    fn test_key_index() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("constraints.key_index.accounts") (id: i64 unique, name: String(16))
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ id: 1, name: "Alice" }] ~> ns("constraints.key_index.accounts")
+        "#).unwrap();
+        let ns = Namespace::new("constraints", "key_index", "accounts");
+
+        // the index is reused while the table is unchanged
+        let keys = KeyIndex::get_keys(&ns, "id").unwrap();
+        assert_eq!(*keys, BTreeSet::from(["1".to_string()]));
+        assert!(Arc::ptr_eq(&keys, &KeyIndex::get_keys(&ns, "id").unwrap()));
+
+        // and rebuilt once the table has changed
+        interpreter.evaluate(r#"
+            [{ id: 2, name: "Bob" }] ~> ns("constraints.key_index.accounts")
+        "#).unwrap();
+        let keys = KeyIndex::get_keys(&ns, "id").unwrap();
+        assert_eq!(*keys, BTreeSet::from(["1".to_string(), "2".to_string()]));
+    }
+
+    #[test]
+    fn test_unique_index() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("constraints.unique_index.stocks") (symbol: String(8) unique, last_sale: f64)
+        "#).unwrap();
+        let ns = Namespace::new("constraints", "unique_index", "stocks");
+
+        // the unique constraint is enforced via a unique index
+        let indices = ObjectConfig::load(&ns).unwrap().get_indices();
+        assert!(indices.iter().any(|index| index.is_unique()
+            && index.get_indexed_column_names() == &vec!["symbol".to_string()]));
+        interpreter.evaluate(r#"
+            [{ symbol: "ABC", last_sale: 11.77 }, { symbol: "UNO", last_sale: 0.2456 }]
+                ~> ns("constraints.unique_index.stocks")
+        "#).unwrap();
+        let df = Dataframe::open(&ns).unwrap();
+        let names = vec!["symbol".to_string()];
+        assert_eq!(UniqueIndex::find(&ns, &names, &df, &vec!["\"UNO\"".to_string()]).unwrap(), Some(1));
+
+        // writes are recorded within the index (rather than causing it to be rebuilt)
+        interpreter.evaluate(r#"
+            update ns("constraints.unique_index.stocks") via { symbol: "DUO" } where symbol == "UNO"
+        "#).unwrap();
+        let version = UniqueIndex::get_version(&ns).unwrap();
+        let index = UniqueIndex::lock().unwrap().get(&(ns.to_owned(), names.to_owned())).cloned().unwrap();
+        assert_eq!(index.version, version);
+        assert_eq!(index.ids.get(&vec!["\"DUO\"".to_string()]), Some(&1));
+        assert_eq!(index.ids.get(&vec!["\"UNO\"".to_string()]), None);
+
+        // violations are detected; and deleted keys may be reused
+        let result = interpreter.evaluate(r#"
+            [{ symbol: "DUO", last_sale: 1.0 }] ~> ns("constraints.unique_index.stocks")
+        "#);
+        assert_eq!(result.map_err(|e| e.to_string()),
+                   Err(r#"constraints.unique_index.stocks.symbol violates unique: "DUO""#.to_string()));
+        interpreter.evaluate(r#"
+            delete from ns("constraints.unique_index.stocks") where symbol == "DUO"
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ symbol: "DUO", last_sale: 1.0 }] ~> ns("constraints.unique_index.stocks")
+        "#).unwrap();
+        let df = Dataframe::open(&ns).unwrap();
+        assert_eq!(UniqueIndex::find(&ns, &names, &df, &vec!["\"DUO\"".to_string()]).unwrap(), Some(2));
+    }
+}
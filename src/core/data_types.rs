@@ -176,7 +176,29 @@ impl DataType {
     }
 
     /// decodes the typed value based on the supplied data type and buffer
-    pub fn decode(&self, buffer: &Vec<u8>, offset: usize) -> TypedValue {
+    /// Converts the given value into a value of this datatype (e.g. i64 -> String(8));
+    /// values that cannot be converted are returned as-is.
+    pub fn convert(&self, value: &TypedValue) -> TypedValue {
+        match (self, value) {
+            (_, Null | Undefined) => value.to_owned(),
+            (ASCIIType(..), value) => ASCII(value.unwrap_value().chars().collect()),
+            (BinaryType(..), value) => Binary(value.to_bytes()),
+            (BooleanType, Number(number)) => Boolean(number.to_f64() != 0.),
+            (BooleanType, StringValue(s)) => Boolean(s == "true"),
+            (BooleanType, value) => Boolean(value.to_bool()),
+            (NumberType(kind), Number(number)) => Number(kind.convert(number)),
+            (NumberType(kind), Boolean(b)) => Number(kind.convert(&I32Value(if *b { 1 } else { 0 }))),
+            (NumberType(kind), StringValue(s)) => match s.trim().parse::<f64>() {
+                Ok(n) => Number(kind.convert(&Numbers::F64Value(n))),
+                Err(..) => Null
+            },
+            (StringType(..), StringValue(..)) => value.to_owned(),
+            (StringType(..), value) => StringValue(value.unwrap_value()),
+            (_, value) => value.to_owned()
+        }
+    }
+
+    pub fn decode(&self, buffer: &[u8], offset: usize) -> TypedValue {
         match self {
             ArrayType(..) => ArrayValue(Array::new()),
             BinaryType(..) => Binary(Vec::new()),
@@ -220,7 +242,7 @@ impl DataType {
         Ok(tv)
     }
 
-    pub fn decode_field_value(&self, buffer: &Vec<u8>, offset: usize) -> TypedValue {
+    pub fn decode_field_value(&self, buffer: &[u8], offset: usize) -> TypedValue {
         let metadata = FieldMetadata::decode(buffer[offset]);
         if metadata.is_active {
             self.decode(buffer, offset + 1)
@@ -296,11 +318,10 @@ impl DataType {
     pub fn compute_fixed_size(&self) -> usize {
         use crate::data_types::DataType::*;
         let width: usize = match self {
+            // variable-length values reside in the BLOB heap; the row holds a reference
+            dt if dt.is_variable_length() => PTR_LEN,
             ArrayType(size) => *size,
-            ASCIIType(size) => match size {
-                size => *size + size.to_be_bytes().len(),
-                0 => PTR_LEN
-            },
+            ASCIIType(size) => *size + size.to_be_bytes().len(),
             BinaryType(size) => *size,
             BooleanType => 1,
             EnumType(..) => 2,
@@ -309,10 +330,7 @@ impl DataType {
             Indeterminate => 8,
             NumberType(nk) => nk.compute_fixed_size(),
             PlatformOpsType(..) => 4,
-            StringType(size) => match size {
-                size => *size + size.to_be_bytes().len(),
-                0 => PTR_LEN
-            },
+            StringType(size) => *size + size.to_be_bytes().len(),
             StructureType(columns) => columns.len() * 8,
             TableType(columns, ..) => columns.len() * 8,
             TupleType(types) => types.iter().map(|t| t.compute_fixed_size()).sum(),
@@ -323,6 +341,15 @@ impl DataType {
         width + 1 // +1 for field metadata
     }
 
+    /// Indicates whether values of this datatype are variable-length (e.g. "String" or "Binary"),
+    /// and therefore are stored outside the row.
+    /// NOTE: rather than in slotted pages, the values are kept in the table's BLOB store
+    /// (whose freed allocations are reused, and reclaimed by `tools::vacuum`); the row holds
+    /// a 9-byte reference to the value (i.e. the field metadata and the u64 offset).
+    pub fn is_variable_length(&self) -> bool {
+        matches!(self, ASCIIType(0) | BinaryType(0) | StringType(0))
+    }
+
     pub fn get_default_value(&self) -> TypedValue {
         match self {
             ArrayType(..) => ArrayValue(Array::new()),
@@ -663,6 +690,16 @@ mod tests {
             verify_type_construction("String(10)", StringType(10));
         }
 
+        #[test]
+        fn test_variable_length() {
+            for dt in [ASCIIType(0), BinaryType(0), StringType(0)] {
+                assert!(dt.is_variable_length());
+                assert_eq!(dt.compute_fixed_size(), 9);
+            }
+            assert!(!StringType(10).is_variable_length());
+            assert_eq!(StringType(10).compute_fixed_size(), 19);
+        }
+
         #[test]
         fn test_struct() {
             verify_type_construction(
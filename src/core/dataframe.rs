@@ -6,27 +6,42 @@
 use std::collections::HashSet;
 use crate::byte_row_collection::ByteRowCollection;
 use crate::columns::Column;
-use crate::expression::{Conditions, Expression};
+use crate::constraints::{Constraint, Constraints, ForeignKeys};
+use crate::errors::throw;
+use crate::errors::Errors::{Exact, NotImplemented};
+use crate::expression::{Alterations, Conditions, Expression, TableOptions};
 use crate::field::FieldMetadata;
 use crate::file_row_collection::FileRowCollection;
 use crate::hybrid_row_collection::HybridRowCollection;
 use crate::journaling::JournaledRowCollection;
 use crate::machine::Machine;
 use crate::model_row_collection::ModelRowCollection;
+use crate::named_sequences::NamedSequence;
+use crate::namespace_leases::NamespaceLease;
 use crate::namespaces::Namespace;
 use crate::numbers::Numbers::RowsAffected;
 use crate::object_config::ObjectConfig;
+use crate::page_cache::PageCache;
 use crate::parameter::Parameter;
+use crate::partitioned_row_collection::PartitionedRowCollection;
 use crate::row_collection::RowCollection;
 use crate::row_metadata::RowMetadata;
 use crate::sequences::Sequence;
 use crate::structures::Row;
+use crate::system_catalog::SystemCatalog;
 use crate::typed_values::TypedValue;
-use crate::typed_values::TypedValue::Number;
+use crate::typed_values::TypedValue::{ErrorValue, Number};
 use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
 use std::sync::Arc;
 use crate::dataframe::Dataframe::Model;
 
+/// the marker of the target of a move; naming the namespace moved into it
+const MOVED_FROM_MARKER: &str = ".moved_from";
+/// the marker of the source of a move; naming the namespace it is being moved to
+const MOVED_TO_MARKER: &str = ".moved_to";
+
 /// DataFrame is a logical representation of table
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum Dataframe {
@@ -35,9 +50,143 @@ pub enum Dataframe {
     Hybrid(HybridRowCollection),
     Journaled(JournaledRowCollection),
     Model(ModelRowCollection),
+    Partitioned(PartitionedRowCollection),
 }
 
 impl Dataframe {
+    /// Alters the columns of an existing table (i.e. add, alter, drop or rename a column);
+    /// rewriting its rows into the new layout, and keeping its indices and partitions consistent.
+    /// Returns the number of rows migrated.
+    pub fn alter_table(ns: &Namespace, alteration: &Alterations) -> std::io::Result<usize> {
+        let config = ObjectConfig::load(ns)?;
+        let mut options = config.get_options();
+        if options.contains(&TableOptions::Replicated) || options.contains(&TableOptions::Replica) {
+            return throw(NotImplemented("altering a replicated table".into()));
+        }
+        let params = config.get_columns();
+        let mut partitions = config.get_partitions().cloned().unwrap_or_default();
+        let mut indices = config.get_indices();
+        let mut constraints = config.get_constraints();
+        let identities = constraints.iter()
+            .filter_map(|constraint| match constraint {
+                Constraint::Identity(column) => Some(column.to_owned()),
+                _ => None
+            })
+            .collect::<Vec<_>>();
+        let ttl_column = options.iter().find_map(|option| match option {
+            TableOptions::Ttl(_, column) => Some(column.to_owned()),
+            _ => None
+        });
+        let find_column = |name: &str| match params.iter().position(|p| p.get_name() == name) {
+            Some(index) => Ok(index),
+            None => throw(Exact(format!("Column '{}' not found", name)))
+        };
+        let ensure_absent = |name: &str| match params.iter().any(|p| p.get_name() == name) {
+            true => throw(Exact(format!("Column '{}' already exists", name))),
+            false => Ok(())
+        };
+
+        // determine the new columns, and the (previous) source column of each
+        let mut new_params = params.clone();
+        let mut sources = (0..params.len()).map(Some).collect::<Vec<_>>();
+        match alteration {
+            Alterations::AddColumn(param) => {
+                ensure_absent(param.get_name())?;
+                new_params.push(param.to_owned());
+                sources.push(None);
+            }
+            Alterations::AlterColumn(param) => {
+                let index = find_column(param.get_name())?;
+                if partitions.iter().any(|name| name == param.get_name()) {
+                    return throw(Exact(format!("Partition column '{}' cannot be altered", param.get_name())));
+                }
+                if ttl_column.as_deref() == Some(param.get_name())
+                    && params[index].get_data_type() != param.get_data_type() {
+                    return throw(Exact(format!("TTL column '{}' cannot be retyped", param.get_name())));
+                }
+                new_params[index] = param.to_owned();
+            }
+            Alterations::DropColumn(name) => {
+                let index = find_column(name)?;
+                if partitions.contains(name) {
+                    return throw(Exact(format!("Partition column '{}' cannot be dropped", name)));
+                }
+                if ttl_column.as_ref() == Some(name) {
+                    return throw(Exact(format!("TTL column '{}' cannot be dropped", name)));
+                }
+                if params.len() == 1 {
+                    return throw(Exact(format!("Column '{}' is the only column", name)));
+                }
+                new_params.remove(index);
+                sources.remove(index);
+                indices.retain(|index| !index.get_indexed_column_names().contains(name));
+                constraints.retain(|constraint| !constraint.is_declared_on(name));
+            }
+            Alterations::RenameColumn(old_name, new_name) => {
+                let index = find_column(old_name)?;
+                ensure_absent(new_name)?;
+                let param = &params[index];
+                new_params[index] = Parameter::with_default(new_name, param.get_data_type(), param.get_default_value());
+                partitions = partitions.into_iter()
+                    .map(|name| if &name == old_name { new_name.to_owned() } else { name })
+                    .collect();
+                indices = indices.into_iter()
+                    .map(|index| index.with_renamed_column(old_name, new_name))
+                    .collect();
+                constraints = constraints.into_iter()
+                    .map(|constraint| constraint.with_renamed_column(old_name, new_name))
+                    .collect();
+                options = options.into_iter()
+                    .map(|option| match option {
+                        TableOptions::Ttl(millis, column) if &column == old_name =>
+                            TableOptions::Ttl(millis, new_name.to_owned()),
+                        option => option
+                    })
+                    .collect();
+            }
+        }
+
+        // rewrite the rows into the new layout
+        let transform = |row: Row| {
+            let values = row.get_values();
+            let new_values = new_params.iter().zip(sources.iter()).map(|(param, source)| match source {
+                Some(index) if params[*index].get_data_type() == param.get_data_type() => values[*index].to_owned(),
+                Some(index) => param.get_data_type().convert(&values[*index]),
+                None => match param.get_default_value() {
+                    TypedValue::Null | TypedValue::Undefined => param.get_data_type().get_default_value(),
+                    value => value
+                }
+            }).collect();
+            Row::new(row.get_id(), new_values)
+        };
+        let migrated = if partitions.is_empty() {
+            FileRowCollection::open(ns)?.rewrite(&new_params, transform)?
+        } else {
+            PartitionedRowCollection::open(ns)?.rewrite(&new_params, &partitions, transform)?
+        };
+
+        // the rows held in memory (by a hybrid table) no longer match the new layout
+        HybridRowCollection::discard(ns);
+
+        // update the configuration
+        config.with_columns(new_params)
+            .with_indices(indices)
+            .with_partitions(partitions)
+            .with_constraints(constraints)
+            .with_options(options)
+            .save(ns)?;
+
+        // the identity sequence of a column follows it
+        match alteration {
+            Alterations::DropColumn(name) if identities.contains(name) =>
+                NamedSequence::drop_identity(ns, name)?,
+            Alterations::RenameColumn(old_name, new_name) if identities.contains(old_name) =>
+                NamedSequence::rename_identity(ns, old_name, new_name)?,
+            _ => {}
+        }
+        Ok(migrated)
+    }
+
     /// Creates a new table within the specified namespace and having the specified columns
     pub fn create_table(ns: &Namespace, params: &Vec<Parameter>) -> std::io::Result<Self> {
         let path = ns.get_table_file_path();
@@ -48,23 +197,230 @@ impl Dataframe {
         Ok(Self::Disk(FileRowCollection::new(columns, file, path.as_str())))
     }
 
+    /// Opens an existing table; partitioned tables are opened with all of their partitions,
+    /// and the tables of the system catalog (e.g. `oxide.system.tables`) are built on demand
+    pub fn open(ns: &Namespace) -> std::io::Result<Self> {
+        if SystemCatalog::is_catalog(ns) {
+            return Ok(Model(SystemCatalog::open(ns)?));
+        }
+        Self::recover_move(ns)?;
+        let config = ObjectConfig::load(ns)?;
+        let options = config.get_options();
+        match config.get_partitions() {
+            Some(partitions) if !partitions.is_empty() =>
+                Ok(Self::Partitioned(PartitionedRowCollection::open(ns)?)),
+            _ if options.iter().any(|o| matches!(o, TableOptions::Hybrid(..))) =>
+                Ok(Self::Hybrid(HybridRowCollection::open(ns, &options)?)),
+            _ => Ok(Self::Disk(FileRowCollection::open(ns)?))
+        }
+    }
+
+    /// Renames (or moves) a table to another namespace; moving the files of its namespace
+    /// (e.g. its table, BLOB, configuration, index and journal files), as well as the events
+    /// of a journaled table, and redirecting the foreign keys of the tables related to it.
+    /// Fails if the target table already exists, or either namespace is leased by another process.
+    pub fn rename_table(ns: &Namespace, target: &Namespace) -> std::io::Result<()> {
+        if SystemCatalog::is_catalog(ns) || SystemCatalog::is_catalog(target) {
+            return throw(Exact("System catalog tables cannot be renamed".into()));
+        }
+        if ns == target {
+            return throw(Exact(format!("Table {ns} cannot be renamed to itself")));
+        }
+        let moves = [
+            (ns.to_owned(), target.to_owned()),
+            (JournaledRowCollection::get_events_namespace(ns), JournaledRowCollection::get_events_namespace(target)),
+        ];
+        for (from, to) in &moves {
+            Self::recover_move(from)?;
+            Self::recover_move(to)?;
+        }
+        if !Path::new(&ns.get_table_file_path()).exists() {
+            return throw(Exact(format!("Table {ns} not found")));
+        }
+        let moves = moves.into_iter()
+            .filter(|(from, _)| Path::new(&from.get_table_file_path()).exists())
+            .collect::<Vec<_>>();
+        for (_, to) in &moves {
+            if Path::new(&to.get_table_file_path()).exists() {
+                return throw(Exact(format!("Table {to} already exists")));
+            }
+        }
+
+        // move the namespaces (while writes through the page cache are held off)
+        for (from, to) in &moves {
+            NamespaceLease::acquire(from)?;
+            NamespaceLease::acquire(to)?;
+        }
+        HybridRowCollection::discard(ns);
+        let result = PageCache::shared().snapshot(|| {
+            moves.iter().try_for_each(|(from, to)| Self::move_namespace(from, to))
+        });
+        for (from, to) in &moves {
+            NamespaceLease::release(from);
+            NamespaceLease::release(to);
+        }
+        result?;
+        ForeignKeys::rename(ns, target)
+    }
+
+    /// Moves the directory of a namespace to another namespace. The files of the namespace are
+    /// hard-linked (under their new names) into a staging directory beside the target, which is
+    /// then published by a single rename; so that the target appears complete or not at all.
+    /// The source is only removed once the target has been published; a move interrupted
+    /// (e.g. by a crash) is recovered once either namespace is opened (see [Dataframe::recover_move]).
+    fn move_namespace(from: &Namespace, to: &Namespace) -> std::io::Result<()> {
+        let staging_path = Self::stage_move(from, to)?;
+        let to_path = to.get_root_path();
+        let to_path = to_path.trim_end_matches('/');
+
+        // any remnants of the target (e.g. the files of a dropped table) are replaced
+        if Path::new(to_path).exists() {
+            fs::remove_dir_all(to_path)?;
+        }
+        fs::rename(&staging_path, to_path)?;
+        Self::recover_move(to)
+    }
+
+    /// Stages the move of a namespace (see [Dataframe::move_namespace]); returning the path of the
+    /// staging directory. Both namespaces are marked, so that an interrupted move can be recovered.
+    fn stage_move(from: &Namespace, to: &Namespace) -> std::io::Result<String> {
+        let (from_path, to_path) = (from.get_root_path(), to.get_root_path());
+        let staging_path = Self::get_staging_path(to);
+        if Path::new(&staging_path).exists() {
+            fs::remove_dir_all(&staging_path)?;
+        }
+        fs::create_dir_all(&staging_path)?;
+
+        // link the files of the table under their new names (e.g. "stocks.table.blob" => "stocks_2025.table.blob")
+        let prefix = format!("{}.", from.name);
+        for entry in fs::read_dir(&from_path)? {
+            let file_name = entry?.file_name().to_string_lossy().to_string();
+            if file_name == MOVED_FROM_MARKER || file_name == MOVED_TO_MARKER { continue; }
+            let new_name = match file_name.strip_prefix(prefix.as_str()) {
+                Some(extension) => format!("{}.{extension}", to.name),
+                None => file_name.to_owned()
+            };
+            Self::link_files(&format!("{from_path}{file_name}"), &format!("{staging_path}/{new_name}"))?;
+        }
+        Self::write_move_marker(&format!("{staging_path}/{MOVED_FROM_MARKER}"), from)?;
+        Self::write_move_marker(&format!("{from_path}{MOVED_TO_MARKER}"), to)?;
+        if let Some(parent) = Path::new(to_path.trim_end_matches('/')).parent() {
+            fs::File::open(parent)?.sync_all()?;
+        }
+        Ok(staging_path)
+    }
+
+    /// Completes (or undoes) an interrupted move of a namespace (see [Dataframe::move_namespace]):
+    /// a move whose target has been published is completed by removing its source; otherwise
+    /// the move is undone by discarding its staging directory.
+    pub fn recover_move(ns: &Namespace) -> std::io::Result<()> {
+        let root_path = ns.get_root_path();
+
+        // the target of a published move: the source is removed (if it still awaits removal)
+        let marker_path = format!("{root_path}{MOVED_FROM_MARKER}");
+        if let Some(from) = Self::read_move_marker(&marker_path)? {
+            let from_path = from.get_root_path();
+            if Self::read_move_marker(&format!("{from_path}{MOVED_TO_MARKER}"))?.as_ref() == Some(ns) {
+                fs::remove_dir_all(&from_path)?;
+            }
+            fs::remove_file(&marker_path)?;
+        }
+
+        // the source of a move: completed if the target was published; otherwise undone
+        let marker_path = format!("{root_path}{MOVED_TO_MARKER}");
+        if let Some(to) = Self::read_move_marker(&marker_path)? {
+            let is_published = Self::read_move_marker(
+                &format!("{}{MOVED_FROM_MARKER}", to.get_root_path()))?.as_ref() == Some(ns);
+            if is_published {
+                return Self::recover_move(&to);
+            }
+            let staging_path = Self::get_staging_path(&to);
+            if Path::new(&staging_path).exists() {
+                fs::remove_dir_all(&staging_path)?;
+            }
+            fs::remove_file(&marker_path)?;
+        }
+        Ok(())
+    }
+
+    fn get_staging_path(to: &Namespace) -> String {
+        format!("{}.rename", to.get_root_path().trim_end_matches('/'))
+    }
+
+    /// Hard-links a file (or, recursively, the files of a directory) to another path
+    fn link_files(from: &str, to: &str) -> std::io::Result<()> {
+        if !Path::new(from).is_dir() {
+            return fs::hard_link(from, to);
+        }
+        fs::create_dir_all(to)?;
+        for entry in fs::read_dir(from)? {
+            let file_name = entry?.file_name().to_string_lossy().to_string();
+            Self::link_files(&format!("{from}/{file_name}"), &format!("{to}/{file_name}"))?;
+        }
+        Ok(())
+    }
+
+    fn read_move_marker(path: &str) -> std::io::Result<Option<Namespace>> {
+        match fs::read_to_string(path) {
+            Ok(ns_id) => Namespace::parse(ns_id.trim()).map(Some),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err)
+        }
+    }
+
+    /// Durably writes the marker of a move (i.e. the namespace at its other end)
+    fn write_move_marker(path: &str, ns: &Namespace) -> std::io::Result<()> {
+        let temp_path = format!("{path}.tmp");
+        fs::write(&temp_path, ns.id())?;
+        fs::File::open(&temp_path)?.sync_all()?;
+        fs::rename(&temp_path, path)
+    }
+
+    /// Appends rows in bulk (see [FileRowCollection::bulk_load]); tables lacking
+    /// a bulk load path append the rows one at a time. Returns the number of rows appended.
+    pub fn bulk_load(&mut self, rows: Vec<Row>) -> std::io::Result<usize> {
+        match self {
+            Self::Disk(frc) => frc.bulk_load(rows),
+            Self::Hybrid(hrc) => hrc.bulk_load(rows),
+            df => df.append_rows(rows).to_result(|v| v.to_usize())
+        }
+    }
+
+    /// Returns a view of this table excluding the partitions that can't satisfy the condition
+    pub fn prune(self, condition: &Option<Conditions>) -> Self {
+        match (self, condition) {
+            (Self::Partitioned(prc), Some(condition)) => Self::Partitioned(prc.prune(condition)),
+            (df, _) => df
+        }
+    }
+
     /// deletes rows from the table based on a condition
     pub fn delete_where(
         mut self,
         machine: &Machine,
         condition: &Option<Conditions>,
         limit: TypedValue,
+        foreign_keys: &Option<ForeignKeys>,
     ) -> std::io::Result<TypedValue> {
         let mut deleted = 0;
+        self = self.prune(condition);
+        let mut rows = Vec::new();
         for id in self.get_indices_with_limit(limit)? {
             // read an active row
             if let Some(row) = self.read_one(id)? {
-                // if the predicate matches the condition, delete the row.
+                // if the predicate matches the condition, mark the row for deletion.
                 if row.matches(machine, condition, self.get_columns()) {
-                    deleted += self.delete_row(id).to_result(|v| v.to_i64())?;
+                    rows.push(row);
                 }
             }
         }
+        // apply the delete actions of the referencing tables
+        if let Some(foreign_keys) = foreign_keys {
+            foreign_keys.delete_dependents(&rows)?;
+        }
+        for row in rows {
+            deleted += self.delete_row(row.get_id()).to_result(|v| v.to_i64())?;
+        }
         Ok(Number(RowsAffected(deleted)))
     }
 
@@ -76,9 +432,14 @@ impl Dataframe {
         values: &Vec<Expression>,
         condition: &Option<Conditions>,
         limit: TypedValue,
+        foreign_keys: &Option<ForeignKeys>,
     ) -> std::io::Result<(Dataframe, TypedValue)> {
         let mut overwritten = 0;
-        let mut df = df;
+        let mut df = df.prune(condition);
+        let parent_keys = match foreign_keys {
+            Some(foreign_keys) => foreign_keys.get_parent_keys()?,
+            None => Vec::new()
+        };
         for id in df.get_indices_with_limit(limit)? {
             // read an active row
             if let Some(row) = df.read_one(id)? {
@@ -88,6 +449,9 @@ impl Dataframe {
                         machine.with_row(df.get_columns(), &row).evaluate_as_atoms(fields)?;
                     if let (_, TypedValue::ArrayValue(my_values)) = machine.evaluate_array(values)? {
                         let new_row = row.transform(df.get_columns(), &my_fields, &my_values.get_values())?;
+                        if let Some(foreign_keys) = foreign_keys {
+                            foreign_keys.verify_update(&parent_keys, &row, &new_row)?;
+                        }
                         overwritten += df.overwrite_row(row.get_id(), new_row).to_result(|v| v.to_i64())?;
                     }
                 }
@@ -96,6 +460,51 @@ impl Dataframe {
         Ok((df, Number(RowsAffected(overwritten))))
     }
 
+    /// Reclaims the unused space of the table's BLOB file(s);
+    /// returning the number of rows whose external references were updated.
+    pub fn vacuum(&mut self) -> std::io::Result<usize> {
+        match self {
+            Self::Binary(..) | Self::Model(..) => Ok(0),
+            Self::Disk(frc) => frc.vacuum(),
+            Self::Partitioned(prc) => prc.vacuum(),
+            Self::Hybrid(hrc) => hrc.vacuum(),
+            Self::Journaled(..) => throw(NotImplemented("vacuuming a journaled table".into())),
+        }
+    }
+
+    /// Deletes the expired rows of a table having a TTL; returning the number of rows deleted
+    pub fn expire_rows(&mut self) -> std::io::Result<usize> {
+        match self {
+            Self::Disk(frc) => frc.expire_rows(),
+            _ => Ok(0)
+        }
+    }
+
+    /// Verifies the integrity of the table's records and BLOB references;
+    /// returning the problems found as (kind, location, problem).
+    pub fn verify(&self) -> std::io::Result<Vec<(String, String, String)>> {
+        match self {
+            Self::Binary(..) | Self::Model(..) => Ok(Vec::new()),
+            Self::Disk(frc) => frc.verify(),
+            Self::Partitioned(prc) => prc.verify(),
+            Self::Hybrid(hrc) => hrc.get_file_row_collection().verify(),
+            Self::Journaled(jrc) => jrc.verify(),
+        }
+    }
+
+    /// Enforces the given constraints (and column defaults) upon all subsequent writes
+    pub fn with_constraints(self, constraints: Option<Constraints>) -> std::io::Result<Self> {
+        match self {
+            Self::Disk(frc) => Ok(Self::Disk(frc.with_constraints(constraints))),
+            Self::Hybrid(hrc) => Ok(Self::Hybrid(hrc.with_constraints(constraints))),
+            Self::Journaled(jrc) => Ok(Self::Journaled(jrc.with_constraints(constraints))),
+            Self::Model(mrc) => Ok(Self::Model(mrc.with_constraints(constraints))),
+            Self::Partitioned(prc) => Ok(Self::Partitioned(prc.with_constraints(constraints))),
+            df if constraints.is_none() => Ok(df),
+            Self::Binary(..) => throw(NotImplemented("constraints on a binary table".into())),
+        }
+    }
+
     pub fn to_model(self) -> ModelRowCollection {
         let (rows, columns) = (self.get_rows(), self.get_columns());
         ModelRowCollection::from_columns_and_rows(columns, &rows)
@@ -172,8 +581,14 @@ impl Dataframe {
         values: &Vec<Expression>,
         condition: &Option<Conditions>,
         limit: TypedValue,
+        foreign_keys: &Option<ForeignKeys>,
     ) -> std::io::Result<TypedValue> {
+        rc = rc.prune(condition);
         let columns = rc.get_columns().clone();
+        let parent_keys = match foreign_keys {
+            Some(foreign_keys) => foreign_keys.get_parent_keys()?,
+            None => Vec::new()
+        };
         let mut updated = 0;
         for id in rc.get_indices_with_limit(limit)? {
             // read an active row
@@ -184,8 +599,13 @@ impl Dataframe {
                         ms.with_row(&columns, &row).evaluate_as_atoms(fields)?;
                     if let (_, TypedValue::ArrayValue(field_values)) = ms.evaluate_array(values)? {
                         let new_row = row.transform(&columns, &field_names, &field_values.get_values())?;
-                        let result = rc.overwrite_row(id, new_row);
-                        if result.is_ok() { updated += 1 }
+                        if let Some(foreign_keys) = foreign_keys {
+                            foreign_keys.verify_update(&parent_keys, &row, &new_row)?;
+                        }
+                        match rc.overwrite_row(id, new_row) {
+                            ErrorValue(err) => return throw(err),
+                            result => if result.is_ok() { updated += 1 }
+                        }
                     }
                 }
             }
@@ -195,6 +615,17 @@ impl Dataframe {
 }
 
 impl RowCollection for Dataframe {
+    fn compact(&mut self) -> TypedValue {
+        match self {
+            Self::Binary(rc) => rc.compact(),
+            Self::Disk(rc) => rc.compact(),
+            Self::Hybrid(rc) => rc.compact(),
+            Self::Journaled(rc) => rc.compact(),
+            Self::Model(rc) => rc.compact(),
+            Self::Partitioned(rc) => rc.compact(),
+        }
+    }
+
     fn get_columns(&self) -> &Vec<Column> {
         match self {
             Self::Binary(rc) => rc.get_columns(),
@@ -202,6 +633,7 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.get_columns(),
             Self::Journaled(rc) => rc.get_columns(),
             Self::Model(rc) => rc.get_columns(),
+            Self::Partitioned(rc) => rc.get_columns(),
         }
     }
 
@@ -212,6 +644,7 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.get_record_size(),
             Self::Journaled(rc) => rc.get_record_size(),
             Self::Model(rc) => rc.get_record_size(),
+            Self::Partitioned(rc) => rc.get_record_size(),
         }
     }
 
@@ -222,6 +655,7 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.get_rows(),
             Self::Journaled(rc) => rc.get_rows(),
             Self::Model(rc) => rc.get_rows(),
+            Self::Partitioned(rc) => rc.get_rows(),
         }
     }
 
@@ -232,6 +666,7 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.len(),
             Self::Journaled(rc) => rc.len(),
             Self::Model(rc) => rc.len(),
+            Self::Partitioned(rc) => rc.len(),
         }
     }
 
@@ -242,6 +677,7 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.overwrite_field(id, column_id, new_value),
             Self::Journaled(rc) => rc.overwrite_field(id, column_id, new_value),
             Self::Model(rc) => rc.overwrite_field(id, column_id, new_value),
+            Self::Partitioned(rc) => rc.overwrite_field(id, column_id, new_value),
         }
     }
 
@@ -252,6 +688,7 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.overwrite_field_metadata(id, column_id, metadata),
             Self::Journaled(rc) => rc.overwrite_field_metadata(id, column_id, metadata),
             Self::Model(rc) => rc.overwrite_field_metadata(id, column_id, metadata),
+            Self::Partitioned(rc) => rc.overwrite_field_metadata(id, column_id, metadata),
         }
     }
 
@@ -262,6 +699,7 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.overwrite_row(id, row),
             Self::Journaled(rc) => rc.overwrite_row(id, row),
             Self::Model(rc) => rc.overwrite_row(id, row),
+            Self::Partitioned(rc) => rc.overwrite_row(id, row),
         }
     }
 
@@ -272,6 +710,18 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.overwrite_row_metadata(id, metadata),
             Self::Journaled(rc) => rc.overwrite_row_metadata(id, metadata),
             Self::Model(rc) => rc.overwrite_row_metadata(id, metadata),
+            Self::Partitioned(rc) => rc.overwrite_row_metadata(id, metadata),
+        }
+    }
+
+    fn read_active_rows(&self) -> std::io::Result<Vec<Row>> {
+        match self {
+            Self::Binary(rc) => rc.read_active_rows(),
+            Self::Disk(rc) => rc.read_active_rows(),
+            Self::Hybrid(rc) => rc.read_active_rows(),
+            Self::Journaled(rc) => rc.read_active_rows(),
+            Self::Model(rc) => rc.read_active_rows(),
+            Self::Partitioned(rc) => rc.read_active_rows(),
         }
     }
 
@@ -282,6 +732,7 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.read_field(id, column_id),
             Self::Journaled(rc) => rc.read_field(id, column_id),
             Self::Model(rc) => rc.read_field(id, column_id),
+            Self::Partitioned(rc) => rc.read_field(id, column_id),
         }
     }
 
@@ -292,6 +743,18 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.read_field_metadata(id, column_id),
             Self::Journaled(rc) => rc.read_field_metadata(id, column_id),
             Self::Model(rc) => rc.read_field_metadata(id, column_id),
+            Self::Partitioned(rc) => rc.read_field_metadata(id, column_id),
+        }
+    }
+
+    fn read_range_with_metadata(&self, index: std::ops::Range<usize>) -> std::io::Result<Vec<(Row, RowMetadata)>> {
+        match self {
+            Self::Binary(rc) => rc.read_range_with_metadata(index),
+            Self::Disk(rc) => rc.read_range_with_metadata(index),
+            Self::Hybrid(rc) => rc.read_range_with_metadata(index),
+            Self::Journaled(rc) => rc.read_range_with_metadata(index),
+            Self::Model(rc) => rc.read_range_with_metadata(index),
+            Self::Partitioned(rc) => rc.read_range_with_metadata(index),
         }
     }
 
@@ -302,6 +765,7 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.read_row(id),
             Self::Journaled(rc) => rc.read_row(id),
             Self::Model(rc) => rc.read_row(id),
+            Self::Partitioned(rc) => rc.read_row(id),
         }
     }
 
@@ -312,6 +776,7 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.read_row_metadata(id),
             Self::Journaled(rc) => rc.read_row_metadata(id),
             Self::Model(rc) => rc.read_row_metadata(id),
+            Self::Partitioned(rc) => rc.read_row_metadata(id),
         }
     }
 
@@ -322,6 +787,7 @@ impl RowCollection for Dataframe {
             Self::Hybrid(rc) => rc.resize(new_size),
             Self::Journaled(rc) => rc.resize(new_size),
             Self::Model(rc) => rc.resize(new_size),
+            Self::Partitioned(rc) => rc.resize(new_size),
         }
     }
 }
@@ -342,6 +808,38 @@ mod tests {
     use crate::table_renderer::TableRenderer;
     use crate::testdata::{make_quote, make_quote_columns};
     use crate::typed_values::TypedValue::{Null, Number, StringValue};
+    use crate::dataframe::{MOVED_FROM_MARKER, MOVED_TO_MARKER};
+    use crate::namespaces::Namespace;
+    use crate::testdata::make_quote_parameters;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_interrupted_move_is_undone() {
+        let (from, to) = create_move("dataframe.move_undone.stocks", "dataframe.move_undone.stocks_2025");
+
+        // the move was staged, but not published
+        let staging_path = Dataframe::stage_move(&from, &to).unwrap();
+        assert!(Path::new(&staging_path).exists());
+        let df = Dataframe::open(&from).unwrap();
+        assert_eq!(df.get_rows(), vec![make_quote(0, "ABC", "AMEX", 11.77)]);
+        assert!(!Path::new(&staging_path).exists());
+        assert!(!Path::new(&format!("{}{MOVED_TO_MARKER}", from.get_root_path())).exists());
+        assert!(Dataframe::open(&to).is_err());
+    }
+
+    #[test]
+    fn test_interrupted_move_is_completed() {
+        let (from, to) = create_move("dataframe.move_completed.stocks", "dataframe.move_completed.stocks_2025");
+
+        // the move was published, but its source wasn't removed
+        let staging_path = Dataframe::stage_move(&from, &to).unwrap();
+        fs::rename(&staging_path, to.get_root_path().trim_end_matches('/')).unwrap();
+        let df = Dataframe::open(&to).unwrap();
+        assert_eq!(df.get_rows(), vec![make_quote(0, "ABC", "AMEX", 11.77)]);
+        assert!(!Path::new(&from.get_root_path()).exists());
+        assert!(!Path::new(&format!("{}{MOVED_FROM_MARKER}", to.get_root_path())).exists());
+    }
 
     #[test]
     fn test_to_model() {
@@ -432,6 +930,17 @@ mod tests {
         ])
     }
 
+    /// Creates a table (of a single row) to be moved to another (vacant) namespace
+    fn create_move(from: &str, to: &str) -> (Namespace, Namespace) {
+        let (from, to) = (Namespace::parse(from).unwrap(), Namespace::parse(to).unwrap());
+        for ns in [&from, &to] {
+            let _ = fs::remove_dir_all(ns.get_root_path());
+        }
+        let mut df = Dataframe::create_table(&from, &make_quote_parameters()).unwrap();
+        df.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        (from, to)
+    }
+
     fn create_dataframe() -> Dataframe {
         Dataframe::Binary(ByteRowCollection::from_rows(
             make_quote_columns(),
@@ -11,14 +11,14 @@ use actix::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::columns::Column;
+use crate::constraints::ForeignKeys;
 use crate::dataframe::Dataframe;
-use crate::dataframe::Dataframe::Disk;
-use crate::file_row_collection::FileRowCollection;
 use crate::namespaces::Namespace;
 use crate::object_config::ObjectConfig;
 use crate::row_collection::RowCollection;
 use crate::row_metadata::RowMetadata;
 use crate::structures::Row;
+use crate::typed_values::TypedValue::NamespaceValue;
 
 // define the Dataframe I/O actor
 #[derive(Debug)]
@@ -35,7 +35,13 @@ impl DataframeActor {
     }
 
     fn append_row(&mut self, ns: &Namespace, row: Row) -> std::io::Result<usize> {
-        Ok(self.get_or_load_dataframe(ns)?.append_row(row).to_usize())
+        Self::verify_references(ns, &vec![row.to_owned()])?;
+        self.get_or_load_dataframe(ns)?.append_row(row).to_result(|v| v.to_usize())
+    }
+
+    fn append_rows(&mut self, ns: &Namespace, rows: Vec<Row>) -> std::io::Result<usize> {
+        Self::verify_references(ns, &rows)?;
+        self.get_or_load_dataframe(ns)?.bulk_load(rows)
     }
 
     fn create_table(&mut self, ns: Namespace, cfg: ObjectConfig) -> std::io::Result<&mut Dataframe> {
@@ -46,6 +52,10 @@ impl DataframeActor {
         Ok(self.get_or_load_dataframe(ns)?.delete_row(id).to_usize())
     }
 
+    fn expire_rows(&mut self, ns: &Namespace) -> std::io::Result<usize> {
+        self.get_or_load_dataframe(ns)?.expire_rows()
+    }
+
     fn get_columns(&mut self, ns: &Namespace) -> std::io::Result<&Vec<Column>> {
         Ok(&self.get_or_load_dataframe(ns)?.get_columns())
     }
@@ -69,7 +79,7 @@ impl DataframeActor {
     fn get_or_load_dataframe(&mut self, ns: &Namespace) -> std::io::Result<&mut Dataframe> {
         match self.resources.entry(ns.id()) {
             Entry::Occupied(v) => Ok(v.into_mut()),
-            Entry::Vacant(x) => Ok(x.insert(Disk(FileRowCollection::open(ns)?)))
+            Entry::Vacant(x) => Ok(x.insert(Dataframe::open(ns)?))
         }
     }
 
@@ -128,6 +138,15 @@ impl DataframeActor {
         self.get_or_load_dataframe(ns)?.read_row_metadata(id)
     }
 
+    /// Verifies that the references (i.e. foreign keys) of rows about to be appended exist;
+    /// as is done for appends by the query engine.
+    fn verify_references(ns: &Namespace, rows: &Vec<Row>) -> std::io::Result<()> {
+        match ForeignKeys::load_value(&NamespaceValue(ns.to_owned()))? {
+            Some(foreign_keys) => foreign_keys.verify_rows(rows),
+            None => Ok(())
+        }
+    }
+
     fn update_row(
         &mut self,
         ns: &Namespace,
@@ -148,10 +167,14 @@ impl Handler<IORequest> for DataframeActor {
         match msg {
             IORequest::AppendRow { ns, row } =>
                 handle_result(self.append_row(&ns, row)),
+            IORequest::AppendRows { ns, rows } =>
+                handle_result(self.append_rows(&ns, rows)),
             IORequest::CreateTable { ns, cfg } =>
                 handle_result(self.create_table(ns, cfg).map(|_| 1usize)),
             IORequest::DeleteRow { ns, id } =>
                 handle_result(self.delete_row(&ns, id)),
+            IORequest::ExpireRows { ns } =>
+                handle_result(self.expire_rows(&ns)),
             IORequest::GetColumns { ns } =>
                 handle_result(self.get_columns(&ns)),
             IORequest::GetNamespaces =>
@@ -187,8 +210,10 @@ fn handle_result<T: serde::Serialize>(result: std::io::Result<T>) -> String {
 #[rtype(result = "String")]
 pub enum IORequest {
     AppendRow { ns: Namespace, row: Row },
+    AppendRows { ns: Namespace, rows: Vec<Row> },
     CreateTable { ns: Namespace, cfg: ObjectConfig },
     DeleteRow { ns: Namespace, id: usize },
+    ExpireRows { ns: Namespace },
     GetColumns { ns: Namespace },
     GetNamespaces,
     ReadFully { ns: Namespace },
@@ -210,8 +235,20 @@ macro_rules! append_row {
             ns: $ns.to_owned(),
             row: $row
         }).await
-            .map(|s|serde_json::from_str::<usize>(&s).unwrap())
             .map_err(|e|crate::cnv_error!(e))
+            .and_then(|s|serde_json::from_str::<usize>(&s).map_err(|_|crate::cnv_error!(s)))
+    }
+}
+
+#[macro_export]
+macro_rules! append_rows {
+    ($actor:expr, $ns:expr, $rows:expr) => {
+        $actor.send(crate::dataframe_actor::IORequest::AppendRows {
+            ns: $ns.to_owned(),
+            rows: $rows
+        }).await
+            .map_err(|e|crate::cnv_error!(e))
+            .and_then(|s|serde_json::from_str::<usize>(&s).map_err(|_|crate::cnv_error!(s)))
     }
 }
 
@@ -251,6 +288,17 @@ macro_rules! delete_row {
     }
 }
 
+#[macro_export]
+macro_rules! expire_rows {
+    ($actor:expr, $ns:expr) => {
+        $actor.send(crate::dataframe_actor::IORequest::ExpireRows {
+            ns: $ns.to_owned()
+        }).await
+            .map_err(|e|crate::cnv_error!(e))
+            .and_then(|s|serde_json::from_str::<usize>(&s).map_err(|_|crate::cnv_error!(s)))
+    }
+}
+
 #[macro_export]
 macro_rules! get_columns {
     ($actor:expr, $ns:expr) => {
@@ -269,8 +317,8 @@ macro_rules! overwrite_row {
             ns: $ns.to_owned(),
             row: $row
         }).await
-            .map(|s|serde_json::from_str::<usize>(&s).unwrap())
             .map_err(|e|crate::cnv_error!(e))
+            .and_then(|s|serde_json::from_str::<usize>(&s).map_err(|_|crate::cnv_error!(s)))
     }
 }
 
@@ -338,8 +386,8 @@ macro_rules! update_row {
             ns: $ns.to_owned(),
             row: $row
         }).await
-            .map(|s|serde_json::from_str::<usize>(&s).unwrap())
             .map_err(|e|crate::cnv_error!(e))
+            .and_then(|s|serde_json::from_str::<usize>(&s).map_err(|_|crate::cnv_error!(s)))
     }
 }
 
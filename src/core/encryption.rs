@@ -0,0 +1,227 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// EncryptionKey class
+////////////////////////////////////////////////////////////////////
+
+use crate::errors::throw;
+use crate::errors::Errors::Exact;
+use crate::machine::Machine;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use std::env;
+use std::fmt::{Debug, Formatter};
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::Path;
+
+/// the environment variable containing a hex-encoded 256-bit key
+pub const KEY_ENV_VAR: &str = "OXIDE_KEY";
+
+/// the environment variable containing the path of a keyfile (hex-encoded);
+/// the file must only be accessible by its owner (i.e. mode 0600).
+pub const KEY_FILE_ENV_VAR: &str = "OXIDE_KEY_FILE";
+
+pub const KEY_LEN: usize = 32;
+pub const NONCE_LEN: usize = 12;
+pub const TAG_LEN: usize = 16;
+
+/// the number of bytes added to a payload when it is sealed (nonce + tag)
+pub const ENCRYPTION_OVERHEAD: usize = NONCE_LEN + TAG_LEN;
+
+/// Represents an authenticated encryption key (ChaCha20-Poly1305)
+/// used to protect table rows and BLOBs at rest.
+#[derive(Clone, Eq, PartialEq)]
+pub struct EncryptionKey {
+    key: [u8; KEY_LEN],
+}
+
+impl EncryptionKey {
+
+    ////////////////////////////////////////////////////////////////
+    // static methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Decodes a key from a 64-character hex string
+    pub fn from_hex(hex_string: &str) -> std::io::Result<Self> {
+        match hex::decode(hex_string.trim()) {
+            Ok(bytes) if bytes.len() == KEY_LEN => {
+                let mut key = [0u8; KEY_LEN];
+                key.copy_from_slice(&bytes);
+                Ok(Self { key })
+            }
+            Ok(bytes) => throw(Exact(format!(
+                "Encryption keys must be {} bytes, but found {}", KEY_LEN, bytes.len()))),
+            Err(err) => throw(Exact(format!("Invalid encryption key: {}", err)))
+        }
+    }
+
+    /// Creates a new random key
+    pub fn generate() -> Self {
+        Self { key: rand::random::<[u8; KEY_LEN]>() }
+    }
+
+    /// Returns the path of the default keyfile (i.e. `$OXIDE_HOME/keys/oxide.key`)
+    pub fn get_default_key_path() -> String {
+        format!("{}/keys/oxide.key", Machine::oxide_home())
+    }
+
+    /// Loads the encryption key from the `OXIDE_KEY` environment variable, from the
+    /// keyfile named by `OXIDE_KEY_FILE`, or from the default keyfile (see [Self::get_default_key_path]).
+    /// Keys are never generated implicitly, so encrypted tables can't be opened
+    /// (or created) unless a key has been configured.
+    pub fn load() -> std::io::Result<Self> {
+        match (env::var(KEY_ENV_VAR), env::var(KEY_FILE_ENV_VAR)) {
+            (Ok(hex_string), _) => Self::from_hex(hex_string.as_str()),
+            (Err(..), Ok(path)) => Self::load_file(path.as_str()),
+            (Err(..), Err(..)) => {
+                let path = Self::get_default_key_path();
+                match Path::new(&path).exists() {
+                    true => Self::load_file(path.as_str()),
+                    false => throw(Exact(format!(
+                        "Encrypted tables require a key: set {KEY_ENV_VAR} or {KEY_FILE_ENV_VAR}, or create {path}")))
+                }
+            }
+        }
+    }
+
+    /// Loads the key from a keyfile; refusing files readable by the group or others
+    pub fn load_file(path: &str) -> std::io::Result<Self> {
+        let mode = fs::metadata(path)?.permissions().mode();
+        if mode & 0o077 != 0 {
+            return throw(Exact(format!(
+                "Keyfile {path} must only be accessible by its owner (mode 0600), but its mode is {:o}", mode & 0o777)));
+        }
+        Self::from_hex(fs::read_to_string(path)?.as_str())
+    }
+
+    ////////////////////////////////////////////////////////////////
+    // instance methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Decrypts and authenticates a sealed payload (nonce|ciphertext|tag)
+    pub fn decrypt(&self, sealed: &[u8], aad: &[u8]) -> std::io::Result<Vec<u8>> {
+        if sealed.len() < ENCRYPTION_OVERHEAD {
+            return throw(Exact("Encrypted payload is truncated".into()));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&self.key));
+        cipher.decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+            .or_else(|_| throw(Exact("Decryption failed: the data was tampered with or the key is incorrect".into())))
+    }
+
+    /// Encrypts a payload; returning the sealed bytes (nonce|ciphertext|tag)
+    pub fn encrypt(&self, plaintext: &[u8], aad: &[u8]) -> std::io::Result<Vec<u8>> {
+        let nonce = rand::random::<[u8; NONCE_LEN]>();
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&self.key));
+        let ciphertext = cipher.encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+            .or_else(|_| throw(Exact("Encryption failed".into())))?;
+        let mut sealed = Vec::with_capacity(ENCRYPTION_OVERHEAD + plaintext.len());
+        sealed.extend(nonce);
+        sealed.extend(ciphertext);
+        Ok(sealed)
+    }
+
+    /// Writes the key (hex-encoded) to the specified path
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        if let Some(parent) = Path::new(path).parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new().create(true).truncate(true).write(true)
+            .mode(0o600).open(path)?;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+        file.write_all(hex::encode(self.key).as_bytes())
+    }
+}
+
+impl Debug for EncryptionKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EncryptionKey(****)")
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testdata::run_isolated_test;
+
+    #[test]
+    fn test_encrypt_then_decrypt() {
+        let key = EncryptionKey::generate();
+        let sealed = key.encrypt(b"Hello World", b"row 0").unwrap();
+        assert_eq!(sealed.len(), 11 + ENCRYPTION_OVERHEAD);
+        assert_eq!(key.decrypt(&sealed, b"row 0").unwrap(), b"Hello World".to_vec());
+    }
+
+    #[test]
+    fn test_decrypt_rejects_tampering() {
+        let key = EncryptionKey::generate();
+        let mut sealed = key.encrypt(b"Hello World", b"row 0").unwrap();
+        assert!(key.decrypt(&sealed, b"row 1").is_err());
+        sealed[NONCE_LEN] ^= 0xFF;
+        assert!(key.decrypt(&sealed, b"row 0").is_err());
+        assert!(EncryptionKey::generate().decrypt(&sealed, b"row 0").is_err());
+    }
+
+    #[test]
+    fn test_hex_round_trip() {
+        let key = EncryptionKey::generate();
+        let path = format!("{}/keys/test.key", Machine::oxide_home());
+        let path = path.as_str();
+        key.save(path).unwrap();
+        let loaded = EncryptionKey::load_file(path).unwrap();
+        assert_eq!(key, loaded);
+        assert!(EncryptionKey::from_hex("abcd").is_err());
+    }
+
+    #[test]
+    fn test_load_file_rejects_shared_keyfiles() {
+        let path = format!("{}/keys/shared.key", Machine::oxide_home());
+        let path = path.as_str();
+        EncryptionKey::generate().save(path).unwrap();
+        fs::set_permissions(path, fs::Permissions::from_mode(0o644)).unwrap();
+        let err = EncryptionKey::load_file(path).unwrap_err();
+        assert!(err.to_string().contains("mode 0600"));
+    }
+
+    #[test]
+    fn test_load_without_a_key() {
+        assert!(run_isolated_test("encryption::tests::load_without_a_key"));
+    }
+
+    /// (run in a child process by [test_load_without_a_key])
+    #[test]
+    #[ignore]
+    fn load_without_a_key() {
+        env::remove_var(KEY_ENV_VAR);
+        env::remove_var(KEY_FILE_ENV_VAR);
+        env::set_var("OXIDE_HOME", format!("{}/keys/without", Machine::oxide_home()));
+        assert_eq!(EncryptionKey::load().map_err(|e| e.to_string()),
+                   Err(format!("Encrypted tables require a key: set OXIDE_KEY or OXIDE_KEY_FILE, or create {}",
+                               EncryptionKey::get_default_key_path())));
+    }
+
+    #[test]
+    fn test_load_default_keyfile() {
+        assert!(run_isolated_test("encryption::tests::load_default_keyfile"));
+    }
+
+    /// (run in a child process by [test_load_default_keyfile])
+    #[test]
+    #[ignore]
+    fn load_default_keyfile() {
+        env::remove_var(KEY_ENV_VAR);
+        env::remove_var(KEY_FILE_ENV_VAR);
+        env::set_var("OXIDE_HOME", format!("{}/keys/default", Machine::oxide_home()));
+        let path = EncryptionKey::get_default_key_path();
+        let key = EncryptionKey::generate();
+        key.save(path.as_str()).unwrap();
+        assert_eq!(EncryptionKey::load().unwrap(), key);
+
+        // the default keyfile must only be accessible by its owner as well
+        fs::set_permissions(path.as_str(), fs::Permissions::from_mode(0o644)).unwrap();
+        assert!(EncryptionKey::load().unwrap_err().to_string().contains("mode 0600"));
+    }
+}
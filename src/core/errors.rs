@@ -93,6 +93,8 @@ impl Display for TypeMismatchErrors {
 pub enum Errors {
     AssertionError(String, String),
     CannotSubtract(String, String),
+    ChecksumMismatch(String, usize),
+    ConstraintViolation(String, String, String, String),
     Empty,
     Exact(String),
     ExactNear(String, Token),
@@ -102,6 +104,7 @@ pub enum Errors {
     IndexOutOfRange(String, usize, usize),
     InvalidNamespace(String),
     Multiple(Vec<Errors>),
+    NamespaceLocked(String, String),
     NotImplemented(String),
     PackageNotFound(String),
     PlatformOpError(PlatformOps),
@@ -119,6 +122,10 @@ impl Display for Errors {
                 format!("Assertion Error: {a} was not {b}"),
             Errors::CannotSubtract(a, b) =>
                 format!("Cannot subtract {b} from {a}"),
+            Errors::ChecksumMismatch(path, id) =>
+                format!("Checksum mismatch detected in {path} (row {id})"),
+            Errors::ConstraintViolation(table, column, constraint, value) =>
+                format!("{table}.{column} violates {constraint}: {value}"),
             Errors::Empty => String::from("Doh."),
             Errors::Exact(message) => format!("{message}"),
             Errors::ExactNear(message, token) =>
@@ -139,6 +146,8 @@ impl Display for Errors {
                     .map(|e| e.to_string())
                     .collect::<Vec<_>>()
                     .join("\n")),
+            Errors::NamespaceLocked(ns, pid) =>
+                format!("{ns} is locked by another process (pid {pid})"),
             Errors::NotImplemented(expr) =>
                 format!("Not yet implemented - {expr}"),
             Errors::PackageNotFound(name) =>
@@ -179,6 +188,10 @@ mod tests {
                "Assertion Error: true was not false");
         verify(CannotSubtract("a".into(), "b".into()),
                "Cannot subtract b from a");
+        verify(ChecksumMismatch("stocks.table".into(), 5),
+               "Checksum mismatch detected in stocks.table (row 5)");
+        verify(ConstraintViolation("stocks".into(), "symbol".into(), "not null".into(), "null".into()),
+               "stocks.symbol violates not null: null");
         verify(PlatformOpError(PlatformOps::UtilHex),
                "Conversion error: \"util::hex(x)\"");
         verify(Exact("Something bad happened".into()),
@@ -199,6 +212,8 @@ mod tests {
             column_number: 18,
         }), "Illegal use of operator '+'");
         verify(InvalidNamespace("a.b.c".into()), "Invalid namespace reference a.b.c");
+        verify(NamespaceLocked("a.b.c".into(), "123".into()),
+               "a.b.c is locked by another process (pid 123)");
         verify(NotImplemented("magic()".into()), "Not yet implemented - magic()");
         verify(PackageNotFound("wth".into()), "Package 'wth' not found");
         verify(IndexOutOfRange("bytes".into(), 5, 4),
@@ -0,0 +1,148 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// expiry module
+////////////////////////////////////////////////////////////////////
+
+use crate::dataframe_actor::DataframeActor;
+use crate::expression::TableOptions;
+use crate::namespace_leases::NamespaceLease;
+use crate::namespaces::Namespace;
+use crate::object_config::ObjectConfig;
+use crate::system_catalog::SystemCatalog;
+use crate::*;
+use actix::Addr;
+use log::{error, info};
+use std::time::Duration;
+
+/// the interval between sweeps of the expired rows
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Returns the namespaces of all tables (under `$OXIDE_HOME`) having a TTL
+pub fn find_expiring_tables() -> std::io::Result<Vec<Namespace>> {
+    let mut namespaces = SystemCatalog::find_tables()?;
+    namespaces.retain(|ns| ObjectConfig::load(ns)
+        .map(|cfg| cfg.get_options().iter().any(|o| matches!(o, TableOptions::Ttl(..))))
+        .unwrap_or(false));
+    Ok(namespaces)
+}
+
+/// Deletes the expired rows of all tables having a TTL; returning the number of rows deleted.
+/// The rows are deleted by the actor serving the tables, so the sweep is serialized with
+/// the other writes to them. A table that can't be swept is logged and skipped; as is
+/// (quietly) a table whose namespace is leased by another process.
+pub async fn sweep_expired_rows(actor: &Addr<DataframeActor>) -> std::io::Result<usize> {
+    let mut expired = 0;
+    for ns in find_expiring_tables()? {
+        if !NamespaceLease::try_acquire(&ns).unwrap_or(false) { continue; }
+        match expire_rows!(actor, ns) {
+            Ok(count) => expired += count,
+            Err(err) => error!("Failed to expire the rows of {ns}: {err}"),
+        }
+    }
+    Ok(expired)
+}
+
+/// Starts the background sweep of expired rows (within the actor system of the given actor)
+pub fn start_expiry_sweep(actor: Addr<DataframeActor>) {
+    actix::spawn(async move {
+        loop {
+            match sweep_expired_rows(&actor).await {
+                Ok(0) => {}
+                Ok(count) => info!("Expired {count} row(s)"),
+                Err(err) => error!("Failed to sweep the expired rows: {err}"),
+            }
+            actix::clock::sleep(SWEEP_INTERVAL).await;
+        }
+    });
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_row_collection::FileRowCollection;
+    use crate::interpreter::Interpreter;
+    use crate::row_collection::RowCollection;
+    use crate::typed_values::TypedValue::StringValue;
+    use actix::Actor;
+
+    #[actix::test]
+    async fn test_expired_rows_are_invisible_then_swept() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("expiry.sweep.quotes") (
+                symbol: String(8),
+                updated_time: Date
+            ) with ttl("1h", updated_time)
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ symbol: "ABC", updated_time: cal::now() },
+             { symbol: "OLD", updated_time: util::to_date(1577836800000) },
+             { symbol: "UNO", updated_time: cal::now() }] ~> ns("expiry.sweep.quotes")
+        "#).unwrap();
+
+        // the expired row is invisible immediately
+        let ns = Namespace::parse("expiry.sweep.quotes").unwrap();
+        let frc = FileRowCollection::open(&ns).unwrap();
+        let symbols = |frc: &FileRowCollection| frc.read_active_rows().unwrap().iter()
+            .map(|row| row[0].to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(symbols(&frc), vec![StringValue("ABC".into()), StringValue("UNO".into())]);
+        assert_eq!(frc.len().unwrap(), 3);
+
+        // the sweep deletes it (via the actor) without renumbering the remaining rows
+        assert!(find_expiring_tables().unwrap().contains(&ns));
+        let actor = DataframeActor::new().start();
+        assert_eq!(expire_rows!(actor, ns).unwrap(), 1);
+        let frc = FileRowCollection::open(&ns).unwrap();
+        assert_eq!(symbols(&frc), vec![StringValue("ABC".into()), StringValue("UNO".into())]);
+        assert_eq!(frc.len().unwrap(), 3);
+        assert!(!frc.read_row_metadata(1).unwrap().is_allocated);
+        assert_eq!(frc.read_one(2).unwrap().map(|row| row[0].to_owned()), Some(StringValue("UNO".into())));
+        assert_eq!(expire_rows!(actor, ns).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_describe_reports_ttl() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("expiry.describe.quotes") (
+                symbol: String(8),
+                updated_time: Date
+            ) with ttl("30m", updated_time)
+        "#).unwrap();
+        let described = interpreter.evaluate(r#"
+            tools::describe(ns("expiry.describe.quotes"))
+        "#).unwrap().to_table().unwrap().read_active_rows().unwrap();
+        assert_eq!(described[1][1], StringValue(r#"Date with ttl("30m", updated_time)"#.into()));
+    }
+
+    #[test]
+    fn test_alter_ttl_column() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            drop table ns("expiry.alter.quotes")
+            create table ns("expiry.alter.quotes") (
+                symbol: String(8),
+                updated_time: Date
+            ) with ttl("1h", updated_time)
+        "#).unwrap();
+
+        // the TTL column can't be dropped or retyped
+        assert!(interpreter.evaluate(r#"
+            alter table ns("expiry.alter.quotes") drop column updated_time
+        "#).is_err());
+        assert!(interpreter.evaluate(r#"
+            alter table ns("expiry.alter.quotes") alter column updated_time: String(32)
+        "#).is_err());
+
+        // renaming the TTL column renames the TTL option too
+        interpreter.evaluate(r#"
+            alter table ns("expiry.alter.quotes") rename column updated_time to ts
+        "#).unwrap();
+        let ns = Namespace::parse("expiry.alter.quotes").unwrap();
+        assert!(ObjectConfig::load(&ns).unwrap().get_options()
+            .contains(&TableOptions::Ttl(3_600_000, "ts".into())));
+        assert!(FileRowCollection::open(&ns).is_ok());
+    }
+}
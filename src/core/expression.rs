@@ -4,14 +4,17 @@
 ////////////////////////////////////////////////////////////////////
 
 use crate::byte_code_compiler::ByteCodeCompiler;
+use crate::change_capture::ChangeKind;
+use crate::constraints::Constraint;
 use crate::data_types::DataType;
 use crate::data_types::DataType::VaryingType;
 use crate::sequences::{Array, Sequence};
 
 use crate::errors::throw;
-use crate::errors::Errors::{IllegalOperator, TypeMismatch};
+use crate::errors::Errors::{Exact, IllegalOperator, TypeMismatch};
 use crate::errors::TypeMismatchErrors::{ConstantValueExpected, UnsupportedType};
 use crate::expression::Expression::{CodeBlock, Condition, FunctionCall, If, Literal, Return, Variable, While};
+use crate::hybrid_row_collection::HybridPolicy;
 use crate::inferences::Inferences;
 use crate::numbers::Numbers;
 use crate::numbers::Numbers::I64Value;
@@ -32,6 +35,26 @@ pub const TRUE: Expression = Condition(Conditions::True);
 pub const NULL: Expression = Literal(TypedValue::Null);
 pub const UNDEFINED: Expression = Literal(TypedValue::Undefined);
 
+/// Represents a table alteration (e.g. add, alter, drop or rename a column)
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum Alterations {
+    AddColumn(Parameter),
+    AlterColumn(Parameter),
+    DropColumn(String),
+    RenameColumn(String, String),
+}
+
+impl Alterations {
+    pub fn to_code(&self) -> String {
+        match self {
+            Alterations::AddColumn(param) => format!("add column {}", param.to_code()),
+            Alterations::AlterColumn(param) => format!("alter column {}", param.to_code()),
+            Alterations::DropColumn(name) => format!("drop column {name}"),
+            Alterations::RenameColumn(from, to) => format!("rename column {from} to {to}"),
+        }
+    }
+}
+
 /// Represents Logical Conditions
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum Conditions {
@@ -83,17 +106,88 @@ pub enum CreationEntity {
     },
     TableEntity {
         columns: Vec<Parameter>,
+        constraints: Vec<Constraint>,
         from: Option<Box<Expression>>,
         options: Vec<TableOptions>,
     },
+    SequenceEntity {
+        start: Option<Box<Expression>>,
+        increment: Option<Box<Expression>>,
+    },
     TableFnEntity {
         fx: Box<Expression>,
     },
+    TriggerEntity {
+        name: String,
+        kinds: Vec<ChangeKind>,
+        fx: Box<Expression>,
+    },
 }
 
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum TableOptions {
-    Journaling
+    ChangeCapture,
+    Encrypted,
+    /// rows are written through to disk, while up to `capacity` of them are also held in memory
+    Hybrid(usize, HybridPolicy),
+    Journaling,
+    MemoryMapped,
+    Partitioned(Vec<String>),
+    Replica,
+    Replicated,
+    /// the table belongs to a session, and is dropped when the session ends (i.e. `create temp table`)
+    Temporary,
+    /// rows expire once the given duration (in milliseconds) has elapsed since the date of a column
+    Ttl(i64, String),
+}
+
+impl TableOptions {
+    /// Formats a duration (in milliseconds) using its largest exact unit
+    /// ex: 1800000 => "30m"
+    pub fn format_duration(millis: i64) -> String {
+        for (unit, size) in [("d", 86_400_000), ("h", 3_600_000), ("m", 60_000), ("s", 1_000)] {
+            if millis != 0 && millis % size == 0 {
+                return format!("{}{}", millis / size, unit);
+            }
+        }
+        format!("{}ms", millis)
+    }
+
+    /// Parses a duration (e.g. "250ms", "30s", "30m", "12h" or "7d") into milliseconds
+    pub fn parse_duration(text: &str) -> std::io::Result<i64> {
+        let text = text.trim();
+        let split = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+        let (amount, unit) = text.split_at(split);
+        let size = match unit {
+            "ms" => 1,
+            "s" => 1_000,
+            "m" => 60_000,
+            "h" => 3_600_000,
+            "d" => 86_400_000,
+            _ => return throw(Exact(format!("Invalid duration '{}': expected ms, s, m, h or d", text)))
+        };
+        match amount.parse::<i64>().ok().and_then(|n| n.checked_mul(size)) {
+            Some(millis) if millis > 0 => Ok(millis),
+            _ => throw(Exact(format!("Invalid duration '{}'", text)))
+        }
+    }
+
+    pub fn to_code(&self) -> String {
+        match self {
+            TableOptions::ChangeCapture => "change_capture".into(),
+            TableOptions::Encrypted => "encrypted".into(),
+            TableOptions::Hybrid(capacity, HybridPolicy::Positional) => format!("hybrid({})", capacity),
+            TableOptions::Hybrid(capacity, policy) => format!("hybrid({}, {})", capacity, policy.to_code()),
+            TableOptions::Journaling => "journaling".into(),
+            TableOptions::MemoryMapped => "memory_mapped".into(),
+            TableOptions::Partitioned(columns) => format!("partitioned({})", columns.join(", ")),
+            TableOptions::Replica => "replica".into(),
+            TableOptions::Replicated => "replicated".into(),
+            TableOptions::Temporary => "temp".into(),
+            TableOptions::Ttl(millis, column) =>
+                format!("ttl(\"{}\", {})", Self::format_duration(*millis), column),
+        }
+    }
 }
 
 /// Represents an import definition
@@ -122,9 +216,15 @@ impl Display for ImportOps {
 /// Represents a data modification event
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum Mutations {
+    Alter {
+        path: Box<Expression>,
+        alteration: Alterations,
+    },
     Append {
         path: Box<Expression>,
         source: Box<Expression>,
+        /// appends the rows via the bulk load path (e.g. `append ... with bulk`)
+        is_bulk: bool,
     },
     Create { path: Box<Expression>, entity: CreationEntity },
     Declare(CreationEntity),
@@ -141,6 +241,7 @@ pub enum Mutations {
         condition: Option<Conditions>,
         limit: Option<Box<Expression>>,
     },
+    Rename { path: Box<Expression>, target: Box<Expression> },
     Truncate {
         path: Box<Expression>,
         limit: Option<Box<Expression>>,
@@ -172,6 +273,7 @@ pub enum MutateTarget {
 /// Represents an enumeration of queryables
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 pub enum Queryables {
+    AsOf { from: Box<Expression>, moment: Box<Expression> },
     Limit { from: Box<Expression>, limit: Box<Expression> },
     Select {
         fields: Vec<Expression>,
@@ -234,6 +336,7 @@ pub enum Expression {
     Modulo(Box<Expression>, Box<Expression>),
     Multiply(Box<Expression>, Box<Expression>),
     Neg(Box<Expression>),
+    NextVal(Box<Expression>),
     Ns(Box<Expression>),
     Parameters(Vec<Parameter>),
     Plus(Box<Expression>, Box<Expression>),
@@ -336,6 +439,7 @@ impl Expression {
             Expression::Multiply(a, b) =>
                 format!("{} * {}", Self::decompile(a), Self::decompile(b)),
             Expression::Neg(a) => format!("-({})", Self::decompile(a)),
+            Expression::NextVal(a) => format!("nextval({})", Self::decompile(a)),
             Expression::Ns(a) => format!("ns({})", Self::decompile(a)),
             Expression::Parameters(parameters) => Self::decompile_parameters(parameters),
             Expression::Plus(a, b) =>
@@ -417,6 +521,20 @@ impl Expression {
             .join(", ")
     }
 
+    /// Decompiles column definitions along with the constraints declared upon them
+    /// ex: symbol: String(8) not null unique, last_sale: f64 check(last_sale >= 0.0)
+    pub fn decompile_columns(params: &Vec<Parameter>, constraints: &Vec<Constraint>) -> String {
+        params.iter().map(|p| {
+            constraints.iter()
+                .filter(|c| c.get_column_names() == vec![p.get_name().to_string()])
+                .fold(p.to_code(), |code, c| format!("{} {}", code, c.to_code()))
+        }).collect::<Vec<_>>().join(", ")
+    }
+
+    pub fn decompile_change_kinds(kinds: &Vec<ChangeKind>) -> String {
+        kinds.iter().map(|k| k.to_code()).collect::<Vec<_>>().join(", ")
+    }
+
     pub fn decompile_directives(directive: &Directives) -> String {
         match directive {
             Directives::MustAck(a) => format!("[+] {}", Self::decompile(a)),
@@ -452,6 +570,22 @@ impl Expression {
         opt.to_owned().map(|i| Self::decompile(&i)).unwrap_or("".into())
     }
 
+    pub fn decompile_sequence_options(
+        start: &Option<Box<Expression>>,
+        increment: &Option<Box<Expression>>,
+    ) -> String {
+        let mut options = String::new();
+        if let Some(start) = start { options.push_str(&format!(" start with {}", Self::decompile(start))) }
+        if let Some(increment) = increment { options.push_str(&format!(" increment by {}", Self::decompile(increment))) }
+        options
+    }
+
+    pub fn decompile_table_options(options: &Vec<TableOptions>) -> String {
+        options.iter()
+            .filter(|o| **o != TableOptions::Temporary)
+            .map(|o| format!(" with {}", o.to_code())).collect::<Vec<_>>().join("")
+    }
+
     pub fn decompile_update_list(fields: &Vec<Expression>, values: &Vec<Expression>) -> String {
         fields.iter().zip(values.iter()).map(|(f, v)|
             format!("{} = {}", Self::decompile(f), Self::decompile(v))).collect::<Vec<String>>().join(", ")
@@ -466,25 +600,40 @@ impl Expression {
 
     pub fn decompile_modifications(expr: &Mutations) -> String {
         match expr {
-            Mutations::Append { path, source } =>
-                format!("append {} {}", Self::decompile(path), Self::decompile(source)),
+            Mutations::Alter { path, alteration } =>
+                format!("alter table {} {}", Self::decompile(path), alteration.to_code()),
+            Mutations::Append { path, source, is_bulk } =>
+                format!("append {} {}{}", Self::decompile(path), Self::decompile(source),
+                        if *is_bulk { " with bulk" } else { "" }),
             Mutations::Create { path, entity } =>
                 match entity {
                     CreationEntity::IndexEntity { columns } =>
                         format!("create index {} [{}]", Self::decompile(path), Self::decompile_list(columns)),
-                    CreationEntity::TableEntity { columns, from, options } =>
-                        format!("create table {} ({})", Self::decompile(path), Self::decompile_parameters(columns)),
+                    CreationEntity::TableEntity { columns, constraints, from, options } =>
+                        format!("create {}table {} ({}){}",
+                                if options.contains(&TableOptions::Temporary) { "temp " } else { "" },
+                                Self::decompile(path), Self::decompile_columns(columns, constraints),
+                                Self::decompile_table_options(options)),
+                    CreationEntity::SequenceEntity { start, increment } =>
+                        format!("create sequence {}{}", Self::decompile(path), Self::decompile_sequence_options(start, increment)),
                     CreationEntity::TableFnEntity { fx } =>
                         format!("create table {} fn({})", Self::decompile(path), Self::decompile(fx)),
+                    CreationEntity::TriggerEntity { name, kinds, fx } =>
+                        format!("create trigger {} on {} after {} {}", name, Self::decompile(path),
+                                Self::decompile_change_kinds(kinds), Self::decompile(fx)),
                 }
             Mutations::Declare(entity) =>
                 match entity {
                     CreationEntity::IndexEntity { columns } =>
                         format!("index [{}]", Self::decompile_list(columns)),
-                    CreationEntity::TableEntity { columns, from, options } =>
-                        format!("table({})", Self::decompile_parameters(columns)),
+                    CreationEntity::TableEntity { columns, constraints, from, options } =>
+                        format!("table({})", Self::decompile_columns(columns, constraints)),
+                    CreationEntity::SequenceEntity { start, increment } =>
+                        format!("sequence{}", Self::decompile_sequence_options(start, increment)),
                     CreationEntity::TableFnEntity { fx } =>
                         format!("table fn({})", Self::decompile(fx)),
+                    CreationEntity::TriggerEntity { name, kinds, fx } =>
+                        format!("trigger {} after {} {}", name, Self::decompile_change_kinds(kinds), Self::decompile(fx)),
                 }
             Mutations::Drop(target) => {
                 let (kind, path) = match target {
@@ -502,6 +651,8 @@ impl Expression {
                         condition.to_owned().map(|e| format!(" where {}", Self::decompile_cond(&e))).unwrap_or("".into()),
                         limit.to_owned().map(|e| format!(" limit {}", Self::decompile(&e))).unwrap_or("".into()),
                 ),
+            Mutations::Rename { path, target } =>
+                format!("rename table {} to {}", Self::decompile(path), Self::decompile(target)),
             Mutations::Truncate { path, limit } =>
                 format!("truncate {}{}", Self::decompile(path), Self::decompile_limit(limit)),
             Mutations::Undelete { path, condition, limit } =>
@@ -514,6 +665,8 @@ impl Expression {
 
     pub fn decompile_queryables(expr: &Queryables) -> String {
         match expr {
+            Queryables::AsOf { from, moment } =>
+                format!("{} as of {}", Self::decompile(from), Self::decompile(moment)),
             Queryables::Limit { from: a, limit: b } =>
                 format!("{} limit {}", Self::decompile(a), Self::decompile(b)),
             Queryables::Where { from, condition } =>
@@ -939,6 +1092,17 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_from_as_of() {
+        let from = DatabaseOp(Queryable(Queryables::AsOf {
+            from: Box::new(From(Box::new(
+                Ns(Box::new(Literal(StringValue("machine.overwrite.stocks".into()))))
+            ))),
+            moment: Box::new(Literal(Number(I64Value(3)))),
+        }));
+        assert_eq!(from.to_code(), "from ns(\"machine.overwrite.stocks\") as of 3")
+    }
+
     #[test]
     fn test_overwrite() {
         let model = DatabaseOp(Mutation(Mutations::Overwrite {
@@ -1101,6 +1265,26 @@ mod tests {
         assert_eq!(Expression::decompile(&model), "f(2, 3)")
     }
 
+    #[test]
+    fn test_alter_table_in_namespace() {
+        let path = Box::new(Ns(Box::new(Literal(StringValue("compiler.alter.stocks".into())))));
+        let model = DatabaseOp(Mutation(Mutations::Alter {
+            path: path.clone(),
+            alteration: Alterations::AddColumn(Parameter::new("market", StringType(8))),
+        }));
+        assert_eq!(
+            Expression::decompile(&model),
+            r#"alter table ns("compiler.alter.stocks") add column market: String(8)"#);
+
+        let model = DatabaseOp(Mutation(Mutations::Alter {
+            path,
+            alteration: Alterations::RenameColumn("exchange".into(), "market".into()),
+        }));
+        assert_eq!(
+            Expression::decompile(&model),
+            r#"alter table ns("compiler.alter.stocks") rename column exchange to market"#);
+    }
+
     #[test]
     fn test_create_index_in_namespace() {
         let model = DatabaseOp(Mutation(Mutations::Create {
@@ -1128,6 +1312,7 @@ mod tests {
                     Parameter::with_default("exchange", StringType(8), StringValue("NYSE".into())),
                     Parameter::with_default("last_sale", NumberType(F64Kind), Number(F64Value(0.))),
                 ],
+                constraints: vec![],
                 from: None,
                 options: vec![],
             },
@@ -1137,6 +1322,25 @@ mod tests {
             r#"create table ns("compiler.create.stocks") (symbol: String(8) := "ABC", exchange: String(8) := "NYSE", last_sale: f64 := 0.0)"#)
     }
 
+    #[test]
+    fn test_create_table_with_options_in_namespace() {
+        let model = DatabaseOp(Mutation(Mutations::Create {
+            path: Box::new(Ns(Box::new(Literal(StringValue("compiler.encrypted.stocks".into()))))),
+            entity: TableEntity {
+                columns: vec![
+                    Parameter::new("symbol", StringType(8)),
+                    Parameter::new("last_sale", NumberType(F64Kind)),
+                ],
+                constraints: vec![],
+                from: None,
+                options: vec![TableOptions::Encrypted, TableOptions::Journaling],
+            },
+        }));
+        assert_eq!(
+            Expression::decompile(&model),
+            r#"create table ns("compiler.encrypted.stocks") (symbol: String(8), last_sale: f64) with encrypted with journaling"#)
+    }
+
     #[test]
     fn test_declare_table() {
         let model = DatabaseOp(Mutation(Mutations::Declare(TableEntity {
@@ -1145,6 +1349,7 @@ mod tests {
                 Parameter::new("exchange", StringType(8)),
                 Parameter::new("last_sale", NumberType(F64Kind)),
             ],
+            constraints: vec![],
             from: None,
             options: vec![],
         })));
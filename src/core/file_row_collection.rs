@@ -5,24 +5,37 @@
 
 use crate::blobs::{BLOBCellMetadata, BLOBStore};
 use crate::byte_code_compiler::ByteCodeCompiler;
+use crate::change_capture::{ChangeEvent, ChangeFeed, Trigger};
+use crate::checksums::{append_checksum, is_intact, is_zeroed, ChecksumPolicy, CHECKSUM_LEN};
 use crate::columns::Column;
+use crate::constraints::{Constraints, FileVersion};
+use crate::dataframe::Dataframe::Model;
 use crate::data_types::DataType::NumberType;
+use crate::encryption::{EncryptionKey, ENCRYPTION_OVERHEAD};
 use crate::errors::{throw, Errors};
+use crate::expression::TableOptions;
 use crate::field;
 use crate::field::FieldMetadata;
 use crate::machine::Machine;
+use crate::memory_map::MemoryMap;
+use crate::model_row_collection::ModelRowCollection;
+use crate::namespace_leases::NamespaceLease;
 use crate::namespaces::Namespace;
 use crate::number_kind::NumberKind::U64Kind;
 use crate::numbers::Numbers;
 use crate::object_config::ObjectConfig;
+use crate::page_cache::{PageCache, PagedFile};
 use crate::parameter::Parameter;
 use crate::platform::PlatformOps;
+use crate::replication::{ReplicationEvent, ReplicationLog};
 use crate::row_collection::{RowCollection, RowEncoding};
 use crate::row_metadata::RowMetadata;
 use crate::structures::Row;
+use crate::table_header::{TableHeader, CHECKSUMMED_BLOBS_VERSION, CHECKSUMMED_RECORDS_VERSION, TABLE_FORMAT_VERSION, TABLE_HEADER_LEN};
 use crate::typed_values::TypedValue;
-use crate::typed_values::TypedValue::{ErrorValue, Number};
-use log::error;
+use crate::typed_values::TypedValue::{Boolean, ErrorValue, Number, StringValue, TableValue, Undefined};
+use chrono::Local;
+use log::{error, warn};
 use serde::de::Error;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
@@ -35,14 +48,30 @@ use std::os::unix::fs::FileExt;
 use std::path::Path;
 use std::sync::Arc;
 
+/// the number of rows encoded into each buffer written by [FileRowCollection::bulk_load]
+const BULK_LOAD_CHUNK_SIZE: usize = 8192;
+
 /// File-based RowCollection implementation
 #[derive(Clone)]
 pub struct FileRowCollection {
     blobs: BLOBStore,
+    changes: Option<ChangeFeed>,
+    checksum_policy: ChecksumPolicy,
     columns: Vec<Column>,
-    file: Arc<File>,
+    constraints: Option<Constraints>,
+    data_offset: u64,
+    encryption: Option<EncryptionKey>,
+    file: Arc<PagedFile>,
+    format_version: u16,
+    is_memory_mapped: bool,
+    is_write_protected: bool,
+    lease: Option<Namespace>,
     path: String,
     record_size: usize,
+    replication: Option<ReplicationLog>,
+    table_id: u64,
+    triggers: Vec<Trigger>,
+    ttl: Option<(i64, usize)>,
 }
 
 impl FileRowCollection {
@@ -50,24 +79,31 @@ impl FileRowCollection {
         columns: Vec<Column>,
         path: &str,
     ) -> std::io::Result<Self> {
-        let full_blob_path = format!("{}.blob", path);
-        let blobs = BLOBStore::open_file(full_blob_path.as_str(), true).unwrap();
-        Ok(Self {
-            record_size: Row::compute_record_size(&columns),
-            columns,
-            blobs,
-            file: Arc::from(File::open(path)?),
-            path: path.to_string(),
-        })
+        Ok(Self::new(columns, Arc::from(File::open(path)?), path))
     }
 
     /// Creates a new table within the specified namespace and having the specified columns
     pub fn create_table(ns: &Namespace, params: &Vec<Parameter>) -> std::io::Result<Self> {
+        Self::create_table_with_options(ns, params, &Vec::new())
+    }
+
+    /// Creates a new table within the specified namespace and having the specified columns and options
+    pub fn create_table_with_options(
+        ns: &Namespace,
+        params: &Vec<Parameter>,
+        options: &Vec<TableOptions>,
+    ) -> std::io::Result<Self> {
         let path = ns.get_table_file_path();
         let columns = Column::from_parameters(params);
-        ObjectConfig::build_table(params.clone()).save(ns)?;
+        ObjectConfig::build_table(params.clone()).with_options(options.clone()).save(ns)?;
         let file = Arc::new(Self::table_file_create(ns)?);
-        Ok(Self::new(columns, file, path.as_str()))
+        if options.contains(&TableOptions::Replicated) {
+            ReplicationLog::create(ns)?;
+        }
+        if options.contains(&TableOptions::ChangeCapture) {
+            ChangeFeed::create(ns)?;
+        }
+        Self::new(columns, file, path.as_str()).with_options(options)
     }
 
     pub fn get_related_filename(path: &str, extension: &str) -> (String, String) {
@@ -96,12 +132,30 @@ impl FileRowCollection {
     ) -> Self {
         let full_blob_path = format!("{}.blob", path);
         let blobs = BLOBStore::open_file(full_blob_path.as_str(), true).unwrap();
+        let (data_offset, header) = Self::init_header(&file, &columns).unwrap_or_else(|err| {
+            error!("Failed to initialize the header of {path}: {err}");
+            (0, None)
+        });
+        let format_version = header.map(|h| h.version).unwrap_or(0);
         Self {
-            record_size: Row::compute_record_size(&columns),
+            record_size: Row::compute_record_size(&columns) + CHECKSUM_LEN,
+            data_offset,
             columns,
-            blobs,
-            file,
+            blobs: blobs.with_checksums(format_version >= CHECKSUMMED_BLOBS_VERSION),
+            changes: None,
+            constraints: None,
+            checksum_policy: ChecksumPolicy::load(),
+            encryption: None,
+            file: Arc::new(PagedFile::new(file, path).unwrap()),
+            format_version,
+            is_memory_mapped: false,
+            is_write_protected: false,
+            lease: NamespaceLease::find_namespace(path),
             path: path.to_string(),
+            replication: None,
+            table_id: header.map(|h| h.table_id).unwrap_or(0),
+            triggers: Vec::new(),
+            ttl: None,
         }
     }
 
@@ -112,8 +166,17 @@ impl FileRowCollection {
     fn open_file(ns: &Namespace, file: File) -> std::io::Result<Self> {
         let cfg = ObjectConfig::load(&ns)?;
         let path = ns.get_table_file_path();
+        Self::roll_forward(path.as_str())?;
         let columns = Column::from_parameters(&cfg.get_columns());
-        Ok(Self::new(columns, Arc::new(file), path.as_str()))
+        let triggers = cfg.get_triggers().iter()
+            .map(Trigger::compile)
+            .collect::<std::io::Result<Vec<_>>>()?;
+        let constraints = Constraints::from_config(ns, &cfg, &columns);
+        Self::new(columns, Arc::new(file), path.as_str())
+            .with_constraints(constraints)
+            .with_options(&cfg.get_options())?
+            .with_triggers(triggers)
+            .with_verified_header()
     }
 
     pub fn open_or_create(ns: &Namespace, params: Vec<Parameter>) -> std::io::Result<Self> {
@@ -133,11 +196,873 @@ impl FileRowCollection {
         }
     }
 
+    /// Appends rows in bulk; the rows are validated as a batch (e.g. uniqueness is verified
+    /// once rather than per row), then encoded into large contiguous buffers which are written
+    /// directly to the (pre-sized) table file. Returns the number of rows appended.
+    pub fn bulk_load(&mut self, rows: Vec<Row>) -> std::io::Result<usize> {
+        self.bulk_load_with(rows, |_| ())
+    }
+
+    /// Appends rows in bulk (see [FileRowCollection::bulk_load]); passing each row,
+    /// as written (e.g. with its column defaults applied), to the given function.
+    pub fn bulk_load_with(&mut self, rows: Vec<Row>, mut on_written: impl FnMut(Row)) -> std::io::Result<usize> {
+        if let Err(err) = self.check_writable() { return throw(err); }
+        let start = self.len()?;
+        let before = self.get_version();
+        let rows = match &self.constraints {
+            Some(constraints) => match constraints.validate_rows(self, start, rows) {
+                Ok(rows) => rows,
+                Err(err) => return throw(err)
+            },
+            None => rows.into_iter().enumerate()
+                .map(|(n, row)| row.with_row_id(start + n))
+                .collect()
+        };
+        if rows.is_empty() { return Ok(0); }
+
+        // pre-size the file, then write the rows in chunks; publishing the new rows
+        // (e.g. to the replication log, change feed and triggers) once each chunk is written.
+        // Like any position beyond the end of the table, the new rows had no prior state.
+        let end = start + rows.len();
+        let captured = if self.is_observed() { Some(None) } else { None };
+        let mut indexed = Vec::new();
+        self.file.set_len(self.convert_rowid_to_offset(end))?;
+        for chunk in rows.chunks(BULK_LOAD_CHUNK_SIZE) {
+            let mut buffer = Vec::with_capacity(chunk.len() * self.record_size);
+            let mut written = Vec::new();
+            let mut decoded = Vec::with_capacity(chunk.len());
+            for row in chunk {
+                let record = self.encode_record(row.get_id(), row, None);
+                decoded.push(self.decode_written_record(&record, row));
+                if self.is_published() { written.push(record.clone()); }
+                let mut sealed = self.seal_record(record)?;
+                append_checksum(&mut sealed);
+                buffer.extend(sealed);
+            }
+            self.file.write_direct(self.convert_rowid_to_offset(chunk[0].get_id()), &buffer)?;
+            for (row, record) in chunk.iter().zip(written.iter()) {
+                self.publish_row(row.get_id(), captured.clone(), record);
+            }
+            for row in decoded {
+                if before.is_some() { indexed.push(row.to_owned()) }
+                on_written(row)
+            }
+        }
+        self.index_rows(before, &indexed);
+        Ok(rows.len())
+    }
+
+    /// Writes any cached (dirty) pages of the table file back to disk
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+
+    /// Returns the path of the table file
+    pub fn get_path(&self) -> &str {
+        self.path.as_str()
+    }
+
+    /// Validates the table header against the columns of this collection; headerless
+    /// or older-format files (i.e. written by an earlier version) are upgraded in place.
+    pub fn with_verified_header(mut self) -> std::io::Result<Self> {
+        match TableHeader::read(self.file.get_file())? {
+            Some(header) if header.version < TABLE_FORMAT_VERSION => {
+                header.validate(&self.columns, self.path.as_str())?;
+                self.upgrade()?;
+            }
+            Some(header) => header.validate(&self.columns, self.path.as_str())?,
+            None => { self.upgrade()?; }
+        }
+        Ok(self)
+    }
+
+    /// Enforces the given constraints (and column defaults) upon all subsequent writes
+    pub fn with_constraints(self, constraints: Option<Constraints>) -> Self {
+        Self { constraints, ..self }
+    }
+
+    /// Returns the version of this table prior to a write (see [Constraints::get_version])
+    fn get_version(&self) -> Option<Vec<FileVersion>> {
+        self.constraints.as_ref().and_then(|constraints| constraints.get_version())
+    }
+
+    /// Records the rows just written within the unique indices of this table (see [Constraints::index_rows])
+    fn index_rows(&self, before: Option<Vec<FileVersion>>, rows: &[Row]) {
+        if let Some(constraints) = &self.constraints {
+            constraints.index_rows(self, before, rows)
+        }
+    }
+
+    /// Applies the constraints (and column defaults) of this table to a value
+    /// about to be written to the specified field.
+    pub fn validate_field(&self, id: usize, column_id: usize, value: TypedValue) -> Result<TypedValue, Errors> {
+        match &self.constraints {
+            Some(constraints) => constraints.validate_field(self, id, column_id, value),
+            None => Ok(value)
+        }
+    }
+
+    /// Applies the constraints (and column defaults) of this table to a row
+    /// about to be written to the specified position.
+    pub fn validate_row(&self, id: usize, row: Row) -> Result<Row, Errors> {
+        match &self.constraints {
+            Some(constraints) => constraints.validate_row(self, id, row),
+            None => Ok(row)
+        }
+    }
+
+    /// Determines how records with mismatched checksums are handled upon read
+    pub fn with_checksum_policy(self, checksum_policy: ChecksumPolicy) -> Self {
+        Self { checksum_policy, ..self }
+    }
+
+    /// Enables encryption at rest; rows and BLOBs are sealed with the given key
+    pub fn with_encryption(self, key: EncryptionKey) -> Self {
+        Self {
+            record_size: Row::compute_record_size(&self.columns) + ENCRYPTION_OVERHEAD + CHECKSUM_LEN,
+            blobs: self.blobs.with_encryption(key.clone()),
+            encryption: Some(key),
+            ..self
+        }
+    }
+
+    /// Applies the storage-related table options (e.g. encryption, replication)
+    pub fn with_options(self, options: &Vec<TableOptions>) -> std::io::Result<Self> {
+        let frc = if options.contains(&TableOptions::Encrypted) {
+            self.with_encryption(EncryptionKey::load()?)
+        } else { self };
+        let frc = if options.contains(&TableOptions::Replicated) {
+            let log = ReplicationLog::open_file(
+                ReplicationLog::get_log_file_path(frc.path.as_str()).as_str())?;
+            let log = match &frc.encryption {
+                Some(key) => log.with_encryption(key.clone()),
+                None => log
+            };
+            frc.with_replication(log)
+        } else { frc };
+        let frc = if options.contains(&TableOptions::ChangeCapture) {
+            let feed = ChangeFeed::open_file(
+                ChangeFeed::get_feed_file_path(frc.path.as_str()).as_str())?;
+            let feed = match &frc.encryption {
+                Some(key) => feed.with_encryption(key.clone()),
+                None => feed
+            };
+            frc.with_change_feed(feed)
+        } else { frc };
+        let frc = match options.iter().find_map(|o| match o {
+            TableOptions::Ttl(millis, column) => Some((*millis, column)),
+            _ => None
+        }) {
+            Some((millis, name)) => match frc.columns.iter().position(|c| c.get_name() == name) {
+                Some(index) => frc.with_ttl(Some((millis, index))),
+                None => return throw(Errors::Exact(format!("TTL column '{}' not found", name)))
+            },
+            None => frc
+        };
+        Ok(frc.with_memory_map(options.contains(&TableOptions::MemoryMapped))
+            .with_write_protection(options.contains(&TableOptions::Replica)))
+    }
+
+    /// Captures all subsequent changes within the given change feed
+    pub fn with_change_feed(self, feed: ChangeFeed) -> Self {
+        Self { changes: Some(feed), ..self }
+    }
+
+    /// Enables (or disables) memory-mapped range reads and scans
+    pub fn with_memory_map(self, is_memory_mapped: bool) -> Self {
+        Self { is_memory_mapped, ..self }
+    }
+
+    /// Expires rows once the given duration (in milliseconds) has elapsed since
+    /// the date found within the given column; expired rows are no longer readable.
+    pub fn with_ttl(self, ttl: Option<(i64, usize)>) -> Self {
+        Self { ttl, ..self }
+    }
+
+    /// Publishes all subsequent changes to the given replication log
+    pub fn with_replication(self, log: ReplicationLog) -> Self {
+        Self { replication: Some(log), ..self }
+    }
+
+    /// Invokes the given triggers upon all subsequent changes
+    pub fn with_triggers(self, triggers: Vec<Trigger>) -> Self {
+        Self { triggers, ..self }
+    }
+
+    /// Enables (or disables) write protection; e.g. for replica tables
+    pub fn with_write_protection(self, is_write_protected: bool) -> Self {
+        Self { is_write_protected, ..self }
+    }
+
+    /// Indicates whether the rows of this table are encrypted at rest
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption.is_some()
+    }
+
+    /// Verifies that this table may be written to; i.e. it isn't write-protected, and
+    /// this process holds (or can acquire) the single-writer lease of its namespace.
+    pub fn check_writable(&self) -> Result<(), Errors> {
+        if self.is_write_protected { return Err(Errors::WriteProtected); }
+        match &self.lease {
+            Some(ns) => match NamespaceLease::try_acquire(ns) {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(Errors::NamespaceLocked(ns.get_full_name(), NamespaceLease::get_holder(ns))),
+                Err(err) => Err(Errors::Exact(err.to_string()))
+            }
+            None => Ok(())
+        }
+    }
+
+    /// Appends a change event to the replication log (if enabled)
+    fn publish(&self, event: ReplicationEvent) -> std::io::Result<()> {
+        match &self.replication {
+            Some(log) => log.append(&event).map(|_| ()),
+            None => Ok(())
+        }
+    }
+
+    /// Indicates whether a change feed or triggers observe the changes of this table
+    fn is_observed(&self) -> bool {
+        self.changes.is_some() || !self.triggers.is_empty()
+    }
+
+    /// Indicates whether the changes of this table are published (e.g. replicated or observed)
+    fn is_published(&self) -> bool {
+        self.replication.is_some() || self.is_observed()
+    }
+
+    /// Captures the state of a row prior to a write; or [None] if neither
+    /// a change feed nor triggers observe the changes of this table.
+    fn capture(&self, id: usize) -> Option<Option<Row>> {
+        if !self.is_observed() {
+            return None;
+        }
+        Some(self.read_one(id).ok().flatten())
+    }
+
+    /// Writes a (plaintext) record, then publishes the row's new state.
+    fn write_and_publish(&self, id: usize, captured: Option<Option<Row>>, record: Vec<u8>) -> TypedValue {
+        let written = if self.is_published() { Some(record.clone()) } else { None };
+        match self.write_record(id, record) {
+            Ok(n) => {
+                if let Some(record) = written { self.publish_row(id, captured, &record); }
+                Number(n)
+            }
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        }
+    }
+
+    /// Publishes the new state of a row (decoded from the record just written) to the replication
+    /// log; and, given the row's prior state, captures the change and invokes the triggers observing it.
+    /// NOTE: publishing follows the (durable) write and is not atomic with it; so a change is
+    /// published at most once: failures (or a crash) between the write and its publication are
+    /// logged rather than reported as a failure of the write, which has already taken effect.
+    fn publish_row(&self, id: usize, captured: Option<Option<Row>>, record: &[u8]) {
+        let (row, metadata) = self.decode_record(record);
+        let new = if metadata.is_allocated { Some(row.to_owned()) } else { None };
+        if let Err(err) = self.publish(ReplicationEvent::RowChanged { id, row, metadata }) {
+            error!("{}: failed to replicate row #{id}: {err}", self.path);
+        }
+        if let Some(event) = captured.and_then(|old| ChangeEvent::new(id, old, new)) {
+            if let Some(Err(err)) = self.changes.as_ref().map(|feed| feed.append(&event)) {
+                error!("{}: failed to capture the change of row #{id}: {err}", self.path);
+            }
+            for trigger in self.triggers.iter() {
+                if let ErrorValue(err) = trigger.fire(&self.columns, &event) {
+                    error!("{}: {err}", self.path);
+                }
+            }
+        }
+    }
+
+    /// Determines the offset of the first record and the header; writing a header to new (empty) files.
+    /// Headerless files (i.e. written by an earlier version) have their records at offset 0.
+    fn init_header(file: &File, columns: &Vec<Column>) -> std::io::Result<(u64, Option<TableHeader>)> {
+        if let Some(header) = TableHeader::read(file)? {
+            Ok((TABLE_HEADER_LEN as u64, Some(header)))
+        } else if file.metadata()?.len() == 0 {
+            // the file is new (or was truncated), so any cached pages are stale
+            PageCache::shared().invalidate(PageCache::get_file_id(file)?)?;
+            let header = TableHeader::new(columns);
+            header.write(file)?;
+            Ok((TABLE_HEADER_LEN as u64, Some(header)))
+        } else { Ok((0, None)) }
+    }
+
+    /// Adopts the given format version (e.g. once the table file has been upgraded)
+    fn set_format_version(&mut self, format_version: u16) {
+        self.format_version = format_version;
+        self.blobs = self.blobs.clone().with_checksums(format_version >= CHECKSUMMED_BLOBS_VERSION);
+    }
+
+    /// Rewrites a headerless or older-format table file into the current format (i.e. with
+    /// a header, and checksummed records and BLOBs); returning the number of records migrated.
+    /// Files that are already in the current format are left as-is.
+    pub fn upgrade(&mut self) -> std::io::Result<usize> {
+        let existing = TableHeader::read(self.file.get_file())?;
+        let count = match existing {
+            Some(header) if header.version >= TABLE_FORMAT_VERSION => return Ok(0),
+            Some(header) if header.version >= CHECKSUMMED_RECORDS_VERSION => 0,
+            existing => self.upgrade_records(existing)?
+        };
+        self.upgrade_blobs()?;
+        Ok(count)
+    }
+
+    /// Appends a checksum to each record of a headerless (or version 1) table file
+    fn upgrade_records(&mut self, existing: Option<TableHeader>) -> std::io::Result<usize> {
+        self.flush()?;
+        let staging_path = format!("{}.upgrade", self.path);
+        let contents = fs::read(self.path.as_str())?;
+        let records = contents.get(self.data_offset as usize..).unwrap_or_default();
+        let legacy_record_size = self.record_size - CHECKSUM_LEN;
+        let header = TableHeader { version: CHECKSUMMED_RECORDS_VERSION, ..TableHeader::new(&self.columns) };
+        let header = match existing {
+            Some(existing) => TableHeader { table_id: existing.table_id, ..header },
+            None => header
+        };
+        let mut bytes = header.encode();
+        let mut count = 0;
+        for record in records.chunks(legacy_record_size) {
+            let mut record = record.to_vec();
+            // a trailing partial record is carried over as-is
+            if record.len() == legacy_record_size {
+                append_checksum(&mut record);
+                count += 1;
+            }
+            bytes.extend(record);
+        }
+        fs::write(staging_path.as_str(), bytes)?;
+
+        Self::copy_file(staging_path.as_str(), self.path.as_str())?;
+        fs::remove_file(staging_path.as_str())?;
+        self.file.invalidate()?;
+        self.data_offset = TABLE_HEADER_LEN as u64;
+        self.table_id = header.table_id;
+        self.set_format_version(header.version);
+        Ok(count)
+    }
+
+    /// Copies the BLOBs of a version 2 table file into a checksummed BLOB file (marking the
+    /// records never written as empty along the way); then stamps the current format version.
+    fn upgrade_blobs(&mut self) -> std::io::Result<()> {
+        let (staging_path, _) = self.stage_vacuum()?;
+        let staging_file = OpenOptions::new().read(true).write(true).open(staging_path.as_str())?;
+        match TableHeader::read(&staging_file)? {
+            Some(header) => TableHeader { version: TABLE_FORMAT_VERSION, ..header }.write(&staging_file)?,
+            None => return throw(Errors::Exact(format!("{staging_path}: the table header is missing")))
+        }
+        staging_file.sync_all()?;
+        self.swap_in_staged_files(staging_path.as_str())?;
+        self.set_format_version(TABLE_FORMAT_VERSION);
+        Ok(())
+    }
+
+    /// Returns the BLOB offset referenced by the cell at the buffer offset;
+    /// if its value is stored externally.
+    fn get_external_offset(buffer: &[u8], offset: usize) -> Option<u64> {
+        match buffer.get(offset) {
+            Some(&metadata) if FieldMetadata::decode(metadata).is_external =>
+                Some(NumberType(U64Kind).decode_field_value(buffer, offset).to_u64()),
+            _ => None
+        }
+    }
+
+    /// Decodes a (plaintext) record into a row and its metadata;
+    /// expired rows are decoded as unallocated.
+    fn decode_record(&self, buffer: &[u8]) -> (Row, RowMetadata) {
+        let mut rmd = RowMetadata::from_bytes(buffer, 0);
+        let id = ByteCodeCompiler::decode_row_id(buffer, 1);
+        let values = self.columns.iter().enumerate().map(|(column_id, column)| {
+            let fmd = FieldMetadata::decode(buffer[column.get_offset()]);
+            if fmd.is_external {
+                let offset = NumberType(U64Kind).decode_field_value(buffer, column.get_offset()).to_u64();
+                let (_, value) = self.blobs.read(offset, &self.get_blob_aad(buffer, column_id))
+                    .unwrap_or_else(|err| (BLOBCellMetadata::new(0, 0, 0), ErrorValue(Errors::Exact(err.to_string()))));
+                value
+            } else {
+                let data_type = column.get_data_type();
+                data_type.decode_field_value(buffer, column.get_offset())
+            }
+        }).collect::<Vec<_>>();
+        if rmd.is_allocated && self.is_expired(&values) {
+            rmd.is_allocated = false;
+        }
+        (Row::new(id, values), rmd)
+    }
+
+    /// Decodes a (plaintext) record just encoded from the given row; the values of
+    /// fields stored externally are taken from the row rather than read back from disk.
+    fn decode_written_record(&self, buffer: &[u8], row: &Row) -> Row {
+        let values = self.columns.iter().enumerate().map(|(column_id, column)| {
+            if FieldMetadata::decode(buffer[column.get_offset()]).is_external {
+                row[column_id].to_owned()
+            } else {
+                column.get_data_type().decode_field_value(buffer, column.get_offset())
+            }
+        }).collect::<Vec<_>>();
+        Row::new(ByteCodeCompiler::decode_row_id(buffer, 1), values)
+    }
+
+    /// Deletes the expired rows of this table; returning the number of rows deleted.
+    /// Rows are deleted in place (i.e. row IDs are unchanged); their space is only
+    /// reclaimed once the table is (explicitly) compacted.
+    pub fn expire_rows(&mut self) -> std::io::Result<usize> {
+        let column_id = match self.ttl {
+            Some((_, column_id)) => column_id,
+            None => return Ok(0)
+        };
+        let mut expired = 0;
+        for id in 0..self.len()? {
+            // expired rows read as unallocated; so consult the stored metadata
+            if self.read_row_metadata(id)?.is_allocated {
+                let mut values = vec![Undefined; self.columns.len()];
+                values[column_id] = self.read_field(id, column_id);
+                if self.is_expired(&values) {
+                    expired += self.delete_row(id).to_result(|v| v.to_usize())?;
+                }
+            }
+        }
+        Ok(expired)
+    }
+
+    /// Returns true, if the TTL of this table has elapsed for the given (row) values
+    fn is_expired(&self, values: &Vec<TypedValue>) -> bool {
+        match (self.ttl, self.ttl.and_then(|(_, index)| values.get(index))) {
+            (Some((millis, _)), Some(Number(n))) =>
+                n.to_i64().saturating_add(millis) <= Local::now().timestamp_millis(),
+            _ => false
+        }
+    }
+
+    /// Maps the table file into memory for a range read or scan; or [None] if memory-mapping
+    /// is disabled or the table is encrypted, in which case rows are read via the page cache.
+    fn map_file(&self) -> std::io::Result<Option<MemoryMap>> {
+        if !self.is_memory_mapped || self.is_encrypted() { return Ok(None); }
+        // the mapping must reflect any unflushed writes
+        self.flush()?;
+        let mapping = MemoryMap::open(self.path.as_str()).and_then(|mapping| match mapping {
+            // the path may since name another file (e.g. the table was dropped and re-created)
+            Some(mapping) if PageCache::get_file_id(mapping.get_file())? != PageCache::get_file_id(self.file.get_file())? => Ok(None),
+            mapping => Ok(mapping)
+        });
+        Ok(mapping.unwrap_or_else(|err| {
+            warn!("Failed to memory-map {}: {err}; falling back to buffered reads", self.path);
+            None
+        }))
+    }
+
+    /// Copies a file over another (preserving the inode shared by open handles);
+    /// once memory-mapped reads of the target have completed.
+    fn copy_file(from: &str, to: &str) -> std::io::Result<()> {
+        let target = OpenOptions::new().write(true).create(true).truncate(false).open(to)?;
+        MemoryMap::exclusive(&target, || fs::copy(from, to))?;
+        target.sync_all()
+    }
+
+    /// Reads a row directly from the mapped table file; rows beyond the mapping
+    /// (or sealed rows) are read via the page cache.
+    fn read_mapped_row(&self, mapping: &MemoryMap, id: usize) -> std::io::Result<(Row, RowMetadata)> {
+        match mapping.get(self.convert_rowid_to_offset(id) as usize, self.record_size) {
+            Some(record) if !RowMetadata::from_bytes(record, 0).is_encrypted => {
+                self.verify_record(id, record)?;
+                Ok(self.decode_record(&record[..record.len() - CHECKSUM_LEN]))
+            }
+            _ => self.read_row(id)
+        }
+    }
+
+    /// Encodes a row => (metadata|row ID|data); the BLOBs referenced by
+    /// the previous record at the same position (if any) are reused.
+    fn encode_record(&self, id: usize, row: &Row, previous: Option<&Vec<u8>>) -> Vec<u8> {
+        let capacity = Row::compute_record_size(&self.columns);
+        let mut encoded = Vec::with_capacity(capacity);
+        encoded.push(RowMetadata::new(true).encode());
+        encoded.extend(ByteCodeCompiler::encode_row_id(row.get_id()));
+        let fields = self.columns.iter().zip(row.get_values().iter()).enumerate()
+            .flat_map(|(column_id, (column, value))| {
+                let previous = previous
+                    .and_then(|record| Self::get_external_offset(record, column.get_offset()));
+                let aad = self.get_blob_aad(&encoded, column_id);
+                self.blobs.encode_field(column, value, previous, &aad).unwrap_or_else(|err| {
+                    error!("Failed to write row #{id}: {err} ({})", row.to_json_string(&self.columns));
+                    vec![]
+                })
+            }).collect::<Vec<_>>();
+        encoded.extend(fields);
+        encoded.resize(capacity, 0u8);
+        encoded
+    }
+
+    /// Rewrites the BLOBs referenced by the rows of this table into a new
+    /// BLOB file (discarding any unreferenced or free space), and updates the
+    /// external offsets of the owning rows. Returns the number of rows updated.
+    /// NOTE: BLOBs owned by deleted (but not yet compacted) rows are preserved.
+    pub fn vacuum(&mut self) -> std::io::Result<usize> {
+        if let Err(err) = self.check_writable() { return throw(err); }
+        let (staging_path, count) = self.stage_vacuum()?;
+        self.swap_in_staged_files(staging_path.as_str())?;
+        Ok(count)
+    }
+
+    /// Writes the vacuumed copies of the table file and its BLOB file; returning
+    /// the path of the staged table file and the number of rows updated.
+    fn stage_vacuum(&self) -> std::io::Result<(String, usize)> {
+        let staging_path = format!("{}.vacuum", self.path);
+        let staging = BLOBStore::open_file(format!("{staging_path}.blob").as_str(), true)?;
+        staging.truncate()?;
+
+        // copy the referenced BLOBs, and determine the updated records
+        let mut updates = Vec::new();
+        for id in 0..self.len()? {
+            let mut record = self.read_record(id)?;
+            // records never written are marked as empty
+            let mut is_modified = is_zeroed(&record);
+            for column in &self.columns {
+                let offset = column.get_offset();
+                if let Some(blob_offset) = Self::get_external_offset(&record, offset) {
+                    let header = self.blobs.copy_blob(blob_offset, &staging)?;
+                    record[(offset + 1)..(offset + 9)].copy_from_slice(&header.offset.to_be_bytes());
+                    is_modified = true;
+                }
+            }
+            if is_modified { updates.push((id, record)); }
+        }
+
+        // stage a copy of the table containing the fixed up rows, then swap in both files
+        self.flush()?;
+        fs::copy(self.path.as_str(), staging_path.as_str())?;
+        let staging_file = OpenOptions::new().write(true).open(staging_path.as_str())?;
+        for (id, record) in &updates {
+            let mut sealed = self.seal_record(record.to_owned())?;
+            append_checksum(&mut sealed);
+            staging_file.write_all_at(&sealed, self.convert_rowid_to_offset(*id))?;
+        }
+        staging_file.sync_all()?;
+        staging.sync()?;
+        Ok((staging_path, updates.len()))
+    }
+
+    /// Swaps the staged copies of the table file and its BLOB file (i.e. `{staging_path}`
+    /// and `{staging_path}.blob`) into place. A commit marker is published once both copies
+    /// are complete, so that a swap interrupted by a crash is rolled forward upon the next open.
+    fn swap_in_staged_files(&mut self, staging_path: &str) -> std::io::Result<()> {
+        Self::commit_staged_files(self.path.as_str(), staging_path)?;
+        Self::roll_forward(self.path.as_str())?;
+        self.blobs.invalidate()?;
+        self.file.invalidate()
+    }
+
+    /// Atomically publishes the commit marker of the given table file
+    fn commit_staged_files(path: &str, staging_path: &str) -> std::io::Result<()> {
+        let marker_path = Self::get_commit_marker_path(path);
+        let temp_path = format!("{marker_path}.tmp");
+        fs::write(temp_path.as_str(), staging_path)?;
+        File::open(temp_path.as_str())?.sync_all()?;
+        fs::rename(temp_path.as_str(), marker_path.as_str())
+    }
+
+    /// Completes the swap of staged files recorded by the commit marker of the given
+    /// table file (if any); see [FileRowCollection::swap_in_staged_files].
+    /// NOTE: copying (rather than renaming) preserves the inodes shared by open handles.
+    fn roll_forward(path: &str) -> std::io::Result<()> {
+        let marker_path = Self::get_commit_marker_path(path);
+        let staging_path = match fs::read_to_string(marker_path.as_str()) {
+            Ok(staging_path) => staging_path,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err)
+        };
+        // the staged table file is removed (after the staged BLOB file has been copied) only
+        // once both copies are durable; so if it's gone, only the cleanup remains.
+        let staging_blob_path = format!("{staging_path}.blob");
+        if Path::new(staging_path.as_str()).exists() {
+            for (from, to) in [(staging_blob_path.to_owned(), format!("{path}.blob")), (staging_path.to_owned(), path.to_string())] {
+                Self::copy_file(from.as_str(), to.as_str())?;
+            }
+            fs::remove_file(staging_path.as_str())?;
+        }
+        match fs::remove_file(staging_blob_path.as_str()) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        fs::remove_file(marker_path.as_str())
+    }
+
+    fn get_commit_marker_path(path: &str) -> String {
+        format!("{path}.commit")
+    }
+
+    /// Scans every record (and the BLOBs referenced by active rows) of this table,
+    /// regardless of the checksum policy; returning the problems found as (kind, location, problem).
+    pub fn verify(&self) -> std::io::Result<Vec<(String, String, String)>> {
+        let blobs_len = self.blobs.len()?;
+        let mut problems = Vec::new();
+        let mut report = |kind: &str, id: usize, problem: String| {
+            problems.push((kind.to_string(), format!("{}#{id}", self.path), problem));
+        };
+        for id in 0..self.len()? {
+            let stored = self.read_at(self.convert_rowid_to_offset(id), self.record_size)?;
+            if !is_intact(&stored) {
+                report("record", id, "checksum mismatch".into());
+                continue;
+            }
+            let record = match self.unseal_record(stored[..self.record_size - CHECKSUM_LEN].to_vec()) {
+                Ok(record) => record,
+                Err(err) => {
+                    report("record", id, err.to_string());
+                    continue;
+                }
+            };
+            if !RowMetadata::decode(record[0]).is_allocated { continue; }
+            for (column_id, column) in self.columns.iter().enumerate() {
+                if let Some(offset) = Self::get_external_offset(&record, column.get_offset()) {
+                    let name = column.get_name();
+                    match self.blobs.read_header(offset) {
+                        Ok(header) if header.offset != offset || header.is_free() || header.used > header.allocated =>
+                            report("blob", id, format!("{name}: invalid BLOB header at offset {offset}")),
+                        Ok(header) if offset + header.allocated > blobs_len =>
+                            report("blob", id, format!("{name}: BLOB at offset {offset} extends beyond the end of the file")),
+                        Ok(..) => if let Err(err) = self.blobs.read::<TypedValue>(offset, &self.get_blob_aad(&record, column_id)) {
+                            report("blob", id, format!("{name}: {err}"))
+                        }
+                        Err(err) => report("blob", id, format!("{name}: {err}"))
+                    }
+                }
+            }
+        }
+        Ok(problems)
+    }
+
+    /// Rewrites the rows of this table into the layout of the given columns (e.g. after
+    /// a column has been added or dropped); the transform maps each existing row onto
+    /// the new columns. Row IDs and metadata are preserved. Returns the number of rows rewritten.
+    pub fn rewrite<F>(&mut self, params: &Vec<Parameter>, transform: F) -> std::io::Result<usize>
+    where
+        F: Fn(Row) -> Row,
+    {
+        if let Err(err) = self.check_writable() { return throw(err); }
+        let staging_path = format!("{}.alter", self.path);
+        let file = OpenOptions::new().truncate(true).create(true).read(true).write(true)
+            .open(staging_path.as_str())?;
+        let staging = Self::new(Column::from_parameters(params), Arc::new(file), staging_path.as_str());
+        staging.blobs.truncate()?;
+        let mut staging = match &self.encryption {
+            Some(key) => staging.with_encryption(key.clone()),
+            None => staging
+        };
+
+        // copy the rows into the new layout
+        let count = self.len()?;
+        for id in 0..count {
+            let (row, metadata) = self.read_row(id)?;
+            staging.overwrite_row(id, transform(row)).to_result(|_| ())?;
+            staging.overwrite_row_metadata(id, metadata).to_result(|_| ())?;
+        }
+
+        // swap in the new files
+        staging.flush()?;
+        staging.file.get_file().sync_all()?;
+        staging.blobs.sync()?;
+        self.flush()?;
+        staging.file.invalidate()?;
+        self.swap_in_staged_files(staging_path.as_str())?;
+        self.columns = staging.columns;
+        self.data_offset = staging.data_offset;
+        self.record_size = staging.record_size;
+        self.table_id = staging.table_id;
+        self.set_format_version(staging.format_version);
+        Ok(count)
+    }
+
+    /// Overwrites a row (see [RowCollection::overwrite_row]); returning the outcome and,
+    /// if successful, the row as written (e.g. with its column defaults applied).
+    pub fn write_row(&mut self, id: usize, row: Row) -> (TypedValue, Option<Row>) {
+        if let Err(err) = self.check_writable() { return (ErrorValue(err), None); }
+        let before = self.get_version();
+        let row = match self.validate_row(id, row) {
+            Ok(row) => row,
+            Err(err) => return (ErrorValue(err), None)
+        };
+        let captured = self.capture(id);
+        let previous = self.read_record(id).ok();
+        let encoded = self.encode_record(id, &row, previous.as_ref());
+        let written = self.decode_written_record(&encoded, &row);
+
+        // write the row
+        match self.write_and_publish(id, captured, encoded) {
+            ErrorValue(err) => (ErrorValue(err), None),
+            result => {
+                self.index_rows(before, &[written.to_owned()]);
+                (result, Some(written))
+            }
+        }
+    }
+
+    /// Overwrites a field (see [RowCollection::overwrite_field]); returning the outcome and,
+    /// if successful, the value as written (e.g. with its column default applied).
+    pub fn write_field(&mut self, id: usize, column_id: usize, new_value: TypedValue) -> (TypedValue, Option<TypedValue>) {
+        if let Err(err) = self.check_writable() { return (ErrorValue(err), None); }
+        let before = self.get_version();
+        let new_value = match self.validate_field(id, column_id, new_value) {
+            Ok(value) => value,
+            Err(err) => return (ErrorValue(err), None)
+        };
+        let captured = self.capture(id);
+        let column = &self.columns[column_id];
+        let record = match self.read_record(id) {
+            Ok(record) => record,
+            Err(err) => return (ErrorValue(Errors::Exact(err.to_string())), None)
+        };
+        let previous = Self::get_external_offset(&record, column.get_offset());
+        let aad = self.get_blob_aad(&record, column_id);
+        let buffer = self.blobs.encode_field(&column, &new_value, previous, &aad)
+            .unwrap_or_else(|err| {
+                error!("Failed to write to {}@({id}, {column_id}): {} ({})", column.get_name(), err, new_value);
+                Self::empty_cell(column)
+            });
+        let written = match FieldMetadata::decode(buffer[0]).is_external {
+            true => new_value,
+            false => column.get_data_type().decode_field_value(&buffer, 0)
+        };
+        match self.overwrite_cell(id, captured, column, buffer) {
+            ErrorValue(err) => (ErrorValue(err), None),
+            result => {
+                if let Ok(row) = self.read_one(id) {
+                    let rows = row.map(|row| vec![row]).unwrap_or_default();
+                    self.index_rows(before, &rows);
+                }
+                (result, Some(written))
+            }
+        }
+    }
+
+    /// Overwrites the bytes of a single cell by rewriting the whole record (and its checksum)
+    fn overwrite_cell(&self, id: usize, captured: Option<Option<Row>>, column: &Column, bytes: Vec<u8>) -> TypedValue {
+        let mut record = match self.read_record(id) {
+            Ok(record) => record,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+        let offset = column.get_offset();
+        record[offset..(offset + bytes.len())].copy_from_slice(&bytes);
+        self.write_and_publish(id, captured, record)
+    }
+
+    /// Reads the record of the given row ID; decrypting it if the
+    /// row metadata indicates that it is encrypted.
+    fn read_record(&self, id: usize) -> std::io::Result<Vec<u8>> {
+        self.unseal_record(self.read_stored_record(id)?)
+    }
+
+    /// Reads the record of the given row ID as stored (i.e. possibly encrypted);
+    /// verifying and removing its checksum.
+    fn read_stored_record(&self, id: usize) -> std::io::Result<Vec<u8>> {
+        let mut buffer = self.read_at(self.convert_rowid_to_offset(id), self.record_size)?;
+        self.verify_record(id, &buffer)?;
+        buffer.truncate(self.record_size - CHECKSUM_LEN);
+        Ok(buffer)
+    }
+
+    /// Verifies the checksum of a stored record; applying the checksum policy upon mismatch.
+    /// Zeroed records are only accepted beyond the end of the table (as records never
+    /// written within it are marked as empty); except within tables of earlier versions.
+    fn verify_record(&self, id: usize, record: &[u8]) -> std::io::Result<()> {
+        if is_intact(record) { return Ok(()); }
+        if is_zeroed(record) && (self.format_version < CHECKSUMMED_BLOBS_VERSION
+            || self.convert_rowid_to_offset(id) >= self.file.len()?) {
+            return Ok(());
+        }
+        self.checksum_policy.enforce(self.path.as_str(), id)
+    }
+
+    /// Writes a (plaintext) record for the given row ID; encrypting it if enabled
+    fn write_record(&self, id: usize, record: Vec<u8>) -> std::io::Result<Numbers> {
+        let mut encoded = self.seal_record(record)?;
+        append_checksum(&mut encoded);
+        self.fill_gap(id)?;
+        self.write_at(self.convert_rowid_to_offset(id), &encoded)
+    }
+
+    /// Marks the records between the end of the table and the given row ID as empty
+    fn fill_gap(&self, id: usize) -> std::io::Result<()> {
+        let start = self.len()?;
+        if id <= start { return Ok(()); }
+        let mut empty = vec![0u8; self.record_size - CHECKSUM_LEN];
+        append_checksum(&mut empty);
+        for chunk_start in (start..id).step_by(BULK_LOAD_CHUNK_SIZE) {
+            let count = (id - chunk_start).min(BULK_LOAD_CHUNK_SIZE);
+            self.file.write_direct(self.convert_rowid_to_offset(chunk_start), &empty.repeat(count))?;
+        }
+        Ok(())
+    }
+
+    fn seal_record(&self, mut record: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        let metadata = RowMetadata::decode(record[0]);
+        match &self.encryption {
+            None => {
+                record[0] = metadata.with_encrypted(false).encode();
+                Ok(record)
+            }
+            Some(key) => {
+                // the row metadata and ID are authenticated (but not encrypted),
+                // so flags can't be flipped and records can't be swapped
+                let header_len = Row::overhead();
+                record[0] = metadata.with_encrypted(true).encode();
+                let sealed = key.encrypt(&record[header_len..], &self.get_record_aad(&record))?;
+                record.truncate(header_len);
+                record.extend(sealed);
+                Ok(record)
+            }
+        }
+    }
+
+    fn unseal_record(&self, mut buffer: Vec<u8>) -> std::io::Result<Vec<u8>> {
+        let rmd = RowMetadata::from_bytes(&buffer, 0);
+        let plain_size = Row::compute_record_size(&self.columns);
+        if !rmd.is_encrypted {
+            // within an encrypted table, only never-written (zeroed) records are unsealed
+            if self.encryption.is_some() && buffer.iter().any(|b| *b != 0) {
+                return throw(Errors::Exact(format!("{}: an unsealed record was found within an encrypted table", self.path)));
+            }
+            buffer.truncate(plain_size);
+            return Ok(buffer);
+        }
+        match &self.encryption {
+            Some(key) => {
+                let header_len = Row::overhead();
+                let payload = key.decrypt(&buffer[header_len..], &self.get_record_aad(&buffer))?;
+                buffer.truncate(header_len);
+                buffer.extend(payload);
+                Ok(buffer)
+            }
+            None => throw(Errors::Exact(format!("{} contains encrypted rows, but encryption is not enabled", self.path)))
+        }
+    }
+
+    /// Returns the associated data authenticated with a sealed record: the table ID,
+    /// followed by the record's metadata and row ID
+    fn get_record_aad(&self, record: &[u8]) -> Vec<u8> {
+        let mut aad = self.table_id.to_be_bytes().to_vec();
+        aad.extend(&record[0..Row::overhead()]);
+        aad
+    }
+
+    /// Returns the associated data authenticated with a sealed BLOB: the table ID,
+    /// the row ID (of the given record) and the position of the column referencing it
+    fn get_blob_aad(&self, record: &[u8], column_id: usize) -> Vec<u8> {
+        let mut aad = self.table_id.to_be_bytes().to_vec();
+        aad.extend(&record[1..Row::overhead()]);
+        aad.extend((column_id as u64).to_be_bytes());
+        aad
+    }
+
     /// convenience function to create, read or write a table file
     pub(crate) fn table_file_create(ns: &Namespace) -> std::io::Result<File> {
         fs::create_dir_all(ns.get_root_path())?;
-        OpenOptions::new().truncate(true).create(true).read(true).write(true)
-            .open(ns.get_table_file_path())
+        NamespaceLease::acquire(ns)?;
+        let file = OpenOptions::new().truncate(false).create(true).read(true).write(true)
+            .open(ns.get_table_file_path())?;
+        MemoryMap::exclusive(&file, || file.set_len(0))?;
+        Ok(file)
     }
 
     /// convenience function to read or write a table file
@@ -174,6 +1099,27 @@ impl Debug for FileRowCollection {
 }
 
 impl RowCollection for FileRowCollection {
+    fn describe(&self) -> TypedValue {
+        let params = PlatformOps::get_tools_describe_parameters();
+        let mut mrc = ModelRowCollection::from_parameters(&params);
+        for (index, column) in self.columns.iter().enumerate() {
+            // the TTL is reported alongside the type of its date column
+            let data_type = match self.ttl {
+                Some((millis, ttl_index)) if ttl_index == index =>
+                    format!("{} with {}", column.get_data_type().to_code(),
+                            TableOptions::Ttl(millis, column.get_name().to_string()).to_code()),
+                _ => column.get_data_type().to_code()
+            };
+            mrc.append_row(Row::new(0, vec![
+                StringValue(column.get_name().to_string()),
+                StringValue(data_type),
+                StringValue(column.get_default_value().unwrap_value()),
+                Boolean(true),
+            ]));
+        }
+        TableValue(Model(mrc))
+    }
+
     fn create_related_structure(
         &self,
         columns: Vec<Column>,
@@ -193,8 +1139,20 @@ impl RowCollection for FileRowCollection {
         Ok(Box::new(frc))
     }
 
+    fn delete_related_structure(&self, extension: &str) -> std::io::Result<()> {
+        let (_, full_path) = Self::get_related_filename(self.path.as_str(), extension);
+        match fs::remove_file(full_path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err),
+            _ => Ok(())
+        }
+    }
+
     fn get_columns(&self) -> &Vec<Column> { &self.columns }
 
+    fn convert_rowid_to_offset(&self, id: usize) -> u64 {
+        self.data_offset + (id * self.record_size) as u64
+    }
+
     fn get_record_size(&self) -> usize { self.record_size }
 
     fn get_rows(&self) -> Vec<Row> {
@@ -202,7 +1160,8 @@ impl RowCollection for FileRowCollection {
     }
 
     fn len(&self) -> std::io::Result<usize> {
-        Ok((self.file.metadata()?.len() as usize) / self.record_size)
+        let data_len = self.file.len()?.saturating_sub(self.data_offset);
+        Ok(data_len as usize / self.record_size)
     }
 
     fn overwrite_field(
@@ -211,14 +1170,7 @@ impl RowCollection for FileRowCollection {
         column_id: usize,
         new_value: TypedValue,
     ) -> TypedValue {
-        let column = &self.columns[column_id];
-        let offset = self.convert_rowid_to_offset(id) + column.get_offset() as u64;
-        let buffer = self.blobs.encode_field(&column, &new_value)
-            .unwrap_or_else(|err| {
-                error!("Failed to write to {}@({id}, {column_id}): {} ({})", column.get_name(), err, new_value);
-                Self::empty_cell(column)
-            });
-        TypedValue::from_result(self.write_at(offset, &buffer).map(|n| Number(n)))
+        self.write_field(id, column_id, new_value).0
     }
 
     fn overwrite_field_metadata(
@@ -227,49 +1179,43 @@ impl RowCollection for FileRowCollection {
         column_id: usize,
         metadata: FieldMetadata,
     ) -> TypedValue {
-        let row_offset = self.convert_rowid_to_offset(id);
+        if let Err(err) = self.check_writable() { return ErrorValue(err); }
+        let captured = self.capture(id);
         let column = &self.columns[column_id];
-        let column_offset = column.get_offset() as u64;
-        let cell_offset = row_offset + column_offset;
-        TypedValue::from_result(self.write_at(cell_offset, &[metadata.encode()].to_vec())
-            .map(|n| Number(n)))
+        self.overwrite_cell(id, captured, column, vec![metadata.encode()])
     }
 
     fn overwrite_row(&mut self, id: usize, row: Row) -> TypedValue {
-        let row_offset = self.convert_rowid_to_offset(id);
-        let capacity = self.get_record_size();
-        let blobs = &self.blobs;
-
-        // encode the row => (metadata|row ID|data)
-        let mut encoded = Vec::with_capacity(capacity);
-        encoded.push(RowMetadata::new(true).encode());
-        encoded.extend(ByteCodeCompiler::encode_row_id(row.get_id()));
-        encoded.extend(self.columns.iter().zip(row.get_values().iter())
-            .flat_map(|(column, value)|
-                blobs.encode_field(column, value).unwrap_or_else(|err| {
-                    error!("Failed to write row #{id}: {err} ({})", row.to_json_string(&self.columns));
-                    vec![]
-                })
-            ).collect::<Vec<_>>());
-        encoded.resize(capacity, 0u8);
-
-        // write the row
-        TypedValue::from_result(self.write_at(row_offset, &encoded)
-            .map(|n| Number(n)))
+        self.write_row(id, row).0
     }
 
     fn overwrite_row_metadata(&mut self, id: usize, metadata: RowMetadata) -> TypedValue {
-        let row_offset = self.convert_rowid_to_offset(id);
-        TypedValue::from_result(self.write_at(row_offset, &[metadata.encode()].to_vec())
-            .map(|n| Number(n)))
+        if let Err(err) = self.check_writable() { return ErrorValue(err); }
+        let captured = self.capture(id);
+        // the metadata is authenticated along with the payload, so the record is resealed
+        match self.read_record(id) {
+            Ok(mut record) => {
+                record[0] = metadata.encode();
+                self.write_and_publish(id, captured, record)
+            }
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+        }
+    }
+
+    fn read_active_rows(&self) -> std::io::Result<Vec<Row>> {
+        self.read_range(self.get_indices()?)
     }
 
     fn read_field(&self, id: usize, column_id: usize) -> TypedValue {
         let column = &self.columns[column_id];
-        let row_offset = self.convert_rowid_to_offset(id);
-        let cell_offset = row_offset + column.get_offset() as u64;
-        match self.read_at(cell_offset, column.get_fixed_size()) {
-            Ok(buffer) => column.get_data_type().decode_field_value(&buffer, 0),
+        match self.read_record(id) {
+            Ok(record) => match Self::get_external_offset(&record, column.get_offset()) {
+                Some(offset) => match self.blobs.read(offset, &self.get_blob_aad(&record, column_id)) {
+                    Ok((_, value)) => value,
+                    Err(err) => ErrorValue(Errors::Exact(err.to_string()))
+                }
+                None => column.get_data_type().decode_field_value(&record, column.get_offset())
+            }
             Err(err) => ErrorValue(Errors::Exact(err.to_string()))
         }
     }
@@ -280,48 +1226,41 @@ impl RowCollection for FileRowCollection {
         column_id: usize,
     ) -> std::io::Result<FieldMetadata> {
         let column = &self.columns[column_id];
-        let row_offset = self.convert_rowid_to_offset(id);
-        let cell_offset = row_offset + column.get_offset() as u64;
-        let buffer = self.read_at(cell_offset, 1)?;
-        let meta = FieldMetadata::decode(buffer[0]);
-        Ok(meta)
+        let buffer = self.read_record(id)?;
+        Ok(FieldMetadata::decode(buffer[column.get_offset()]))
+    }
+
+    fn read_range_with_metadata(&self, index: std::ops::Range<usize>) -> std::io::Result<Vec<(Row, RowMetadata)>> {
+        match self.map_file()? {
+            Some(mapping) => index.map(|row_id| self.read_mapped_row(&mapping, row_id)).collect(),
+            None => index.map(|row_id| self.read_row(row_id)).collect()
+        }
     }
 
     fn read_row(&self, id: usize) -> std::io::Result<(Row, RowMetadata)> {
-        let row_offset = self.convert_rowid_to_offset(id);
-        let buffer = self.read_at(row_offset, self.record_size)?;
-        let columns = &self.columns;
+        let buffer = self.read_record(id)?;
 
         // if the buffer is empty, just return an empty row
         if buffer.len() == 0 {
-            return Ok((Row::create(0, columns), RowMetadata::new(false)));
+            return Ok((Row::create(0, &self.columns), RowMetadata::new(false)));
         }
-        let rmd = RowMetadata::from_bytes(&buffer, 0);
-        let id = ByteCodeCompiler::decode_row_id(&buffer, 1);
-        let values = columns.iter().map(|column| {
-            let fmd = FieldMetadata::decode(buffer[column.get_offset()]);
-            if fmd.is_external {
-                let offset = NumberType(U64Kind).decode_field_value(&buffer, column.get_offset()).to_u64();
-                let (_, value) = self.blobs.read(offset)
-                    .unwrap_or_else(|err| (BLOBCellMetadata::new(0, 0, 0), ErrorValue(Errors::Exact(err.to_string()))));
-                value
-            } else {
-                let data_type = column.get_data_type();
-                data_type.decode_field_value(&buffer, column.get_offset())
-            }
-        }).collect();
-        Ok((Row::new(id, values), rmd))
+        Ok(self.decode_record(&buffer))
     }
 
     fn read_row_metadata(&self, id: usize) -> std::io::Result<RowMetadata> {
-        let row_offset = self.convert_rowid_to_offset(id);
-        let buffer = self.read_at(row_offset, 1)?;
+        let buffer = self.read_stored_record(id)?;
         Ok(RowMetadata::decode(buffer[0]))
     }
 
     fn resize(&mut self, new_size: usize) -> TypedValue {
-        let new_length = new_size as u64 * self.record_size as u64;
-        match self.file.set_len(new_length) {
+        if let Err(err) = self.check_writable() { return ErrorValue(err); }
+        let new_length = self.data_offset + new_size as u64 * self.record_size as u64;
+        let result = match self.len() {
+            Ok(size) if new_size > size => self.fill_gap(new_size),
+            Ok(..) => self.file.set_len(new_length),
+            Err(err) => Err(err)
+        };
+        match result.and_then(|_| self.publish(ReplicationEvent::Resized { size: new_size })) {
             Ok(..) => Number(Numbers::Ack),
             Err(err) => ErrorValue(Errors::Exact(err.to_string()))
         }
@@ -330,15 +1269,14 @@ impl RowCollection for FileRowCollection {
 
 impl RowEncoding for FileRowCollection {
     fn read_at(&self, offset: u64, count: usize) -> std::io::Result<Vec<u8>> {
-        let mut buffer: Vec<u8> = vec![0u8; count];
-        match self.file.read_at(&mut buffer, offset) {
-            Ok(_n_bytes) => Ok(buffer),
+        match self.file.read_at(offset, count) {
+            Ok(buffer) => Ok(buffer),
             Err(err) => throw(Errors::Exact(err.to_string()))
         }
     }
 
     fn write_at(&self, offset: u64, bytes: &Vec<u8>) -> std::io::Result<Numbers> {
-        let _n_bytes = self.file.write_at(bytes.as_slice(), offset)?;
+        self.file.write_at(offset, bytes.as_slice())?;
         Ok(Numbers::RowsAffected(1))
     }
 }
@@ -367,21 +1305,95 @@ impl<'de> Deserialize<'de> for FileRowCollection {
             path: String,
         }
 
+        // tables of a namespace are reopened with their configuration (e.g. encryption,
+        // constraints, triggers and TTL); other (scratch) tables from their file alone
         let helper = FileRowCollectionHelper::deserialize(deserializer)?;
-        let file = File::open(&helper.path).map_err(D::Error::custom)?;
-        Ok(FileRowCollection::new(helper.columns, Arc::new(file), helper.path.as_str()))
+        match NamespaceLease::find_namespace(helper.path.as_str())
+            .filter(|ns| ns.get_table_file_path() == helper.path) {
+            Some(ns) => FileRowCollection::open(&ns).map_err(D::Error::custom),
+            None => {
+                let file = OpenOptions::new().read(true).write(true).open(&helper.path)
+                    .map_err(D::Error::custom)?;
+                Ok(FileRowCollection::new(helper.columns, Arc::new(file), helper.path.as_str()))
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::blobs::HEADER_LEN;
+    use crate::checksums::{append_checksum, ChecksumPolicy, CHECKSUM_LEN};
+    use crate::columns::Column;
+    use crate::data_types::DataType::{BinaryType, NumberType, StringType};
+    use crate::dataframe::Dataframe;
+    use crate::expression::TableOptions;
     use crate::file_row_collection::FileRowCollection;
     use crate::namespaces::Namespace;
-    use crate::numbers::Numbers::F64Value;
+    use crate::number_kind::NumberKind::{F32Kind, F64Kind};
+    use crate::numbers::Numbers::{Ack, F64Value, RowId};
+    use crate::object_config::ObjectConfig;
+    use crate::parameter::Parameter;
     use crate::row_collection::RowCollection;
+    use crate::row_collection::RowEncoding;
     use crate::structures::Row;
-    use crate::testdata::make_quote_parameters;
-    use crate::typed_values::TypedValue::{Number, StringValue};
+    use crate::table_header::{TableHeader, CHECKSUMMED_RECORDS_VERSION, TABLE_FORMAT_VERSION, TABLE_HEADER_LEN};
+    use crate::testdata::{make_quote, make_quote_parameters};
+    use crate::typed_values::TypedValue::{Binary, ErrorValue, Null, Number, StringValue};
+    use std::fs;
+    use std::time::Instant;
+
+    #[test]
+    fn test_bulk_load() {
+        let ns = Namespace::parse("frc.bulk_load.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table_with_options(
+            &ns, &make_quote_parameters(), &vec![TableOptions::Encrypted],
+        ).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+        assert_eq!(frc.bulk_load(vec![
+            make_quote(0, "VERY_LONG_SYMBOL", "NYSE", 12.13),
+            make_quote(0, "UNO", "OTC", 0.2456),
+        ]).unwrap(), 2);
+        assert_eq!(frc.len().unwrap(), 3);
+
+        // the rows are readable by other handles (i.e. via the page cache)
+        let other = FileRowCollection::open(&ns).unwrap();
+        assert_eq!(other.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "VERY_LONG_SYMBOL", "NYSE", 12.13),
+            make_quote(2, "UNO", "OTC", 0.2456),
+        ]);
+        assert_eq!(frc.bulk_load(vec![]).unwrap(), 0);
+        assert_eq!(frc.verify().unwrap(), vec![]);
+    }
+
+    /// Compares appending rows one at a time with appending them in bulk
+    /// (run with `cargo test --release -- --ignored test_bulk_load_speedup`).
+    #[ignore]
+    #[test]
+    fn test_bulk_load_speedup() {
+        let total = 20_000;
+        let make_rows = || (0..total)
+            .map(|n| make_quote(0, format!("S{n}").as_str(), "NYSE", n as f64))
+            .collect::<Vec<_>>();
+
+        // append the rows one at a time
+        let mut frc = create_file_row_collection("frc.bulk_load_speedup.append");
+        let start = Instant::now();
+        frc.append_rows(make_rows());
+        let append_msec = start.elapsed().as_secs_f64() * 1000.;
+
+        // append the rows in bulk
+        let mut bulk = create_file_row_collection("frc.bulk_load_speedup.bulk");
+        let start = Instant::now();
+        assert_eq!(bulk.bulk_load(make_rows()).unwrap(), total);
+        let bulk_msec = start.elapsed().as_secs_f64() * 1000.;
+
+        assert_eq!(bulk.read_active_rows().unwrap(), frc.read_active_rows().unwrap());
+        let speedup = append_msec / bulk_msec;
+        println!("append({total}) - one at a time: {append_msec:.4} msec, bulk: {bulk_msec:.4} msec ({speedup:.1}x)");
+        assert!(speedup > 1.2, "expected bulk loading to be faster; got {speedup:.1}x");
+    }
 
     #[test]
     fn test_column_overflow() {
@@ -396,6 +1408,384 @@ mod tests {
         assert_eq!(row0, row1)
     }
 
+    #[test]
+    fn test_encrypted_rows_and_blobs() {
+        let ns = Namespace::parse("frc.encrypted.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table_with_options(
+            &ns, &make_quote_parameters(), &vec![TableOptions::Encrypted],
+        ).unwrap();
+        let row0 = Row::new(0, vec![
+            StringValue("VERY_LONG_SYMBOL".into()),
+            StringValue("NYSE".into()),
+            Number(F64Value(12.13))
+        ]);
+        frc.append_row(row0.clone());
+
+        // the row should be sealed on disk
+        let (row1, rmd) = frc.read_row(0).unwrap();
+        assert_eq!(row0, row1);
+        assert!(rmd.is_encrypted);
+        frc.flush().unwrap();
+        let raw = fs::read(ns.get_table_file_path()).unwrap();
+        assert!(!raw.windows(4).any(|w| w == b"NYSE"));
+        let raw = fs::read(format!("{}.blob", ns.get_table_file_path())).unwrap();
+        assert!(!raw.windows(16).any(|w| w == b"VERY_LONG_SYMBOL"));
+
+        // deleting and restoring the row must preserve the encryption bit
+        frc.delete_row(0);
+        frc.undelete_row(0);
+        frc.overwrite_field(0, 1, StringValue("AMEX".into()));
+        assert_eq!(frc.read_field(0, 1), StringValue("AMEX".into()));
+
+        // re-open the table from its configuration
+        let frc = FileRowCollection::open(&ns).unwrap();
+        assert!(frc.is_encrypted());
+        assert_eq!(frc.read_row(0).unwrap().0, Row::new(0, vec![
+            StringValue("VERY_LONG_SYMBOL".into()),
+            StringValue("AMEX".into()),
+            Number(F64Value(12.13))
+        ]));
+    }
+
+    #[test]
+    fn test_encrypted_table_serialization_round_trip() {
+        let ns = Namespace::parse("frc.encrypted.round_trip").unwrap();
+        let mut frc = FileRowCollection::create_table_with_options(
+            &ns, &make_quote_parameters(), &vec![TableOptions::Encrypted],
+        ).unwrap();
+        frc.append_row(make_quote(0, "ABC", "AMEX", 11.77));
+
+        // a deserialized table keeps its encryption key (and record size)
+        let bytes = bincode::serialize(&Dataframe::Disk(frc)).unwrap();
+        match bincode::deserialize::<Dataframe>(&bytes).unwrap() {
+            Dataframe::Disk(mut frc) => {
+                assert!(frc.is_encrypted());
+                assert_eq!(frc.read_row(0).unwrap().0, make_quote(0, "ABC", "AMEX", 11.77));
+                assert_eq!(frc.append_row(make_quote(1, "UNO", "OTC", 0.2456)), Number(RowId(1)));
+            }
+            other => panic!("Expected a disk table, got {other:?}")
+        }
+    }
+
+    #[test]
+    fn test_encrypted_records_and_blobs_are_authenticated() {
+        let ns = Namespace::parse("frc.encrypted.tampered").unwrap();
+        let mut frc = FileRowCollection::create_table_with_options(
+            &ns, &make_quote_parameters(), &vec![TableOptions::Encrypted],
+        ).unwrap();
+        frc.append_row(make_quote(0, "VERY_LONG_SYMBOL_A", "NYSE", 12.13));
+        frc.append_row(make_quote(1, "VERY_LONG_SYMBOL_B", "AMEX", 11.77));
+        frc.flush().unwrap();
+
+        // swapping the (sealed) BLOBs of two rows is detected
+        let blob_path = format!("{}.blob", ns.get_table_file_path());
+        let original = fs::read(&blob_path).unwrap();
+        let [a, b] = [0, 1].map(|id| {
+            let record = frc.read_record(id).unwrap();
+            let header = frc.blobs.read_header(FileRowCollection::get_external_offset(&record, frc.columns[0].get_offset()).unwrap()).unwrap();
+            (header.offset as usize + HEADER_LEN)..(header.offset + header.used) as usize
+        });
+        let mut swapped = original.clone();
+        swapped[a.clone()].copy_from_slice(&original[b.clone()]);
+        swapped[b].copy_from_slice(&original[a]);
+        fs::write(&blob_path, swapped).unwrap();
+        assert!(matches!(frc.read_field(0, 0), ErrorValue(..)));
+        fs::write(&blob_path, original).unwrap();
+        assert_eq!(frc.read_field(0, 0), StringValue("VERY_LONG_SYMBOL_A".into()));
+
+        // flipping the metadata of a record (e.g. deleting it) is detected, even if its checksum is fixed up
+        let path = ns.get_table_file_path();
+        let mut raw = fs::read(&path).unwrap();
+        let offset = frc.convert_rowid_to_offset(0) as usize;
+        let mut record = raw[offset..(offset + frc.record_size - CHECKSUM_LEN)].to_vec();
+        record[0] ^= 0b1000_0000;
+        append_checksum(&mut record);
+        raw[offset..(offset + frc.record_size)].copy_from_slice(&record);
+        fs::write(&path, raw).unwrap();
+        frc.file.invalidate().unwrap();
+        assert!(frc.read_row(0).is_err());
+        assert!(frc.read_row(1).is_ok());
+    }
+
+    #[test]
+    fn test_interrupted_vacuum_is_rolled_forward() {
+        let ns = Namespace::parse("frc.vacuum.recovery").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.blobs.truncate().unwrap();
+        frc.append_row(make_quote(0, "VERY_LONG_SYMBOL_A", "NYSE", 12.13));
+        frc.append_row(make_quote(1, "VERY_LONG_SYMBOL_B", "AMEX", 11.77));
+        frc.overwrite_field(0, 0, StringValue("ABC".into()));
+        let blobs_len = frc.blobs.len().unwrap();
+
+        // simulate a crash once the vacuumed files have been staged and committed
+        let (staging_path, count) = frc.stage_vacuum().unwrap();
+        assert_eq!(count, 1);
+        FileRowCollection::commit_staged_files(frc.path.as_str(), staging_path.as_str()).unwrap();
+        assert_eq!(frc.blobs.len().unwrap(), blobs_len);
+        drop(frc);
+
+        // re-opening the table completes the swap
+        let frc = FileRowCollection::open(&ns).unwrap();
+        assert!(frc.blobs.len().unwrap() < blobs_len);
+        assert_eq!(frc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "NYSE", 12.13),
+            make_quote(1, "VERY_LONG_SYMBOL_B", "AMEX", 11.77),
+        ]);
+        for path in [staging_path.to_owned(), format!("{staging_path}.blob"), format!("{}.commit", frc.path)] {
+            assert!(!fs::exists(path).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_blob_reuse_and_vacuum() {
+        let ns = Namespace::parse("frc.vacuum.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        let blob_path = format!("{}.blob", ns.get_table_file_path());
+        for (id, symbol) in ["VERY_LONG_SYMBOL_0", "VERY_LONG_SYMBOL_1", "VERY_LONG_SYMBOL_2"].iter().enumerate() {
+            frc.append_row(Row::new(id, vec![
+                StringValue(symbol.to_string()), StringValue("NYSE".into()), Number(F64Value(12.13))
+            ]));
+        }
+        let blob_size = fs::metadata(&blob_path).unwrap().len();
+
+        // overwriting a BLOB with a value of the same size reuses its allocation
+        frc.overwrite_field(0, 0, StringValue("VERY_LONG_SYMBOL_X".into()));
+        assert_eq!(fs::metadata(&blob_path).unwrap().len(), blob_size);
+
+        // a much larger value is relocated, leaving behind free space
+        frc.overwrite_field(1, 0, StringValue("EXTREMELY_LONG_SYMBOL_NUMBER_1".into()));
+        let blob_size = fs::metadata(&blob_path).unwrap().len();
+
+        // vacuum the table, and verify the rows
+        assert_eq!(frc.vacuum().unwrap(), 3);
+        assert!(fs::metadata(&blob_path).unwrap().len() < blob_size);
+        assert_eq!(frc.read_field(0, 0), StringValue("VERY_LONG_SYMBOL_X".into()));
+        assert_eq!(frc.read_field(1, 0), StringValue("EXTREMELY_LONG_SYMBOL_NUMBER_1".into()));
+        assert_eq!(frc.read_field(2, 0), StringValue("VERY_LONG_SYMBOL_2".into()));
+    }
+
+    #[test]
+    fn test_variable_length_columns() {
+        let ns = Namespace::parse("frc.varlen.articles").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &vec![
+            Parameter::new("title", StringType(0)),
+            Parameter::new("body", StringType(0)),
+            Parameter::new("image", BinaryType(0)),
+        ]).unwrap();
+        assert_eq!(frc.get_record_size(), Row::overhead() + 3 * 9 + CHECKSUM_LEN);
+
+        // variable-length values of any size (including small ones) are stored out of the row
+        let body = "All work and no play makes Jack a dull boy. ".repeat(100);
+        let row0 = Row::new(0, vec![
+            StringValue("A".into()), StringValue(body.clone()), Binary(vec![0xCA, 0xFE, 0xBA, 0xBE])
+        ]);
+        let row1 = Row::new(1, vec![StringValue("B".into()), Null, Null]);
+        frc.append_rows(vec![row0.clone(), row1.clone()]);
+        assert_eq!(frc.read_row(0).unwrap().0, row0);
+        assert_eq!(frc.read_row(1).unwrap().0, row1);
+        assert_eq!(frc.read_field(0, 1), StringValue(body));
+
+        // replacing a value with null releases its BLOB
+        frc.overwrite_field(0, 1, Null);
+        assert_eq!(frc.read_field(0, 1), Null);
+        assert_eq!(frc.read_field(0, 2), Binary(vec![0xCA, 0xFE, 0xBA, 0xBE]));
+    }
+
+    #[test]
+    fn test_rewrite() {
+        let ns = Namespace::parse("frc.rewrite.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_rows(vec![
+            Row::new(0, vec![StringValue("VERY_LONG_SYMBOL".into()), StringValue("NYSE".into()), Number(F64Value(12.13))]),
+            Row::new(1, vec![StringValue("BIZ".into()), StringValue("AMEX".into()), Number(F64Value(0.2456))]),
+        ]);
+        frc.delete_row(1);
+
+        // replace the exchange column with an unsized market column
+        let params = vec![
+            Parameter::new("symbol", StringType(8)),
+            Parameter::new("last_sale", NumberType(F64Kind)),
+            Parameter::new("market", StringType(0)),
+        ];
+        assert_eq!(frc.rewrite(&params, |row| {
+            let values = row.get_values();
+            Row::new(row.get_id(), vec![values[0].to_owned(), values[2].to_owned(), values[1].to_owned()])
+        }).unwrap(), 2);
+        assert_eq!(frc.get_record_size(), Row::compute_record_size(&Column::from_parameters(&params)) + CHECKSUM_LEN);
+        assert_eq!(frc.read_one(0).unwrap(), Some(Row::new(0, vec![
+            StringValue("VERY_LONG_SYMBOL".into()), Number(F64Value(12.13)), StringValue("NYSE".into())
+        ])));
+
+        // deleted rows remain deleted
+        assert_eq!(frc.read_one(1).unwrap(), None);
+        assert!(!fs::exists(format!("{}.alter", ns.get_table_file_path())).unwrap());
+    }
+
+    #[test]
+    fn test_header_upgrade_and_validation() {
+        let ns = Namespace::parse("frc.header.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        let row0 = Row::new(0, vec![StringValue("BIZ".into()), StringValue("NYSE".into()), Number(F64Value(23.66))]);
+        frc.append_row(row0.clone());
+        frc.flush().unwrap();
+
+        // simulate a file written before the header (and checksums) were introduced
+        let path = ns.get_table_file_path();
+        let raw = fs::read(&path).unwrap();
+        assert_eq!(&raw[0..4], b"OXTB");
+        let legacy = raw[TABLE_HEADER_LEN..].chunks(frc.get_record_size())
+            .flat_map(|record| record[..record.len() - CHECKSUM_LEN].to_vec())
+            .collect::<Vec<_>>();
+        fs::write(&path, legacy).unwrap();
+
+        // opening the table upgrades the file (assigning it a new table ID)
+        let frc = FileRowCollection::open(&ns).unwrap();
+        let upgraded = fs::read(&path).unwrap();
+        assert_eq!(upgraded[0..24], raw[0..24]);
+        assert_eq!(upgraded[TABLE_HEADER_LEN..], raw[TABLE_HEADER_LEN..]);
+        assert_eq!(frc.read_one(0).unwrap(), Some(row0));
+
+        // a mismatched configuration is rejected
+        let mut params = make_quote_parameters();
+        params[2] = Parameter::new("last_sale", NumberType(F32Kind));
+        ObjectConfig::build_table(params).save(&ns).unwrap();
+        assert!(FileRowCollection::open(&ns).is_err());
+    }
+
+    #[test]
+    fn test_checksum_verification() {
+        let ns = Namespace::parse("frc.checksums.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.append_rows(vec![
+            make_quote(0, "ABC", "NYSE", 11.77),
+            make_quote(1, "VERY_LONG_SYMBOL", "AMEX", 0.2456),
+        ]);
+        frc.flush().unwrap();
+        assert_eq!(frc.verify().unwrap(), vec![]);
+
+        // damage the exchange of the first row
+        let path = ns.get_table_file_path();
+        let mut raw = fs::read(&path).unwrap();
+        let offset = TABLE_HEADER_LEN + raw[TABLE_HEADER_LEN..].windows(4).position(|w| w == b"NYSE").unwrap();
+        raw[offset] = b'M';
+        fs::write(&path, raw).unwrap();
+
+        // the damage is detected (or merely reported, depending on the policy)
+        let frc = FileRowCollection::open(&ns).unwrap().with_checksum_policy(ChecksumPolicy::Fail);
+        assert!(frc.read_row(0).is_err());
+        assert_eq!(frc.read_row(1).unwrap().0, make_quote(1, "VERY_LONG_SYMBOL", "AMEX", 0.2456));
+        assert_eq!(frc.verify().unwrap(), vec![
+            ("record".into(), format!("{path}#0"), "checksum mismatch".into())
+        ]);
+        let frc = frc.with_checksum_policy(ChecksumPolicy::Warn);
+        assert_eq!(frc.read_row(0).unwrap().0, make_quote(0, "ABC", "MYSE", 11.77));
+    }
+
+    #[test]
+    fn test_unwritten_records_are_marked_empty() {
+        let ns = Namespace::parse("frc.empty_records.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        frc.overwrite_row(2, make_quote(2, "ABC", "NYSE", 11.77));
+        assert_eq!(frc.resize(4), Number(Ack));
+        assert_eq!(frc.len().unwrap(), 4);
+        assert_eq!(frc.read_one(0).unwrap(), None);
+        assert_eq!(frc.verify().unwrap(), vec![]);
+
+        // records beyond the end of the table are zeroed, whereas a zeroed record within it is damage
+        assert_eq!(frc.read_one(5).unwrap(), None);
+        let zeros = vec![0u8; frc.get_record_size()];
+        frc.write_at(frc.convert_rowid_to_offset(0), &zeros).unwrap();
+        assert!(frc.read_row(0).is_err());
+        let path = ns.get_table_file_path();
+        assert_eq!(frc.verify().unwrap(), vec![
+            ("record".into(), format!("{path}#0"), "checksum mismatch".into())
+        ]);
+    }
+
+    #[test]
+    fn test_blob_checksum_upgrade() {
+        // simulate a table written before BLOBs were checksummed
+        let ns = Namespace::parse("frc.blob_upgrade.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table(&ns, &make_quote_parameters()).unwrap();
+        let header = TableHeader::read(frc.file.get_file()).unwrap().unwrap();
+        TableHeader { version: CHECKSUMMED_RECORDS_VERSION, ..header }.write(frc.file.get_file()).unwrap();
+        frc.set_format_version(CHECKSUMMED_RECORDS_VERSION);
+        let (row0, row2) = (make_quote(0, "VERY_LONG_SYMBOL", "NYSE", 11.77), make_quote(2, "ABC", "AMEX", 0.2456));
+        frc.overwrite_row(0, row0.clone());
+        frc.overwrite_row(2, row2.clone());
+        let zeros = vec![0u8; frc.get_record_size()];
+        frc.write_at(frc.convert_rowid_to_offset(1), &zeros).unwrap();
+        assert_eq!(frc.read_one(1).unwrap(), None);
+
+        // opening the table upgrades the file
+        let frc = FileRowCollection::open(&ns).unwrap();
+        let header = TableHeader::read(frc.file.get_file()).unwrap().unwrap();
+        assert_eq!(header.version, TABLE_FORMAT_VERSION);
+        assert_eq!(frc.read_range(0..3).unwrap(), vec![row0, row2]);
+        assert_eq!(frc.verify().unwrap(), vec![]);
+
+        // the BLOBs are now checksummed
+        let record = frc.read_record(0).unwrap();
+        let offset = FileRowCollection::get_external_offset(&record, frc.columns[0].get_offset()).unwrap();
+        let blob_path = format!("{}.blob", ns.get_table_file_path());
+        let mut raw = fs::read(&blob_path).unwrap();
+        raw[offset as usize + HEADER_LEN + 2] ^= 0b0000_0100;
+        fs::write(&blob_path, raw).unwrap();
+        assert!(matches!(frc.read_field(0, 0), ErrorValue(err) if err.to_string().contains("checksum mismatch")));
+    }
+
+    #[test]
+    fn test_memory_mapped_reads() {
+        let ns = Namespace::parse("frc.memory_mapped.stocks").unwrap();
+        let mut frc = FileRowCollection::create_table_with_options(
+            &ns, &make_quote_parameters(), &vec![TableOptions::MemoryMapped],
+        ).unwrap();
+        let total = 10_000;
+        frc.append_rows((0..total).map(|n| make_quote(0, format!("S{n}").as_str(), "NYSE", n as f64)).collect());
+        frc.delete_row(1);
+
+        // mapped reads must match buffered reads (including unflushed writes)
+        let buffered = FileRowCollection::open(&ns).unwrap().with_memory_map(false);
+        frc.overwrite_field(2, 1, StringValue("AMEX".into()));
+        let mapped_rows = frc.read_range(0..total).unwrap();
+        let buffered_rows = buffered.read_range(0..total).unwrap();
+        assert_eq!(mapped_rows, buffered_rows);
+        assert_eq!(mapped_rows.len(), total - 1);
+        assert_eq!(mapped_rows[1], make_quote(2, "S2", "AMEX", 2.));
+        assert_eq!(frc.read_active_rows().unwrap(), buffered_rows);
+        assert_eq!(frc.examine_range(0..3), buffered.examine_range(0..3));
+
+        // rows beyond the end of the file are read like buffered rows
+        assert_eq!(frc.read_range(total - 1..total + 2).unwrap(), buffered.read_range(total - 1..total + 2).unwrap());
+    }
+
+    /// Compares memory-mapped and buffered full scans of a table of the
+    /// same size as the one of `hash_table_row_collection::test_performance`.
+    #[ignore]
+    #[test]
+    fn test_memory_mapped_performance() {
+        let ns = Namespace::parse("frc.memory_mapped.performance").unwrap();
+        let mut frc = FileRowCollection::create_table_with_options(
+            &ns, &make_quote_parameters(), &vec![TableOptions::MemoryMapped],
+        ).unwrap();
+        let total = 25_000;
+        frc.append_rows((0..total).map(|n| make_quote(0, format!("S{n}").as_str(), "NYSE", n as f64)).collect());
+        let buffered = FileRowCollection::open(&ns).unwrap().with_memory_map(false);
+        for (label, table) in [("memory-mapped", &frc), ("buffered", &buffered)] {
+            let timings = (0..10).map(|_| {
+                let start = Instant::now();
+                assert_eq!(table.read_range(0..total).unwrap().len(), total);
+                start.elapsed().as_secs_f64() * 1000.
+            }).collect::<Vec<_>>();
+            let (msec_min, msec_max, msec_total) = timings.iter()
+                .fold((timings[0], 0f64, 0f64), |(msec_min, msec_max, msec_total), msec| {
+                    (msec_min.min(*msec), msec_max.max(*msec), msec_total + *msec)
+                });
+            println!("read_range({total}) {label} timings (msec) - avg: {:.4}, min: {:.4}, max: {:.4}",
+                     msec_total / timings.len() as f64, msec_min, msec_max);
+        }
+    }
+
     fn create_file_row_collection(path: &str) -> FileRowCollection {
         FileRowCollection::create_table(
             &Namespace::parse(path).unwrap(),
@@ -3,36 +3,57 @@
 // HashTableRowCollection class
 ////////////////////////////////////////////////////////////////////
 
+use std::collections::HashMap;
 use std::ops::Range;
 
 use crate::columns::Column;
 use crate::data_types::DataType::NumberType;
 use crate::sequences::Array;
 
-use crate::errors::Errors;
 use crate::errors::Errors::*;
 use crate::errors::TypeMismatchErrors::*;
-use crate::errors::TypeMismatchErrors::{OutcomeExpected, RowsAffectedExpected};
+use crate::errors::{throw, Errors};
 use crate::field::FieldMetadata;
 use crate::number_kind::NumberKind::*;
 use crate::numbers::Numbers;
-use crate::numbers::Numbers::U64Value;
+use crate::numbers::Numbers::{F64Value, U64Value};
 use crate::parameter::Parameter;
 use crate::row_collection::RowCollection;
 use crate::row_metadata::RowMetadata;
 use crate::structures::Row;
+use crate::structures::SoftStructure;
+use crate::structures::Structures::Soft;
 use crate::typed_values::TypedValue;
 use crate::typed_values::TypedValue::*;
 use log::warn;
 
+/// the number of slots (of the oldest retiring keys table) migrated by each write during a rehash
+const REHASH_BATCH_SIZE: u64 = 64;
+
+/// the largest number of buckets a hash table may grow to
+const MAX_BUCKET_COUNT: u64 = 1 << 32;
+
 /// Hash-Table-based RowCollection implementation
 #[derive(Debug)]
 pub struct HashTableRowCollection {
     key_column_index: usize,
     bucket_count: u64,
     bucket_depth: u64,
+    key_count: u64,
+    max_load_factor: f64,
     data_table: Box<dyn RowCollection>,
     keys_table: Box<dyn RowCollection>,
+    retiring: Vec<RetiringKeys>,
+}
+
+/// Represents a keys table being retired by an (incremental) rehash; keys are written
+/// to the current keys table, while the keys of the retiring tables are migrated to it
+/// a batch at a time.
+#[derive(Debug)]
+struct RetiringKeys {
+    bucket_count: u64,
+    keys_table: Box<dyn RowCollection>,
+    cursor: u64,
 }
 
 impl HashTableRowCollection {
@@ -51,7 +72,6 @@ impl HashTableRowCollection {
     fn create_hash_keys_row(
         data_row_id: usize,
         keys_row_id: usize,
-        _keys_columns: &Vec<Column>,
         new_value: &TypedValue,
     ) -> Row {
         Row::new(keys_row_id, vec![
@@ -67,9 +87,7 @@ impl HashTableRowCollection {
         bucket_depth: u64,
         data_table: Box<dyn RowCollection>,
     ) -> std::io::Result<HashTableRowCollection> {
-        let src_column = &data_table.get_columns()[key_column_index];
-        let keys_columns = Self::create_hash_keys_columns(src_column);
-        let keys_table = data_table.create_related_structure(keys_columns, key_column_index.to_string().as_str())?;
+        let keys_table = Self::create_keys_table(data_table.as_ref(), key_column_index, bucket_count)?;
         Ok(Self::create_with_tables_and_options(key_column_index, bucket_count, bucket_depth, data_table, keys_table))
     }
 
@@ -83,10 +101,13 @@ impl HashTableRowCollection {
     ) -> HashTableRowCollection {
         Self {
             key_column_index,
-            bucket_count,
-            bucket_depth,
+            bucket_count: bucket_count.max(1),
+            bucket_depth: bucket_depth.max(1),
+            key_count: keys_table.count(|_| true),
+            max_load_factor: 0.75,
             data_table,
             keys_table,
+            retiring: Vec::new(),
         }
     }
 
@@ -95,20 +116,84 @@ impl HashTableRowCollection {
         key_column_index: usize,
         data_table: Box<dyn RowCollection>,
     ) -> std::io::Result<HashTableRowCollection> {
-        let bucket_count = 100_000;
-        let bucket_depth = bucket_count / 10;
+        // start small; the hash table grows as keys are added
+        let bucket_count = 1_000;
+        let bucket_depth = 16;
+        Self::create_with_options(key_column_index, bucket_count, bucket_depth, data_table)
+    }
+
+    /// Creates an (empty) keys table sized for the given number of buckets
+    fn create_keys_table(
+        data_table: &dyn RowCollection,
+        key_column_index: usize,
+        bucket_count: u64,
+    ) -> std::io::Result<Box<dyn RowCollection>> {
         let src_column = &data_table.get_columns()[key_column_index];
         let keys_columns = Self::create_hash_keys_columns(src_column);
-        let keys_table =
-            data_table.create_related_structure(keys_columns, key_column_index.to_string().as_str())?;
-        Ok(Self::create_with_tables_and_options(key_column_index, bucket_count, bucket_depth, data_table, keys_table))
+        let extension = Self::get_keys_table_extension(key_column_index, bucket_count);
+        data_table.create_related_structure(keys_columns, extension.as_str())
+    }
+
+    /// Returns the file extension of the keys table sized for the given number of buckets
+    fn get_keys_table_extension(key_column_index: usize, bucket_count: u64) -> String {
+        format!("{key_column_index}.{bucket_count}")
+    }
+
+    /// Returns the ID of the slot linking the given key (if any)
+    fn find_slot(
+        keys_table: &dyn RowCollection,
+        slots: Range<u64>,
+        key: &TypedValue,
+    ) -> std::io::Result<Option<(usize, usize)>> {
+        for slot in slots {
+            if let Some(row) = keys_table.read_one(slot as usize)? {
+                if row[1] == *key {
+                    return Ok(Some((slot as usize, row[0].to_usize())));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the ID of the first free slot of a bucket (if any)
+    fn find_free_slot(
+        keys_table: &dyn RowCollection,
+        slots: Range<u64>,
+    ) -> std::io::Result<Option<usize>> {
+        for slot in slots {
+            if keys_table.read_one(slot as usize)?.is_none() {
+                return Ok(Some(slot as usize));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Translates a key into the slots of its bucket
+    fn get_bucket_slots(key: &TypedValue, bucket_count: u64, bucket_depth: u64) -> Range<u64> {
+        let start = (key.hash_code() % bucket_count) * bucket_depth;
+        start..start + bucket_depth
+    }
+
+    /// Converts the outcome of a keys table operation into a result
+    fn to_result(outcome: TypedValue) -> std::io::Result<TypedValue> {
+        match outcome {
+            ErrorValue(err) => throw(err),
+            other => Ok(other)
+        }
     }
 
     //////////////////////////////////////////////////////////
     //  INSTANCE METHODS
     //////////////////////////////////////////////////////////
 
-    /// Audits the hash table; returning any invalid keys
+    /// Sets the ratio of keys to slots beyond which the hash table grows
+    pub fn with_max_load_factor(mut self, max_load_factor: f64) -> Self {
+        self.max_load_factor = max_load_factor;
+        self
+    }
+
+    /// Audits the hash table; returning any invalid keys (collisions),
+    /// along with the utilization of its buckets
     pub fn audit(&mut self) -> TypedValue {
         let mut collisions = Vec::new();
         let data_table_row_id_range = match self.data_table.get_indices() {
@@ -133,21 +218,33 @@ impl HashTableRowCollection {
                 }
             }
         }
-        TypedValue::ArrayValue(Array::from(collisions))
-    }
 
-    /// Translates a key into its hash-key row offset
-    fn convert_key_to_row_id(&self, key: &TypedValue) -> usize {
-        let bucket_depth = 100;
-        let start = (key.hash_code() % self.bucket_count) * bucket_depth;
-        start as usize
-    }
-
-    /// Translates a key into its hash-key row offset
-    fn convert_key_to_row_id_range(&self, key: &TypedValue) -> Range<u64> {
-        let bucket_depth = 100;
-        let start = (key.hash_code() % self.bucket_count) * bucket_depth;
-        start..start + bucket_depth
+        // tally the keys of each bucket
+        let mut buckets = HashMap::new();
+        let tables = std::iter::once(self.keys_table.as_ref())
+            .chain(self.retiring.iter().map(|r| r.keys_table.as_ref()));
+        for keys_table in tables {
+            let len = match keys_table.len() {
+                Ok(len) => len,
+                Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+            };
+            for slot in 0..len {
+                if let Ok(Some(row)) = keys_table.read_one(slot) {
+                    let bucket = row[1].hash_code() % self.get_bucket_count();
+                    *buckets.entry(bucket).or_insert(0u64) += 1;
+                }
+            }
+        }
+        TypedValue::Structured(Soft(SoftStructure::new(&vec![
+            ("collisions", TypedValue::ArrayValue(Array::from(collisions))),
+            ("bucket_count", Number(U64Value(self.get_bucket_count()))),
+            ("bucket_depth", Number(U64Value(self.bucket_depth))),
+            ("buckets_used", Number(U64Value(buckets.len() as u64))),
+            ("max_bucket_len", Number(U64Value(buckets.values().max().copied().unwrap_or(0)))),
+            ("key_count", Number(U64Value(self.key_count))),
+            ("load_factor", Number(F64Value(self.get_load_factor()))),
+            ("is_rehashing", Boolean(!self.retiring.is_empty())),
+        ])))
     }
 
     /// Performs a fast lookup (O(1)) via hash index on the key column
@@ -155,118 +252,248 @@ impl HashTableRowCollection {
         &self,
         key: &TypedValue,
     ) -> std::io::Result<Option<Row>> {
-        let keys_row_id_range = self.convert_key_to_row_id_range(key);
-        //println!("find_row_by_key:keys_row_id_range {:?}", keys_row_id_range);
-        for row_id in keys_row_id_range {
-            if let Some(row) = self.keys_table.read_one(row_id as usize)? {
-                //println!("find_row_by_key[{}]:row {}", row_id, row.to_string());
-                if row[1] == *key {
-                    let data_row_id = match row[0].to_owned() {
-                        Number(number) => number.to_usize(),
-                        _ => 0
-                    };
-                    return self.read_one(data_row_id);
-                }
-            }
+        match self.find_linked_row_id(key)? {
+            Some(data_row_id) => Ok(self.read_one(data_row_id)?
+                .filter(|row| row[self.key_column_index] == *key)),
+            None => Ok(None)
         }
-        Ok(None)
+    }
+
+    /// Returns the number of buckets that keys are written to
+    pub fn get_bucket_count(&self) -> u64 {
+        self.bucket_count
     }
 
     pub fn get_key_column(&self) -> &Column {
         &self.get_columns()[self.key_column_index]
     }
 
+    /// Returns the ratio of keys to the slots that keys are written to
+    pub fn get_load_factor(&self) -> f64 {
+        self.key_count as f64 / (self.get_bucket_count() * self.bucket_depth) as f64
+    }
+
+    /// Returns the ID of the data row linked to the given key (if any)
+    fn find_linked_row_id(&self, key: &TypedValue) -> std::io::Result<Option<usize>> {
+        let slots = Self::get_bucket_slots(key, self.bucket_count, self.bucket_depth);
+        if let Some((_, data_row_id)) = Self::find_slot(self.keys_table.as_ref(), slots, key)? {
+            return Ok(Some(data_row_id));
+        }
+        // keys that have yet to be migrated reside in the retiring tables
+        for retiring in &self.retiring {
+            let slots = Self::get_bucket_slots(key, retiring.bucket_count, self.bucket_depth);
+            if let Some((_, data_row_id)) = Self::find_slot(retiring.keys_table.as_ref(), slots, key)? {
+                return Ok(Some(data_row_id));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Removes the file of a keys table that is no longer in use
+    fn delete_keys_table(&self, bucket_count: u64) -> std::io::Result<()> {
+        let extension = Self::get_keys_table_extension(self.key_column_index, bucket_count);
+        self.data_table.delete_related_structure(extension.as_str())
+    }
+
+    /// Ends the rehash of the oldest retiring table once all of its keys have been migrated
+    fn finish_rehash(&mut self) -> std::io::Result<()> {
+        let bucket_count = self.retiring.remove(0).bucket_count;
+        self.delete_keys_table(bucket_count)
+    }
+
+    /// Starts growing the hash table (to twice as many buckets); the current keys table
+    /// is retired, and its keys are migrated to the resized one incrementally.
+    fn grow(&mut self, data_row_id: usize, key_value: &TypedValue) -> std::io::Result<()> {
+        let bucket_count = self.bucket_count * 2;
+        if bucket_count > MAX_BUCKET_COUNT {
+            return throw(HashTableOverflow(data_row_id, key_value.unwrap_value()));
+        }
+        let keys_table = Self::create_keys_table(self.data_table.as_ref(), self.key_column_index, bucket_count)?;
+        let retired = std::mem::replace(&mut self.keys_table, keys_table);
+        self.retiring.push(RetiringKeys { bucket_count: self.bucket_count, keys_table: retired, cursor: 0 });
+        self.bucket_count = bucket_count;
+        Ok(())
+    }
+
+    /// Links a key to a data row; growing the hash table as needed
     fn link_key_value(
         &mut self,
         data_row_id: usize,
         key_value: &TypedValue,
-    ) -> TypedValue {
-        let keys_columns = self.keys_table.get_columns();
-        for keys_row_id in self.convert_key_to_row_id_range(key_value) {
-            let my_keys_row_id = keys_row_id as usize;
-            match self.keys_table.read_one(keys_row_id as usize) {
-                Ok(Some(row)) if &row[0] == key_value =>
-                    return self.keys_table.overwrite_row(
-                        my_keys_row_id,
-                        Self::create_hash_keys_row(data_row_id, my_keys_row_id, keys_columns, key_value)),
-                Ok(Some(_)) => {}
-                Ok(None) =>
-                    return self.keys_table.overwrite_row(
-                        my_keys_row_id,
-                        Self::create_hash_keys_row(data_row_id, my_keys_row_id, keys_columns, key_value), ),
-                Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
-            }
+    ) -> std::io::Result<()> {
+        while !self.try_link_key_value(data_row_id, key_value)? {
+            // the bucket is full: grow the hash table, then retry
+            self.grow(data_row_id, key_value)?;
+        }
+        if self.retiring.is_empty() && self.get_load_factor() > self.max_load_factor
+            && self.bucket_count * 2 <= MAX_BUCKET_COUNT {
+            self.grow(data_row_id, key_value)?;
         }
-        ErrorValue(HashTableOverflow(data_row_id, key_value.unwrap_value()))
+        self.rehash_step(REHASH_BATCH_SIZE)
     }
 
-    fn move_key_value(
-        &mut self,
-        data_row_id: usize,
-        prev_value: &TypedValue,
-        new_value: TypedValue,
-    ) -> TypedValue {
-        let keys_columns = self.keys_table.get_columns();
-        let keys_row_prev_id = self.convert_key_to_row_id(&prev_value);
-        let keys_row_new_id = self.convert_key_to_row_id(&new_value);
-        let keys_row = Self::create_hash_keys_row(data_row_id, keys_row_new_id, keys_columns, &new_value);
-        let a = self.keys_table.overwrite_row(keys_row_new_id, keys_row);
-        if matches!(a, ErrorValue(..)) { return a; }
-        let b = self.keys_table.delete_row(keys_row_prev_id);
-        if matches!(b, ErrorValue(..)) { return b; }
-        a + b
+    /// Migrates a batch of keys from the oldest retiring keys table to the current one
+    fn rehash_step(&mut self, batch_size: u64) -> std::io::Result<()> {
+        let (capacity, cursor) = match self.retiring.first() {
+            Some(retiring) => (retiring.bucket_count * self.bucket_depth, retiring.cursor),
+            None => return Ok(())
+        };
+        let end = (cursor + batch_size).min(capacity).min(self.retiring[0].keys_table.len()? as u64);
+        for slot in cursor..end {
+            if let Some(row) = self.retiring[0].keys_table.read_one(slot as usize)? {
+                let key = &row[1];
+                let new_slot = loop {
+                    let slots = Self::get_bucket_slots(key, self.bucket_count, self.bucket_depth);
+                    match Self::find_free_slot(self.keys_table.as_ref(), slots)? {
+                        Some(new_slot) => break new_slot,
+                        // the resized bucket is full: grow again, and keep migrating
+                        None => self.grow(row[0].to_usize(), key)?
+                    }
+                };
+                Self::to_result(self.keys_table.overwrite_row(new_slot, row.with_row_id(new_slot)))?;
+                Self::to_result(self.retiring[0].keys_table.delete_row(slot as usize))?;
+            }
+        }
+        if end < capacity && end < self.retiring[0].keys_table.len()? as u64 {
+            self.retiring[0].cursor = end;
+            Ok(())
+        } else {
+            self.finish_rehash()
+        }
     }
 
     /// (Re)builds the hash key table
     pub fn rebuild(&mut self) -> TypedValue {
-        let keys_columns = self.keys_table.get_columns();
-        let mut keys_table = match self.create_related_structure(keys_columns.to_owned(), self.key_column_index.to_string().as_str()) {
-            Ok(table) => table,
-            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
-        };
-        if keys_table.resize(0) != Number(Numbers::Ack) {
-            warn!("Failed to truncate index for column {}", self.get_key_column().get_name());
+        match self.rebuild_with_bucket_count(self.bucket_count) {
+            Ok(inserted_rows) => Number(Numbers::RowsAffected(inserted_rows as i64)),
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
         }
-        let mut inserted_rows = 0;
-        let data_table_len = match self.data_table.len() {
-            Ok(len) => len,
-            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+    }
+
+    /// (Re)builds the hash key table with the given number of buckets;
+    /// doubling the number of buckets whenever a bucket overflows.
+    fn rebuild_with_bucket_count(&mut self, bucket_count: u64) -> std::io::Result<usize> {
+        let mut bucket_counts = vec![self.bucket_count];
+        bucket_counts.extend(self.retiring.iter().map(|r| r.bucket_count));
+        let mut bucket_count = bucket_count;
+        'rebuild: loop {
+            let mut keys_table = Self::create_keys_table(self.data_table.as_ref(), self.key_column_index, bucket_count)?;
+            if keys_table.resize(0) != Number(Numbers::Ack) {
+                warn!("Failed to truncate index for column {}", self.get_key_column().get_name());
+            }
+            let mut inserted_rows = 0;
+            for data_row_id in 0..self.data_table.len()? {
+                // attempt to read a row ...
+                if let Some(row) = self.data_table.read_one(data_row_id)? {
+                    // translate key into a free slot of its bucket
+                    let key = &row[self.key_column_index];
+                    let slots = Self::get_bucket_slots(key, bucket_count, self.bucket_depth);
+                    let keys_row_id = match Self::find_slot(keys_table.as_ref(), slots.to_owned(), key)? {
+                        Some((slot, _)) => slot,
+                        None => match Self::find_free_slot(keys_table.as_ref(), slots)? {
+                            Some(slot) => { inserted_rows += 1; slot }
+                            None if bucket_count * 2 <= MAX_BUCKET_COUNT => {
+                                bucket_counts.push(bucket_count);
+                                bucket_count *= 2;
+                                continue 'rebuild;
+                            }
+                            None => return throw(HashTableOverflow(data_row_id, key.unwrap_value()))
+                        }
+                    };
+                    let keys_row = Self::create_hash_keys_row(data_row_id, keys_row_id, key);
+                    Self::to_result(keys_table.overwrite_row(keys_row_id, keys_row))?;
+                }
+            }
+            self.keys_table = keys_table;
+            self.bucket_count = bucket_count;
+            self.key_count = inserted_rows as u64;
+            self.retiring.clear();
+
+            // remove the keys tables that were replaced (or abandoned)
+            bucket_counts.sort();
+            bucket_counts.dedup();
+            for retired_bucket_count in bucket_counts {
+                if retired_bucket_count != bucket_count {
+                    self.delete_keys_table(retired_bucket_count)?;
+                }
+            }
+            return Ok(inserted_rows);
+        }
+    }
+
+    /// Attempts to link a key to a data row; returning false if the key's bucket is full
+    fn try_link_key_value(
+        &mut self,
+        data_row_id: usize,
+        key_value: &TypedValue,
+    ) -> std::io::Result<bool> {
+        // keys are written to the current keys table; so move any link from a retiring one
+        for retiring in self.retiring.iter_mut() {
+            let slots = Self::get_bucket_slots(key_value, retiring.bucket_count, self.bucket_depth);
+            if let Some((slot, _)) = Self::find_slot(retiring.keys_table.as_ref(), slots, key_value)? {
+                Self::to_result(retiring.keys_table.delete_row(slot))?;
+                self.key_count -= 1;
+            }
+        }
+        let slots = Self::get_bucket_slots(key_value, self.bucket_count, self.bucket_depth);
+        let keys_row_id = match Self::find_slot(self.keys_table.as_ref(), slots.to_owned(), key_value)? {
+            Some((slot, _)) => slot,
+            None => match Self::find_free_slot(self.keys_table.as_ref(), slots)? {
+                Some(slot) => { self.key_count += 1; slot }
+                None => return Ok(false)
+            }
         };
-        for data_row_id in 0..data_table_len {
-            // attempt to read a row ...
-            if let Ok(Some(row)) = self.data_table.read_one(data_row_id) {
-                // translate key into the hash-key row ID, then lookup the data row ID
-                let key = &row[self.key_column_index];
-                let keys_row_id = self.convert_key_to_row_id(key);
-                let keys_row = Self::create_hash_keys_row(data_row_id, keys_row_id, keys_columns, key);
-
-                // attempt to overwrite the row
-                match keys_table.overwrite_row(keys_row_id, keys_row) {
-                    ErrorValue(message) => return ErrorValue(message),
-                    Number(oc) => inserted_rows += oc.to_usize(),
-                    _ => {}
+        let keys_row = Self::create_hash_keys_row(data_row_id, keys_row_id, key_value);
+        Self::to_result(self.keys_table.overwrite_row(keys_row_id, keys_row))?;
+        Ok(true)
+    }
+
+    /// Removes the link between a key and a data row (if the key is linked to it)
+    fn unlink_key_value(
+        &mut self,
+        data_row_id: usize,
+        key_value: &TypedValue,
+    ) -> std::io::Result<()> {
+        let tables = std::iter::once((&mut self.keys_table, self.bucket_count))
+            .chain(self.retiring.iter_mut().map(|r| (&mut r.keys_table, r.bucket_count)));
+        for (keys_table, bucket_count) in tables {
+            let slots = Self::get_bucket_slots(key_value, bucket_count, self.bucket_depth);
+            if let Some((slot, linked_row_id)) = Self::find_slot(keys_table.as_ref(), slots, key_value)? {
+                if linked_row_id == data_row_id {
+                    Self::to_result(keys_table.delete_row(slot))?;
+                    self.key_count -= 1;
                 }
+                break;
             }
         }
-        self.keys_table = keys_table;
-        Number(Numbers::RowsAffected(inserted_rows as i64))
+        Ok(())
+    }
+
+    /// Moves the link of a data row from its previous key to its new key
+    fn move_key_value(
+        &mut self,
+        data_row_id: usize,
+        prev_value: &TypedValue,
+        new_value: &TypedValue,
+    ) -> std::io::Result<()> {
+        if !matches!(prev_value, Undefined | Null | ErrorValue(..)) && prev_value != new_value {
+            self.unlink_key_value(data_row_id, prev_value)?;
+        }
+        self.link_key_value(data_row_id, new_value)
     }
 }
 
 impl RowCollection for HashTableRowCollection {
     fn delete_row(&mut self, id: usize) -> TypedValue {
-        let key_value = self.read_field(id, self.key_column_index);
-        let keys_row_id = self.convert_key_to_row_id(&key_value);
-        match key_value {
+        match self.read_field(id, self.key_column_index) {
             ErrorValue(msg) => ErrorValue(msg),
             // no previous key value to delete
-            TypedValue::Undefined => self.data_table.delete_row(keys_row_id),
+            Undefined => self.data_table.delete_row(id),
             // delete the hash key value
-            _ =>
-                match self.keys_table.delete_row(keys_row_id) {
-                    Number(..) => self.data_table.delete_row(id),
-                    ErrorValue(msg) => ErrorValue(msg),
-                    other => ErrorValue(TypeMismatch(OutcomeExpected(other.to_code())))
+            key_value =>
+                match self.unlink_key_value(id, &key_value) {
+                    Ok(()) => self.data_table.delete_row(id),
+                    Err(err) => ErrorValue(Errors::Exact(err.to_string()))
                 }
         }
     }
@@ -293,11 +520,13 @@ impl RowCollection for HashTableRowCollection {
         column_id: usize,
         new_value: TypedValue,
     ) -> TypedValue {
-        match self.link_key_value(id, &new_value) {
-            Number(..) => self.data_table.overwrite_field(id, column_id, new_value),
-            ErrorValue(msg) => ErrorValue(msg),
-            other => ErrorValue(TypeMismatch(OutcomeExpected(other.to_code())))
+        if column_id == self.key_column_index {
+            let prev_value = self.read_field(id, column_id);
+            if let Err(err) = self.move_key_value(id, &prev_value, &new_value) {
+                return ErrorValue(Errors::Exact(err.to_string()));
+            }
         }
+        self.data_table.overwrite_field(id, column_id, new_value)
     }
 
     fn overwrite_field_metadata(
@@ -310,12 +539,10 @@ impl RowCollection for HashTableRowCollection {
     }
 
     fn overwrite_row(&mut self, id: usize, row: Row) -> TypedValue {
-        let new_value = &row[self.key_column_index];
-        match self.link_key_value(id, new_value) {
-            Number(oc) if oc.to_usize() > 0 => self.data_table.overwrite_row(id, row),
-            Number(oc) => Number(oc),
-            ErrorValue(err) => ErrorValue(err),
-            other => ErrorValue(TypeMismatch(OutcomeExpected(other.to_code())))
+        let prev_value = self.read_field(id, self.key_column_index);
+        match self.move_key_value(id, &prev_value, &row[self.key_column_index]) {
+            Ok(()) => self.data_table.overwrite_row(id, row),
+            Err(err) => ErrorValue(Errors::Exact(err.to_string()))
         }
     }
 
@@ -348,7 +575,15 @@ impl RowCollection for HashTableRowCollection {
     }
 
     fn resize(&mut self, new_size: usize) -> TypedValue {
-        self.data_table.resize(new_size)
+        let is_shrinking = self.data_table.len().map(|len| new_size < len).unwrap_or(true);
+        match self.data_table.resize(new_size) {
+            // drop the keys of the truncated rows
+            Number(..) if is_shrinking => match self.rebuild() {
+                ErrorValue(err) => ErrorValue(err),
+                _ => Number(Numbers::Ack)
+            }
+            outcome => outcome
+        }
     }
 
     fn scan_first(
@@ -365,15 +600,13 @@ impl RowCollection for HashTableRowCollection {
 
     fn update_row(&mut self, id: usize, row: Row) -> TypedValue {
         let new_value = row[self.key_column_index].to_owned();
-        let old_value = self.read_field(id, self.key_column_index);
-        match old_value {
+        match self.read_field(id, self.key_column_index) {
             ErrorValue(msg) => ErrorValue(msg),
             Undefined => self.data_table.update_row(id, row),
-            _ =>
-                match self.move_key_value(id, &old_value, new_value) {
-                    Number(..) => self.data_table.update_row(id, row),
-                    ErrorValue(err) => ErrorValue(err),
-                    other => ErrorValue(TypeMismatch(RowsAffectedExpected(other.to_code())))
+            old_value =>
+                match self.move_key_value(id, &old_value, &new_value) {
+                    Ok(()) => self.data_table.update_row(id, row),
+                    Err(err) => ErrorValue(Errors::Exact(err.to_string()))
                 }
         }
     }
@@ -383,10 +616,11 @@ impl RowCollection for HashTableRowCollection {
 mod tests {
     use crate::file_row_collection::FileRowCollection;
     use crate::namespaces::Namespace;
-    use crate::numbers::Numbers::{Ack, F64Value, RowsAffected};
+    use crate::numbers::Numbers::{Ack, F64Value, RowId, RowsAffected};
     use crate::row_collection::RowCollection;
     use crate::sequences::{Array, Sequence};
     use crate::structures::Row;
+    use crate::structures::Structure;
     use crate::table_renderer::TableRenderer;
     use crate::testdata::{make_quote_columns, make_quote_parameters, StockQuote};
     use std::time::Instant;
@@ -453,7 +687,147 @@ mod tests {
             ])));
 
         // verify the deleted record (hash index table)
-        //assert_eq!(hkrc.find_row_by_key(&StringValue("CRT".into())).unwrap(), None);
+        assert_eq!(hkrc.find_row_by_key(&StringValue("CRT".into())).unwrap(), None);
+    }
+
+    #[test]
+    fn test_delete_and_overwrite_field_then_find_row_by_key() {
+        let ns = Namespace::new("hash_key", "delete_overwrite", "stocks");
+        let mut hkrc = build_hash_key_table_with_samples(&ns, 0, 1000, 100);
+
+        // overwriting a non-key field leaves the key linked to its row
+        assert_eq!(hkrc.overwrite_field(3, 2, Number(F64Value(21.5))), Number(RowsAffected(1)));
+        assert_eq!(hkrc.find_row_by_key(&StringValue("GE".into())).unwrap(), Some(Row::new(3, vec![
+            StringValue("GE".into()), StringValue("NYSE".into()), Number(F64Value(21.5)),
+        ])));
+
+        // deleting a row unlinks its key (and only its key)
+        assert_eq!(hkrc.delete_row(5), Number(RowsAffected(1)));
+        assert_eq!(hkrc.find_row_by_key(&StringValue("T".into())).unwrap(), None);
+        assert_eq!(hkrc.find_row_by_key(&StringValue("X".into())).unwrap(), Some(Row::new(6, vec![
+            StringValue("X".into()), StringValue("NASDAQ".into()), Number(F64Value(33.33)),
+        ])));
+        assert!(get_collisions(&hkrc.audit()).is_empty());
+    }
+
+    #[test]
+    fn test_automatic_rehash() {
+        // start with a tiny hash table (4 buckets of 4 slots)
+        let ns = Namespace::new("hash_key", "rehash", "stocks");
+        let mut hkrc = build_hash_key_table(&ns, 0, 4, 4);
+        assert_eq!(Number(Ack), hkrc.resize(0));
+        for n in 0..500 {
+            assert_eq!(hkrc.append_row(make_quote(n, format!("S{n:03}").as_str())), Number(RowId(n as u64)));
+        }
+
+        // the hash table grows rather than overflowing
+        assert!(hkrc.get_bucket_count() >= 128);
+        assert!(hkrc.get_load_factor() <= 0.75);
+        for n in [0, 123, 499] {
+            assert_eq!(hkrc.find_row_by_key(&StringValue(format!("S{n:03}"))).unwrap(),
+                       Some(make_quote(n, format!("S{n:03}").as_str())));
+        }
+
+        // verify the bucket utilization
+        let report = hkrc.audit();
+        assert!(get_collisions(&report).is_empty());
+        assert_eq!(get_field(&report, "key_count"), Number(U64Value(500)));
+        assert_eq!(get_field(&report, "bucket_count"), Number(U64Value(hkrc.get_bucket_count())));
+        assert!(get_field(&report, "max_bucket_len").to_u64() <= 4);
+    }
+
+    #[test]
+    fn test_delete_and_update_while_rehashing() {
+        let ns = Namespace::new("hash_key", "rehash_modify", "stocks");
+        let mut hkrc = build_hash_key_table(&ns, 0, 16, 16).with_max_load_factor(0.25);
+        assert_eq!(Number(Ack), hkrc.resize(0));
+        for n in 0..65 {
+            hkrc.append_row(make_quote(n, format!("S{n:03}").as_str()));
+        }
+        assert_eq!(get_field(&hkrc.audit(), "is_rehashing"), Boolean(true));
+
+        // delete one row, and re-key another (mid-rehash)
+        assert_eq!(hkrc.delete_row(3), Number(RowsAffected(1)));
+        assert_eq!(hkrc.find_row_by_key(&StringValue("S003".into())).unwrap(), None);
+        hkrc.update_row(4, make_quote(4, "ZZZ"));
+        assert_eq!(hkrc.find_row_by_key(&StringValue("S004".into())).unwrap(), None);
+        assert_eq!(hkrc.find_row_by_key(&StringValue("ZZZ".into())).unwrap(), Some(make_quote(4, "ZZZ")));
+        for n in 65..70 {
+            hkrc.append_row(make_quote(n, format!("S{n:03}").as_str()));
+        }
+        for n in (5..70).chain(0..3) {
+            assert_eq!(hkrc.find_row_by_key(&StringValue(format!("S{n:03}"))).unwrap(),
+                       Some(make_quote(n, format!("S{n:03}").as_str())));
+        }
+        let report = hkrc.audit();
+        assert!(get_collisions(&report).is_empty());
+        assert_eq!(get_field(&report, "is_rehashing"), Boolean(false));
+        assert_eq!(get_field(&report, "bucket_count"), Number(U64Value(32)));
+        assert_eq!(get_field(&report, "key_count"), Number(U64Value(69)));
+    }
+
+    #[test]
+    fn test_grow_while_rehashing() {
+        // single-slot buckets overflow during migrations as well
+        let ns = Namespace::new("hash_key", "rehash_grow", "stocks");
+        let mut hkrc = build_hash_key_table(&ns, 0, 2, 1);
+        assert_eq!(Number(Ack), hkrc.resize(0));
+        for n in 0..100 {
+            assert_eq!(hkrc.append_row(make_quote(n, format!("S{n:03}").as_str())), Number(RowId(n as u64)));
+        }
+        for n in 0..100 {
+            assert_eq!(hkrc.find_row_by_key(&StringValue(format!("S{n:03}"))).unwrap(),
+                       Some(make_quote(n, format!("S{n:03}").as_str())));
+        }
+        while !hkrc.retiring.is_empty() {
+            hkrc.rehash_step(REHASH_BATCH_SIZE).unwrap();
+        }
+        let report = hkrc.audit();
+        assert!(get_collisions(&report).is_empty());
+        assert_eq!(get_field(&report, "key_count"), Number(U64Value(100)));
+        assert_eq!(get_field(&report, "max_bucket_len"), Number(U64Value(1)));
+    }
+
+    #[test]
+    fn test_retired_keys_tables_are_removed() {
+        let ns = Namespace::new("hash_key", "rehash_files", "stocks");
+        let mut hkrc = build_hash_key_table(&ns, 0, 4, 4);
+        assert_eq!(Number(Ack), hkrc.resize(0));
+        for n in 0..200 {
+            hkrc.append_row(make_quote(n, format!("S{n:03}").as_str()));
+        }
+        while !hkrc.retiring.is_empty() {
+            hkrc.rehash_step(REHASH_BATCH_SIZE).unwrap();
+        }
+
+        // only the keys table of the current size remains
+        let keys_table_exists = |bucket_count: u64| {
+            let extension = format!("0.{bucket_count}");
+            let (_, path) = FileRowCollection::get_related_filename(ns.get_table_file_path().as_str(), extension.as_str());
+            std::path::Path::new(path.as_str()).exists()
+        };
+        let bucket_count = hkrc.get_bucket_count();
+        assert!(bucket_count > 4);
+        assert!(keys_table_exists(bucket_count));
+        let mut retired = 4;
+        while retired < bucket_count {
+            assert!(!keys_table_exists(retired), "keys table for {retired} buckets remains");
+            retired *= 2;
+        }
+
+        // rebuilding at a larger size removes the replaced keys table too
+        hkrc.rebuild_with_bucket_count(bucket_count * 2).unwrap();
+        assert!(keys_table_exists(bucket_count * 2));
+        assert!(!keys_table_exists(bucket_count));
+    }
+
+    #[test]
+    fn test_key_count_of_existing_keys_table() {
+        let ns = Namespace::new("hash_key", "key_count", "stocks");
+        let HashTableRowCollection { data_table, keys_table, .. } =
+            build_hash_key_table_with_samples(&ns, 0, 1000, 100);
+        let hkrc = HashTableRowCollection::create_with_tables_and_options(0, 1_000, 16, data_table, keys_table);
+        assert_eq!(hkrc.get_load_factor(), 9.0 / 16_000.0);
     }
 
     #[ignore]
@@ -529,17 +903,9 @@ mod tests {
         // [331.6797 msec] audit_collisions (0) -> []
 
         // perform an audit of the hash
-        let collisions = match measure_time(|| stocks.audit()) {
-            (TypedValue::ArrayValue(collisions), msec) => {
-                println!("[{:.4} msec] audit_collisions ({}) -> {}", msec, collisions.len(), TypedValue::ArrayValue(collisions.to_owned()));
-                collisions
-            }
-            (other, _) => {
-                println!("[{:.4} msec] audit_collisions -> {}", msec, other);
-                assert_eq!(other, TypedValue::ArrayValue(Array::new()));
-                Array::new()
-            }
-        };
+        let (report, msec) = measure_time(|| stocks.audit());
+        println!("[{:.4} msec] audit -> {}", msec, report);
+        let collisions = get_collisions(&report);
 
         // verification
         assert!(row_a.is_some() && row_b.is_some());
@@ -548,6 +914,26 @@ mod tests {
         assert!(collisions.is_empty())
     }
 
+    fn get_collisions(report: &TypedValue) -> Array {
+        match get_field(report, "collisions") {
+            ArrayValue(collisions) => collisions,
+            other => panic!("Expected an array, got {other}")
+        }
+    }
+
+    fn get_field(report: &TypedValue, name: &str) -> TypedValue {
+        match report {
+            Structured(report) => report.get(name),
+            other => panic!("Expected a structure, got {other}")
+        }
+    }
+
+    fn make_quote(id: usize, symbol: &str) -> Row {
+        Row::new(id, vec![
+            StringValue(symbol.into()), StringValue("NYSE".into()), Number(F64Value(id as f64)),
+        ])
+    }
+
     fn measure_time<F, R>(process: F) -> (R, f64)
     where
         F: FnOnce() -> R,
@@ -3,8 +3,11 @@
 // HybridRowCollection class
 ////////////////////////////////////////////////////////////////////
 
-use crate::byte_row_collection::ByteRowCollection;
 use crate::columns::Column;
+use crate::constraints::Constraints;
+use crate::errors::throw;
+use crate::errors::Errors::Exact;
+use crate::expression::TableOptions;
 use crate::field::FieldMetadata;
 use crate::file_row_collection::FileRowCollection;
 use crate::namespaces::Namespace;
@@ -13,18 +16,194 @@ use crate::row_collection::RowCollection;
 use crate::row_metadata::RowMetadata;
 use crate::structures::Row;
 use crate::typed_values::TypedValue;
-use serde::{Deserialize, Serialize};
-use std::fmt::Debug;
+use serde::de::Error;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Debug, Formatter};
 use std::fs;
 use std::fs::OpenOptions;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, Weak};
 
-/// Hybrid (Memory and Disk) RowCollection implementation
-#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+/// the memory tiers of the hybrid tables open within this process; keyed by table file path.
+/// A memory tier is released once the last handle of its table is dropped.
+static HOT_TIERS: OnceLock<Mutex<HashMap<String, Weak<Mutex<HotTier>>>>> = OnceLock::new();
+
+/// Determines which rows of a [HybridRowCollection] reside in memory
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum HybridPolicy {
+    /// the first `capacity` rows reside in memory
+    Positional,
+    /// the `capacity` most recently read or written rows reside in memory
+    LeastRecentlyUsed,
+    /// the `capacity` most recently appended rows reside in memory
+    RecentAppends,
+}
+
+impl HybridPolicy {
+    pub fn from_code(code: &str) -> std::io::Result<Self> {
+        match code {
+            "positional" => Ok(Self::Positional),
+            "lru" => Ok(Self::LeastRecentlyUsed),
+            "recent_appends" => Ok(Self::RecentAppends),
+            other => throw(Exact(format!("Invalid hybrid policy '{}': expected positional, lru or recent_appends", other)))
+        }
+    }
+
+    pub fn to_code(&self) -> String {
+        match self {
+            Self::Positional => "positional".into(),
+            Self::LeastRecentlyUsed => "lru".into(),
+            Self::RecentAppends => "recent_appends".into(),
+        }
+    }
+}
+
+/// Represents the in-memory tier of a hybrid table
+struct HotTier {
+    capacity: usize,
+    columns: Vec<Column>,
+    policy: HybridPolicy,
+    residents: HashMap<usize, (Row, u64)>,
+    recency: BTreeMap<u64, usize>,
+    tick: u64,
+}
+
+impl HotTier {
+    fn new(columns: &Vec<Column>, capacity: usize, policy: HybridPolicy) -> Self {
+        Self {
+            capacity,
+            columns: columns.clone(),
+            policy,
+            residents: HashMap::new(),
+            recency: BTreeMap::new(),
+            tick: 0,
+        }
+    }
+
+    /// Returns the memory tier of the table file at the given path; an existing tier
+    /// is only reused if it is still held, and shares the same layout, capacity and policy.
+    fn attach(
+        path: &str,
+        columns: &Vec<Column>,
+        capacity: usize,
+        policy: HybridPolicy,
+        is_fresh: bool,
+    ) -> Arc<Mutex<HotTier>> {
+        let mut tiers = HOT_TIERS.get_or_init(|| Mutex::new(HashMap::new()))
+            .lock().unwrap_or_else(|err| err.into_inner());
+        tiers.retain(|_, tier| tier.strong_count() > 0);
+        match tiers.get(path).and_then(|tier| tier.upgrade()) {
+            Some(tier) if !is_fresh && {
+                let tier = Self::lock(&tier);
+                tier.capacity == capacity && tier.policy == policy && &tier.columns == columns
+            } => tier,
+            _ => {
+                let tier = Arc::new(Mutex::new(HotTier::new(columns, capacity, policy)));
+                tiers.insert(path.to_string(), Arc::downgrade(&tier));
+                tier
+            }
+        }
+    }
+
+    /// Discards the memory tier of the table file at the given path
+    fn detach(path: &str) {
+        if let Some(tiers) = HOT_TIERS.get() {
+            tiers.lock().unwrap_or_else(|err| err.into_inner()).remove(path);
+        }
+    }
+
+    /// Indicates whether the table file at the given path has a memory tier
+    #[cfg(test)]
+    fn is_attached(path: &str) -> bool {
+        HOT_TIERS.get()
+            .and_then(|tiers| tiers.lock().unwrap_or_else(|err| err.into_inner())
+                .get(path).map(|tier| tier.strong_count() > 0))
+            .unwrap_or(false)
+    }
+
+    /// Acquires a memory tier; a poisoned tier is safe to reuse,
+    /// since the file tier always holds the authoritative copy of each row.
+    fn lock(tier: &Arc<Mutex<HotTier>>) -> MutexGuard<'_, HotTier> {
+        tier.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Returns the in-memory copy of a row; marking it as recently used (per the policy)
+    fn get(&mut self, id: usize) -> Option<Row> {
+        let row = self.residents.get(&id).map(|(row, _)| row.to_owned())?;
+        if self.policy == HybridPolicy::LeastRecentlyUsed { self.touch(id); }
+        Some(row)
+    }
+
+    /// Places (or refreshes) the in-memory copy of a row, if the policy admits it;
+    /// `is_append` indicates whether the row was just appended to the table.
+    fn put(&mut self, id: usize, row: Row, is_append: bool) {
+        if let Some((resident, _)) = self.residents.get_mut(&id) {
+            *resident = row.with_row_id(id);
+            if self.policy == HybridPolicy::LeastRecentlyUsed { self.touch(id); }
+            return;
+        }
+        let is_admitted = match self.policy {
+            HybridPolicy::Positional => id < self.capacity,
+            HybridPolicy::LeastRecentlyUsed => true,
+            HybridPolicy::RecentAppends => is_append,
+        };
+        if is_admitted && self.capacity > 0 {
+            if self.residents.len() >= self.capacity { self.evict_oldest(); }
+            self.tick += 1;
+            self.residents.insert(id, (row.with_row_id(id), self.tick));
+            self.recency.insert(self.tick, id);
+        }
+    }
+
+    /// Updates a field of the in-memory copy of a row (if any); marking it as recently used
+    fn patch(&mut self, id: usize, column_id: usize, value: TypedValue) {
+        if let Some((row, _)) = self.residents.get_mut(&id) {
+            let mut values = row.get_values();
+            if column_id < values.len() { values[column_id] = value; }
+            *row = row.with_values(values);
+            if self.policy == HybridPolicy::LeastRecentlyUsed { self.touch(id); }
+        }
+    }
+
+    /// Moves a row out of memory
+    fn evict(&mut self, id: usize) {
+        if let Some((_, tick)) = self.residents.remove(&id) {
+            self.recency.remove(&tick);
+        }
+    }
+
+    /// Moves all rows at or beyond the given position out of memory
+    fn evict_beyond(&mut self, size: usize) {
+        let ids = self.residents.keys().filter(|id| **id >= size).cloned().collect::<Vec<_>>();
+        for id in ids { self.evict(id) }
+    }
+
+    fn evict_oldest(&mut self) {
+        if let Some((_, id)) = self.recency.pop_first() {
+            self.residents.remove(&id);
+        }
+    }
+
+    fn touch(&mut self, id: usize) {
+        if let Some((_, tick)) = self.residents.get_mut(&id) {
+            self.recency.remove(tick);
+            self.tick += 1;
+            *tick = self.tick;
+            self.recency.insert(self.tick, id);
+        }
+    }
+}
+
+/// Hybrid (Memory and Disk) RowCollection implementation; all rows are written
+/// through to disk, while a policy-determined subset of them is also held in memory.
+#[derive(Clone)]
 pub struct HybridRowCollection {
-    dividing_line: usize,
-    brc: ByteRowCollection,
+    capacity: usize,
     frc: FileRowCollection,
+    hot: Arc<Mutex<HotTier>>,
+    policy: HybridPolicy,
 }
 
 impl HybridRowCollection {
@@ -36,11 +215,45 @@ impl HybridRowCollection {
     pub fn build(path: &str, columns: &Vec<Column>, capacity: usize) -> std::io::Result<Self> {
         let file = OpenOptions::new().truncate(true).create(true).read(true).write(true)
             .open(path)?;
-        Ok(Self {
-            dividing_line: capacity,
-            brc: ByteRowCollection::new(columns.clone(), capacity),
-            frc: FileRowCollection::new(columns.clone(), Arc::new(file), path),
-        })
+        let frc = FileRowCollection::new(columns.clone(), Arc::new(file), path);
+        Ok(Self::from_file(frc, capacity, HybridPolicy::Positional, true))
+    }
+
+    /// Creates a new hybrid table within the specified namespace
+    pub fn create_table(
+        ns: &Namespace,
+        params: &Vec<Parameter>,
+        options: &Vec<TableOptions>,
+    ) -> std::io::Result<Self> {
+        let (capacity, policy) = Self::get_hybrid_option(options)?;
+        let frc = FileRowCollection::create_table_with_options(ns, params, options)?;
+        Ok(Self::from_file(frc, capacity, policy, true))
+    }
+
+    /// Detaches the memory tier of a hybrid table (e.g. after its rows were rewritten)
+    pub fn discard(ns: &Namespace) {
+        HotTier::detach(ns.get_table_file_path().as_str())
+    }
+
+    fn from_file(
+        frc: FileRowCollection,
+        capacity: usize,
+        policy: HybridPolicy,
+        is_fresh: bool,
+    ) -> Self {
+        let hot = HotTier::attach(frc.get_path(), frc.get_columns(), capacity, policy, is_fresh);
+        Self { capacity, frc, hot, policy }
+    }
+
+    /// Returns the capacity and policy of the hybrid option
+    pub fn get_hybrid_option(options: &Vec<TableOptions>) -> std::io::Result<(usize, HybridPolicy)> {
+        match options.iter().find_map(|o| match o {
+            TableOptions::Hybrid(capacity, policy) => Some((*capacity, *policy)),
+            _ => None
+        }) {
+            Some(option) => Ok(option),
+            None => throw(Exact("Table option hybrid(capacity) expected".into()))
+        }
     }
 
     pub fn new(ns: &Namespace, parameters: &Vec<Parameter>, capacity: usize) -> std::io::Result<Self> {
@@ -51,42 +264,100 @@ impl HybridRowCollection {
             capacity)
     }
 
+    /// Opens an existing hybrid table; rows are moved into memory as they are accessed
+    pub fn open(ns: &Namespace, options: &Vec<TableOptions>) -> std::io::Result<Self> {
+        let (capacity, policy) = Self::get_hybrid_option(options)?;
+        Ok(Self::from_file(FileRowCollection::open(ns)?, capacity, policy, false))
+    }
+
     ////////////////////////////////////////////////////////////////////
     // instance methods
     ////////////////////////////////////////////////////////////////////
 
-    pub fn get_internals(&self) -> (&ByteRowCollection, &FileRowCollection) {
-        (&self.brc, &self.frc)
+    /// Appends rows in bulk (see [FileRowCollection::bulk_load]); the appended
+    /// rows admitted by the policy are moved into memory as they are written.
+    pub fn bulk_load(&mut self, rows: Vec<Row>) -> std::io::Result<usize> {
+        let start = self.frc.len()?;
+        let end = start + rows.len();
+        let admitted = match self.policy {
+            HybridPolicy::Positional => start..end.min(self.capacity),
+            _ => start.max(end.saturating_sub(self.capacity))..end,
+        };
+        let hot = &self.hot;
+        self.frc.bulk_load_with(rows, |row| {
+            let id = row.get_id();
+            if admitted.contains(&id) { HotTier::lock(hot).put(id, row, true) }
+        })
+    }
+
+    /// Returns the rows currently held in memory (in ascending order by row ID), and the file tier
+    pub fn get_internals(&self) -> (Vec<Row>, &FileRowCollection) {
+        let mut rows = HotTier::lock(&self.hot).residents.values()
+            .map(|(row, _)| row.to_owned())
+            .collect::<Vec<_>>();
+        rows.sort_by_key(|row| row.get_id());
+        (rows, &self.frc)
+    }
+
+    /// Returns the IDs of the rows currently held in memory (in ascending order)
+    pub fn get_resident_ids(&self) -> Vec<usize> {
+        let mut ids = HotTier::lock(&self.hot).residents.keys().cloned().collect::<Vec<_>>();
+        ids.sort();
+        ids
     }
 
-    fn localize(&self, id: usize) -> usize {
-        if id >= self.dividing_line {
-            id - self.dividing_line
-        } else {
-            id
+    pub fn get_file_row_collection(&self) -> &FileRowCollection {
+        &self.frc
+    }
+
+    pub fn get_policy(&self) -> HybridPolicy {
+        self.policy
+    }
+
+    /// Reclaims the unused space of the table's BLOB file
+    pub fn vacuum(&mut self) -> std::io::Result<usize> {
+        self.frc.vacuum()
+    }
+
+    /// Enforces the given constraints (and column defaults) upon all subsequent writes
+    pub fn with_constraints(self, constraints: Option<Constraints>) -> Self {
+        Self { frc: self.frc.with_constraints(constraints), ..self }
+    }
+
+    /// Changes the residency policy; the rows currently held in memory are released.
+    pub fn with_policy(self, policy: HybridPolicy) -> Self {
+        let hot = HotTier::attach(self.frc.get_path(), self.frc.get_columns(), self.capacity, policy, true);
+        Self { hot, policy, ..self }
+    }
+
+    /// Writes through to disk; then applies the given change to the in-memory copy of the row
+    /// (i.e. without reading the row back); the copy is moved out of memory if the write failed.
+    fn write_through<T>(
+        &mut self,
+        id: usize,
+        write: impl FnOnce(&mut FileRowCollection) -> (TypedValue, Option<T>),
+        apply: impl FnOnce(&mut HotTier, T, bool),
+    ) -> TypedValue {
+        let is_append = self.frc.len().map(|len| id >= len).unwrap_or(false);
+        let (result, written) = write(&mut self.frc);
+        let mut hot = HotTier::lock(&self.hot);
+        match written {
+            Some(written) => apply(&mut hot, written, is_append),
+            None => hot.evict(id)
         }
+        result
     }
 }
 
 impl RowCollection for HybridRowCollection {
-    fn get_columns(&self) -> &Vec<Column> { self.brc.get_columns() }
+    fn get_columns(&self) -> &Vec<Column> { self.frc.get_columns() }
 
-    fn get_record_size(&self) -> usize { self.brc.get_record_size() }
+    fn get_record_size(&self) -> usize { self.frc.get_record_size() }
 
-    fn get_rows(&self) -> Vec<Row> {
-        let mut rows = vec![];
-        let brc_rows = self.brc.get_rows();
-        let adjust = brc_rows.len();
-        rows.extend(brc_rows);
-        rows.extend(self.frc.get_rows().iter().enumerate()
-            .map(|(id, row)| row.with_row_id(id + adjust))
-            .collect::<Vec<_>>());
-        rows
-    }
+    /// Scans are served from disk; so that they don't flush the rows held in memory.
+    fn get_rows(&self) -> Vec<Row> { self.frc.get_rows() }
 
-    fn len(&self) -> std::io::Result<usize> {
-        Ok(self.brc.len()? + self.frc.len()?)
-    }
+    fn len(&self) -> std::io::Result<usize> { self.frc.len() }
 
     fn overwrite_field(
         &mut self,
@@ -94,11 +365,8 @@ impl RowCollection for HybridRowCollection {
         column_id: usize,
         new_value: TypedValue,
     ) -> TypedValue {
-        if id < self.dividing_line {
-            self.brc.overwrite_field(id, column_id, new_value)
-        } else {
-            self.frc.overwrite_field(self.localize(id), column_id, new_value)
-        }
+        self.write_through(id, |frc| frc.write_field(id, column_id, new_value),
+                           |hot, value, _| hot.patch(id, column_id, value))
     }
 
     fn overwrite_field_metadata(
@@ -107,35 +375,29 @@ impl RowCollection for HybridRowCollection {
         column_id: usize,
         metadata: FieldMetadata,
     ) -> TypedValue {
-        if id < self.dividing_line {
-            self.brc.overwrite_field_metadata(id, column_id, metadata)
-        } else {
-            self.frc.overwrite_field_metadata(self.localize(id), column_id, metadata)
-        }
+        // the resident copy (if any) is moved out of memory; it is reloaded upon its next read
+        self.write_through(id, |frc| (frc.overwrite_field_metadata(id, column_id, metadata), None::<()>),
+                           |_, _, _| ())
     }
 
     fn overwrite_row(&mut self, id: usize, row: Row) -> TypedValue {
-        if id < self.dividing_line {
-            self.brc.overwrite_row(id, row)
-        } else {
-            let row_id = self.localize(id);
-            self.frc.overwrite_row(row_id, row.with_row_id(row_id))
-        }
+        self.write_through(id, |frc| frc.write_row(id, row),
+                           |hot, row, is_append| hot.put(id, row, is_append))
     }
 
     fn overwrite_row_metadata(&mut self, id: usize, metadata: RowMetadata) -> TypedValue {
-        if id < self.dividing_line {
-            self.brc.overwrite_row_metadata(id, metadata)
-        } else {
-            self.frc.overwrite_row_metadata(self.localize(id), metadata)
-        }
+        // the resident copy (if any) remains valid for as long as the row is allocated
+        self.write_through(id, |frc| {
+            let result = frc.overwrite_row_metadata(id, metadata);
+            let is_retained = metadata.is_allocated && !matches!(result, TypedValue::ErrorValue(..));
+            (result, if is_retained { Some(()) } else { None })
+        }, |_, _, _| ())
     }
 
     fn read_field(&self, id: usize, column_id: usize) -> TypedValue {
-        if id < self.dividing_line {
-            self.brc.read_field(id, column_id)
-        } else {
-            self.frc.read_field(self.localize(id), column_id)
+        match self.read_row(id) {
+            Ok((row, rmd)) if rmd.is_allocated => row[column_id].to_owned(),
+            _ => self.frc.read_field(id, column_id)
         }
     }
 
@@ -144,44 +406,97 @@ impl RowCollection for HybridRowCollection {
         id: usize,
         column_id: usize,
     ) -> std::io::Result<FieldMetadata> {
-        if id < self.dividing_line {
-            self.brc.read_field_metadata(id, column_id)
-        } else {
-            self.frc.read_field_metadata(self.localize(id), column_id)
-        }
+        self.frc.read_field_metadata(id, column_id)
     }
 
     fn read_row(&self, id: usize) -> std::io::Result<(Row, RowMetadata)> {
-        if id < self.dividing_line {
-            self.brc.read_row(id)
-        } else {
-            self.frc.read_row(self.localize(id))
-                .map(|(row, rmd)| (row.with_row_id(id), rmd))
+        let mut hot = HotTier::lock(&self.hot);
+        if let Some(row) = hot.get(id) {
+            return Ok((row, RowMetadata::new(true)));
         }
+        let (row, rmd) = self.frc.read_row(id)?;
+        if rmd.is_allocated { hot.put(id, row.to_owned(), false) }
+        Ok((row, rmd))
     }
 
     fn read_row_metadata(&self, id: usize) -> std::io::Result<RowMetadata> {
-        if id < self.dividing_line {
-            self.brc.read_row_metadata(id)
-        } else {
-            self.frc.read_row_metadata(self.localize(id))
+        if HotTier::lock(&self.hot).residents.contains_key(&id) {
+            return Ok(RowMetadata::new(true));
         }
+        self.frc.read_row_metadata(id)
     }
 
     fn resize(&mut self, new_size: usize) -> TypedValue {
-        if new_size < self.dividing_line {
-            self.brc.resize(new_size);
-            self.frc.resize(0)
-        } else {
-            self.frc.resize(self.localize(new_size))
+        let result = self.frc.resize(new_size);
+        HotTier::lock(&self.hot).evict_beyond(new_size);
+        result
+    }
+}
+
+impl Debug for HybridRowCollection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HybridRowCollection({}, {}, {:?})", self.capacity, self.policy.to_code(), self.frc)
+    }
+}
+
+impl Eq for HybridRowCollection {}
+
+impl Ord for HybridRowCollection {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.capacity, self.policy, &self.frc).cmp(&(other.capacity, other.policy, &other.frc))
+    }
+}
+
+impl PartialEq for HybridRowCollection {
+    fn eq(&self, other: &Self) -> bool {
+        (self.capacity, self.policy, &self.frc) == (other.capacity, other.policy, &other.frc)
+    }
+}
+
+impl PartialOrd for HybridRowCollection {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Serialize for HybridRowCollection {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("HybridRowCollection", 3)?;
+        state.serialize_field("capacity", &self.capacity)?;
+        state.serialize_field("policy", &self.policy)?;
+        state.serialize_field("frc", &self.frc)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for HybridRowCollection {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // define a helper struct for deserialization
+        #[derive(Deserialize)]
+        struct HybridRowCollectionHelper {
+            capacity: usize,
+            policy: HybridPolicy,
+            frc: FileRowCollection,
         }
+
+        let helper = HybridRowCollectionHelper::deserialize(deserializer)
+            .map_err(D::Error::custom)?;
+        Ok(Self::from_file(helper.frc, helper.capacity, helper.policy, false))
     }
 }
 
 /// Unit tests
 #[cfg(test)]
 mod tests {
-    use crate::hybrid_row_collection::HybridRowCollection;
+    use crate::dataframe::Dataframe;
+    use crate::hybrid_row_collection::{HotTier, HybridPolicy, HybridRowCollection};
+    use crate::interpreter::Interpreter;
     use crate::namespaces::Namespace;
     use crate::numbers::Numbers::F64Value;
     use crate::row_collection::RowCollection;
@@ -227,20 +542,118 @@ mod tests {
 
     #[test]
     fn test_get_internals() {
-        let mut hrc = create_hybrid_row_collection("hrc.get_internals.stocks");
-        let (brc, frc) = hrc.get_internals();
-        assert_eq!(brc.get_rows(), vec![
+        let hrc = create_hybrid_row_collection("hrc.get_internals.stocks");
+        let (rows, frc) = hrc.get_internals();
+        assert_eq!(rows, vec![
             make_quote(0, "AAB", "NYSE", 22.44),
             make_quote(1, "XYZ", "NASDAQ", 66.67),
             make_quote(2, "SSO", "NYSE", 123.44),
             make_quote(3, "RAND", "AMEX", 11.33),
         ]);
-        assert_eq!(frc.get_rows(), vec![
-            make_quote(0, "IBM", "NYSE", 21.22),
-            make_quote(1, "ATT", "NYSE", 98.44),
-            make_quote(2, "HOCK", "AMEX", 0.0076),
-            make_quote(3, "XIE", "NASDAQ", 33.33),
+        assert_eq!(frc.get_rows(), create_data_set());
+    }
+
+    #[test]
+    fn test_memory_tier_is_released() {
+        let hrc = create_hybrid_row_collection("hrc.released.stocks");
+        let path = hrc.get_file_row_collection().get_path().to_string();
+        let copy = hrc.clone();
+        drop(hrc);
+        assert!(HotTier::is_attached(&path));
+        assert_eq!(copy.get_resident_ids(), vec![0, 1, 2, 3]);
+        drop(copy);
+        assert!(!HotTier::is_attached(&path));
+    }
+
+    #[test]
+    fn test_resident_rows() {
+        let hrc = create_hybrid_row_collection("hrc.resident_rows.stocks");
+        assert_eq!(hrc.get_resident_ids(), vec![0, 1, 2, 3]);
+        assert_eq!(hrc.get_file_row_collection().get_rows(), create_data_set());
+    }
+
+    #[test]
+    fn test_least_recently_used_policy() {
+        let mut hrc = create_hybrid_row_collection("hrc.lru.stocks")
+            .with_policy(HybridPolicy::LeastRecentlyUsed);
+        assert_eq!(hrc.get_resident_ids(), Vec::<usize>::new());
+
+        // rows move into memory as they are read
+        for id in [6, 1, 7, 2] { hrc.read_one(id).unwrap(); }
+        assert_eq!(hrc.get_resident_ids(), vec![1, 2, 6, 7]);
+
+        // the least recently used row (6) is moved out of memory
+        hrc.read_one(6).unwrap();
+        hrc.read_one(0).unwrap();
+        assert_eq!(hrc.get_resident_ids(), vec![0, 2, 6, 7]);
+
+        // writes are applied to both tiers
+        hrc.overwrite_row(2, make_quote(2, "SSO", "NYSE", 124.11));
+        assert_eq!(hrc.read_one(2).unwrap(), Some(make_quote(2, "SSO", "NYSE", 124.11)));
+        assert_eq!(hrc.get_file_row_collection().read_one(2).unwrap(), Some(make_quote(2, "SSO", "NYSE", 124.11)));
+        hrc.overwrite_field(0, 2, Number(F64Value(22.55)));
+        assert_eq!(hrc.get_internals().0[0], make_quote(0, "AAB", "NYSE", 22.55));
+        assert_eq!(hrc.get_file_row_collection().read_one(0).unwrap(), Some(make_quote(0, "AAB", "NYSE", 22.55)));
+
+        // deleted rows are moved out of memory
+        hrc.delete_row(7);
+        assert_eq!(hrc.get_resident_ids(), vec![0, 2, 6]);
+        assert_eq!(hrc.read_one(7).unwrap(), None);
+    }
+
+    #[test]
+    fn test_recent_appends_policy() {
+        let ns = Namespace::parse("hrc.recent_appends.stocks").unwrap();
+        let mut hrc = HybridRowCollection::new(&ns, &make_quote_parameters(), 3).unwrap()
+            .with_policy(HybridPolicy::RecentAppends);
+        hrc.append_rows(create_data_set());
+        assert_eq!(hrc.get_resident_ids(), vec![5, 6, 7]);
+
+        // reads don't move rows into memory
+        assert_eq!(hrc.read_one(0).unwrap(), Some(make_quote(0, "AAB", "NYSE", 22.44)));
+        assert_eq!(hrc.get_resident_ids(), vec![5, 6, 7]);
+
+        // the oldest append is moved out of memory
+        hrc.append_row(make_quote(8, "BOOM", "AMEX", 0.0872));
+        assert_eq!(hrc.get_resident_ids(), vec![6, 7, 8]);
+        hrc.resize(7);
+        assert_eq!(hrc.get_resident_ids(), vec![6]);
+    }
+
+    #[test]
+    fn test_create_and_reopen_hybrid_table() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("hrc.interpreter.stocks") (
+                symbol: String(8),
+                exchange: String(8),
+                last_sale: f64
+            ) with hybrid(2, lru)
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+             { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 },
+             { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 }] ~> ns("hrc.interpreter.stocks")
+        "#).unwrap();
+        let result = interpreter.evaluate(r#"
+            from ns("hrc.interpreter.stocks") where symbol == "UNO"
+        "#).unwrap();
+        assert_eq!(result.to_table().unwrap().get_rows(), vec![
+            Row::new(1, vec![StringValue("UNO".into()), StringValue("OTC".into()), Number(F64Value(0.2456))]),
         ]);
+
+        // the table is reopened as a hybrid table; with its rows intact. Its memory
+        // tier was released along with the last handle, so rows move back in as they are read.
+        let ns = Namespace::parse("hrc.interpreter.stocks").unwrap();
+        match Dataframe::open(&ns).unwrap() {
+            Dataframe::Hybrid(mut hrc) => {
+                assert_eq!(hrc.get_policy(), HybridPolicy::LeastRecentlyUsed);
+                assert_eq!(hrc.len().unwrap(), 3);
+                for id in 0..3 { hrc.read_one(id).unwrap(); }
+                assert_eq!(hrc.get_resident_ids(), vec![1, 2]);
+            }
+            other => panic!("Expected a hybrid table, got {:?}", other)
+        }
     }
 
     #[test]
@@ -71,6 +71,7 @@ impl Inferences {
             Modulo(a, b) => Inferences::infer_a_or_b(a, b),
             Multiply(a, b) => Inferences::infer_a_or_b(a, b),
             Neg(a) => Inferences::infer(a),
+            NextVal(..) => NumberType(NumberKind::I64Kind),
             Ns(..) => NumberType(NumberKind::AckKind),
             Parameters(params) => ArrayType(params.len()),
             Plus(a, b) => Inferences::infer_a_or_b(a, b),
@@ -54,6 +54,11 @@ impl Interpreter {
         Ok(result)
     }
 
+    /// Returns the state of the machine (e.g. its variables)
+    pub fn get_machine(&self) -> &Machine {
+        &self.machine
+    }
+
     /// Sets the value of a variable
     pub fn with_variable(&mut self, name: &str, value: TypedValue) {
         self.machine = self.machine.with_variable(name, value);
@@ -4,35 +4,45 @@
 ////////////////////////////////////////////////////////////////////
 
 use crate::columns::Column;
+use crate::constraints::Constraints;
 use crate::data_types::DataType::{NumberType, StringType, TableType};
 use crate::dataframe::Dataframe;
+use crate::dataframe::Dataframe::Journaled;
+use crate::errors::throw;
 use crate::errors::Errors::{Exact, TypeMismatch};
-use crate::errors::TypeMismatchErrors::StructExpected;
+use crate::errors::TypeMismatchErrors::{CollectionExpected, StructExpected};
 use crate::expression::Expression;
 use crate::expression::Expression::{FunctionCall, Literal, Variable};
 use crate::field::FieldMetadata;
 use crate::file_row_collection::FileRowCollection;
 use crate::interpreter::Interpreter;
 use crate::machine::Machine;
+use crate::model_row_collection::ModelRowCollection;
 use crate::namespaces::Namespace;
 use crate::number_kind::NumberKind::{DateKind, U16Kind, U64Kind};
 use crate::numbers::Numbers;
 use crate::numbers::Numbers::{DateValue, U16Value, U64Value};
+use crate::object_config::ObjectConfig;
 use crate::parameter::Parameter;
 use crate::row_collection::RowCollection;
 use crate::row_metadata::RowMetadata;
 use crate::sequences::{Array, Sequence};
 use crate::structures::{Row, Structure};
 use crate::typed_values::TypedValue;
-use crate::typed_values::TypedValue::{ArrayValue, ErrorValue, Function, Number, StringValue};
+use crate::typed_values::TypedValue::{ArrayValue, ErrorValue, Function, NamespaceValue, Number, StringValue, TableValue};
 use actix::ActorTryFutureExt;
 use chrono::Local;
 use num_traits::ToPrimitive;
 use serde::de::Error;
 use serde::ser::SerializeStruct;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use shared_lib::cnv_error;
 use std::cmp::Ordering;
 use std::fmt::{Debug, Formatter};
+use std::fs;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::sync::Arc;
 
 /// Implemented by [RowCollection] classes offering the ability
 /// to replay events from a journal
@@ -41,6 +51,57 @@ pub trait Journaling: RowCollection {
     fn replay(&mut self) -> TypedValue;
 }
 
+////////////////////////////////////////////////////////////////////
+//      JournalCheckpoint class
+////////////////////////////////////////////////////////////////////
+
+/// Represents a checkpoint of a journaled table; i.e. a copy of its state
+/// along with the number of events that had been applied to it.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JournalCheckpoint {
+    /// the ID of the first event not reflected by the checkpoint
+    pub event_id: usize,
+    pub rows: usize,
+    pub created_time: i64,
+    /// the number of events discarded by compactions
+    #[serde(default)]
+    pub discarded: usize,
+}
+
+impl JournalCheckpoint {
+    /// Loads a checkpoint from disk; or [None] if no checkpoint has been taken
+    pub fn load(path: &str) -> std::io::Result<Option<Self>> {
+        if !Path::new(path).exists() {
+            return Ok(None);
+        }
+        let json_string = fs::read_to_string(path)?;
+        serde_json::from_str::<Self>(&json_string).map(Some).map_err(|e| cnv_error!(e))
+    }
+
+    /// Saves the checkpoint to disk
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        fs::write(path, serde_json::to_string(self)?)
+    }
+}
+
+/// Determines which of the events preceding the latest checkpoint survive a compaction
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum JournalRetention {
+    /// retains (up to) the given number of events preceding the checkpoint
+    KeepEvents(usize),
+    /// retains the events preceding the checkpoint created at or after the given time (in milliseconds)
+    KeepSince(i64),
+}
+
+/// Identifies a moment in the history of a journaled table
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum JournalMoment {
+    /// the moment immediately following the given event
+    AtEvent(usize),
+    /// the given time (in milliseconds)
+    AtTime(i64),
+}
+
 ////////////////////////////////////////////////////////////////////
 //      JournaledRowCollection class
 ////////////////////////////////////////////////////////////////////
@@ -65,11 +126,10 @@ impl JournaledRowCollection {
         ns: &Namespace,
         columns: &Vec<Parameter>,
     ) -> std::io::Result<Self> {
-        let events_ns = Namespace::new(ns.database.clone(), ns.schema.clone(), format!("{}_events", ns.name));
         Ok(Self {
             namespace: ns.clone(),
             columns: Column::from_parameters(&columns),
-            events: FileRowCollection::open_or_create(&events_ns, vec![
+            events: FileRowCollection::open_or_create(&Self::get_events_namespace(ns), vec![
                 Parameter::new("row_id", NumberType(U64Kind)),
                 Parameter::new("column_id", NumberType(U16Kind)),
                 Parameter::new("action", StringType(2)),
@@ -80,6 +140,42 @@ impl JournaledRowCollection {
         })
     }
 
+    /// Opens an existing journaled table
+    pub fn open(ns: &Namespace) -> std::io::Result<Self> {
+        if !Path::new(&Self::get_events_namespace(ns).get_table_file_path()).exists() {
+            return throw(Exact(format!("{ns} is not journaled")));
+        }
+        Self::new(ns, &ObjectConfig::load(ns)?.get_columns())
+    }
+
+    /// Opens the journaled table referenced by a namespace or table value
+    pub fn open_value(table: &TypedValue) -> std::io::Result<Self> {
+        match table {
+            ErrorValue(err) => throw(err.to_owned()),
+            NamespaceValue(ns) => Self::open(ns),
+            TableValue(Journaled(jrc)) => Ok(jrc.to_owned()),
+            z => throw(TypeMismatch(CollectionExpected(z.to_code())))
+        }
+    }
+
+    /// Enforces the given constraints (and column defaults) upon all subsequent writes
+    pub fn with_constraints(self, constraints: Option<Constraints>) -> Self {
+        Self { state: self.state.with_constraints(constraints), ..self }
+    }
+
+    /// Verifies the records and BLOB references of both the events and the current
+    /// state of this table; returning the problems found as (kind, location, problem).
+    pub fn verify(&self) -> std::io::Result<Vec<(String, String, String)>> {
+        let mut problems = self.events.verify()?;
+        problems.extend(self.state.verify()?);
+        Ok(problems)
+    }
+
+    /// Returns the namespace of the events of a journaled table (e.g. "stocks_events")
+    pub fn get_events_namespace(ns: &Namespace) -> Namespace {
+        Namespace::new(ns.database.clone(), ns.schema.clone(), format!("{}_events", ns.name))
+    }
+
     fn make_field_action(id: usize, column_id: usize, action: &str, value: &TypedValue) -> Row {
         Row::new(id, vec![
             Number(U64Value(id as u64)),
@@ -99,10 +195,242 @@ impl JournaledRowCollection {
     //      instance functions
     ////////////////////////////////////////////////////////////////////
 
+    /// Persists a copy of the current state along with the number of events applied to it;
+    /// so that subsequent replays resume from this checkpoint rather than the first event.
+    pub fn checkpoint(&mut self) -> std::io::Result<JournalCheckpoint> {
+        // write the state to a staging file, then move it into place
+        let path = self.namespace.get_file_path("checkpoint");
+        let staging_path = format!("{path}.new");
+        for stale_path in [staging_path.to_owned(), format!("{staging_path}.blob")] {
+            if Path::new(&stale_path).exists() {
+                fs::remove_file(&stale_path)?;
+            }
+        }
+        let file = OpenOptions::new().truncate(true).create(true).read(true).write(true)
+            .open(&staging_path)?;
+        let mut copy = FileRowCollection::new(self.state.get_columns().clone(), Arc::new(file), &staging_path);
+        let rows = self.state.len()?;
+        for id in 0..rows {
+            let (row, metadata) = self.state.read_row(id)?;
+            if metadata.is_allocated {
+                copy.overwrite_row(id, row).to_result(|_| ())?;
+            }
+        }
+        copy.resize(rows).to_result(|_| ())?;
+        copy.flush()?;
+        drop(copy);
+        fs::rename(&staging_path, &path)?;
+        fs::rename(format!("{staging_path}.blob"), format!("{path}.blob"))?;
+
+        // record the events reflected by the checkpoint
+        let checkpoint = JournalCheckpoint {
+            event_id: self.events.len()?,
+            rows,
+            created_time: Local::now().timestamp_millis(),
+            discarded: self.get_checkpoint()?.map(|c| c.discarded).unwrap_or(0),
+        };
+        checkpoint.save(&self.get_checkpoint_config_path())?;
+        Ok(checkpoint)
+    }
+
+    /// Discards the events preceding the latest checkpoint that fall outside the given
+    /// retention policy; returning the number of events discarded. Events following the
+    /// checkpoint are always retained, and nothing is discarded until a checkpoint is taken.
+    pub fn compact_journal(&mut self, retention: JournalRetention) -> std::io::Result<usize> {
+        let checkpoint = match self.get_checkpoint()? {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(0)
+        };
+        let length = self.events.len()?;
+        let cutoff = checkpoint.event_id.min(length);
+        let discarded = match retention {
+            JournalRetention::KeepEvents(count) => cutoff.saturating_sub(count),
+            JournalRetention::KeepSince(millis) => {
+                let mut discarded = 0;
+                while discarded < cutoff {
+                    match self.events.read_field(discarded, 4) {
+                        Number(DateValue(created_time)) if created_time < millis => discarded += 1,
+                        _ => break
+                    }
+                }
+                discarded
+            }
+        };
+        if discarded == 0 {
+            return Ok(0);
+        }
+
+        // the checkpoint is updated first; so that an interrupted compaction merely
+        // causes already-applied events (whose replay is idempotent) to be replayed.
+        let retained = self.events.read_range(discarded..length)?;
+        JournalCheckpoint {
+            event_id: checkpoint.event_id - discarded,
+            discarded: checkpoint.discarded + discarded,
+            ..checkpoint
+        }
+            .save(&self.get_checkpoint_config_path())?;
+        self.events.resize(0).to_result(|_| ())?;
+        for row in retained {
+            let id = row.get_id() - discarded;
+            self.events.overwrite_row(id, Row::new(id, row.get_values())).to_result(|_| ())?;
+        }
+        self.events.resize(length - discarded).to_result(|_| ())?;
+        Ok(discarded)
+    }
+
+    /// Returns the latest checkpoint; or [None] if no checkpoint has been taken
+    pub fn get_checkpoint(&self) -> std::io::Result<Option<JournalCheckpoint>> {
+        JournalCheckpoint::load(&self.get_checkpoint_config_path())
+    }
+
+    fn get_checkpoint_config_path(&self) -> String {
+        self.namespace.get_file_path("checkpoint.json")
+    }
+
     /// returns the namespace
     pub fn get_namespace(&self) -> &Namespace {
         &self.namespace
     }
+
+    /// Returns the events pertaining to the given row; in the order they were recorded
+    pub fn get_history(&self, row_id: usize) -> std::io::Result<Vec<Row>> {
+        let mut history = Vec::new();
+        let mut event_id = 0;
+        while let Some(event) = self.events.find_next(event_id, |_| true)? {
+            event_id = event.get_id() + 1;
+            if event[0].to_usize() == row_id {
+                history.push(event);
+            }
+        }
+        Ok(history)
+    }
+
+    /// Reconstructs the table as it was at the given moment; by replaying the events
+    /// recorded up to it (into a model, so that the current state is left untouched).
+    pub fn as_of(&self, moment: JournalMoment) -> std::io::Result<ModelRowCollection> {
+        // determine the number of events to apply
+        let length = self.events.len()?;
+        let end = match moment {
+            JournalMoment::AtEvent(event_id) => (event_id + 1).min(length),
+            JournalMoment::AtTime(millis) => {
+                let mut end = 0;
+                while end < length {
+                    match self.events.read_field(end, 4) {
+                        Number(DateValue(created_time)) if created_time <= millis => end += 1,
+                        _ => break
+                    }
+                }
+                end
+            }
+        };
+
+        // resume from the latest checkpoint if it precedes the moment
+        let mut mrc = ModelRowCollection::new(self.state.get_columns().clone());
+        let follows = |checkpoint: &JournalCheckpoint| checkpoint.event_id <= end && match moment {
+            // without events preceding the checkpoint, only its creation time bounds it
+            JournalMoment::AtTime(millis) if checkpoint.event_id == 0 => millis >= checkpoint.created_time,
+            _ => true
+        };
+        let mut event_id = match self.get_checkpoint()? {
+            Some(checkpoint) if follows(&checkpoint) => {
+                Self::restore_checkpoint(&self.namespace, &mut mrc)?;
+                checkpoint.event_id
+            }
+            Some(checkpoint) if checkpoint.discarded > 0 =>
+                return throw(Exact(format!("The history of {} preceding its latest checkpoint has been compacted", self.namespace))),
+            _ => 0
+        };
+        let mut interpreter = Interpreter::new();
+        while event_id < end {
+            let (event, metadata) = self.events.read_row(event_id)?;
+            if metadata.is_allocated {
+                Self::apply_event(&mut mrc, &mut interpreter, &event, self.events.get_columns());
+            }
+            event_id += 1;
+        }
+        Ok(mrc)
+    }
+
+    /// Applies an event to the given state; returning the number of rows affected
+    fn apply_event(
+        state: &mut impl RowCollection,
+        interpreter: &mut Interpreter,
+        event: &Row,
+        event_columns: &Vec<Column>,
+    ) -> i64 {
+        let mut rows_affected = 0;
+        match event.get_values().as_slice() {
+            [row_id, column_id, action, new_value, _date] => {
+                match action.unwrap_value().as_str() {
+                    // change field action
+                    "CF" =>
+                        match interpreter.evaluate(new_value.unwrap_value().as_str()) {
+                            Ok(value) => {
+                                let id = row_id.to_usize();
+                                let empty_values = state.get_columns().iter().map(|_| TypedValue::Null).collect::<Vec<_>>();
+                                while id >= state.len().unwrap_or(0) {
+                                    state.append_row(Row::new(id, empty_values.clone()));
+                                    rows_affected += 1;
+                                };
+                                state.overwrite_field(id, column_id.to_usize(), value);
+                            }
+                            Err(err) => eprintln!("{}", err)
+                        }
+                    // change row action
+                    "CR" =>
+                        match interpreter.evaluate(new_value.unwrap_value().as_str()) {
+                            Ok(ArrayValue(value)) => {
+                                let id = row_id.to_usize();
+                                state.overwrite_row(id, Row::new(id, value.get_values().clone()));
+                                rows_affected += 1;
+                            }
+                            Ok(value) => eprintln!("Expected array value: {}", value.to_code()),
+                            Err(err) => eprintln!("{}", err)
+                        }
+                    // delete row action
+                    "DR" => {
+                        state.delete_row(row_id.to_usize());
+                        rows_affected += 1;
+                    }
+                    // unhandled action
+                    other =>
+                        eprintln!("Invalid action code {other}: {}", event.to_json_string(event_columns))
+                }
+            }
+            other => {
+                eprintln!("Invalid event: {:?}", other.to_vec())
+            }
+        }
+        rows_affected
+    }
+
+    /// Copies the rows of the latest checkpoint into the given (empty) state;
+    /// returning the checkpoint and the number of rows restored.
+    fn restore_checkpoint(
+        ns: &Namespace,
+        state: &mut impl RowCollection,
+    ) -> std::io::Result<Option<(JournalCheckpoint, usize)>> {
+        let checkpoint = match JournalCheckpoint::load(&ns.get_file_path("checkpoint.json"))? {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(None)
+        };
+        let path = ns.get_file_path("checkpoint");
+        let file = OpenOptions::new().read(true).write(true).open(&path)?;
+        let copy = FileRowCollection::new(state.get_columns().clone(), Arc::new(file), &path)
+            .with_verified_header()?;
+        let mut restored = 0;
+        for row in copy.iter() {
+            state.overwrite_row(row.get_id(), row).to_result(|_| ())?;
+            restored += 1;
+        }
+        // account for any deleted rows trailing the last active row
+        if state.len()? < checkpoint.rows {
+            let id = checkpoint.rows - 1;
+            state.overwrite_row(id, Row::create(id, state.get_columns())).to_result(|_| ())?;
+            state.delete_row(id).to_result(|_| ())?;
+        }
+        Ok(Some((checkpoint, restored)))
+    }
 }
 
 impl Debug for JournaledRowCollection {
@@ -116,54 +444,21 @@ impl Eq for JournaledRowCollection {}
 impl Journaling for JournaledRowCollection {
     fn replay(&mut self) -> TypedValue {
         let mut rows_affected = 0;
-        let empty_values = self.state.get_columns().iter().map(|_| TypedValue::Null).collect::<Vec<_>>();
         let mut interpreter = Interpreter::new();
 
-        // rebuild the current state
+        // rebuild the current state (resuming from the latest checkpoint)
         self.state.resize(0);
-        for row in self.events.iter() {
-            match row.get_values().as_slice() {
-                [row_id, column_id, action, new_value, _date] => {
-                    match action.unwrap_value().as_str() {
-                        // change field action
-                        "CF" =>
-                            match interpreter.evaluate(new_value.unwrap_value().as_str()) {
-                                Ok(value) => {
-                                    let id = row_id.to_usize();
-                                    while id >= self.state.len().unwrap_or(0) {
-                                        self.state.append_row(Row::new(id, empty_values.clone()));
-                                        rows_affected += 1;
-                                    };
-                                    self.state.overwrite_field(id, column_id.to_usize(), value);
-                                }
-                                Err(err) => eprintln!("{}", err)
-                            }
-                        // change row action
-                        "CR" =>
-                            match interpreter.evaluate(new_value.unwrap_value().as_str()) {
-                                Ok(ArrayValue(value)) => {
-                                    let id = row_id.to_usize();
-                                    self.state.overwrite_row(id, Row::new(id, value.get_values().clone()));
-                                    rows_affected += 1;
-                                }
-                                Ok(value) => eprintln!("Expected array value: {}", value.to_code()),
-                                Err(err) => eprintln!("{}", err)
-                            }
-                        // delete row action
-                        "DR" => {
-                            self.state.delete_row(row_id.to_usize());
-                            rows_affected += 1;
-                        }
-                        // unhandled action
-                        other =>
-                            eprintln!("Invalid action code {other}: {}",
-                                      row.to_json_string(self.events.get_columns()))
-                    }
-                }
-                other => {
-                    eprintln!("Invalid event: {:?}", other.to_vec())
-                }
+        let mut event_id = match Self::restore_checkpoint(&self.namespace, &mut self.state) {
+            Ok(Some((checkpoint, restored))) => {
+                rows_affected += restored as i64;
+                checkpoint.event_id
             }
+            Ok(None) => 0,
+            Err(err) => return ErrorValue(Exact(err.to_string()))
+        };
+        while let Ok(Some(row)) = self.events.find_next(event_id, |_| true) {
+            event_id = row.get_id() + 1;
+            rows_affected += Self::apply_event(&mut self.state, &mut interpreter, &row, self.events.get_columns());
         }
         Number(Numbers::RowsAffected(rows_affected))
     }
@@ -215,6 +510,11 @@ impl RowCollection for JournaledRowCollection {
     }
 
     fn overwrite_field(&mut self, id: usize, column_id: usize, new_value: TypedValue) -> TypedValue {
+        // only the changes satisfying the constraints of the state are journaled
+        let new_value = match self.state.validate_field(id, column_id, new_value) {
+            Ok(value) => value,
+            Err(err) => return ErrorValue(err)
+        };
         self.events.append_row(Self::make_field_action(id, column_id, "CF", &new_value));
         self.state.overwrite_field(id, column_id, new_value)
     }
@@ -224,6 +524,10 @@ impl RowCollection for JournaledRowCollection {
     }
 
     fn overwrite_row(&mut self, id: usize, row: Row) -> TypedValue {
+        let row = match self.state.validate_row(id, row) {
+            Ok(row) => row,
+            Err(err) => return ErrorValue(err)
+        };
         self.events.append_row(Self::make_row_action(id, 0, "CR", &row));
         self.state.overwrite_row(id, row)
     }
@@ -557,7 +861,7 @@ mod tests {
     #[cfg(test)]
     mod journaled_collection_tests {
         use crate::file_row_collection::FileRowCollection;
-        use crate::journaling::{JournaledRowCollection, Journaling};
+        use crate::journaling::{JournalMoment, JournalRetention, JournaledRowCollection, Journaling};
         use crate::namespaces::Namespace;
         use crate::numbers::Numbers;
         use crate::numbers::Numbers::F64Value;
@@ -565,6 +869,8 @@ mod tests {
         use crate::table_renderer::TableRenderer;
         use crate::testdata::{make_quote, make_quote_parameters};
         use crate::typed_values::TypedValue::Number;
+        use chrono::Local;
+        use std::fs;
 
         #[test]
         fn test_events_crud() {
@@ -672,6 +978,130 @@ mod tests {
             ])
         }
 
+        #[test]
+        fn test_checkpoint_and_compaction() {
+            let ns = Namespace::new("event_src", "checkpoint", "stocks");
+            let _ = fs::remove_file(ns.get_file_path("checkpoint.json"));
+            let mut jrc = JournaledRowCollection::new(&ns, &make_quote_parameters()).unwrap();
+            jrc.events.resize(0);
+            jrc.state.resize(0);
+            assert_eq!(jrc.compact_journal(JournalRetention::KeepEvents(0)).unwrap(), 0);
+
+            // checkpoint the initial state, then make some changes
+            jrc.append_rows(vec![
+                make_quote(0, "ABC", "AMEX", 11.77),
+                make_quote(1, "UNO", "OTC", 0.2456),
+                make_quote(2, "BIZ", "NYSE", 23.66),
+            ]);
+            let checkpoint = jrc.checkpoint().unwrap();
+            assert_eq!((checkpoint.event_id, checkpoint.rows), (3, 3));
+            jrc.overwrite_field(0, 2, Number(F64Value(11.88)));
+            jrc.delete_row(1);
+            let expected = vec![
+                make_quote(0, "ABC", "AMEX", 11.88),
+                make_quote(2, "BIZ", "NYSE", 23.66),
+            ];
+
+            // the replay resumes from the checkpoint (3 rows restored + 1 row deleted)
+            jrc.state.resize(0);
+            assert_eq!(jrc.replay(), Number(Numbers::RowsAffected(4)));
+            assert_eq!(jrc.get_rows(), expected);
+
+            // compaction only discards events preceding the checkpoint
+            assert_eq!(jrc.compact_journal(JournalRetention::KeepEvents(1)).unwrap(), 2);
+            assert_eq!(jrc.events.len().unwrap(), 3);
+            assert_eq!(jrc.get_checkpoint().unwrap().map(|c| c.event_id), Some(1));
+            assert_eq!(jrc.compact_journal(JournalRetention::KeepSince(0)).unwrap(), 0);
+            let cutoff = Local::now().timestamp_millis() + 1000;
+            assert_eq!(jrc.compact_journal(JournalRetention::KeepSince(cutoff)).unwrap(), 1);
+            assert_eq!(jrc.get_checkpoint().unwrap().map(|c| c.event_id), Some(0));
+
+            // the compacted journal replays to the same state
+            jrc.state.resize(0);
+            assert_eq!(jrc.replay(), Number(Numbers::RowsAffected(4)));
+            assert_eq!(jrc.get_rows(), expected);
+        }
+
+        #[test]
+        fn test_verify() {
+            let ns = Namespace::new("event_src", "verify", "stocks");
+            let mut jrc = JournaledRowCollection::new(&ns, &make_quote_parameters()).unwrap();
+            jrc.events.resize(0);
+            jrc.state.resize(0);
+            jrc.append_rows(vec![
+                make_quote(0, "ABC", "AMEX", 11.77),
+                make_quote(1, "UNO", "OTC", 0.2456),
+            ]);
+            assert_eq!(jrc.verify().unwrap(), vec![]);
+
+            // damage to the current state is reported
+            jrc.state.flush().unwrap();
+            let path = ns.get_table_file_path();
+            let mut raw = fs::read(&path).unwrap();
+            let offset = raw.windows(4).position(|w| w == b"AMEX").unwrap();
+            raw[offset] = b'N';
+            fs::write(&path, raw).unwrap();
+            let jrc = JournaledRowCollection::open(&ns).unwrap();
+            assert_eq!(jrc.verify().unwrap(), vec![
+                ("record".into(), format!("{path}#0"), "checksum mismatch".into())
+            ]);
+        }
+
+        #[test]
+        fn test_as_of_and_history() {
+            let ns = Namespace::new("event_src", "as_of", "stocks");
+            let _ = fs::remove_file(ns.get_file_path("checkpoint.json"));
+            let mut jrc = JournaledRowCollection::new(&ns, &make_quote_parameters()).unwrap();
+            jrc.events.resize(0);
+            jrc.state.resize(0);
+
+            // make some changes (events 0..=4)
+            jrc.append_rows(vec![
+                make_quote(0, "ABC", "AMEX", 11.77),
+                make_quote(1, "UNO", "OTC", 0.2456),
+                make_quote(2, "BIZ", "NYSE", 23.66),
+            ]);
+            jrc.overwrite_field(0, 2, Number(F64Value(11.88)));
+            jrc.delete_row(1);
+
+            // reconstruct the table as of each event
+            assert_eq!(jrc.as_of(JournalMoment::AtEvent(1)).unwrap().get_rows(), vec![
+                make_quote(0, "ABC", "AMEX", 11.77),
+                make_quote(1, "UNO", "OTC", 0.2456),
+            ]);
+            assert_eq!(jrc.as_of(JournalMoment::AtEvent(3)).unwrap().get_rows(), vec![
+                make_quote(0, "ABC", "AMEX", 11.88),
+                make_quote(1, "UNO", "OTC", 0.2456),
+                make_quote(2, "BIZ", "NYSE", 23.66),
+            ]);
+            assert_eq!(jrc.as_of(JournalMoment::AtTime(0)).unwrap().get_rows(), vec![]);
+            let expected = vec![
+                make_quote(0, "ABC", "AMEX", 11.88),
+                make_quote(2, "BIZ", "NYSE", 23.66),
+            ];
+            let now = Local::now().timestamp_millis();
+            assert_eq!(jrc.as_of(JournalMoment::AtTime(now)).unwrap().get_rows(), expected);
+
+            // the checkpoint is used for later moments; the earlier ones are replayed from scratch
+            jrc.checkpoint().unwrap();
+            assert_eq!(jrc.as_of(JournalMoment::AtEvent(4)).unwrap().get_rows(), expected);
+            assert_eq!(jrc.as_of(JournalMoment::AtEvent(0)).unwrap().get_rows(), vec![
+                make_quote(0, "ABC", "AMEX", 11.77),
+            ]);
+
+            // the history lists every change to a row
+            let history = jrc.get_history(0).unwrap();
+            assert_eq!(history.iter().map(|e| (e.get_id(), e[2].unwrap_value())).collect::<Vec<_>>(), vec![
+                (0, "CR".to_string()),
+                (3, "CF".to_string()),
+            ]);
+
+            // the moments preceding a compaction can no longer be reconstructed
+            assert_eq!(jrc.compact_journal(JournalRetention::KeepEvents(0)).unwrap(), 5);
+            assert_eq!(jrc.as_of(JournalMoment::AtEvent(0)).unwrap().get_rows(), expected);
+            assert!(jrc.as_of(JournalMoment::AtTime(0)).is_err());
+        }
+
         fn show(frc: &FileRowCollection, label: &str) {
             println!("{}", label);
             let lines = TableRenderer::from_rows_with_ids(frc.get_columns(), &frc.get_rows()).unwrap();
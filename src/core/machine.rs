@@ -30,7 +30,7 @@ use crate::data_types::DataType::{ArrayType, FunctionType, StringType, Structure
 use crate::sequences::{Array, Sequence};
 
 use crate::dataframe::Dataframe;
-use crate::dataframe::Dataframe::{Disk, Model};
+use crate::dataframe::Dataframe::Model;
 
 use crate::errors::Errors::*;
 use crate::errors::TypeMismatchErrors::{FunctionArgsExpected, OutcomeExpected, ParameterExpected, StructExpected, UnsupportedType};
@@ -41,7 +41,6 @@ use crate::expression::Expression::*;
 use crate::expression::MutateTarget::{IndexTarget, TableTarget};
 use crate::expression::{Conditions, Expression, ImportOps, ACK, UNDEFINED};
 use crate::expression::{DatabaseOps, Directives, Mutations, Queryables};
-use crate::file_row_collection::FileRowCollection;
 use crate::inferences::Inferences;
 use crate::model_row_collection::ModelRowCollection;
 use crate::namespaces::Namespace;
@@ -185,6 +184,7 @@ impl Machine {
             Multiply(a, b) =>
                 self.do_inline_2(a, b, |aa, bb| aa * bb),
             Neg(a) => Ok(self.do_negate(a)),
+            NextVal(a) => do_eval_next_val(self, a),
             Ns(a) => do_eval_ns(self, a),
             Parameters(params) => Ok(self.evaluate_parameters(params)),
             Plus(a, b) =>
@@ -946,7 +946,7 @@ impl Machine {
             }
             NamespaceValue(ns) => {
                 let id = index.to_usize();
-                let frc = FileRowCollection::open(&ns)?;
+                let frc = Dataframe::open(&ns)?;
                 match frc.read_one(id)? {
                     Some(row) => Structured(Firm(row, frc.get_parameters())),
                     None => Structured(Firm(Row::create(id, frc.get_columns()), frc.get_parameters()))
@@ -1176,7 +1176,7 @@ impl Machine {
     ) -> std::io::Result<A> {
         match table {
             NamespaceValue(ns) =>
-                f(Box::new(FileRowCollection::open(&ns)?)),
+                f(Box::new(Dataframe::open(&ns)?)),
             TableValue(rcv) => f(Box::new(rcv.to_owned())),
             z => throw(Exact(format!("{} is not a table", z)))
         }
@@ -1188,7 +1188,7 @@ impl Machine {
         f: fn(Dataframe) -> std::io::Result<A>,
     ) -> std::io::Result<A> {
         match table {
-            NamespaceValue(ns) => f(Disk(FileRowCollection::open(&ns)?)),
+            NamespaceValue(ns) => f(Dataframe::open(&ns)?),
             TableValue(rc) => f(rc.to_owned()),
             z => throw(TypeMismatch(UnsupportedType(TableType(vec![], 0), z.get_type())))
         }
@@ -1318,6 +1318,7 @@ mod tests {
     use crate::expression::MutateTarget::TableTarget;
     use crate::expression::Queryables;
     use crate::expression::{FALSE, NULL, TRUE};
+    use crate::file_row_collection::FileRowCollection;
     use crate::number_kind::NumberKind::I64Kind;
     use crate::table_renderer::TableRenderer;
     use crate::testdata::*;
@@ -1786,6 +1787,7 @@ mod tests {
                 path: Box::new(Ns(Box::new(Literal(StringValue("machine.create.stocks".into()))))),
                 entity: TableEntity {
                     columns: make_quote_parameters(),
+                    constraints: vec![],
                     from: None,
                     options: vec![],
                 },
@@ -1818,6 +1820,7 @@ mod tests {
                 path: Box::new(Ns(Box::new(Literal(StringValue(path.into()))))),
                 entity: TableEntity {
                     columns: make_quote_parameters(),
+                    constraints: vec![],
                     from: None,
                     options: vec![],
                 },
@@ -1852,6 +1855,7 @@ mod tests {
                     Parameter::new("exchange", StringType(8)),
                     Parameter::new("last_sale", NumberType(F64Kind)),
                 ],
+                constraints: vec![],
                 from: None,
                 options: vec![],
             })));
@@ -1956,6 +1960,7 @@ mod tests {
                     ("exchange".into(), Literal(StringValue("NASDAQ".into()))),
                     ("last_sale".into(), Literal(Number(F64Value(16.99)))),
                 ])))),
+                is_bulk: false,
             }))).unwrap();
             assert_eq!(result, Number(RowsAffected(1)));
 
@@ -3,25 +3,34 @@
 //      Oxide REST Server
 ////////////////////////////////////////////////////////////////////
 
-use crate::oxide_server::start_http_server;
+use crate::namespaces::Namespace;
+use crate::oxide_server::{start_http_server, start_http_server_with_expiry};
 use crate::repl::{read_line_from_stdin, REPLState};
+use crate::replication::start_follower;
+use crate::temp_tables::TempTables;
 use crate::terminal::TerminalState;
-use log::LevelFilter;
+use log::{error, LevelFilter};
 use serde::{Deserialize, Serialize};
 use shared_lib::cnv_error;
 use std::env;
 use std::string::ToString;
 
+mod backups;
 mod blobs;
 mod byte_code_compiler;
 mod byte_row_collection;
+mod change_capture;
+mod checksums;
 mod columns;
 mod compiler;
+mod constraints;
 mod cursor;
 mod dataframe;
 mod dataframe_actor;
 mod data_types;
+mod encryption;
 mod errors;
+mod expiry;
 mod expression;
 mod field;
 mod file_row_collection;
@@ -31,23 +40,32 @@ mod inferences;
 mod interpreter;
 mod journaling;
 mod machine;
+mod memory_map;
 mod model_row_collection;
+mod named_sequences;
+mod namespace_leases;
 mod namespaces;
 mod number_kind;
 mod numbers;
 mod object_config;
 mod oxide_server;
+mod page_cache;
 mod parameter;
+mod partitioned_row_collection;
 mod platform;
 mod query_engine;
 mod readme;
 mod repl;
+mod replication;
 mod row_collection;
 mod row_metadata;
 mod sequences;
 mod server;
 mod structures;
+mod system_catalog;
+mod table_header;
 mod table_renderer;
+mod temp_tables;
 mod template;
 mod terminal;
 mod testdata;
@@ -64,6 +82,7 @@ const LOCAL_HOST: &str = "0.0.0.0";
 #[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
 enum ApplicationModes {
     EmbeddedSession(u16),
+    FollowerSession(String, u16, u16, Vec<String>),
     OfflineSession,
     RemoteSession(String, u16),
     StartupFailure(String),
@@ -83,6 +102,13 @@ impl ApplicationModes {
                     Ok(port) => ApplicationModes::EmbeddedSession(port),
                     Err(err) => ApplicationModes::StartupFailure(err.to_string())
                 }
+            [_, action, host, leader_port, port, namespaces @ ..]
+            if action == "follow" && is_u16(leader_port) && is_u16(port) && !namespaces.is_empty() =>
+                match (parse_u16(leader_port), parse_u16(port)) {
+                    (Ok(leader_port), Ok(port)) =>
+                        ApplicationModes::FollowerSession(host.into(), leader_port, port, namespaces.to_vec()),
+                    (Err(err), _) | (_, Err(err)) => ApplicationModes::StartupFailure(err.to_string())
+                }
             [_, host, port] if is_u16(port) =>
                 match parse_u16(port) {
                     Ok(port) => ApplicationModes::RemoteSession(host.into(), port),
@@ -101,11 +127,25 @@ async fn main() -> std::io::Result<()> {
         .filter_level(LevelFilter::Info)
         .init();
 
+    // drop the temporary tables left behind by previous runs
+    if let Err(err) = TempTables::purge_abandoned() {
+        error!("Failed to drop abandoned temporary tables: {err}");
+    }
+
     // start the REPL based on the commandline arguments
     match ApplicationModes::parse(env::args().collect()) {
         ApplicationModes::EmbeddedSession(port) => {
             println!("Starting embedded Oxide service on port {port}...");
+            start_http_server_with_expiry(port);
+            start_online_session(LOCAL_HOST, port).await?
+        }
+        ApplicationModes::FollowerSession(host, leader_port, port, namespaces) => {
+            println!("Starting Oxide follower of {host}:{leader_port} on port {port}...");
+            let namespaces = namespaces.iter()
+                .map(|s| Namespace::parse(s.as_str()))
+                .collect::<std::io::Result<Vec<_>>>()?;
             start_http_server(port);
+            start_follower(host, leader_port, namespaces);
             start_online_session(LOCAL_HOST, port).await?
         }
         ApplicationModes::RemoteSession(host, port) => {
@@ -187,6 +227,17 @@ mod tests {
     }
 
 
+    #[test]
+    fn test_parse_args_follower_session() {
+        let args = ApplicationModes::parse(vec![
+            "oxide".into(), "follow".into(), "leader.acme.com".into(), "8754".into(), "8755".into(),
+            "stocks.nyse.quotes".into(), "stocks.otc.quotes".into(),
+        ]);
+        assert_eq!(args, ApplicationModes::FollowerSession(
+            "leader.acme.com".into(), 8754, 8755,
+            vec!["stocks.nyse.quotes".into(), "stocks.otc.quotes".into()]));
+    }
+
     #[test]
     fn test_parse_args_local_session() {
         let args = ApplicationModes::parse(vec![
@@ -0,0 +1,127 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// MemoryMap class
+////////////////////////////////////////////////////////////////////
+
+use std::fs::File;
+use std::os::fd::AsRawFd;
+
+/// Represents a read-only, shared memory-mapping of a file; reflecting
+/// the file as it was when mapped (subsequent growth is not visible).
+/// Reading a mapped page beyond the end of a truncated file raises SIGBUS; so the mapping
+/// holds a shared lock on the file, which truncations wait for (see [MemoryMap::exclusive]).
+/// NOTE: the lock is advisory; processes that truncate the file without it are not held off.
+pub struct MemoryMap {
+    address: *mut libc::c_void,
+    length: usize,
+    file: File,
+}
+
+impl MemoryMap {
+    /// Maps the entire file at the given path into memory; or [None] if the file is empty
+    pub fn open(path: &str) -> std::io::Result<Option<Self>> {
+        // the lock belongs to this mapping's own handle, which releases it when closed
+        let file = File::open(path)?;
+        Self::lock(&file, libc::LOCK_SH)?;
+        let length = file.metadata()?.len() as usize;
+        if length == 0 { return Ok(None); }
+        let address = unsafe {
+            libc::mmap(std::ptr::null_mut(), length, libc::PROT_READ, libc::MAP_SHARED, file.as_raw_fd(), 0)
+        };
+        if address == libc::MAP_FAILED {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(Some(Self { address, length, file }))
+    }
+
+    /// Runs the given function while holding an exclusive lock on the given file;
+    /// i.e. once no mapping of it exists. Whatever shrinks a mappable file must use it.
+    pub fn exclusive<A>(file: &File, f: impl FnOnce() -> std::io::Result<A>) -> std::io::Result<A> {
+        Self::lock(file, libc::LOCK_EX)?;
+        let result = f();
+        Self::lock(file, libc::LOCK_UN)?;
+        result
+    }
+
+    fn lock(file: &File, operation: libc::c_int) -> std::io::Result<()> {
+        loop {
+            if unsafe { libc::flock(file.as_raw_fd(), operation) } == 0 { return Ok(()); }
+            let err = std::io::Error::last_os_error();
+            if err.kind() != std::io::ErrorKind::Interrupted { return Err(err); }
+        }
+    }
+
+    /// Returns the mapped bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.address as *const u8, self.length) }
+    }
+
+    /// Returns the mapped file
+    pub fn get_file(&self) -> &File { &self.file }
+
+    /// Returns the mapped bytes within the given range; or [None] if it lies beyond the mapping
+    pub fn get(&self, offset: usize, count: usize) -> Option<&[u8]> {
+        self.as_bytes().get(offset..offset.checked_add(count)?)
+    }
+}
+
+impl Drop for MemoryMap {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.address, self.length); }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::Machine;
+    use std::fs;
+    use std::fs::OpenOptions;
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn test_map_file() {
+        let home = Machine::oxide_home();
+        fs::create_dir_all(&home).unwrap();
+        let path = format!("{home}/memory_map.bin");
+        let path = path.as_str();
+        fs::write(path, b"Hello World").unwrap();
+        let mapping = MemoryMap::open(path).unwrap().unwrap();
+        assert_eq!(mapping.as_bytes(), b"Hello World");
+        assert_eq!(mapping.get(6, 5), Some(b"World".as_slice()));
+        assert_eq!(mapping.get(6, 6), None);
+
+        // empty files can't be mapped
+        fs::write(path, b"").unwrap();
+        assert!(MemoryMap::open(path).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_truncation_waits_for_mappings() {
+        let home = Machine::oxide_home();
+        fs::create_dir_all(&home).unwrap();
+        let path = format!("{home}/memory_map_truncation.bin");
+        fs::write(&path, vec![7u8; 3 * 4096]).unwrap();
+        let mapping = MemoryMap::open(path.as_str()).unwrap().unwrap();
+
+        // the truncation must wait until the mapping has been dropped
+        let (tx, rx) = mpsc::channel();
+        let truncation = {
+            let path = path.clone();
+            thread::spawn(move || {
+                let file = OpenOptions::new().write(true).open(path).unwrap();
+                MemoryMap::exclusive(&file, || file.set_len(0)).unwrap();
+                tx.send(()).unwrap();
+            })
+        };
+        assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+        assert_eq!(mapping.get(2 * 4096, 4096), Some(vec![7u8; 4096].as_slice()));
+        drop(mapping);
+        rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        truncation.join().unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+    }
+}
@@ -5,6 +5,7 @@
 
 use crate::byte_code_compiler::ByteCodeCompiler;
 use crate::columns::Column;
+use crate::constraints::Constraints;
 
 use crate::field::FieldMetadata;
 use crate::numbers::Numbers;
@@ -13,13 +14,14 @@ use crate::row_collection::RowCollection;
 use crate::row_metadata::RowMetadata;
 use crate::structures::Row;
 use crate::typed_values::TypedValue;
-use crate::typed_values::TypedValue::{Null, Number};
+use crate::typed_values::TypedValue::{ErrorValue, Null, Number};
 use serde::{Deserialize, Serialize};
 
 /// Row-model-vector-based [RowCollection] implementation
 #[derive(Clone, Debug, Eq, Ord, PartialEq, Serialize, Deserialize, PartialOrd)]
 pub struct ModelRowCollection {
     columns: Vec<Column>,
+    constraints: Option<Constraints>,
     row_data: Vec<(Row, RowMetadata)>,
     record_size: usize,
     watermark: usize,
@@ -82,16 +84,23 @@ impl ModelRowCollection {
             record_size: Row::compute_record_size(&columns),
             watermark: 0,
             columns,
+            constraints: None,
             row_data: Vec::new(),
         }
     }
 
+    /// Enforces the given constraints (and column defaults) upon all subsequent writes
+    pub fn with_constraints(self, constraints: Option<Constraints>) -> Self {
+        Self { constraints, ..self }
+    }
+
     /// Creates a new [ModelRowCollection] prefilled with rows
     pub fn with_rows(columns: Vec<Column>, row_data: Vec<(Row, RowMetadata)>) -> ModelRowCollection {
         ModelRowCollection {
             record_size: Row::compute_record_size(&columns),
             watermark: row_data.len(),
             columns,
+            constraints: None,
             row_data,
         }
     }
@@ -126,6 +135,12 @@ impl RowCollection for ModelRowCollection {
         new_value: TypedValue,
     ) -> TypedValue {
         if id >= self.row_data.len() { return Null; }
+        let new_value = match self.constraints.as_ref()
+            .map(|c| c.validate_field(self, id, column_id, new_value.to_owned())) {
+            Some(Ok(value)) => value,
+            Some(Err(err)) => return ErrorValue(err),
+            None => new_value
+        };
         let (row, meta) = &self.row_data[id];
         let rows_affected = if meta.is_allocated {
             let old_values = row.get_values();
@@ -162,6 +177,12 @@ impl RowCollection for ModelRowCollection {
     }
 
     fn overwrite_row(&mut self, id: usize, row: Row) -> TypedValue {
+        let row = match self.constraints.as_ref().map(|c| c.validate_row(self, id, row.to_owned())) {
+            Some(Ok(row)) => row,
+            Some(Err(err)) => return ErrorValue(err),
+            None => row
+        };
+
         // resize the rows to prevent overflow
         if self.row_data.len() <= id {
             self.row_data.resize(id + 1, (Row::create(id, &self.columns), RowMetadata::new(false)));
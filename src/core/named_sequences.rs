@@ -0,0 +1,328 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// NamedSequence class
+////////////////////////////////////////////////////////////////////
+
+use crate::errors::throw;
+use crate::errors::Errors::Exact;
+use crate::namespace_leases::NamespaceLease;
+use crate::namespaces::Namespace;
+use serde::{Deserialize, Serialize};
+use shared_lib::cnv_error;
+use std::collections::HashMap;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+/// the number of values reserved by each (durable) advancement of a sequence
+const RESERVATION_SIZE: usize = 100;
+
+/// the values reserved by this process, but not yet issued, of each sequence; keyed by path
+static RESERVATIONS: OnceLock<Mutex<HashMap<String, Vec<i64>>>> = OnceLock::new();
+
+/// Represents a durable sequence of integers
+/// ex: create sequence ns("finance.trades.trade_ids") start with 1000 increment by 10
+/// NOTE: values are reserved in blocks (see [RESERVATION_SIZE]), and the sequence is only
+/// advanced by the holder of its namespace's lease (see [NamespaceLease]); so values are
+/// never issued twice, although those left unissued when the process exits are skipped.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct NamedSequence {
+    start: i64,
+    increment: i64,
+    last_value: Option<i64>,
+}
+
+impl NamedSequence {
+    ////////////////////////////////////////////////////////////////
+    // static methods
+    ////////////////////////////////////////////////////////////////
+
+    pub fn new(start: i64, increment: i64) -> Self {
+        Self { start, increment, last_value: None }
+    }
+
+    /// Creates (or resets) the sequence of a namespace
+    pub fn create(ns: &Namespace, start: i64, increment: i64) -> std::io::Result<Self> {
+        if increment == 0 {
+            return throw(Exact(format!("The increment of sequence {} must not be zero", ns)));
+        }
+        Self::reset(ns, &Self::get_sequence_path(ns), Self::new(start, increment))
+    }
+
+    /// Advances the sequence of a namespace; returning its next value
+    pub fn next_value(ns: &Namespace) -> std::io::Result<i64> {
+        let path = Self::get_sequence_path(ns);
+        if !Path::new(&path).exists() {
+            return throw(Exact(format!("Sequence {} not found", ns)));
+        }
+        Self::advance(ns, &path)
+    }
+
+    /// Creates (or restarts) the identity sequence of a table's column; which starts at 1
+    pub fn create_identity(ns: &Namespace, column: &str) -> std::io::Result<Self> {
+        Self::reset(ns, &Self::get_identity_path(ns, column), Self::new(1, 1))
+    }
+
+    /// Advances the identity sequence of a table's column; returning its next value.
+    /// The sequence is created along with the table; so a missing sequence is an error
+    /// (rather than restarted at 1, which would reissue identities).
+    pub fn next_identity(ns: &Namespace, column: &str) -> std::io::Result<i64> {
+        let path = Self::get_identity_path(ns, column);
+        if !Path::new(&path).exists() {
+            return throw(Exact(format!("Identity sequence of column '{}' of {} not found", column, ns)));
+        }
+        Self::advance(ns, &path)
+    }
+
+    /// Moves the identity sequence of a renamed column
+    pub fn rename_identity(ns: &Namespace, old_column: &str, new_column: &str) -> std::io::Result<()> {
+        NamespaceLease::acquire(ns)?;
+        let mut reservations = Self::reservations();
+        let (old_path, new_path) = (Self::get_identity_path(ns, old_column), Self::get_identity_path(ns, new_column));
+        fs::rename(&old_path, &new_path)?;
+        if let Some(reserved) = reservations.remove(&old_path) {
+            reservations.insert(new_path, reserved);
+        }
+        Ok(())
+    }
+
+    /// Removes the identity sequence of a dropped column
+    pub fn drop_identity(ns: &Namespace, column: &str) -> std::io::Result<()> {
+        NamespaceLease::acquire(ns)?;
+        let mut reservations = Self::reservations();
+        let path = Self::get_identity_path(ns, column);
+        match fs::remove_file(&path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => return Err(err),
+            _ => {}
+        }
+        reservations.remove(&path);
+        Ok(())
+    }
+
+    /// Returns the path of the file holding the identity sequence of a table's column
+    pub fn get_identity_path(ns: &Namespace, column: &str) -> String {
+        ns.get_file_path(format!("{}.seq", column).as_str())
+    }
+
+    /// Returns the path of the file holding the sequence of a namespace
+    pub fn get_sequence_path(ns: &Namespace) -> String {
+        ns.get_file_path("seq")
+    }
+
+    /// Issues the next value of the sequence stored at the given path; reserving
+    /// a new block of values (durably written before any of them is issued) as needed.
+    fn advance(ns: &Namespace, path: &str) -> std::io::Result<i64> {
+        NamespaceLease::acquire(ns)?;
+        let mut reservations = Self::reservations();
+        let mut sequence = Self::load(path)?;
+
+        // the reserved values are only issued while the sequence remains as this process left
+        // it; e.g. it was neither restarted nor advanced by another (lease-holding) process.
+        // NOTE: the reserved values are held in reverse; so the first is the last value reserved.
+        if let Some(reserved) = reservations.get_mut(path)
+            .filter(|reserved| reserved.first() == sequence.last_value.as_ref()) {
+            if let Some(value) = reserved.pop() {
+                return Ok(value);
+            }
+        }
+        let first = match sequence.last_value {
+            Some(last_value) => last_value.checked_add(sequence.increment),
+            None => Some(sequence.start),
+        };
+        let mut reserved = Vec::with_capacity(RESERVATION_SIZE);
+        let mut next = first;
+        while let Some(value) = next.filter(|_| reserved.len() < RESERVATION_SIZE) {
+            reserved.push(value);
+            next = value.checked_add(sequence.increment);
+        }
+        let value = match reserved.first() {
+            Some(value) => *value,
+            None => return throw(Exact(format!("Sequence {} is exhausted", ns)))
+        };
+        sequence.last_value = reserved.last().cloned();
+        sequence.save(path)?;
+        reserved.reverse();
+        reserved.pop();
+        reservations.insert(path.to_string(), reserved);
+        Ok(value)
+    }
+
+    /// Writes a new (or restarted) sequence; discarding the values reserved from its predecessor
+    fn reset(ns: &Namespace, path: &str, sequence: Self) -> std::io::Result<Self> {
+        NamespaceLease::acquire(ns)?;
+        let mut reservations = Self::reservations();
+        fs::create_dir_all(ns.get_root_path())?;
+        sequence.save(path)?;
+        reservations.remove(path);
+        Ok(sequence)
+    }
+
+    /// Returns the reserved values of all sequences; a poisoned lock is safe to reuse,
+    /// since sequences are replaced atomically (see [NamedSequence::save]) before
+    /// the values they reserve are recorded.
+    fn reservations() -> MutexGuard<'static, HashMap<String, Vec<i64>>> {
+        RESERVATIONS.get_or_init(|| Mutex::new(HashMap::new()))
+            .lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn load(path: &str) -> std::io::Result<Self> {
+        let json_string = fs::read_to_string(path)?;
+        serde_json::from_str::<Self>(&json_string).map_err(|e| cnv_error!(e))
+    }
+
+    /// Writes the sequence to a temporary file, then moves it into place;
+    /// so a crash never leaves a partially written sequence behind.
+    fn save(&self, path: &str) -> std::io::Result<()> {
+        let temp_path = format!("{}.tmp", path);
+        let mut file = File::create(&temp_path)?;
+        file.write_all(serde_json::to_string(self)?.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+    use crate::numbers::Numbers::I64Value;
+    use crate::typed_values::TypedValue::Number;
+    use std::os::unix::io::AsRawFd;
+    use std::thread;
+
+    #[test]
+    fn test_next_value() {
+        let ns = Namespace::parse("sequences.named.order_ids").unwrap();
+        let sequence = NamedSequence::create(&ns, 1000, 10).unwrap();
+        assert_eq!(sequence, NamedSequence::new(1000, 10));
+        assert_eq!(NamedSequence::next_value(&ns).unwrap(), 1000);
+        assert_eq!(NamedSequence::next_value(&ns).unwrap(), 1010);
+
+        // a block of values is reserved at once; so a restarted process resumes after the block
+        let path = NamedSequence::get_sequence_path(&ns);
+        assert_eq!(NamedSequence::load(&path).unwrap().last_value, Some(1000 + 10 * (RESERVATION_SIZE as i64 - 1)));
+        NamedSequence::reservations().remove(&path);
+        assert_eq!(NamedSequence::next_value(&ns).unwrap(), 1000 + 10 * RESERVATION_SIZE as i64);
+        assert!(NamedSequence::create(&ns, 1, 0).is_err());
+        assert!(NamedSequence::next_value(&Namespace::parse("sequences.named.missing").unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_concurrent_next_value() {
+        let ns = Namespace::parse("sequences.concurrent.order_ids").unwrap();
+        NamedSequence::create(&ns, 1, 1).unwrap();
+        let handles = (0..4).map(|_| {
+            let ns = ns.to_owned();
+            thread::spawn(move || (0..25).map(|_| NamedSequence::next_value(&ns).unwrap()).collect::<Vec<_>>())
+        }).collect::<Vec<_>>();
+        let mut values = handles.into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect::<Vec<_>>();
+        values.sort();
+        assert_eq!(values, (1..=100).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_next_value_requires_lease() {
+        let ns = Namespace::parse("sequences.leased.order_ids").unwrap();
+        NamedSequence::create(&ns, 1, 1).unwrap();
+        NamespaceLease::release(&ns);
+
+        // while another process holds the lease, the sequence cannot be advanced
+        let other = File::options().read(true).write(true).open(NamespaceLease::get_lock_path(&ns)).unwrap();
+        assert_eq!(unsafe { libc::flock(other.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) }, 0);
+        assert!(NamedSequence::next_value(&ns).is_err());
+        drop(other);
+        assert_eq!(NamedSequence::next_value(&ns).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_create_sequence_and_nextval() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create sequence ns("sequences.interpreter.trade_ids") start with 100 increment by 5
+        "#).unwrap();
+        let result = interpreter.evaluate(r#"
+            nextval(ns("sequences.interpreter.trade_ids"))
+        "#).unwrap();
+        assert_eq!(result, Number(I64Value(100)));
+        let result = interpreter.evaluate(r#"
+            nextval(ns("sequences.interpreter.trade_ids"))
+        "#).unwrap();
+        assert_eq!(result, Number(I64Value(105)));
+    }
+
+    #[test]
+    fn test_identity_column() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("sequences.identity.stocks") (
+                id: i64 identity,
+                symbol: String(8),
+                last_sale: f64
+            )
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ symbol: "ABC", last_sale: 11.77 }, { symbol: "UNO", last_sale: 0.2456 }]
+                ~> ns("sequences.identity.stocks")
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            delete from ns("sequences.identity.stocks") where symbol == "ABC"
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ symbol: "BIZ", last_sale: 23.66 }] ~> ns("sequences.identity.stocks")
+        "#).unwrap();
+
+        // identities are never reused; not even after deletes and compaction
+        let mut df = interpreter.evaluate(r#"
+            ns("sequences.identity.stocks")
+        "#).unwrap().to_table().unwrap();
+        df.compact();
+        let mut ids = df.read_active_rows().unwrap().iter()
+            .map(|row| row[0].to_owned())
+            .collect::<Vec<_>>();
+        ids.sort();
+        assert_eq!(ids, vec![Number(I64Value(2)), Number(I64Value(3))]);
+    }
+
+    #[test]
+    fn test_identity_column_renamed() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            drop table ns("sequences.identity_renamed.stocks")
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            create table ns("sequences.identity_renamed.stocks") (
+                id: i64 identity,
+                symbol: String(8)
+            )
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ symbol: "ABC" }] ~> ns("sequences.identity_renamed.stocks")
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            alter table ns("sequences.identity_renamed.stocks") rename column id to stock_id
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ symbol: "UNO" }] ~> ns("sequences.identity_renamed.stocks")
+        "#).unwrap();
+
+        // the sequence moves with the column; so no identity is issued twice
+        let ns = Namespace::parse("sequences.identity_renamed.stocks").unwrap();
+        assert!(!Path::new(&NamedSequence::get_identity_path(&ns, "id")).exists());
+        let df = interpreter.evaluate(r#"
+            ns("sequences.identity_renamed.stocks")
+        "#).unwrap().to_table().unwrap();
+        let ids = df.read_active_rows().unwrap().iter()
+            .map(|row| row[0].to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![Number(I64Value(1)), Number(I64Value(2))]);
+
+        // a missing sequence is an error rather than restarted
+        fs::remove_file(NamedSequence::get_identity_path(&ns, "stock_id")).unwrap();
+        assert!(NamedSequence::next_identity(&ns, "stock_id").is_err());
+    }
+}
@@ -0,0 +1,223 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// NamespaceLease class
+////////////////////////////////////////////////////////////////////
+
+use crate::errors::throw;
+use crate::errors::Errors::NamespaceLocked;
+use crate::machine::Machine;
+use crate::namespaces::Namespace;
+use std::collections::HashMap;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::os::unix::io::AsRawFd;
+use std::process;
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+/// the lock files of the namespaces leased by this process; keyed by path
+static LEASES: OnceLock<Mutex<HashMap<String, File>>> = OnceLock::new();
+
+/// Represents the single-writer lease of a namespace. The first process to write to
+/// (or change the schema of) a namespace acquires an exclusive advisory lock on the
+/// namespace's lock file, and holds it until the process exits or the table is dropped;
+/// writes by any other process are rejected with [NamespaceLocked]. The lock files reside
+/// outside of the namespaces (see [NamespaceLease::get_lock_path]); so that replacing, moving
+/// or deleting the directory of a namespace (e.g. a restore, rename or drop) doesn't discard
+/// a held lock, and hand the namespace to another process while the operation is under way.
+/// NOTE: reads are never blocked, although they may not reflect the (cached) writes of the lease holder.
+pub struct NamespaceLease;
+
+impl NamespaceLease {
+    ////////////////////////////////////////////////////////////////
+    // static methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Acquires the lease of a namespace (if not already held by this process)
+    pub fn acquire(ns: &Namespace) -> std::io::Result<()> {
+        match Self::try_acquire(ns)? {
+            true => Ok(()),
+            false => throw(NamespaceLocked(ns.get_full_name(), Self::get_holder(ns)))
+        }
+    }
+
+    /// Returns the namespace owning the given file (i.e. `$OXIDE_HOME/ns/database/schema/name/...`);
+    /// files outside of `$OXIDE_HOME/ns` (e.g. scratch tables) belong to no namespace, and aren't leased.
+    pub fn find_namespace(path: &str) -> Option<Namespace> {
+        let root = format!("{}/ns/", Machine::oxide_home());
+        let parts = path.strip_prefix(root.as_str())?.split('/').collect::<Vec<_>>();
+        match parts.as_slice() {
+            [database, schema, name, _, ..] => Some(Namespace::new(*database, *schema, *name)),
+            _ => None
+        }
+    }
+
+    /// Returns the process ID recorded by the holder of the lease of a namespace
+    pub fn get_holder(ns: &Namespace) -> String {
+        fs::read_to_string(Self::get_lock_path(ns)).ok()
+            .map(|pid| pid.trim().to_string())
+            .filter(|pid| !pid.is_empty())
+            .unwrap_or_else(|| "unknown".into())
+    }
+
+    /// Returns the path of the lock file of a namespace
+    /// (i.e. `$OXIDE_HOME/locks/database.schema.name.lock`)
+    pub fn get_lock_path(ns: &Namespace) -> String {
+        format!("{}/{}.lock", Self::get_locks_path(), ns.get_full_name())
+    }
+
+    /// Returns the directory containing the lock files of all namespaces
+    fn get_locks_path() -> String {
+        format!("{}/locks", Machine::oxide_home())
+    }
+
+    /// Indicates whether this process holds the lease of a namespace
+    pub fn is_held(ns: &Namespace) -> bool {
+        Self::leases().contains_key(&Self::get_lock_path(ns))
+    }
+
+    /// Releases the lease of a namespace (e.g. once its table has been dropped)
+    pub fn release(ns: &Namespace) {
+        Self::leases().remove(&Self::get_lock_path(ns));
+    }
+
+    /// Attempts to acquire the lease of a namespace; returning false
+    /// if the lease is held by another process.
+    pub fn try_acquire(ns: &Namespace) -> std::io::Result<bool> {
+        let path = Self::get_lock_path(ns);
+        let mut leases = Self::leases();
+        if leases.contains_key(&path) {
+            return Ok(true);
+        }
+        fs::create_dir_all(Self::get_locks_path())?;
+        // the file isn't truncated until locked; since it records the process ID of the current holder
+        let mut file = OpenOptions::new().create(true).truncate(false).read(true).write(true).open(&path)?;
+        if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) } != 0 {
+            let err = std::io::Error::last_os_error();
+            return if err.kind() == ErrorKind::WouldBlock { Ok(false) } else { Err(err) };
+        }
+
+        // record the holder of the lease
+        file.set_len(0)?;
+        file.write_all(process::id().to_string().as_bytes())?;
+        leases.insert(path, file);
+        Ok(true)
+    }
+
+    /// Returns the leases held by this process; a poisoned registry is safe to reuse,
+    /// since it is only ever modified by single (infallible) insertions and removals.
+    fn leases() -> MutexGuard<'static, HashMap<String, File>> {
+        LEASES.get_or_init(|| Mutex::new(HashMap::new()))
+            .lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+
+    /// Locks the lock file of a namespace via a separate file description;
+    /// which is how the lease of another process appears to this one.
+    fn lock_elsewhere(ns: &Namespace) -> File {
+        fs::create_dir_all(NamespaceLease::get_locks_path()).unwrap();
+        let mut file = OpenOptions::new().create(true).truncate(false).read(true).write(true)
+            .open(NamespaceLease::get_lock_path(ns)).unwrap();
+        assert_eq!(unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) }, 0);
+        file.set_len(0).unwrap();
+        file.write_all(b"12345").unwrap();
+        file
+    }
+
+    #[test]
+    fn test_find_namespace() {
+        let ns = Namespace::parse("leases.find.stocks").unwrap();
+        assert_eq!(NamespaceLease::find_namespace(ns.get_table_file_path().as_str()), Some(ns.to_owned()));
+        assert_eq!(NamespaceLease::find_namespace(
+            format!("{}partitions/p_NYSE.table", ns.get_root_path()).as_str()), Some(ns));
+        assert_eq!(NamespaceLease::find_namespace("oxide_db/stocks.table"), None);
+    }
+
+    #[test]
+    fn test_acquire_and_release() {
+        let ns = Namespace::parse("leases.acquire.stocks").unwrap();
+        NamespaceLease::release(&ns);
+        let other = lock_elsewhere(&ns);
+        assert!(!NamespaceLease::try_acquire(&ns).unwrap());
+        assert_eq!(
+            NamespaceLease::acquire(&ns).map_err(|e| e.to_string()),
+            Err("leases.acquire.stocks is locked by another process (pid 12345)".to_string()));
+
+        // once the other process lets go, the lease can be acquired
+        drop(other);
+        NamespaceLease::acquire(&ns).unwrap();
+        assert_eq!(NamespaceLease::get_holder(&ns), process::id().to_string());
+        let mut file = OpenOptions::new().read(true).write(true)
+            .open(NamespaceLease::get_lock_path(&ns)).unwrap();
+        assert_ne!(unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) }, 0);
+        file.flush().unwrap();
+
+        // once released, another process can take over
+        NamespaceLease::release(&ns);
+        drop(lock_elsewhere(&ns));
+    }
+
+    #[test]
+    fn test_lease_survives_replacement_of_namespace() {
+        let ns = Namespace::parse("leases.replaced.stocks").unwrap();
+        NamespaceLease::release(&ns);
+        NamespaceLease::acquire(&ns).unwrap();
+        assert!(!NamespaceLease::get_lock_path(&ns).starts_with(&ns.get_root_path()));
+
+        // the directory of the namespace is replaced (e.g. by a restore); the lease still holds
+        fs::create_dir_all(ns.get_root_path()).unwrap();
+        fs::remove_dir_all(ns.get_root_path()).unwrap();
+        let file = OpenOptions::new().create(true).truncate(false).read(true).write(true)
+            .open(NamespaceLease::get_lock_path(&ns)).unwrap();
+        assert_ne!(unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) }, 0);
+        NamespaceLease::release(&ns);
+    }
+
+    #[test]
+    fn test_writes_are_rejected_while_leased_elsewhere() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("leases.writes.stocks") (symbol: String(8), exchange: String(8), last_sale: f64)
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 }] ~> ns("leases.writes.stocks")
+        "#).unwrap();
+
+        // another process takes over the lease
+        let ns = Namespace::parse("leases.writes.stocks").unwrap();
+        NamespaceLease::release(&ns);
+        let other = lock_elsewhere(&ns);
+        let result = interpreter.evaluate(r#"
+            [{ symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }] ~> ns("leases.writes.stocks")
+        "#);
+        assert_eq!(result.map_err(|e| e.to_string()),
+                   Err("leases.writes.stocks is locked by another process (pid 12345)".to_string()));
+        let result = interpreter.evaluate(r#"
+            alter table ns("leases.writes.stocks") drop column exchange
+        "#);
+        assert!(result.is_err());
+        let result = interpreter.evaluate(r#"
+            rename table ns("leases.writes.stocks") to ns("leases.writes.stocks_2025")
+        "#);
+        assert_eq!(result.map_err(|e| e.to_string()),
+                   Err("leases.writes.stocks is locked by another process (pid 12345)".to_string()));
+
+        // reads are never blocked
+        let df = interpreter.evaluate(r#"ns("leases.writes.stocks")"#).unwrap().to_table().unwrap();
+        assert_eq!(df.get_rows().len(), 1);
+
+        // once the other process lets go, writes resume
+        drop(other);
+        interpreter.evaluate(r#"
+            [{ symbol: "UNO", exchange: "OTC", last_sale: 0.2456 }] ~> ns("leases.writes.stocks")
+        "#).unwrap();
+        let df = interpreter.evaluate(r#"ns("leases.writes.stocks")"#).unwrap().to_table().unwrap();
+        assert_eq!(df.get_rows().len(), 2);
+    }
+}
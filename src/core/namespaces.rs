@@ -10,6 +10,10 @@ use serde::{Deserialize, Serialize};
 use crate::machine::Machine;
 use shared_lib::fail;
 
+/// the (reserved) database of the temporary tables of sessions; which resides outside of the
+/// public namespaces (i.e. within `$OXIDE_HOME/temp` rather than `$OXIDE_HOME/ns`)
+pub const TEMP_DATABASE: &str = "$temp";
+
 // Namespace is a logical representation of a Lollypop object namespace or path
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
 pub struct Namespace {
@@ -40,6 +44,14 @@ impl Namespace {
         format!("{}.{}.{}", self.database, self.schema, self.name)
     }
 
+    /// Returns the directory of a database (ex: "$OXIDE_HOME/ns/database")
+    pub fn get_database_path(database: &str) -> String {
+        match database {
+            TEMP_DATABASE => format!("{}/temp", Machine::oxide_home()),
+            _ => format!("{}/ns/{database}", Machine::oxide_home())
+        }
+    }
+
     pub fn get_blob_file_path(&self) -> String {
         self.get_file_path("blob")
     }
@@ -72,9 +84,7 @@ impl Namespace {
     pub fn get_root_path(&self) -> String {
         // ex:  "$OXIDE_HOME/ns/database/schema/name/"
         let mut builder = String::new();
-        builder.push_str(&*Machine::oxide_home());
-        builder.push_str("/ns/");
-        builder.push_str(&self.database);
+        builder.push_str(&*Self::get_database_path(&self.database));
         builder.push('/');
         builder.push_str(&*self.schema);
         builder.push('/');
@@ -153,6 +163,13 @@ mod tests {
         assert_eq!(ns.get_root_path(), format!("{}/ns/securities/nasdaq/stocks/", oxide_home))
     }
 
+    #[test]
+    fn test_get_root_path_of_temp_table() {
+        let oxide_home = Machine::oxide_home();
+        let ns = Namespace::new(TEMP_DATABASE, "p4123_t9_s1", "stocks");
+        assert_eq!(ns.get_root_path(), format!("{}/temp/p4123_t9_s1/stocks/", oxide_home))
+    }
+
     #[test]
     fn test_get_table_file_path() {
         let oxide_home = Machine::oxide_home();
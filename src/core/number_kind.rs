@@ -51,7 +51,31 @@ impl NumberKind {
     }
 
     /// decodes the typed value based on the supplied data type and buffer
-    pub fn decode(&self, buffer: &Vec<u8>, offset: usize) -> Numbers {
+    /// Converts the given number into a number of this kind (e.g. i64 -> f32)
+    pub fn convert(&self, number: &Numbers) -> Numbers {
+        match self {
+            NumberKind::AckKind => Ack,
+            NumberKind::RowIdKind => RowId(number.to_u64()),
+            NumberKind::RowsAffectedKind => RowsAffected(number.to_i64()),
+            NumberKind::DateKind => DateValue(number.to_i64()),
+            NumberKind::F32Kind => F32Value(number.to_f32()),
+            NumberKind::F64Kind => F64Value(number.to_f64()),
+            NumberKind::I8Kind => I8Value(number.to_i8()),
+            NumberKind::I16Kind => I16Value(number.to_i16()),
+            NumberKind::I32Kind => I32Value(number.to_i32()),
+            NumberKind::I64Kind => I64Value(number.to_i64()),
+            NumberKind::I128Kind => I128Value(number.to_i128()),
+            NumberKind::U8Kind => U8Value(number.to_u8()),
+            NumberKind::U16Kind => U16Value(number.to_u16()),
+            NumberKind::U32Kind => U32Value(number.to_u32()),
+            NumberKind::U64Kind => U64Value(number.to_u64()),
+            NumberKind::U128Kind => U128Value(number.to_u128()),
+            NumberKind::UUIDKind => UUIDValue(number.to_u128()),
+            NumberKind::NaNKind => NaNValue
+        }
+    }
+
+    pub fn decode(&self, buffer: &[u8], offset: usize) -> Numbers {
         match self {
             NumberKind::AckKind => Ack,
             NumberKind::RowIdKind => ByteCodeCompiler::decode_u8x8(buffer, offset, |b| RowId(u64::from_be_bytes(b))),
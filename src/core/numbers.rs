@@ -78,7 +78,7 @@ impl Numbers {
     ////////////////////////////////////////////////////////////////////
 
     /// decodes the typed value based on the supplied data type and buffer
-    pub fn decode(buffer: &Vec<u8>, offset: usize, kind: NumberKind) -> Numbers {
+    pub fn decode(buffer: &[u8], offset: usize, kind: NumberKind) -> Numbers {
         kind.decode(buffer, offset)
     }
 
@@ -7,7 +7,10 @@ use std::fs;
 
 use serde::{Deserialize, Serialize};
 
+use crate::change_capture::ChangeKind;
 use crate::cnv_error;
+use crate::constraints::Constraint;
+use crate::expression::TableOptions;
 use crate::namespaces::Namespace;
 use crate::object_config::ObjectConfig::TableConfig;
 use crate::parameter::Parameter;
@@ -19,6 +22,14 @@ pub enum ObjectConfig {
         columns: Vec<Parameter>,
         indices: Vec<HashIndexConfig>,
         partitions: Vec<String>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        options: Vec<TableOptions>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        constraints: Vec<Constraint>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        dependents: Vec<Namespace>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        triggers: Vec<TriggerConfig>,
     },
 }
 
@@ -29,6 +40,10 @@ impl ObjectConfig {
             columns,
             indices: vec![],
             partitions: vec![],
+            options: vec![],
+            constraints: vec![],
+            dependents: vec![],
+            triggers: vec![],
         }
     }
 
@@ -44,6 +59,18 @@ impl ObjectConfig {
         }
     }
 
+    pub fn get_constraints(&self) -> Vec<Constraint> {
+        match self {
+            ObjectConfig::TableConfig { constraints, .. } => constraints.clone(),
+        }
+    }
+
+    pub fn get_dependents(&self) -> Vec<Namespace> {
+        match self {
+            ObjectConfig::TableConfig { dependents, .. } => dependents.clone(),
+        }
+    }
+
     pub fn get_indices(&self) -> Vec<HashIndexConfig> {
         match self {
             ObjectConfig::TableConfig { indices, .. } => indices.clone(),
@@ -51,6 +78,12 @@ impl ObjectConfig {
         }
     }
 
+    pub fn get_options(&self) -> Vec<TableOptions> {
+        match self {
+            ObjectConfig::TableConfig { options, .. } => options.clone(),
+        }
+    }
+
     pub fn get_partitions(&self) -> Option<&Vec<String>> {
         match self {
             ObjectConfig::TableConfig { partitions, .. } => Some(partitions),
@@ -58,6 +91,12 @@ impl ObjectConfig {
         }
     }
 
+    pub fn get_triggers(&self) -> Vec<TriggerConfig> {
+        match self {
+            ObjectConfig::TableConfig { triggers, .. } => triggers.clone(),
+        }
+    }
+
     /// Loads a dataframe configuration from disk.
     pub fn load(ns: &Namespace) -> std::io::Result<Self> {
         let config_string = fs::read_to_string(ns.get_config_file_path())?;
@@ -71,13 +110,81 @@ impl ObjectConfig {
         fs::write(&ns.get_config_file_path(), json_string)
     }
 
+    pub fn with_columns(self, columns: Vec<Parameter>) -> Self {
+        match self {
+            ObjectConfig::TableConfig { indices, partitions, options, triggers, constraints, dependents, .. } => {
+                TableConfig {
+                    columns,
+                    indices,
+                    partitions,
+                    options,
+                    constraints,
+                    dependents,
+                    triggers,
+                }
+            }
+        }
+    }
+
+    pub fn with_constraints(self, constraints: Vec<Constraint>) -> Self {
+        match self {
+            ObjectConfig::TableConfig { columns, indices, partitions, options, dependents, triggers, .. } => {
+                TableConfig {
+                    columns,
+                    indices,
+                    partitions,
+                    options,
+                    constraints,
+                    dependents,
+                    triggers,
+                }
+            }
+        }
+    }
+
+    pub fn with_dependents(self, dependents: Vec<Namespace>) -> Self {
+        match self {
+            ObjectConfig::TableConfig { columns, indices, partitions, options, constraints, triggers, .. } => {
+                TableConfig {
+                    columns,
+                    indices,
+                    partitions,
+                    options,
+                    constraints,
+                    dependents,
+                    triggers,
+                }
+            }
+        }
+    }
+
     pub fn with_indices(self, indices: Vec<HashIndexConfig>) -> Self {
         match self {
-            ObjectConfig::TableConfig { columns, partitions, .. } => {
+            ObjectConfig::TableConfig { columns, partitions, options, triggers, constraints, dependents, .. } => {
                 TableConfig {
                     columns,
                     indices,
                     partitions,
+                    options,
+                    constraints,
+                    dependents,
+                    triggers,
+                }
+            }
+        }
+    }
+
+    pub fn with_options(self, options: Vec<TableOptions>) -> Self {
+        match self {
+            ObjectConfig::TableConfig { columns, indices, partitions, triggers, constraints, dependents, .. } => {
+                TableConfig {
+                    columns,
+                    indices,
+                    partitions,
+                    options,
+                    constraints,
+                    dependents,
+                    triggers,
                 }
             }
         }
@@ -85,11 +192,31 @@ impl ObjectConfig {
 
     pub fn with_partitions(self, partitions: Vec<String>) -> Self {
         match self {
-            ObjectConfig::TableConfig { columns, indices, .. } => {
+            ObjectConfig::TableConfig { columns, indices, options, triggers, constraints, dependents, .. } => {
                 TableConfig {
                     columns,
                     indices,
                     partitions,
+                    options,
+                    constraints,
+                    dependents,
+                    triggers,
+                }
+            }
+        }
+    }
+
+    pub fn with_triggers(self, triggers: Vec<TriggerConfig>) -> Self {
+        match self {
+            ObjectConfig::TableConfig { columns, indices, partitions, options, constraints, dependents, .. } => {
+                TableConfig {
+                    columns,
+                    indices,
+                    partitions,
+                    options,
+                    constraints,
+                    dependents,
+                    triggers,
                 }
             }
         }
@@ -107,6 +234,44 @@ impl HashIndexConfig {
     pub fn new(indexed_column_names: Vec<String>, is_unique: bool) -> Self {
         HashIndexConfig { indexed_column_names, is_unique }
     }
+
+    pub fn get_indexed_column_names(&self) -> &Vec<String> { &self.indexed_column_names }
+
+    pub fn is_unique(&self) -> bool { self.is_unique }
+
+    /// Returns a copy of this configuration with the given column renamed
+    pub fn with_renamed_column(self, old_name: &str, new_name: &str) -> Self {
+        HashIndexConfig {
+            indexed_column_names: self.indexed_column_names.into_iter()
+                .map(|name| if name == old_name { new_name.to_string() } else { name })
+                .collect(),
+            ..self
+        }
+    }
+}
+
+/// Represents the configuration of a trigger; i.e. a function invoked
+/// with the old and new states of each row changed in the given ways.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct TriggerConfig {
+    name: String,
+    kinds: Vec<ChangeKind>,
+    code: String,
+}
+
+impl TriggerConfig {
+    /// Creates a new trigger configuration
+    pub fn new(name: &str, kinds: Vec<ChangeKind>, code: &str) -> Self {
+        TriggerConfig { name: name.to_string(), kinds, code: code.to_string() }
+    }
+
+    /// Returns the source code of the trigger function
+    pub fn get_code(&self) -> &str { self.code.as_str() }
+
+    /// Returns the kinds of changes observed by the trigger
+    pub fn get_kinds(&self) -> &Vec<ChangeKind> { &self.kinds }
+
+    pub fn get_name(&self) -> &str { self.name.as_str() }
 }
 
 /// Unit tests
@@ -141,7 +306,19 @@ mod tests {
             ],
             indices: Vec::new(),
             partitions: Vec::new(),
+            options: Vec::new(),
+            constraints: Vec::new(),
+            dependents: Vec::new(),
+            triggers: Vec::new(),
         });
         Ok(())
     }
+
+    #[test]
+    fn test_index_rename_column() {
+        let index = HashIndexConfig::new(vec!["symbol".into(), "exchange".into()], true)
+            .with_renamed_column("exchange", "market");
+        assert_eq!(index.get_indexed_column_names(), &vec!["symbol".to_string(), "market".to_string()]);
+        assert_eq!(index, HashIndexConfig::new(vec!["symbol".into(), "market".into()], true));
+    }
 }
\ No newline at end of file
@@ -4,19 +4,24 @@
 ////////////////////////////////////////////////////////////////////
 
 use crate::byte_code_compiler::ByteCodeCompiler;
+use crate::change_capture::ChangeFeedServer;
 use crate::columns::Column;
 use crate::compiler::Compiler;
 use crate::dataframe_actor::DataframeActor;
 use crate::errors::throw;
 use crate::errors::Errors::Exact;
+use crate::expiry::start_expiry_sweep;
 use crate::expression::Expression;
 use crate::expression::Expression::Literal;
 use crate::interpreter::Interpreter;
 use crate::namespaces::Namespace;
 use crate::object_config::ObjectConfig;
+use crate::page_cache::PageCache;
+use crate::replication::ReplicationServer;
 use crate::row_metadata::RowMetadata;
 use crate::server::SystemInfoJs;
 use crate::structures::Row;
+use crate::temp_tables::TempTables;
 use crate::typed_values::TypedValue;
 use crate::typed_values::TypedValue::{ErrorValue, StringValue, Undefined};
 use crate::websockets::OxideWebSocketServer;
@@ -37,7 +42,6 @@ use std::io::{stdout, Write};
 use std::thread;
 use std::thread::JoinHandle;
 use tokio::net::TcpStream;
-use tokio::runtime::Runtime;
 use tokio_tungstenite::tungstenite::handshake::client::Response;
 use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
@@ -49,6 +53,9 @@ macro_rules! web_routes {
         actix_web::App::new()
             .app_data(web::Data::new($shared_state))
             .service(web::resource("/ws").to(handle_websockets))
+            .service(web::resource("/replication").to(handle_replication))
+            .service(web::resource("/changes").to(handle_change_feed))
+            .route("/{database}/{schema}/{name}/batch", web::post().to(handle_rows_post))
             .route("/{database}/{schema}/{name}/{a}/{b}", web::get().to(handle_row_range_get))
             .route("/{database}/{schema}/{name}/{id}", web::delete().to(handle_row_delete))
             .route("/{database}/{schema}/{name}/{id}", web::get().to(handle_row_get))
@@ -61,6 +68,7 @@ macro_rules! web_routes {
             .route("/{database}/{schema}/{name}", web::post().to(handle_config_post))
             .route("/", web::get().to(handle_index))
             .route("/info", web::get().to(handle_sys_info_get))
+            .route("/info/page_cache", web::get().to(handle_page_cache_get))
             .route("/rpc", web::post().to(handle_rpc_post))
     }
 }
@@ -216,6 +224,22 @@ pub async fn handle_row_post(
     }
 }
 
+/// handler function for appending a batch of rows (via the bulk load path) by namespace (database, schema, name)
+// ex: http://localhost:8080/dataframes/create/quotes/batch
+pub async fn handle_rows_post(
+    req: HttpRequest,
+    data: web::Json<Vec<Value>>,
+    path: web::Path<(String, String, String)>,
+) -> impl Responder {
+    match append_rows(req, data, path).await {
+        Ok(outcome) => HttpResponse::Ok().json(outcome),
+        Err(err) => {
+            error!("error {}", err.to_string());
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
 /// handler function for replacing an existing row by namespace (database, schema, name) and offset
 // ex: http://localhost:8080/dataframes/create/quotes
 pub async fn handle_row_put(
@@ -274,12 +298,15 @@ pub async fn handle_rpc_post(
         Err(err) =>
             return HttpResponse::Ok().json(RemoteCallResponse::fail(err.to_string())),
     };
-    match interpreter.evaluate(data.0.get_code()) {
+    let response = match interpreter.evaluate(data.0.get_code()) {
         Ok(result) =>
             HttpResponse::Ok().json(RemoteCallResponse::success(result.to_json())),
         Err(err) =>
             HttpResponse::Ok().json(RemoteCallResponse::fail(err.to_string())),
-    }
+    };
+    // the session of the call ends with it; so its temporary tables are dropped
+    TempTables::release(interpreter.get_machine());
+    response
 }
 
 /// handler function for the system information route
@@ -288,6 +315,31 @@ pub async fn handle_sys_info_get(_session: Session) -> impl Responder {
     HttpResponse::Ok().json(SystemInfoJs::new())
 }
 
+/// handler function for the usage statistics of the shared page cache
+pub async fn handle_page_cache_get(_session: Session) -> impl Responder {
+    HttpResponse::Ok().json(PageCache::shared().get_stats())
+}
+
+/// handler function for the change feed channel; used by consumers
+pub async fn handle_change_feed(
+    req: HttpRequest, stream: web::Payload,
+) -> impl Responder {
+    info!("received change feed ws <- {}", req.peer_addr().unwrap());
+    ws::start(ChangeFeedServer::new(), &req, stream)
+}
+
+/// handler function for the replication channel; used by (authenticated) followers
+pub async fn handle_replication(
+    req: HttpRequest, stream: web::Payload,
+) -> Result<HttpResponse, actix_web::Error> {
+    info!("received replication ws <- {}", req.peer_addr().unwrap());
+    if !ReplicationServer::is_authorized(&req) {
+        error!("refused unauthorized replication ws <- {}", req.peer_addr().unwrap());
+        return Ok(HttpResponse::Unauthorized().finish());
+    }
+    ws::start(ReplicationServer::new(), &req, stream)
+}
+
 pub async fn handle_websockets(
     req: HttpRequest, stream: web::Payload,
 ) -> impl Responder {
@@ -296,15 +348,29 @@ pub async fn handle_websockets(
 }
 
 pub fn start_http_server(port: u16) -> JoinHandle<()> {
+    start_server(port, false)
+}
+
+/// Starts the HTTP server along with the background sweep of expired rows (see [start_expiry_sweep])
+pub fn start_http_server_with_expiry(port: u16) -> JoinHandle<()> {
+    start_server(port, true)
+}
+
+fn start_server(port: u16, is_sweeping: bool) -> JoinHandle<()> {
     thread::spawn(move || {
-        let server = actix_web::HttpServer::new(move || web_routes!(SharedState::new()))
-            .bind(format!("{}:{}", "0.0.0.0", port))
-            .expect(format!("Can't bind to port {port}").as_str())
-            .run();
-        Runtime::new()
-            .expect("Failed to create a Runtime instance")
-            .block_on(server)
-            .expect(format!("Failed while blocking on port {port}").as_str());
+        actix::System::new().block_on(async move {
+            // the workers share a single actor; so the writes to each table are serialized
+            let state = SharedState::new();
+            if is_sweeping {
+                start_expiry_sweep(state.actor.to_owned());
+            }
+            actix_web::HttpServer::new(move || web_routes!(state.to_owned()))
+                .bind(format!("{}:{}", "0.0.0.0", port))
+                .expect(format!("Can't bind to port {port}").as_str())
+                .run()
+                .await
+                .expect(format!("Failed while blocking on port {port}").as_str());
+        })
     })
 }
 
@@ -319,6 +385,18 @@ async fn append_row(
     append_row!(actor, ns, Row::from_json(&columns, &data.0))
 }
 
+async fn append_rows(
+    req: HttpRequest,
+    data: web::Json<Vec<Value>>,
+    path: web::Path<(String, String, String)>,
+) -> std::io::Result<usize> {
+    let ns = Namespace::new(&path.0, &path.1, &path.2);
+    let actor = get_shared_state(&req)?.actor.to_owned();
+    let columns = get_columns!(actor, ns)?;
+    let rows = data.0.iter().map(|value| Row::from_json(&columns, value)).collect();
+    append_rows!(actor, ns, rows)
+}
+
 async fn delete_row_by_id(
     req: HttpRequest,
     path: web::Path<(String, String, String, usize)>,
@@ -388,7 +466,7 @@ async fn update_row_by_id(
 }
 
 /// Represents all the shared state of the application
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct SharedState {
     actor: Addr<DataframeActor>,
 }
@@ -415,10 +493,12 @@ mod tests {
     use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
 
     use super::*;
+    use crate::interpreter::Interpreter;
+    use crate::page_cache::{PageCacheStats, PAGE_CACHE_CAPACITY};
     use crate::testdata::make_quote_parameters;
     use crate::typed_values::TypedValue;
     use crate::typed_values::TypedValue::StringValue;
-    use shared_lib::{ns_uri, range_uri, row_uri};
+    use shared_lib::{batch_uri, ns_uri, range_uri, row_uri};
 
     #[actix::test]
     async fn test_dataframe_config_lifecycle() {
@@ -464,6 +544,20 @@ mod tests {
         assert_eq!(body, "{\"result\":36,\"message\":null}");
     }
 
+    #[actix::test]
+    async fn test_handle_rpc_drops_temp_tables() {
+        let mut app = test::init_service(web_routes!(SharedState::new())).await;
+        let req = test::TestRequest::post().uri("/rpc")
+            .set_json(&json!({"code": "create temp table scratch (symbol: String(8))\nscratch"}))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        let json_value: Value = serde_json::from_str(body.as_str()).unwrap();
+        let ns = Namespace::parse(json_value["result"].as_str().unwrap()).unwrap();
+        assert!(!std::path::Path::new(&ns.get_root_path()).exists());
+    }
+
     #[actix::test]
     async fn test_dataframe_lifecycle() {
         // set up the sessions
@@ -545,6 +639,105 @@ mod tests {
         assert_eq!(body, "{}");
     }
 
+    #[actix::test]
+    async fn test_append_batch() {
+        // set up the sessions
+        let mut app = test::init_service(web_routes!(SharedState::new())).await;
+        let (database, schema, name) = ("web", "dataframe", "batch");
+        let config = ObjectConfig::build_table(make_quote_parameters());
+
+        // POST to create a new table
+        let req = test::TestRequest::post()
+            .uri(&ns_uri(database, schema, name))
+            .set_json(&json!(config)).to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+
+        // POST a batch of stock quotes
+        let req = test::TestRequest::post().uri(&batch_uri(database, schema, name))
+            .set_json(&json!([
+                {"symbol":"ATOM","exchange":"NYSE","last_sale":24.17},
+                {"symbol":"BABY","exchange":"NYSE","last_sale":13.66},
+                {"symbol":"CRY","exchange":"AMEX","last_sale":88.11}
+            ]))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert_eq!(body, "3");
+
+        // GET all stock quotes within a range
+        let req = test::TestRequest::get().uri(&range_uri(database, schema, name, 0, 3)).to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        let json_value: Value = serde_json::from_str(body.as_str()).unwrap();
+        assert_eq!(json_value, json!([
+            {"symbol":"ATOM","exchange":"NYSE","last_sale":24.17},
+            {"symbol":"BABY","exchange":"NYSE","last_sale":13.66},
+            {"symbol":"CRY","exchange":"AMEX","last_sale":88.11}
+        ]));
+    }
+
+    #[actix::test]
+    async fn test_append_batch_verifies_constraints() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create table ns("web.batch_fk.accounts") (code: String(8) unique, name: String(16))
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            create table ns("web.batch_fk.trades") (
+                account: String(8) references ns("web.batch_fk.accounts")(code),
+                symbol: String(8) unique
+            )
+        "#).unwrap();
+        interpreter.evaluate(r#"
+            [{ code: "A1", name: "Alice" }, { code: "B2", name: "Bob" }] ~> ns("web.batch_fk.accounts")
+        "#).unwrap();
+        let mut app = test::init_service(web_routes!(SharedState::new())).await;
+        let (database, schema, name) = ("web", "batch_fk", "trades");
+
+        // a batch referencing a missing account is rejected as a whole
+        let req = test::TestRequest::post().uri(&batch_uri(database, schema, name))
+            .set_json(&json!([
+                {"account":"A1","symbol":"ABC"},
+                {"account":"Z9","symbol":"UNO"}
+            ]))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_server_error());
+
+        // as is a batch violating a unique constraint
+        let req = test::TestRequest::post().uri(&batch_uri(database, schema, name))
+            .set_json(&json!([
+                {"account":"A1","symbol":"ABC"},
+                {"account":"B2","symbol":"ABC"}
+            ]))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_server_error());
+
+        // a valid batch is appended
+        let req = test::TestRequest::post().uri(&batch_uri(database, schema, name))
+            .set_json(&json!([
+                {"account":"A1","symbol":"ABC"},
+                {"account":"B2","symbol":"UNO"}
+            ]))
+            .to_request();
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert_eq!(body, "2");
+        let req = test::TestRequest::get().uri(&range_uri(database, schema, name, 0, 4)).to_request();
+        let resp = test::call_service(&mut app, req).await;
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        let json_value: Value = serde_json::from_str(body.as_str()).unwrap();
+        assert_eq!(json_value, json!([
+            {"account":"A1","symbol":"ABC"},
+            {"account":"B2","symbol":"UNO"}
+        ]));
+    }
+
     #[actix::test]
     async fn test_handle_system_info() {
         // set up the sessions
@@ -559,4 +752,20 @@ mod tests {
         let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
         assert_eq!(body, r#"{"title":"Oxide","version":"0.3"}"#);
     }
+
+    #[actix::test]
+    async fn test_handle_page_cache_stats() {
+        // set up the sessions
+        let mut app = test::init_service(web_routes!(SharedState::new())).await;
+
+        // send a GET request for the page cache statistics
+        let req = test::TestRequest::get().uri("/info/page_cache").to_request();
+
+        // process the request and verify the response
+        let resp = test::call_service(&mut app, req).await;
+        assert!(resp.status().is_success());
+        let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        let stats: PageCacheStats = serde_json::from_str(body.as_str()).unwrap();
+        assert_eq!(stats.capacity, PAGE_CACHE_CAPACITY);
+    }
 }
\ No newline at end of file
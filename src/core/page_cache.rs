@@ -0,0 +1,651 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// PageCache class
+////////////////////////////////////////////////////////////////////
+
+use crate::errors::throw;
+use crate::errors::Errors::Exact;
+use crate::memory_map::MemoryMap;
+use crate::namespace_leases::NamespaceLease;
+use crate::namespaces::Namespace;
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs::File;
+use std::os::unix::fs::{FileExt, MetadataExt};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+/// the size (in bytes) of a cached page
+pub const PAGE_SIZE: usize = 4096;
+
+/// the maximum number of pages held by the shared page cache (i.e. 4 MiB)
+pub const PAGE_CACHE_CAPACITY: usize = 1024;
+
+/// the environment variable used to configure the [WritePolicy] of the shared page cache
+/// ("write-back" or "write-through")
+const PAGE_CACHE_POLICY_ENV_VAR: &str = "OXIDE_PAGE_CACHE_POLICY";
+
+/// the shared page cache used by all table files
+static SHARED_PAGE_CACHE: OnceLock<PageCache> = OnceLock::new();
+
+/// Identifies a file by its device and inode; so that files sharing a path
+/// (or paths sharing a file) are cached coherently.
+pub type FileId = (u64, u64);
+
+/// Identifies a cached page: (file, page number)
+type PageKey = (FileId, u64);
+
+/// Identifies a version of a file: (length, modification time (seconds, nanoseconds))
+type FileStamp = (u64, i64, i64);
+
+/// Determines when writes through a [PageCache] reach the file
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum WritePolicy {
+    /// the affected pages are written back when they are evicted or the file is flushed
+    WriteBack,
+    /// the bytes are written to the file immediately; cached pages are updated in place
+    WriteThrough,
+}
+
+impl WritePolicy {
+    /// Returns the policy configured via the environment; writing back by default.
+    /// NOTE: under [WritePolicy::WriteBack], writes reach the file when their pages are
+    /// evicted or the file is flushed; so until then, a crash loses them, and other processes
+    /// (which read the file directly) don't observe them. [WritePolicy::WriteThrough]
+    /// trades the write performance for writes reaching the file before they return.
+    pub fn load() -> Self {
+        match env::var(PAGE_CACHE_POLICY_ENV_VAR) {
+            Ok(policy) if policy.eq_ignore_ascii_case("write-through") => WritePolicy::WriteThrough,
+            _ => WritePolicy::WriteBack
+        }
+    }
+}
+
+/// Represents a cached page of a file
+struct Page {
+    bytes: Vec<u8>,
+    file: Arc<File>,
+    is_dirty: bool,
+    tick: u64,
+}
+
+/// Represents the usage statistics of a [PageCache]
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PageCacheStats {
+    pub capacity: usize,
+    pub pages: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub write_backs: u64,
+}
+
+struct PageCacheState {
+    pages: HashMap<PageKey, Page>,
+    lru: BTreeMap<u64, PageKey>,
+    lengths: HashMap<FileId, u64>,
+    generations: HashMap<FileId, u64>,
+    stats: PageCacheStats,
+    tick: u64,
+}
+
+/// A size-bounded (LRU) cache of file pages. Pages are loaded without holding the
+/// cache's lock; a page is only cached if its file was not written in the meantime.
+pub struct PageCache {
+    policy: WritePolicy,
+    state: Mutex<PageCacheState>,
+    writer: Mutex<()>,
+}
+
+impl PageCache {
+    ////////////////////////////////////////////////////////////////
+    // static methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Creates a new page cache holding up to the given number of pages
+    pub fn new(capacity: usize, policy: WritePolicy) -> Self {
+        Self {
+            policy,
+            state: Mutex::new(PageCacheState {
+                pages: HashMap::new(),
+                lru: BTreeMap::new(),
+                lengths: HashMap::new(),
+                generations: HashMap::new(),
+                stats: PageCacheStats { capacity, ..PageCacheStats::default() },
+                tick: 0,
+            }),
+            writer: Mutex::new(()),
+        }
+    }
+
+    /// Returns the page cache shared by all table files
+    pub fn shared() -> &'static PageCache {
+        SHARED_PAGE_CACHE.get_or_init(|| PageCache::new(PAGE_CACHE_CAPACITY, WritePolicy::load()))
+    }
+
+    ////////////////////////////////////////////////////////////////
+    // instance methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Returns the identifier of the given file
+    pub fn get_file_id(file: &File) -> std::io::Result<FileId> {
+        let metadata = file.metadata()?;
+        Ok((metadata.dev(), metadata.ino()))
+    }
+
+    /// Writes the dirty pages of the given file back to disk
+    pub fn flush(&self, id: FileId) -> std::io::Result<()> {
+        let mut state = self.lock()?;
+        let lengths = state.lengths.get(&id).cloned();
+        let mut write_backs = 0;
+        for ((page_file, page_no), page) in state.pages.iter_mut() {
+            if page.is_dirty && *page_file == id {
+                Self::write_back(*page_no, page, lengths)?;
+                page.is_dirty = false;
+                write_backs += 1;
+            }
+        }
+        state.stats.write_backs += write_backs;
+        Ok(())
+    }
+
+    /// Writes all dirty pages back to disk, then runs the given function while
+    /// writes through the cache are held off; e.g. to copy a consistent set of files.
+    pub fn snapshot<A>(&self, f: impl FnOnce() -> std::io::Result<A>) -> std::io::Result<A> {
+        let _writer = self.lock_writer()?;
+        let mut state = self.lock()?;
+        let lengths = state.lengths.clone();
+        let mut write_backs = 0;
+        for ((page_file, page_no), page) in state.pages.iter_mut() {
+            if page.is_dirty {
+                Self::write_back(*page_no, page, lengths.get(page_file).cloned())?;
+                page.is_dirty = false;
+                write_backs += 1;
+            }
+        }
+        state.stats.write_backs += write_backs;
+        drop(state);
+        f()
+    }
+
+    /// Returns the number of modifications made to the given file through this cache
+    pub fn get_generation(&self, id: FileId) -> std::io::Result<u64> {
+        Ok(self.lock()?.generations.get(&id).cloned().unwrap_or(0))
+    }
+
+    /// Returns the usage statistics of this cache
+    pub fn get_stats(&self) -> PageCacheStats {
+        match self.lock() {
+            Ok(state) => PageCacheStats { pages: state.pages.len(), ..state.stats.clone() },
+            Err(..) => PageCacheStats::default()
+        }
+    }
+
+    /// Discards (without writing back) the cached pages of the given file;
+    /// e.g. after the file has been truncated or replaced.
+    pub fn invalidate(&self, id: FileId) -> std::io::Result<()> {
+        let mut state = self.lock()?;
+        Self::touch(&mut state, id);
+        let keys = state.pages.keys()
+            .filter(|(page_file, _)| *page_file == id)
+            .cloned()
+            .collect::<Vec<_>>();
+        for key in keys {
+            if let Some(page) = state.pages.remove(&key) {
+                state.lru.remove(&page.tick);
+            }
+        }
+        state.lengths.remove(&id);
+        Ok(())
+    }
+
+    /// Returns the logical length of the given file (including any unflushed writes)
+    pub fn len(&self, id: FileId, file: &File) -> std::io::Result<u64> {
+        let state = self.lock()?;
+        let file_len = file.metadata()?.len();
+        Ok(state.lengths.get(&id).map(|&n| n.max(file_len)).unwrap_or(file_len))
+    }
+
+    /// Reads bytes from the given file via the cache; bytes beyond the end of the file are zeros
+    pub fn read_at(&self, id: FileId, file: &Arc<File>, offset: u64, count: usize) -> std::io::Result<Vec<u8>> {
+        let mut buffer = Vec::with_capacity(count);
+        let mut position = offset;
+        while buffer.len() < count {
+            let (page_no, page_offset) = (position / PAGE_SIZE as u64, (position % PAGE_SIZE as u64) as usize);
+            let n = (PAGE_SIZE - page_offset).min(count - buffer.len());
+            self.with_page(id, file, page_no, |page| {
+                buffer.extend_from_slice(&page[page_offset..page_offset + n])
+            })?;
+            position += n as u64;
+        }
+        Ok(buffer)
+    }
+
+    /// Writes bytes to the given file via the cache (see [WritePolicy])
+    pub fn write_at(&self, id: FileId, file: &Arc<File>, offset: u64, bytes: &[u8]) -> std::io::Result<()> {
+        let _writer = self.lock_writer()?;
+        if self.policy == WritePolicy::WriteThrough {
+            file.write_all_at(bytes, offset)?;
+            let mut state = self.lock()?;
+            Self::touch(&mut state, id);
+            Self::patch_pages(&mut state, id, offset, bytes);
+            return Ok(());
+        }
+        let mut state = self.lock()?;
+        Self::touch(&mut state, id);
+        let mut written = 0;
+        while written < bytes.len() {
+            let position = offset + written as u64;
+            let (page_no, page_offset) = (position / PAGE_SIZE as u64, (position % PAGE_SIZE as u64) as usize);
+            let n = (PAGE_SIZE - page_offset).min(bytes.len() - written);
+            let page = self.fetch_page(&mut state, id, file, page_no)?;
+            page.bytes[page_offset..page_offset + n].copy_from_slice(&bytes[written..written + n]);
+            page.is_dirty = true;
+            written += n;
+        }
+        let end = offset + bytes.len() as u64;
+        let length = state.lengths.entry(id).or_insert(0);
+        *length = (*length).max(end);
+        Ok(())
+    }
+
+    /// Writes bytes directly to the given file (bypassing the cache); e.g. for bulk loads.
+    /// The cached pages overlapping the written range are written back and discarded beforehand.
+    pub fn write_direct(&self, id: FileId, file: &Arc<File>, offset: u64, bytes: &[u8]) -> std::io::Result<()> {
+        if bytes.is_empty() { return Ok(()); }
+        let _writer = self.lock_writer()?;
+        let end = offset + bytes.len() as u64;
+        let pages = (offset / PAGE_SIZE as u64)..=((end - 1) / PAGE_SIZE as u64);
+        let mut state = self.lock()?;
+        let lengths = state.lengths.get(&id).cloned();
+        let mut write_backs = 0;
+        for page_no in pages.clone() {
+            if let Some(page) = state.pages.get_mut(&(id, page_no)) {
+                if page.is_dirty {
+                    Self::write_back(page_no, page, lengths)?;
+                    page.is_dirty = false;
+                    write_backs += 1;
+                }
+            }
+        }
+        state.stats.write_backs += write_backs;
+        drop(state);
+        file.write_all_at(bytes, offset)?;
+        let mut state = self.lock()?;
+        Self::touch(&mut state, id);
+        for page_no in pages {
+            if let Some(page) = state.pages.remove(&(id, page_no)) {
+                state.lru.remove(&page.tick);
+            }
+        }
+        if let Some(length) = state.lengths.get_mut(&id) {
+            *length = (*length).max(end);
+        }
+        Ok(())
+    }
+
+    /// Passes the bytes of a page to the given function; upon a miss, the page is loaded
+    /// without holding the cache's lock, and is only cached if the file was not written meanwhile.
+    fn with_page(
+        &self,
+        id: FileId,
+        file: &Arc<File>,
+        page_no: u64,
+        f: impl FnOnce(&[u8]),
+    ) -> std::io::Result<()> {
+        let key = (id, page_no);
+        let generation = {
+            let mut state = self.lock()?;
+            if let Some(page) = Self::get_cached_page(&mut state, key) {
+                f(&page.bytes);
+                return Ok(());
+            }
+            state.stats.misses += 1;
+            state.generations.get(&id).cloned().unwrap_or(0)
+        };
+        let bytes = Self::load_page(file, page_no)?;
+        let mut state = self.lock()?;
+        if let Some(page) = state.pages.get(&key) {
+            // the page was loaded (or written) by another thread meanwhile
+            f(&page.bytes);
+        } else {
+            f(&bytes);
+            if state.generations.get(&id).cloned().unwrap_or(0) == generation {
+                Self::insert_page(&mut state, key, Page { bytes, file: file.clone(), is_dirty: false, tick: 0 })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Retrieves a cached page; marking it as the most-recently used
+    fn get_cached_page(state: &mut PageCacheState, key: PageKey) -> Option<&mut Page> {
+        state.tick += 1;
+        let tick = state.tick;
+        let page = state.pages.get_mut(&key)?;
+        state.stats.hits += 1;
+        state.lru.remove(&page.tick);
+        state.lru.insert(tick, key);
+        page.tick = tick;
+        Some(page)
+    }
+
+    /// Caches a page; evicting the least-recently used pages if the cache is full
+    fn insert_page(state: &mut PageCacheState, key: PageKey, mut page: Page) -> std::io::Result<()> {
+        while state.pages.len() >= state.stats.capacity.max(1) {
+            Self::evict(state)?;
+        }
+        state.tick += 1;
+        page.tick = state.tick;
+        state.lru.insert(page.tick, key);
+        state.pages.insert(key, page);
+        Ok(())
+    }
+
+    /// Reads a page from the given file; bytes beyond the end of the file are zeros
+    fn load_page(file: &File, page_no: u64) -> std::io::Result<Vec<u8>> {
+        let mut bytes = vec![0u8; PAGE_SIZE];
+        let mut loaded = 0;
+        while loaded < PAGE_SIZE {
+            match file.read_at(&mut bytes[loaded..], page_no * PAGE_SIZE as u64 + loaded as u64)? {
+                0 => break,
+                n => loaded += n,
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Copies written bytes into the cached pages they overlap (if any)
+    fn patch_pages(state: &mut PageCacheState, id: FileId, offset: u64, bytes: &[u8]) {
+        let mut written = 0;
+        while written < bytes.len() {
+            let position = offset + written as u64;
+            let (page_no, page_offset) = (position / PAGE_SIZE as u64, (position % PAGE_SIZE as u64) as usize);
+            let n = (PAGE_SIZE - page_offset).min(bytes.len() - written);
+            if let Some(page) = state.pages.get_mut(&(id, page_no)) {
+                page.bytes[page_offset..page_offset + n].copy_from_slice(&bytes[written..written + n]);
+            }
+            written += n;
+        }
+    }
+
+    /// Records that the given file has been modified; so that pages loaded
+    /// before the modification are not cached.
+    fn touch(state: &mut PageCacheState, id: FileId) {
+        *state.generations.entry(id).or_insert(0) += 1;
+    }
+
+    /// Retrieves a page (loading it from the file upon a miss); evicting the
+    /// least-recently used page if the cache is full.
+    fn fetch_page<'a>(
+        &self,
+        state: &'a mut PageCacheState,
+        id: FileId,
+        file: &Arc<File>,
+        page_no: u64,
+    ) -> std::io::Result<&'a mut Page> {
+        let key = (id, page_no);
+        if Self::get_cached_page(state, key).is_none() {
+            state.stats.misses += 1;
+            let bytes = Self::load_page(file, page_no)?;
+            Self::insert_page(state, key, Page { bytes, file: file.clone(), is_dirty: false, tick: 0 })?;
+        }
+        match state.pages.get_mut(&key) {
+            Some(page) => Ok(page),
+            None => throw(Exact(format!("Page {page_no} of file {:?} is not cached", id)))
+        }
+    }
+
+    /// Removes the least-recently used page; writing it back if it is dirty
+    fn evict(state: &mut PageCacheState) -> std::io::Result<()> {
+        let key = match state.lru.pop_first() {
+            Some((_, key)) => key,
+            None => return Ok(())
+        };
+        if let Some(page) = state.pages.remove(&key) {
+            if page.is_dirty {
+                Self::write_back(key.1, &page, state.lengths.get(&key.0).cloned())?;
+                state.stats.write_backs += 1;
+            }
+            state.stats.evictions += 1;
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> std::io::Result<MutexGuard<'_, PageCacheState>> {
+        match self.state.lock() {
+            Ok(guard) => Ok(guard),
+            Err(err) => throw(Exact(err.to_string()))
+        }
+    }
+
+    /// Serializes the writers (which acquire it before the cache's state)
+    fn lock_writer(&self) -> std::io::Result<MutexGuard<'_, ()>> {
+        match self.writer.lock() {
+            Ok(guard) => Ok(guard),
+            Err(err) => throw(Exact(err.to_string()))
+        }
+    }
+
+    /// Writes a dirty page to its file; without extending the file beyond its logical length
+    fn write_back(page_no: u64, page: &Page, length: Option<u64>) -> std::io::Result<()> {
+        let page_start = page_no * PAGE_SIZE as u64;
+        let file_len = page.file.metadata()?.len();
+        let logical_len = length.map(|n| n.max(file_len)).unwrap_or(file_len);
+        let n = logical_len.saturating_sub(page_start).min(PAGE_SIZE as u64) as usize;
+        page.file.write_all_at(&page.bytes[..n], page_start)
+    }
+}
+
+/// Represents a file whose reads and writes are served by the shared [PageCache];
+/// any dirty pages are written back when the last handle is dropped. The file of a
+/// namespace whose lease is held by another process may be written by that process;
+/// so its cached pages are discarded whenever its length or modification time changes.
+pub struct PagedFile {
+    file: Arc<File>,
+    id: FileId,
+    path: String,
+    namespace: Option<Namespace>,
+    stamp: Mutex<FileStamp>,
+}
+
+impl PagedFile {
+    /// Creates a new paged file; any pages cached under the same identifier
+    /// (e.g. those of a deleted file whose inode has been reused) are written back and discarded.
+    pub fn new(file: Arc<File>, path: &str) -> std::io::Result<Self> {
+        let id = PageCache::get_file_id(&file)?;
+        let cache = PageCache::shared();
+        cache.flush(id)?;
+        cache.invalidate(id)?;
+        let stamp = Mutex::new(Self::get_stamp(&file)?);
+        Ok(Self { id, file, path: path.to_string(), namespace: NamespaceLease::find_namespace(path), stamp })
+    }
+
+    /// Returns the length and modification time of the given file
+    fn get_stamp(file: &File) -> std::io::Result<FileStamp> {
+        let metadata = file.metadata()?;
+        Ok((metadata.len(), metadata.mtime(), metadata.mtime_nsec()))
+    }
+
+    /// Writes the dirty pages of this file back to disk
+    pub fn flush(&self) -> std::io::Result<()> {
+        PageCache::shared().flush(self.id)
+    }
+
+    pub fn get_file(&self) -> &File { &self.file }
+
+    /// Discards the cached pages of this file (e.g. after it has been replaced)
+    pub fn invalidate(&self) -> std::io::Result<()> {
+        PageCache::shared().invalidate(self.id)
+    }
+
+    /// Returns the logical length of the file (including any unflushed writes)
+    pub fn len(&self) -> std::io::Result<u64> {
+        PageCache::shared().len(self.id, &self.file)
+    }
+
+    pub fn read_at(&self, offset: u64, count: usize) -> std::io::Result<Vec<u8>> {
+        self.revalidate()?;
+        PageCache::shared().read_at(self.id, &self.file, offset, count)
+    }
+
+    /// Discards the cached pages of this file if another process may have written to it
+    /// since it was last read; i.e. if this process doesn't hold the lease of the file's
+    /// namespace, and the file's length or modification time has changed.
+    fn revalidate(&self) -> std::io::Result<()> {
+        match &self.namespace {
+            Some(ns) if !NamespaceLease::is_held(ns) => {
+                let stamp = Self::get_stamp(&self.file)?;
+                let mut last_stamp = self.stamp.lock().unwrap_or_else(|err| err.into_inner());
+                if *last_stamp != stamp {
+                    self.invalidate()?;
+                    *last_stamp = stamp;
+                }
+                Ok(())
+            }
+            _ => Ok(())
+        }
+    }
+
+    /// Truncates (or extends) the file, once memory-mapped reads of it have completed;
+    /// flushing its cached pages beforehand, and discarding them afterward.
+    pub fn set_len(&self, length: u64) -> std::io::Result<()> {
+        self.flush()?;
+        MemoryMap::exclusive(&self.file, || self.file.set_len(length))?;
+        self.invalidate()
+    }
+
+    pub fn write_at(&self, offset: u64, bytes: &[u8]) -> std::io::Result<()> {
+        PageCache::shared().write_at(self.id, &self.file, offset, bytes)
+    }
+
+    /// Writes bytes directly to the file (bypassing the cache)
+    pub fn write_direct(&self, offset: u64, bytes: &[u8]) -> std::io::Result<()> {
+        PageCache::shared().write_direct(self.id, &self.file, offset, bytes)
+    }
+}
+
+impl Drop for PagedFile {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            error!("Failed to flush {}: {}", self.path, err)
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::machine::Machine;
+    use std::fs;
+    use std::fs::OpenOptions;
+
+    fn create_file(name: &str) -> (String, FileId, Arc<File>) {
+        let home = Machine::oxide_home();
+        fs::create_dir_all(&home).unwrap();
+        let path = format!("{home}/{name}");
+        let file = OpenOptions::new().truncate(true).create(true).read(true).write(true)
+            .open(&path).unwrap();
+        (path, PageCache::get_file_id(&file).unwrap(), Arc::new(file))
+    }
+
+    #[test]
+    fn test_write_back_on_flush() {
+        let cache = PageCache::new(4, WritePolicy::WriteBack);
+        let (path, id, file) = create_file("page_cache_flush.bin");
+        cache.write_at(id, &file, 4090, b"Hello World").unwrap();
+
+        // the bytes are visible via the cache, but not yet on disk
+        assert_eq!(cache.read_at(id, &file, 4090, 11).unwrap(), b"Hello World".to_vec());
+        assert_eq!(cache.len(id, &file).unwrap(), 4101);
+        assert_eq!(fs::metadata(&path).unwrap().len(), 0);
+
+        // flushing writes the dirty pages back
+        cache.flush(id).unwrap();
+        let bytes = fs::read(&path).unwrap();
+        assert_eq!(bytes.len(), 4101);
+        assert_eq!(&bytes[4090..], b"Hello World");
+        assert_eq!(cache.get_stats().write_backs, 2);
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = PageCache::new(2, WritePolicy::WriteBack);
+        let (path, id, file) = create_file("page_cache_lru.bin");
+        for page_no in 0..3u64 {
+            cache.write_at(id, &file, page_no * PAGE_SIZE as u64, &[page_no as u8 + 1]).unwrap();
+        }
+
+        // the first page was evicted (and written back)
+        let stats = cache.get_stats();
+        assert_eq!((stats.pages, stats.evictions, stats.write_backs), (2, 1, 1));
+        assert_eq!(fs::read(&path).unwrap()[0], 1u8);
+
+        // re-reading the evicted page is a miss; the most recent page is a hit
+        assert_eq!(cache.read_at(id, &file, 0, 1).unwrap(), vec![1u8]);
+        assert_eq!(cache.read_at(id, &file, 2 * PAGE_SIZE as u64, 1).unwrap(), vec![3u8]);
+        let stats = cache.get_stats();
+        assert_eq!((stats.hits, stats.misses), (1, 4));
+    }
+
+    #[test]
+    fn test_write_direct() {
+        let cache = PageCache::new(4, WritePolicy::WriteBack);
+        let (path, id, file) = create_file("page_cache_direct.bin");
+        cache.write_at(id, &file, 0, b"Hello World").unwrap();
+
+        // the overlapping (dirty) page is written back, then overwritten on disk
+        cache.write_direct(id, &file, 6, b"Earth").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"Hello Earth".to_vec());
+        assert_eq!(cache.read_at(id, &file, 0, 11).unwrap(), b"Hello Earth".to_vec());
+        let stats = cache.get_stats();
+        assert_eq!((stats.write_backs, stats.misses), (1, 2));
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let cache = PageCache::new(4, WritePolicy::WriteBack);
+        let (path_a, id_a, file_a) = create_file("page_cache_snapshot_a.bin");
+        let (path_b, id_b, file_b) = create_file("page_cache_snapshot_b.bin");
+        cache.write_at(id_a, &file_a, 0, b"Hello").unwrap();
+        cache.write_at(id_b, &file_b, 0, b"World").unwrap();
+
+        // the dirty pages of all files are on disk while the snapshot is taken
+        let bytes = cache.snapshot(|| Ok((fs::read(&path_a)?, fs::read(&path_b)?))).unwrap();
+        assert_eq!(bytes, (b"Hello".to_vec(), b"World".to_vec()));
+        assert_eq!(cache.get_stats().write_backs, 2);
+    }
+
+    #[test]
+    fn test_write_through() {
+        let cache = PageCache::new(4, WritePolicy::WriteThrough);
+        let (path, id, file) = create_file("page_cache_write_through.bin");
+        cache.write_at(id, &file, 0, b"Hello World").unwrap();
+
+        // the bytes are on disk immediately; and the cached page is kept up to date
+        assert_eq!(fs::read(&path).unwrap(), b"Hello World".to_vec());
+        assert_eq!(cache.read_at(id, &file, 0, 11).unwrap(), b"Hello World".to_vec());
+        cache.write_at(id, &file, 6, b"Earth").unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"Hello Earth".to_vec());
+        assert_eq!(cache.read_at(id, &file, 0, 11).unwrap(), b"Hello Earth".to_vec());
+        let stats = cache.get_stats();
+        assert_eq!((stats.hits, stats.misses, stats.write_backs), (1, 1, 0));
+    }
+
+    #[test]
+    fn test_writes_of_another_process_invalidate_pages() {
+        let ns = Namespace::new("page_cache", "shared", "stocks");
+        NamespaceLease::release(&ns);
+        fs::create_dir_all(ns.get_root_path()).unwrap();
+        let path = ns.get_table_file_path();
+        fs::write(&path, b"Hello World").unwrap();
+        let file = PagedFile::new(Arc::new(File::open(&path).unwrap()), path.as_str()).unwrap();
+        assert_eq!(file.read_at(0, 11).unwrap(), b"Hello World".to_vec());
+
+        // another process (holding the lease) writes to the file
+        let other = OpenOptions::new().write(true).open(&path).unwrap();
+        other.write_all_at(b"Hello Earth!", 0).unwrap();
+        assert_eq!(file.read_at(0, 12).unwrap(), b"Hello Earth!".to_vec());
+    }
+}
@@ -0,0 +1,732 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// PartitionedRowCollection class
+////////////////////////////////////////////////////////////////////
+
+use crate::columns::Column;
+use crate::constraints::Constraints;
+use crate::data_types::DataType::NumberType;
+use crate::errors::Errors::{Exact, NotImplemented};
+use crate::errors::{throw, Errors};
+use crate::expression::Expression::{Condition, Literal, Variable};
+use crate::expression::{Conditions, Expression, TableOptions};
+use crate::field::FieldMetadata;
+use crate::file_row_collection::FileRowCollection;
+use crate::namespaces::Namespace;
+use crate::number_kind::NumberKind::DateKind;
+use crate::numbers::Numbers;
+use crate::numbers::Numbers::{DateValue, RowsAffected};
+use crate::object_config::ObjectConfig;
+use crate::parameter::Parameter;
+use crate::row_collection::RowCollection;
+use crate::row_metadata::RowMetadata;
+use crate::structures::Row;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{Boolean, ErrorValue, Null, Number};
+use chrono::DateTime;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::ErrorKind;
+use std::ops::Deref;
+use std::path::Path;
+use std::sync::Arc;
+
+/// the sub-directory (of the namespace directory) containing the partition files
+const PARTITIONS_DIR: &str = "partitions";
+
+/// the prefix of each partition file name (e.g. "part-NYSE.table")
+const PARTITION_PREFIX: &str = "part-";
+
+/// Partitioned (Disk) RowCollection implementation; each distinct value of the
+/// partition columns (or day, for Date columns) is stored within its own table file.
+/// Row IDs are assigned partition-by-partition, in partition key order.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct PartitionedRowCollection {
+    columns: Vec<Column>,
+    constraints: Option<Constraints>,
+    key_columns: Vec<usize>,
+    ns: Namespace,
+    options: Vec<TableOptions>,
+    partitions: Vec<Partition>,
+}
+
+/// Represents a single partition of a [PartitionedRowCollection]
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+struct Partition {
+    key: Vec<String>,
+    frc: FileRowCollection,
+}
+
+impl PartitionedRowCollection {
+
+    ////////////////////////////////////////////////////////////////////
+    // static methods
+    ////////////////////////////////////////////////////////////////////
+
+    /// Creates a new partitioned table within the specified namespace
+    pub fn create_table(
+        ns: &Namespace,
+        params: &Vec<Parameter>,
+        partitions: &Vec<String>,
+        options: &Vec<TableOptions>,
+    ) -> std::io::Result<Self> {
+        let columns = Column::from_parameters(params);
+        let key_columns = Self::resolve_key_columns(&columns, partitions)?;
+        ObjectConfig::build_table(params.clone())
+            .with_partitions(partitions.clone())
+            .with_options(options.clone())
+            .save(ns)?;
+        // the (empty) table file marks the table's existence
+        FileRowCollection::table_file_create(ns)?;
+        let dir = Self::get_partitions_path(ns);
+        if Path::new(&dir).exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::create_dir_all(&dir)?;
+        Ok(Self {
+            columns,
+            constraints: None,
+            key_columns,
+            ns: ns.to_owned(),
+            options: options.to_owned(),
+            partitions: Vec::new(),
+        })
+    }
+
+    /// Decodes a partition file name into its key (e.g. "NYSE,2024-01-05")
+    fn decode_key(file_name: &str) -> std::io::Result<Vec<String>> {
+        file_name.split(',').map(|component| {
+            let bytes = component.as_bytes();
+            let mut decoded = Vec::with_capacity(bytes.len());
+            let mut n = 0;
+            while n < bytes.len() {
+                if bytes[n] == b'%' {
+                    match component.get(n + 1..n + 3).and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                        Some(b) => decoded.push(b),
+                        None => return throw(Exact(format!("Invalid partition file name '{}'", file_name)))
+                    }
+                    n += 3;
+                } else {
+                    decoded.push(bytes[n]);
+                    n += 1;
+                }
+            }
+            String::from_utf8(decoded).or_else(|e| throw(Exact(e.to_string())))
+        }).collect()
+    }
+
+    /// Encodes a partition key as a file name; each component is escaped,
+    /// so that any value produces a valid (and reversible) file name.
+    fn encode_key(key: &Vec<String>) -> String {
+        key.iter().map(|component| {
+            component.bytes().map(|b| match b {
+                b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' => (b as char).to_string(),
+                b => format!("%{:02X}", b)
+            }).collect::<String>()
+        }).collect::<Vec<_>>().join(",")
+    }
+
+    /// Returns the path of the directory containing the partitions of a table
+    pub fn get_partitions_path(ns: &Namespace) -> String {
+        format!("{}{}", ns.get_root_path(), PARTITIONS_DIR)
+    }
+
+    /// Returns the partition key component of a value; Dates are bucketed by (UTC) day,
+    /// and numbers are normalized so that equal values of different kinds share a partition.
+    pub fn get_partition_value(value: &TypedValue) -> String {
+        match value {
+            Number(DateValue(millis)) =>
+                match DateTime::from_timestamp_millis(*millis) {
+                    Some(dt) => dt.format("%Y-%m-%d").to_string(),
+                    None => millis.to_string()
+                }
+            Number(n) => n.to_f64().to_string(),
+            Boolean(b) => b.to_string(),
+            Null | TypedValue::Undefined => "null".into(),
+            other => other.unwrap_value()
+        }
+    }
+
+    /// Opens an existing partitioned table
+    pub fn open(ns: &Namespace) -> std::io::Result<Self> {
+        let cfg = ObjectConfig::load(ns)?;
+        let columns = Column::from_parameters(&cfg.get_columns());
+        let partition_names = cfg.get_partitions().cloned().unwrap_or_default();
+        let key_columns = Self::resolve_key_columns(&columns, &partition_names)?;
+        let options = cfg.get_options();
+        let mut prc = Self {
+            constraints: Constraints::from_config(ns, &cfg, &columns),
+            columns,
+            key_columns,
+            ns: ns.to_owned(),
+            options,
+            partitions: Vec::new(),
+        };
+        let dir = Self::get_partitions_path(ns);
+        if Path::new(&dir).exists() {
+            for entry in fs::read_dir(&dir)? {
+                let path = entry?.path();
+                if path.extension().map(|ext| ext == "table").unwrap_or(false) {
+                    let stem = path.file_stem().and_then(|s| s.to_str())
+                        .and_then(|s| s.strip_prefix(PARTITION_PREFIX));
+                    if let Some(stem) = stem {
+                        let key = Self::decode_key(stem)?;
+                        let frc = prc.open_partition(&key, false)?;
+                        prc.partitions.push(Partition { key, frc });
+                    }
+                }
+            }
+        }
+        prc.partitions.sort();
+        Ok(prc)
+    }
+
+    fn resolve_key_columns(columns: &Vec<Column>, partitions: &Vec<String>) -> std::io::Result<Vec<usize>> {
+        if partitions.is_empty() {
+            return throw(Exact("At least one partition column is required".into()));
+        }
+        partitions.iter().map(|name| {
+            match columns.iter().position(|c| c.get_name() == name) {
+                Some(index) => Ok(index),
+                None => throw(Exact(format!("Partition column '{}' not found", name)))
+            }
+        }).collect()
+    }
+
+    ////////////////////////////////////////////////////////////////////
+    // instance methods
+    ////////////////////////////////////////////////////////////////////
+
+    /// Drops the partition having the given key values (an O(1) file delete);
+    /// returning true, if the partition existed. Like any other write, dropping
+    /// a partition requires the lease of the table's namespace.
+    pub fn drop_partition(&mut self, values: &Vec<TypedValue>) -> std::io::Result<bool> {
+        if values.len() != self.key_columns.len() {
+            return throw(Exact(format!("Expected {} partition key value(s), but got {}",
+                                       self.key_columns.len(), values.len())));
+        }
+        let key = values.iter().map(Self::get_partition_value).collect::<Vec<_>>();
+        match self.partitions.iter().position(|p| p.key == key) {
+            None => Ok(false),
+            Some(index) => {
+                if let Err(err) = self.partitions[index].frc.check_writable() { return throw(err); }
+                self.partitions.remove(index);
+
+                // remove the partition's table file, along with its related files (e.g. BLOBs and indices)
+                let prefix = format!("{}{}.", PARTITION_PREFIX, Self::encode_key(&key));
+                for entry in fs::read_dir(Self::get_partitions_path(&self.ns))? {
+                    let entry = entry?;
+                    if entry.file_name().to_str().map(|name| name.starts_with(&prefix)).unwrap_or(false) {
+                        match fs::remove_file(entry.path()) {
+                            Err(err) if err.kind() != ErrorKind::NotFound => return Err(err),
+                            _ => {}
+                        }
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    /// Returns the partition key of a row
+    pub fn get_row_key(&self, row: &Row) -> Vec<String> {
+        let values = row.get_values();
+        self.key_columns.iter()
+            .map(|&index| Self::get_partition_value(values.get(index).unwrap_or(&Null)))
+            .collect()
+    }
+
+    /// Enforces the given constraints (and column defaults) upon all subsequent writes
+    pub fn with_constraints(self, constraints: Option<Constraints>) -> Self {
+        Self { constraints, ..self }
+    }
+
+    /// Returns a view of this table containing only the partitions that could
+    /// contain rows satisfying the condition.
+    pub fn prune(&self, condition: &Conditions) -> Self {
+        match self.find_candidates(condition) {
+            None => self.to_owned(),
+            Some(candidates) => Self {
+                partitions: self.partitions.iter().enumerate()
+                    .filter(|(index, _)| candidates.contains(index))
+                    .map(|(_, partition)| partition.to_owned())
+                    .collect(),
+                ..self.to_owned()
+            }
+        }
+    }
+
+    /// Rewrites the rows of all partitions into the layout of the given columns (and partition
+    /// columns); returning the number of rows rewritten. See [FileRowCollection::rewrite].
+    pub fn rewrite<F>(
+        &mut self,
+        params: &Vec<Parameter>,
+        partitions: &Vec<String>,
+        transform: F,
+    ) -> std::io::Result<usize>
+    where
+        F: Fn(Row) -> Row,
+    {
+        let columns = Column::from_parameters(params);
+        let key_columns = Self::resolve_key_columns(&columns, partitions)?;
+        let mut rewritten = 0;
+        for partition in self.partitions.iter_mut() {
+            rewritten += partition.frc.rewrite(params, &transform)?;
+        }
+        self.columns = columns;
+        self.key_columns = key_columns;
+        Ok(rewritten)
+    }
+
+    /// Vacuums the BLOB files of all partitions; returning the number of rows updated
+    pub fn vacuum(&mut self) -> std::io::Result<usize> {
+        let mut updated = 0;
+        for partition in self.partitions.iter_mut() {
+            updated += partition.frc.vacuum()?;
+        }
+        Ok(updated)
+    }
+
+    /// Verifies the records and BLOB references of all partitions; returning the problems found
+    pub fn verify(&self) -> std::io::Result<Vec<(String, String, String)>> {
+        let mut problems = Vec::new();
+        for partition in &self.partitions {
+            problems.extend(partition.frc.verify()?);
+        }
+        Ok(problems)
+    }
+
+    /// Returns the indices of the partitions that could satisfy the condition;
+    /// or [None] if the condition does not constrain the partition columns.
+    fn find_candidates(&self, condition: &Conditions) -> Option<BTreeSet<usize>> {
+        fn as_condition(expr: &Expression) -> Option<&Conditions> {
+            match expr {
+                Condition(condition) => Some(condition),
+                _ => None
+            }
+        }
+
+        match condition {
+            Conditions::And(a, b) =>
+                match (as_condition(a).and_then(|c| self.find_candidates(c)),
+                       as_condition(b).and_then(|c| self.find_candidates(c))) {
+                    (Some(x), Some(y)) => Some(x.intersection(&y).cloned().collect()),
+                    (Some(x), None) | (None, Some(x)) => Some(x),
+                    (None, None) => None,
+                }
+            Conditions::Or(a, b) =>
+                match (as_condition(a).and_then(|c| self.find_candidates(c)),
+                       as_condition(b).and_then(|c| self.find_candidates(c))) {
+                    (Some(x), Some(y)) => Some(x.union(&y).cloned().collect()),
+                    _ => None,
+                }
+            Conditions::Equal(a, b) =>
+                match (a.deref(), b.deref()) {
+                    (Variable(name), Literal(value)) | (Literal(value), Variable(name)) =>
+                        self.find_candidates_by_value(name, value),
+                    _ => None
+                }
+            _ => None
+        }
+    }
+
+    fn find_candidates_by_value(&self, name: &str, value: &TypedValue) -> Option<BTreeSet<usize>> {
+        let component = self.key_columns.iter()
+            .position(|&index| self.columns[index].get_name() == name)?;
+        let expected = match (self.columns[self.key_columns[component]].get_data_type(), value) {
+            // a non-date value can't be bucketed consistently with a date column
+            (NumberType(DateKind), v) if !matches!(v, Number(DateValue(..))) => return None,
+            (_, v) => Self::get_partition_value(v)
+        };
+        Some(self.partitions.iter().enumerate()
+            .filter(|(_, p)| p.key[component] == expected)
+            .map(|(index, _)| index)
+            .collect())
+    }
+
+    /// Appends a row to the partition matching its key; creating the partition if necessary
+    fn append_to_partition(&mut self, row: Row) -> TypedValue {
+        let key = self.get_row_key(&row);
+        let index = match self.partitions.binary_search_by(|p| p.key.cmp(&key)) {
+            Ok(index) => index,
+            Err(index) => match self.open_partition(&key, true) {
+                Ok(frc) => {
+                    self.partitions.insert(index, Partition { key, frc });
+                    index
+                }
+                Err(err) => return ErrorValue(Exact(err.to_string()))
+            }
+        };
+        let frc = &mut self.partitions[index].frc;
+        match frc.len() {
+            Ok(local_id) => frc.overwrite_row(local_id, row.with_row_id(local_id)),
+            Err(err) => ErrorValue(Exact(err.to_string()))
+        }
+    }
+
+    fn get_partition_file_path(&self, key: &Vec<String>) -> String {
+        format!("{}/{}{}.table", Self::get_partitions_path(&self.ns), PARTITION_PREFIX, Self::encode_key(key))
+    }
+
+    /// Translates a row ID into a partition index and the row ID within that partition
+    fn locate(&self, id: usize) -> std::io::Result<Option<(usize, usize)>> {
+        let mut base = 0;
+        for (index, partition) in self.partitions.iter().enumerate() {
+            let len = partition.frc.len()?;
+            if id < base + len {
+                return Ok(Some((index, id - base)));
+            }
+            base += len;
+        }
+        Ok(None)
+    }
+
+    fn open_partition(&self, key: &Vec<String>, create: bool) -> std::io::Result<FileRowCollection> {
+        let path = self.get_partition_file_path(key);
+        let file = OpenOptions::new().read(true).write(true).create(create).open(&path)?;
+        FileRowCollection::new(self.columns.clone(), Arc::new(file), path.as_str())
+            .with_options(&self.options)?
+            .with_verified_header()
+    }
+}
+
+impl RowCollection for PartitionedRowCollection {
+    /// Eliminates all deleted rows within each partition
+    fn compact(&mut self) -> TypedValue {
+        let mut affected = Number(RowsAffected(0));
+        for partition in self.partitions.iter_mut() {
+            match partition.frc.compact() {
+                ErrorValue(err) => return ErrorValue(err),
+                result => affected = affected + result
+            }
+        }
+        affected
+    }
+
+    fn get_columns(&self) -> &Vec<Column> { &self.columns }
+
+    fn get_record_size(&self) -> usize {
+        match self.partitions.first() {
+            Some(partition) => partition.frc.get_record_size(),
+            None => Row::compute_record_size(&self.columns)
+        }
+    }
+
+    fn get_rows(&self) -> Vec<Row> {
+        // row IDs are offset by the lengths (deleted rows included) of the preceding partitions
+        let mut rows = vec![];
+        let mut base = 0;
+        for partition in self.partitions.iter() {
+            rows.extend(partition.frc.get_rows().iter()
+                .map(|row| row.with_row_id(base + row.get_id())));
+            base += partition.frc.len().unwrap_or(0);
+        }
+        rows
+    }
+
+    fn len(&self) -> std::io::Result<usize> {
+        let mut total = 0;
+        for partition in self.partitions.iter() {
+            total += partition.frc.len()?;
+        }
+        Ok(total)
+    }
+
+    fn overwrite_field(
+        &mut self,
+        id: usize,
+        column_id: usize,
+        new_value: TypedValue,
+    ) -> TypedValue {
+        let new_value = match self.constraints.as_ref()
+            .map(|c| c.validate_field(self, id, column_id, new_value.to_owned())) {
+            Some(Ok(value)) => value,
+            Some(Err(err)) => return ErrorValue(err),
+            None => new_value
+        };
+        // changing a partition column may move the row to another partition
+        if self.key_columns.contains(&column_id) {
+            return match self.read_row(id) {
+                Ok((row, _)) => {
+                    let mut values = row.get_values();
+                    values[column_id] = new_value;
+                    self.overwrite_row(id, Row::new(id, values))
+                }
+                Err(err) => ErrorValue(Exact(err.to_string()))
+            };
+        }
+        match self.locate(id) {
+            Ok(Some((index, local_id))) =>
+                self.partitions[index].frc.overwrite_field(local_id, column_id, new_value),
+            Ok(None) => ErrorValue(Errors::IndexOutOfRange("Table".into(), id, self.len().unwrap_or(0))),
+            Err(err) => ErrorValue(Exact(err.to_string()))
+        }
+    }
+
+    fn overwrite_field_metadata(
+        &mut self,
+        id: usize,
+        column_id: usize,
+        metadata: FieldMetadata,
+    ) -> TypedValue {
+        match self.locate(id) {
+            Ok(Some((index, local_id))) =>
+                self.partitions[index].frc.overwrite_field_metadata(local_id, column_id, metadata),
+            Ok(None) => ErrorValue(Errors::IndexOutOfRange("Table".into(), id, self.len().unwrap_or(0))),
+            Err(err) => ErrorValue(Exact(err.to_string()))
+        }
+    }
+
+    /// Overwrites a row in place when its partition key is unchanged; otherwise,
+    /// the row is deleted and appended to its (new) partition.
+    /// New rows (e.g. `id` >= len) are routed to the partition matching their key.
+    fn overwrite_row(&mut self, id: usize, row: Row) -> TypedValue {
+        let row = match self.constraints.as_ref().map(|c| c.validate_row(self, id, row.to_owned())) {
+            Some(Ok(row)) => row,
+            Some(Err(err)) => return ErrorValue(err),
+            None => row
+        };
+        match self.locate(id) {
+            Ok(Some((index, local_id))) => {
+                if self.partitions[index].key == self.get_row_key(&row) {
+                    self.partitions[index].frc.overwrite_row(local_id, row.with_row_id(local_id))
+                } else {
+                    match self.partitions[index].frc.delete_row(local_id) {
+                        ErrorValue(err) => ErrorValue(err),
+                        _ => self.append_to_partition(row)
+                    }
+                }
+            }
+            Ok(None) => self.append_to_partition(row),
+            Err(err) => ErrorValue(Exact(err.to_string()))
+        }
+    }
+
+    fn overwrite_row_metadata(&mut self, id: usize, metadata: RowMetadata) -> TypedValue {
+        match self.locate(id) {
+            Ok(Some((index, local_id))) =>
+                self.partitions[index].frc.overwrite_row_metadata(local_id, metadata),
+            Ok(None) => ErrorValue(Errors::IndexOutOfRange("Table".into(), id, self.len().unwrap_or(0))),
+            Err(err) => ErrorValue(Exact(err.to_string()))
+        }
+    }
+
+    fn read_field(&self, id: usize, column_id: usize) -> TypedValue {
+        match self.locate(id) {
+            Ok(Some((index, local_id))) => self.partitions[index].frc.read_field(local_id, column_id),
+            Ok(None) => Null,
+            Err(err) => ErrorValue(Exact(err.to_string()))
+        }
+    }
+
+    fn read_field_metadata(
+        &self,
+        id: usize,
+        column_id: usize,
+    ) -> std::io::Result<FieldMetadata> {
+        match self.locate(id)? {
+            Some((index, local_id)) => self.partitions[index].frc.read_field_metadata(local_id, column_id),
+            None => Ok(FieldMetadata::new(false))
+        }
+    }
+
+    fn read_row(&self, id: usize) -> std::io::Result<(Row, RowMetadata)> {
+        match self.locate(id)? {
+            Some((index, local_id)) =>
+                self.partitions[index].frc.read_row(local_id)
+                    .map(|(row, rmd)| (row.with_row_id(id), rmd)),
+            None => Ok((Row::create(id, &self.columns), RowMetadata::new(false)))
+        }
+    }
+
+    fn read_row_metadata(&self, id: usize) -> std::io::Result<RowMetadata> {
+        match self.locate(id)? {
+            Some((index, local_id)) => self.partitions[index].frc.read_row_metadata(local_id),
+            None => Ok(RowMetadata::new(false))
+        }
+    }
+
+    /// Truncates the table; rows beyond `new_size` are removed (in row ID order)
+    fn resize(&mut self, new_size: usize) -> TypedValue {
+        let mut remaining = new_size;
+        for partition in self.partitions.iter_mut() {
+            let len = match partition.frc.len() {
+                Ok(len) => len,
+                Err(err) => return ErrorValue(Exact(err.to_string()))
+            };
+            if len > remaining {
+                if let ErrorValue(err) = partition.frc.resize(remaining) {
+                    return ErrorValue(err);
+                }
+            }
+            remaining -= len.min(remaining);
+        }
+        if remaining > 0 {
+            return ErrorValue(NotImplemented("growing a partitioned table".into()));
+        }
+        Number(Numbers::Ack)
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expression::Conditions::{And, Equal, GreaterThan, Or};
+    use crate::namespace_leases::NamespaceLease;
+    use crate::numbers::Numbers::{F64Value, I64Value};
+    use crate::testdata::{make_quote, make_quote_parameters};
+    use crate::typed_values::TypedValue::StringValue;
+    use std::os::unix::io::AsRawFd;
+
+    #[test]
+    fn test_encode_decode_key() {
+        let key = vec!["NYSE".to_string(), "a,b/c.d%".to_string(), "".to_string()];
+        let file_name = PartitionedRowCollection::encode_key(&key);
+        assert_eq!(file_name, "NYSE,a%2Cb%2Fc%2Ed%25,");
+        assert_eq!(PartitionedRowCollection::decode_key(&file_name).unwrap(), key);
+    }
+
+    #[test]
+    fn test_get_partition_value() {
+        assert_eq!(PartitionedRowCollection::get_partition_value(&StringValue("NYSE".into())), "NYSE");
+        assert_eq!(PartitionedRowCollection::get_partition_value(&Number(I64Value(5))), "5");
+        assert_eq!(PartitionedRowCollection::get_partition_value(&Number(F64Value(5.0))), "5");
+        assert_eq!(PartitionedRowCollection::get_partition_value(&Number(DateValue(1704456000000))), "2024-01-05");
+    }
+
+    #[test]
+    fn test_append_then_read_partitions() {
+        let ns = Namespace::new("partitioned", "append", "stocks");
+        let mut prc = create_partitioned_table(&ns);
+        assert_eq!(prc.partitions.iter().map(|p| p.key.to_owned()).collect::<Vec<_>>(), vec![
+            vec!["AMEX".to_string()], vec!["NYSE".to_string()], vec!["OTC".to_string()],
+        ]);
+        assert_eq!(prc.len().unwrap(), 5);
+
+        // rows are read partition-by-partition
+        assert_eq!(prc.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "BIZ", "NYSE", 23.66),
+            make_quote(2, "JET", "NYSE", 32.12),
+            make_quote(3, "UNO", "OTC", 0.2456),
+            make_quote(4, "GOTO", "OTC", 0.1428),
+        ]);
+
+        // the partitions survive re-opening
+        let reopened = PartitionedRowCollection::open(&ns).unwrap();
+        assert_eq!(reopened.read_active_rows().unwrap(), prc.read_active_rows().unwrap());
+
+        // changing the partition key moves the row
+        prc.overwrite_row(0, make_quote(0, "ABC", "OTC", 11.77));
+        assert_eq!(prc.read_active_rows().unwrap(), vec![
+            make_quote(1, "BIZ", "NYSE", 23.66),
+            make_quote(2, "JET", "NYSE", 32.12),
+            make_quote(3, "UNO", "OTC", 0.2456),
+            make_quote(4, "GOTO", "OTC", 0.1428),
+            make_quote(5, "ABC", "OTC", 11.77),
+        ]);
+    }
+
+    #[test]
+    fn test_get_rows_after_delete() {
+        let ns = Namespace::new("partitioned", "get_rows", "stocks");
+        let mut prc = create_partitioned_table(&ns);
+        assert_eq!(prc.delete_row(1), Number(RowsAffected(1)));
+
+        // row IDs agree with those of the reads
+        let rows = prc.get_rows();
+        assert_eq!(rows, vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(2, "JET", "NYSE", 32.12),
+            make_quote(3, "UNO", "OTC", 0.2456),
+            make_quote(4, "GOTO", "OTC", 0.1428),
+        ]);
+        for row in rows {
+            assert_eq!(prc.read_one(row.get_id()).unwrap(), Some(row));
+        }
+    }
+
+    #[test]
+    fn test_prune_and_drop_partition() {
+        let ns = Namespace::new("partitioned", "prune", "stocks");
+        let mut prc = create_partitioned_table(&ns);
+        let exchange_is = |name: &str| Condition(Equal(
+            Box::new(Variable("exchange".into())),
+            Box::new(Literal(StringValue(name.into())))));
+
+        // an equality predicate on the partition column selects a single partition
+        let pruned = prc.prune(&Equal(
+            Box::new(Variable("exchange".into())),
+            Box::new(Literal(StringValue("NYSE".into())))));
+        assert_eq!(pruned.partitions.iter().map(|p| p.key.to_owned()).collect::<Vec<_>>(), vec![vec!["NYSE".to_string()]]);
+
+        // disjunctions are combined; other predicates do not prune
+        let pruned = prc.prune(&Or(Box::new(exchange_is("OTC")), Box::new(exchange_is("AMEX"))));
+        assert_eq!(pruned.partitions.iter().map(|p| p.key.to_owned()).collect::<Vec<_>>(), vec![vec!["AMEX".to_string()], vec!["OTC".to_string()]]);
+        let pruned = prc.prune(&And(
+            Box::new(exchange_is("OTC")),
+            Box::new(Condition(GreaterThan(
+                Box::new(Variable("last_sale".into())),
+                Box::new(Literal(Number(F64Value(0.2)))))))));
+        assert_eq!(pruned.partitions.iter().map(|p| p.key.to_owned()).collect::<Vec<_>>(), vec![vec!["OTC".to_string()]]);
+        let pruned = prc.prune(&GreaterThan(
+            Box::new(Variable("last_sale".into())),
+            Box::new(Literal(Number(F64Value(0.2))))));
+        assert_eq!(pruned.partitions.iter().map(|p| p.key.to_owned()).collect::<Vec<_>>().len(), 3);
+
+        // dropping a partition deletes its files (e.g. its table, BLOB and index files)
+        let path = prc.get_partition_file_path(&vec!["OTC".to_string()]);
+        let (_, index_path) = FileRowCollection::get_related_filename(path.as_str(), "symbol_hidx");
+        fs::write(&index_path, b"").unwrap();
+        assert!(Path::new(&path).exists());
+        assert!(Path::new(&format!("{}.blob", path)).exists());
+        assert!(prc.drop_partition(&vec![StringValue("OTC".into())]).unwrap());
+        assert!(!Path::new(&path).exists());
+        assert!(!Path::new(&format!("{}.blob", path)).exists());
+        assert!(!Path::new(&index_path).exists());
+        assert!(Path::new(&prc.get_partition_file_path(&vec!["NYSE".to_string()])).exists());
+        assert!(!prc.drop_partition(&vec![StringValue("OTC".into())]).unwrap());
+        assert_eq!(PartitionedRowCollection::open(&ns).unwrap().read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "BIZ", "NYSE", 23.66),
+            make_quote(2, "JET", "NYSE", 32.12),
+        ]);
+    }
+
+    #[test]
+    fn test_drop_partition_while_leased_elsewhere() {
+        let ns = Namespace::new("partitioned", "drop_leased", "stocks");
+        let mut prc = create_partitioned_table(&ns);
+
+        // another process takes over the lease
+        NamespaceLease::release(&ns);
+        let other = OpenOptions::new().create(true).truncate(false).read(true).write(true)
+            .open(NamespaceLease::get_lock_path(&ns)).unwrap();
+        assert_eq!(unsafe { libc::flock(other.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) }, 0);
+        let path = prc.get_partition_file_path(&vec!["OTC".to_string()]);
+        assert!(prc.drop_partition(&vec![StringValue("OTC".into())]).is_err());
+        assert!(Path::new(&path).exists());
+
+        // once the other process lets go, the partition can be dropped
+        drop(other);
+        assert!(prc.drop_partition(&vec![StringValue("OTC".into())]).unwrap());
+        assert!(!Path::new(&path).exists());
+    }
+
+    fn create_partitioned_table(ns: &Namespace) -> PartitionedRowCollection {
+        let mut prc = PartitionedRowCollection::create_table(
+            ns, &make_quote_parameters(), &vec!["exchange".into()], &Vec::new(),
+        ).unwrap();
+        assert_eq!(prc.append_rows(vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+            make_quote(3, "GOTO", "OTC", 0.1428),
+            make_quote(4, "JET", "NYSE", 32.12),
+        ]), Number(RowsAffected(5)));
+        prc
+    }
+}
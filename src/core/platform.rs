@@ -3,23 +3,28 @@
 // PlatformOps class
 ////////////////////////////////////////////////////////////////////
 
+use crate::backups::BackupManifest;
 use crate::byte_code_compiler::ByteCodeCompiler;
 use crate::compiler::Compiler;
+use crate::columns::Column;
 use crate::data_types::DataType;
 use crate::data_types::DataType::*;
 use crate::sequences::{Array, Sequence, Sequences};
 
-use crate::dataframe::Dataframe::{Disk, Model};
+use crate::dataframe::Dataframe;
+use crate::dataframe::Dataframe::{Disk, Model, Partitioned};
 use crate::errors::throw;
 use crate::errors::Errors::*;
 use crate::errors::TypeMismatchErrors::{ArgumentsMismatched, CollectionExpected, DateExpected, StringExpected, StructExpected, TableExpected, UnsupportedType};
 use crate::expression::Expression::{CodeBlock, Literal, Scenario};
 use crate::file_row_collection::FileRowCollection;
+use crate::journaling::{JournalRetention, JournaledRowCollection, Journaling};
 use crate::machine::Machine;
 use crate::model_row_collection::ModelRowCollection;
 use crate::namespaces::Namespace;
 use crate::number_kind::NumberKind::*;
 use crate::numbers::Numbers::*;
+use crate::object_config::ObjectConfig;
 use crate::oxide_server;
 use crate::parameter::Parameter;
 use crate::platform::PlatformOps::*;
@@ -109,17 +114,26 @@ pub enum PlatformOps {
     StrSubstring,
     StrToString,
     // tools package
+    ToolsBackup,
+    ToolsCheckpoint,
     ToolsCompact,
+    ToolsCompactJournal,
     ToolsDescribe,
+    ToolsDropPartition,
     ToolsFetch,
+    ToolsHistory,
     ToolsPop,
     ToolsPush,
+    ToolsReplay,
+    ToolsRestore,
     ToolsReverse,
     ToolsScan,
     ToolsToArray,
     ToolsToCSV,
     ToolsToJSON,
     ToolsToTable,
+    ToolsVacuum,
+    ToolsVerify,
     // util package
     UtilBase64,
     UtilBinary,
@@ -145,7 +159,7 @@ pub enum PlatformOps {
     WwwURLEncode,
 }
 
-pub const PLATFORM_OPCODES: [PlatformOps; 74] = {
+pub const PLATFORM_OPCODES: [PlatformOps; 83] = {
     use PlatformOps::*;
     [
         // cal
@@ -164,8 +178,9 @@ pub const PLATFORM_OPCODES: [PlatformOps; 74] = {
         StrEndsWith, StrFormat, StrIndexOf, StrJoin, StrLeft, StrLen,
         StrRight, StrSplit, StrStartsWith, StrSubstring, StrToString,
         // tools
-        ToolsCompact, ToolsDescribe, ToolsFetch, ToolsPop, ToolsPush, ToolsReverse, ToolsScan,
-        ToolsToArray, ToolsToCSV, ToolsToJSON, ToolsToTable,
+        ToolsBackup, ToolsCheckpoint, ToolsCompact, ToolsCompactJournal, ToolsDescribe, ToolsDropPartition,
+        ToolsFetch, ToolsHistory, ToolsPop, ToolsPush, ToolsReplay, ToolsRestore, ToolsReverse, ToolsScan,
+        ToolsToArray, ToolsToCSV, ToolsToJSON, ToolsToTable, ToolsVacuum, ToolsVerify,
         // util
         UtilBase64, UtilBinary, UtilHex, UtilMD5, UtilToASCII, UtilToDate,
         UtilToF32, UtilToF64,
@@ -241,17 +256,26 @@ impl PlatformOps {
             PlatformOps::StrStartsWith => Ok(self.adapter_fn2(ms, args, Self::do_str_start_with)),
             PlatformOps::StrSubstring => Ok(self.adapter_fn3(ms, args, Self::do_str_substring)),
             PlatformOps::StrToString => Ok(self.adapter_fn1(ms, args, Self::do_str_to_string)),
+            PlatformOps::ToolsBackup => Ok(self.adapter_fn2(ms, args, Self::do_tools_backup)),
+            PlatformOps::ToolsCheckpoint => Ok(self.adapter_fn1(ms, args, Self::do_tools_checkpoint)),
             PlatformOps::ToolsCompact => Ok(self.adapter_fn1(ms, args, Self::do_tools_compact)),
+            PlatformOps::ToolsCompactJournal => Ok(self.adapter_fn2(ms, args, Self::do_tools_compact_journal)),
             PlatformOps::ToolsDescribe => Ok(self.adapter_fn1(ms, args, Self::do_tools_describe)),
+            PlatformOps::ToolsDropPartition => Ok(self.adapter_fn2(ms, args, Self::do_tools_drop_partition)),
             PlatformOps::ToolsFetch => Ok(self.adapter_fn2(ms, args, Self::do_tools_fetch)),
+            PlatformOps::ToolsHistory => Ok(self.adapter_fn2(ms, args, Self::do_tools_history)),
             PlatformOps::ToolsPop => Ok(self.adapter_fn1(ms, args, Self::do_tools_pop)),
             PlatformOps::ToolsPush => Self::do_tools_push(ms, args),
+            PlatformOps::ToolsReplay => Ok(self.adapter_fn1(ms, args, Self::do_tools_replay)),
+            PlatformOps::ToolsRestore => Ok(Self::do_tools_restore(ms, args)),
             PlatformOps::ToolsReverse => Ok(self.adapter_fn1(ms, args, Self::do_tools_reverse)),
             PlatformOps::ToolsScan => Ok(self.adapter_fn1(ms, args, Self::do_tools_scan)),
             PlatformOps::ToolsToArray => Ok(self.adapter_fn1(ms, args, Self::do_tools_to_array)),
             PlatformOps::ToolsToCSV => Ok(self.adapter_fn1(ms, args, Self::do_tools_to_csv)),
             PlatformOps::ToolsToJSON => Ok(self.adapter_fn1(ms, args, Self::do_tools_to_json)),
             PlatformOps::ToolsToTable => Ok(self.adapter_fn1(ms, args, Self::do_tools_to_table)),
+            PlatformOps::ToolsVacuum => Ok(self.adapter_fn1(ms, args, Self::do_tools_vacuum)),
+            PlatformOps::ToolsVerify => Ok(self.adapter_fn1(ms, args, Self::do_tools_verify)),
             PlatformOps::UtilBase64 => Ok(self.adapter_fn1(ms, args, Self::do_util_base64)),
             PlatformOps::UtilBinary => Ok(self.adapter_fn1(ms, args, Self::do_util_binary)),
             PlatformOps::UtilMD5 => Ok(self.adapter_fn1(ms, args, Self::do_util_md5)),
@@ -326,17 +350,26 @@ impl PlatformOps {
             PlatformOps::StrStartsWith => "Returns true if string `a` starts with string `b`",
             PlatformOps::StrSubstring => "Returns a substring of string `s` from `m` to `n`",
             PlatformOps::StrToString => "Converts a value to its text-based representation",
+            PlatformOps::ToolsBackup => "Copies a consistent snapshot of a table, schema or database to a directory",
+            PlatformOps::ToolsCheckpoint => "Persists the state of a journaled table; so that replays resume from it",
             PlatformOps::ToolsCompact => "Shrinks a table by removing deleted rows",
+            PlatformOps::ToolsCompactJournal => "Discards the events of a journaled table preceding its latest checkpoint",
             PlatformOps::ToolsDescribe => "Describes a table or structure",
+            PlatformOps::ToolsDropPartition => "Drops a partition (by key) of a partitioned table",
             PlatformOps::ToolsFetch => "Retrieves a raw structure from a table",
+            PlatformOps::ToolsHistory => "Lists every change made to a row of a journaled table",
             PlatformOps::ToolsPop => "Removes and returns a value or object from a Sequence",
             PlatformOps::ToolsPush => "Appends a value or object to a Sequence",
+            PlatformOps::ToolsReplay => "Rebuilds the state of a journaled table from its latest checkpoint and events",
+            PlatformOps::ToolsRestore => "Restores a backup; optionally renaming the table, schema or database backed up",
             PlatformOps::ToolsReverse => "Returns a reverse copy of a table, string or array",
             PlatformOps::ToolsScan => "Returns existence metadata for a table",
             PlatformOps::ToolsToArray => "Converts a collection into an array",
             PlatformOps::ToolsToCSV => "Converts a collection to CSV format",
             PlatformOps::ToolsToJSON => "Converts a collection to JSON format",
             PlatformOps::ToolsToTable => "Converts an object into a to_table",
+            PlatformOps::ToolsVacuum => "Reclaims the unused BLOB space of a table",
+            PlatformOps::ToolsVerify => "Checks the records, BLOB references and indices of a table for damage",
             PlatformOps::UtilBase64 => "Translates bytes into Base 64",
             PlatformOps::UtilBinary => "Translates a numeric value into binary",
             PlatformOps::UtilHex => "Translates bytes into hexadecimal",
@@ -394,14 +427,14 @@ impl PlatformOps {
                 now():::year_of()
             "#,
             PlatformOps::IoFileCreate => r#"
-                io::create_file("quote.json", { symbol: "TRX", exchange: "NYSE", last_sale: 45.32 })
+                io::create_file(str::format("{}/quote.json", oxide::home()), { symbol: "TRX", exchange: "NYSE", last_sale: 45.32 })
             "#,
             PlatformOps::IoFileExists => r#"
-                io::exists("quote.json")
+                io::exists(str::format("{}/quote.json", oxide::home()))
             "#,
             PlatformOps::IoFileReadText => r#"
                 import io, util
-                file := "temp_secret.txt"
+                file := str::format("{}/secret.txt", oxide::home())
                 file:::create_file(md5("**keep**this**secret**"))
                 file:::read_text_file()
             "#,
@@ -487,6 +520,22 @@ impl PlatformOps {
             PlatformOps::StrStartsWith => "str::starts_with('Hello World', 'World')",
             PlatformOps::StrSubstring => "str::substring('Hello World', 0, 5)",
             PlatformOps::StrToString => "str::to_string(125.75)",
+            PlatformOps::ToolsBackup => r#"
+                [+] stocks := ns("platform.backup.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "JET", exchange: "NASDAQ", last_sale: 32.12 }] ~> stocks
+                [+] tools::backup(stocks, str::format("{}/backups/platform.backup", oxide::home()))
+            "#,
+            PlatformOps::ToolsCheckpoint => r#"
+                [+] stocks := ns("platform.checkpoint.stocks")
+                [+] create table ns("platform.checkpoint.stocks") (
+                        symbol: String(8), exchange: String(8), last_sale: f64
+                    ) with journaling
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "JET", exchange: "NASDAQ", last_sale: 32.12 }] ~> stocks
+                [+] tools::checkpoint(stocks)
+            "#,
             PlatformOps::ToolsCompact => r#"
                 [+] stocks := ns("platform.compact.stocks")
                 [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
@@ -500,9 +549,31 @@ impl PlatformOps {
                 [+] delete from stocks where last_sale > 1.0
                 [+] from stocks
             "#,
+            PlatformOps::ToolsCompactJournal => r#"
+                [+] stocks := ns("platform.compact_journal.stocks")
+                [+] create table ns("platform.compact_journal.stocks") (
+                        symbol: String(8), exchange: String(8), last_sale: f64
+                    ) with journaling
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "JET", exchange: "NASDAQ", last_sale: 32.12 }] ~> stocks
+                [+] tools::checkpoint(stocks)
+                [+] tools::compact_journal(stocks, 0)
+            "#,
             PlatformOps::ToolsDescribe => r#"
                 tools::describe({ symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 })
             "#,
+            PlatformOps::ToolsDropPartition => r#"
+                [+] stocks := ns("platform.drop_partition.stocks")
+                [+] create table ns("platform.drop_partition.stocks") (
+                        symbol: String(8), exchange: String(8), last_sale: f64
+                    ) with partitioned(exchange)
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                     { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 },
+                     { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                     { symbol: "GOTO", exchange: "OTC", last_sale: 0.1428 }] ~> stocks
+                [+] tools::drop_partition(stocks, "OTC")
+                [+] from stocks
+            "#,
             PlatformOps::ToolsFetch => r#"
                 [+] stocks := ns("platform.fetch.stocks")
                 [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
@@ -511,6 +582,15 @@ impl PlatformOps {
                      { symbol: "JET", exchange: "NASDAQ", last_sale: 32.12 }] ~> stocks
                 [+] tools::fetch(stocks, 2)
             "#,
+            PlatformOps::ToolsHistory => r#"
+                [+] stocks := ns("platform.history.stocks")
+                [+] create table ns("platform.history.stocks") (
+                        symbol: String(8), exchange: String(8), last_sale: f64
+                    ) with journaling
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "JET", exchange: "NASDAQ", last_sale: 32.12 }] ~> stocks
+                [+] tools::history(stocks, 1)
+            "#,
             PlatformOps::ToolsPop => r#"
                 import tools
                 [+] stocks := ns("platform.pop.stocks")
@@ -530,6 +610,25 @@ impl PlatformOps {
                 [+] stocks::push({ symbol: "XYZ", exchange: "NASDAQ", last_sale: 24.78 })
                 [+] stocks
             "#,
+            PlatformOps::ToolsReplay => r#"
+                [+] stocks := ns("platform.replay.stocks")
+                [+] create table ns("platform.replay.stocks") (
+                        symbol: String(8), exchange: String(8), last_sale: f64
+                    ) with journaling
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "JET", exchange: "NASDAQ", last_sale: 32.12 }] ~> stocks
+                [+] tools::checkpoint(stocks)
+                [+] tools::replay(stocks)
+            "#,
+            PlatformOps::ToolsRestore => r#"
+                [+] stocks := ns("platform.restore.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 12.49 },
+                     { symbol: "JET", exchange: "NASDAQ", last_sale: 32.12 }] ~> stocks
+                [+] tools::backup("platform.restore", str::format("{}/backups/platform.restore", oxide::home()))
+                [+] tools::restore(str::format("{}/backups/platform.restore", oxide::home()), "platform.restored")
+                [+] from ns("platform.restored.stocks")
+            "#,
             PlatformOps::ToolsReverse => r#"
                 import tools
                 to_table(reverse(['cat', 'dog', 'ferret', 'mouse']))
@@ -572,6 +671,21 @@ impl PlatformOps {
             PlatformOps::ToolsToTable => r#"
                 tools::to_table(['cat', 'dog', 'ferret', 'mouse'])
             "#,
+            PlatformOps::ToolsVacuum => r#"
+                [+] stocks := ns("platform.vacuum.stocks")
+                [+] table(symbol: String(4), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                     { symbol: "BIZARRE", exchange: "NYSE", last_sale: 23.66 },
+                     { symbol: "OUTERSPACE", exchange: "OTC", last_sale: 0.2456 }] ~> stocks
+                [+] tools::vacuum(stocks)
+            "#,
+            PlatformOps::ToolsVerify => r#"
+                [+] stocks := ns("platform.verify.stocks")
+                [+] table(symbol: String(4), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                     { symbol: "BIZARRE", exchange: "NYSE", last_sale: 23.66 }] ~> stocks
+                [+] tools::verify(stocks)
+            "#,
             PlatformOps::UtilBase64 => "util::base64('Hello World')",
             PlatformOps::UtilBinary => "(0b1011 & 0b1101):::to_binary()",
             PlatformOps::UtilHex => "util::hex('Hello World')",
@@ -663,17 +777,26 @@ impl PlatformOps {
             PlatformOps::StrStartsWith => "starts_with",
             PlatformOps::StrSubstring => "substring",
             PlatformOps::StrToString => "to_string",
+            PlatformOps::ToolsBackup => "backup",
+            PlatformOps::ToolsCheckpoint => "checkpoint",
             PlatformOps::ToolsCompact => "compact",
+            PlatformOps::ToolsCompactJournal => "compact_journal",
             PlatformOps::ToolsDescribe => "describe",
+            PlatformOps::ToolsDropPartition => "drop_partition",
             PlatformOps::ToolsFetch => "fetch",
+            PlatformOps::ToolsHistory => "history",
             PlatformOps::ToolsPop => "pop",
             PlatformOps::ToolsPush => "push",
+            PlatformOps::ToolsReplay => "replay",
+            PlatformOps::ToolsRestore => "restore",
             PlatformOps::ToolsReverse => "reverse",
             PlatformOps::ToolsScan => "scan",
             PlatformOps::ToolsToArray => "to_array",
             PlatformOps::ToolsToCSV => "to_csv",
             PlatformOps::ToolsToJSON => "to_json",
             PlatformOps::ToolsToTable => "to_table",
+            PlatformOps::ToolsVacuum => "vacuum",
+            PlatformOps::ToolsVerify => "verify",
             PlatformOps::UtilBase64 => "base64",
             PlatformOps::UtilBinary => "to_binary",
             PlatformOps::UtilHex => "hex",
@@ -719,9 +842,10 @@ impl PlatformOps {
             StrLeft | StrLen | StrRight | StrSplit |
             StrStartsWith | StrSubstring | StrToString => "str",
             // tools
-            ToolsCompact | ToolsDescribe | ToolsFetch | ToolsPop | ToolsPush |
-            ToolsReverse | ToolsScan | ToolsToArray | ToolsToCSV | ToolsToJSON |
-            ToolsToTable => "tools",
+            ToolsBackup | ToolsCheckpoint | ToolsCompact | ToolsCompactJournal | ToolsDescribe |
+            ToolsDropPartition | ToolsFetch | ToolsHistory | ToolsPop | ToolsPush | ToolsReplay | ToolsRestore |
+            ToolsReverse | ToolsScan | ToolsToArray | ToolsToCSV | ToolsToJSON | ToolsToTable | ToolsVacuum |
+            ToolsVerify => "tools",
             // util
             UtilBase64 | UtilBinary | UtilHex | UtilMD5 | UtilToASCII | UtilToDate |
             UtilToF32 | UtilToF64 |
@@ -757,11 +881,11 @@ impl PlatformOps {
             => vec![VaryingType(vec![])],
             // single-parameter (string)
             IoFileExists | IoFileReadText | IoStdErr | IoStdOut | OxidePrintln | OsCall |
-            OxideCompile | OxideDebug | OxideEval | StrLen | WwwURLDecode | WwwURLEncode
+            OxideCompile | OxideDebug | OxideEval | StrLen | ToolsRestore | WwwURLDecode | WwwURLEncode
             => vec![StringType(0)],
             // single-parameter (table)
-            ToolsCompact | ToolsDescribe | ToolsPop | ToolsReverse | ToolsScan |
-            ToolsToArray | ToolsToCSV | ToolsToJSON
+            ToolsCheckpoint | ToolsCompact | ToolsDescribe | ToolsPop | ToolsReplay | ToolsReverse |
+            ToolsScan | ToolsToArray | ToolsToCSV | ToolsToJSON | ToolsVacuum | ToolsVerify
             => vec![TableType(Vec::new(), 0)],
             // two-parameter (lazy, lazy)
             KungFuMatches | ToolsPush
@@ -776,8 +900,17 @@ impl PlatformOps {
             KungFuFeature
             => vec![StringType(0), StructureType(vec![])],
             // two-parameter (table, u64)
-            ToolsFetch
+            ToolsFetch | ToolsHistory
             => vec![TableType(vec![], 0), NumberType(U64Kind)],
+            // two-parameter (table, i64)
+            ToolsCompactJournal
+            => vec![TableType(vec![], 0), NumberType(I64Kind)],
+            // two-parameter (table, lazy)
+            ToolsDropPartition
+            => vec![TableType(vec![], 0), VaryingType(vec![])],
+            // two-parameter (lazy, string)
+            ToolsBackup
+            => vec![VaryingType(vec![]), StringType(0)],
             // two-parameter (array, string)
             StrJoin
             => vec![ArrayType(0), StringType(0)],
@@ -815,7 +948,8 @@ impl PlatformOps {
             ToolsToArray => ArrayType(0),
             IoFileReadText | StrSplit | ToolsToCSV | ToolsToJSON => ArrayType(0),
             // boolean
-            IoFileExists | KungFuMatches | StrEndsWith | StrStartsWith => BooleanType,
+            IoFileExists | KungFuMatches | StrEndsWith | StrStartsWith |
+            ToolsDropPartition => BooleanType,
             // bytes
             UtilMD5 => BinaryType(16),
             // date
@@ -840,6 +974,8 @@ impl PlatformOps {
             UtilToU32 => NumberType(U32Kind),
             UtilToU64 => NumberType(U64Kind),
             UtilToU128 | OxideUUID => NumberType(U128Kind),
+            ToolsBackup | ToolsCheckpoint | ToolsCompactJournal | ToolsReplay | ToolsRestore |
+            ToolsVacuum => NumberType(RowsAffectedKind),
             // outcome
             IoFileCreate | KungFuAssert | OsClear | OxidePrintln | OxideReset |
             ToolsPush | WwwServe => NumberType(AckKind),
@@ -858,6 +994,8 @@ impl PlatformOps {
             ToolsCompact | ToolsFetch | ToolsReverse | ToolsScan |
             ToolsToTable => TableType(Vec::new(), 0),
             ToolsDescribe => TableType(Self::get_tools_describe_parameters(), 0),
+            ToolsHistory => TableType(Self::get_tools_history_parameters(), 0),
+            ToolsVerify => TableType(Self::get_tools_verify_parameters(), 0),
         }
     }
 
@@ -1482,6 +1620,34 @@ impl PlatformOps {
         (ms, StringValue(a.unwrap_value()))
     }
 
+    /// Backs up a table, schema or database to a directory; returning the number of files copied
+    /// ex: tools::backup(stocks, "/backups/stocks")
+    fn do_tools_backup(ms: Machine, scope: &TypedValue, path: &TypedValue) -> (Machine, TypedValue) {
+        let scope = match scope {
+            ErrorValue(err) => return (ms, ErrorValue(err.to_owned())),
+            NamespaceValue(ns) => ns.id(),
+            StringValue(scope) => scope.to_owned(),
+            z => return (ms, ErrorValue(TypeMismatch(StringExpected(z.to_code()))))
+        };
+        let path = match path {
+            StringValue(path) => path,
+            z => return (ms, ErrorValue(TypeMismatch(StringExpected(z.to_code()))))
+        };
+        match BackupManifest::backup(&scope, path) {
+            Ok(manifest) => (ms, Number(RowsAffected(manifest.files.len() as i64))),
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
+
+    /// Persists the state of a journaled table; returning the number of rows checkpointed
+    /// ex: tools::checkpoint(stocks)
+    fn do_tools_checkpoint(ms: Machine, table: &TypedValue) -> (Machine, TypedValue) {
+        match JournaledRowCollection::open_value(table).and_then(|mut jrc| jrc.checkpoint()) {
+            Ok(checkpoint) => (ms, Number(RowsAffected(checkpoint.rows as i64))),
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
+
     fn do_tools_compact(ms: Machine, table: &TypedValue) -> (Machine, TypedValue) {
         match table {
             ErrorValue(err) => (ms, ErrorValue(err.to_owned())),
@@ -1496,6 +1662,21 @@ impl PlatformOps {
         }
     }
 
+    /// Discards the events of a journaled table preceding its latest checkpoint; retaining either
+    /// the given number of events, or (given a date) the events created at or after it.
+    /// ex: tools::compact_journal(stocks, 1000)
+    fn do_tools_compact_journal(ms: Machine, table: &TypedValue, retention: &TypedValue) -> (Machine, TypedValue) {
+        let retention = match retention {
+            Number(DateValue(millis)) => JournalRetention::KeepSince(*millis),
+            Number(n) => JournalRetention::KeepEvents(n.to_usize()),
+            z => return (ms, ErrorValue(TypeMismatch(UnsupportedType(NumberType(I64Kind), z.get_type()))))
+        };
+        match JournaledRowCollection::open_value(table).and_then(|mut jrc| jrc.compact_journal(retention)) {
+            Ok(discarded) => (ms, Number(RowsAffected(discarded as i64))),
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
+
     fn do_tools_describe(
         ms: Machine,
         item: &TypedValue,
@@ -1515,6 +1696,41 @@ impl PlatformOps {
         }
     }
 
+    /// Drops a partition of a partitioned table; the key is a value (or an array of
+    /// values for multi-column partitions).
+    /// ex: tools::drop_partition(stocks, "OTC")
+    fn do_tools_drop_partition(
+        ms: Machine,
+        table: &TypedValue,
+        key: &TypedValue,
+    ) -> (Machine, TypedValue) {
+        fn drop_it(ms: Machine, df: Dataframe, values: Vec<TypedValue>) -> (Machine, TypedValue) {
+            match df {
+                Partitioned(mut prc) =>
+                    match prc.drop_partition(&values) {
+                        Ok(dropped) => (ms, Boolean(dropped)),
+                        Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+                    }
+                _ => (ms, ErrorValue(Exact("Table is not partitioned".into())))
+            }
+        }
+
+        let values = match key {
+            ArrayValue(items) => items.get_values(),
+            value => vec![value.to_owned()]
+        };
+        match table {
+            ErrorValue(err) => (ms, ErrorValue(err.to_owned())),
+            NamespaceValue(ns) =>
+                match Dataframe::open(&ns) {
+                    Ok(df) => drop_it(ms, df, values),
+                    Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+                }
+            TableValue(df) => drop_it(ms, df.to_owned(), values),
+            z => (ms, ErrorValue(TypeMismatch(CollectionExpected(z.to_code()))))
+        }
+    }
+
     /// Retrieves a raw structure from a table
     /// ex: util::fetch(stocks, 5)
     /// ex: stocks:::fetch(5)
@@ -1549,6 +1765,27 @@ impl PlatformOps {
         }
     }
 
+
+    /// Lists the events pertaining to a row of a journaled table
+    /// ex: tools::history(ns("journal.history.stocks"), 1)
+    fn do_tools_history(
+        ms: Machine,
+        table: &TypedValue,
+        row_id: &TypedValue,
+    ) -> (Machine, TypedValue) {
+        match JournaledRowCollection::open_value(table).and_then(|jrc| jrc.get_history(row_id.to_usize())) {
+            Ok(events) => {
+                let columns = Column::from_parameters(&Self::get_tools_history_parameters());
+                let rows = events.into_iter().map(|event| {
+                    let mut values = event.get_values();
+                    values[0] = Number(U64Value(event.get_id() as u64));
+                    Row::new(0, values)
+                }).collect::<Vec<_>>();
+                (ms, TableValue(Model(ModelRowCollection::from_columns_and_rows(&columns, &rows))))
+            }
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
     fn do_tools_pop(ms: Machine, value: &TypedValue) -> (Machine, TypedValue) {
         match value.to_sequence() {
             Ok(seq) => {
@@ -1609,6 +1846,34 @@ impl PlatformOps {
         }
     }
 
+    /// Rebuilds the state of a journaled table; returning the number of rows affected
+    /// ex: tools::replay(stocks)
+    fn do_tools_replay(ms: Machine, table: &TypedValue) -> (Machine, TypedValue) {
+        match JournaledRowCollection::open_value(table) {
+            Ok(mut jrc) => (ms, jrc.replay()),
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
+
+    /// Restores a backup; optionally renaming the table, schema or database backed up
+    /// ex: tools::restore("/backups/stocks", "securities.archive.stocks")
+    fn do_tools_restore(ms: Machine, args: Vec<TypedValue>) -> (Machine, TypedValue) {
+        let result = match args.as_slice() {
+            // restore("/backups/stocks")
+            [StringValue(path)] => BackupManifest::restore(path, None),
+            // restore("/backups/stocks", "securities.archive.stocks")
+            [StringValue(path), StringValue(target)] => BackupManifest::restore(path, Some(target)),
+            [StringValue(path), NamespaceValue(ns)] => BackupManifest::restore(path, Some(&ns.id())),
+            [ErrorValue(err), ..] => return (ms, ErrorValue(err.to_owned())),
+            [z] | [z, _] => return (ms, ErrorValue(TypeMismatch(StringExpected(z.to_code())))),
+            other => return (ms, ErrorValue(TypeMismatch(ArgumentsMismatched(1, other.len()))))
+        };
+        match result {
+            Ok(manifest) => (ms, Number(RowsAffected(manifest.files.len() as i64))),
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
+
     fn do_tools_reverse(ms: Machine, value: &TypedValue) -> (Machine, TypedValue) {
         match value {
             ArrayValue(a) => (ms, ArrayValue(a.rev())),
@@ -1714,6 +1979,79 @@ impl PlatformOps {
         }
     }
 
+    /// Rewrites the live BLOBs of a table, reclaiming the space of stale ones
+    /// ex: tools::vacuum(stocks)
+    fn do_tools_vacuum(ms: Machine, table: &TypedValue) -> (Machine, TypedValue) {
+        let result = match table {
+            ErrorValue(err) => return (ms, ErrorValue(err.to_owned())),
+            NamespaceValue(ns) => Dataframe::open(&ns).and_then(|mut df| df.vacuum()),
+            TableValue(df) => df.to_owned().vacuum(),
+            z => return (ms, ErrorValue(TypeMismatch(CollectionExpected(z.to_code()))))
+        };
+        match result {
+            Ok(updated) => (ms, Number(RowsAffected(updated as i64))),
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
+
+    /// Checks the records, BLOB references and indices of a table; returning a table of the problems found
+    /// ex: tools::verify(stocks)
+    fn do_tools_verify(ms: Machine, table: &TypedValue) -> (Machine, TypedValue) {
+        // NOTE: index entries aren't materialized (indices are enforced upon write); so the entries
+        // verified are the keys of each row, which must be unique within a unique index.
+        fn verify(ns: &Namespace) -> std::io::Result<Vec<(String, String, String)>> {
+            let df = Dataframe::open(ns)?;
+            let mut problems = df.verify()?;
+            let config = ObjectConfig::load(ns)?;
+            let column_names = config.get_columns().iter()
+                .map(|p| p.get_name().to_string())
+                .collect::<Vec<_>>();
+            for index in config.get_indices() {
+                let indexed_columns = index.get_indexed_column_names();
+                let location = format!("index({})", indexed_columns.join(", "));
+                let positions = indexed_columns.iter()
+                    .map(|name| column_names.iter().position(|c| c == name).ok_or(name))
+                    .collect::<Vec<_>>();
+                for name in positions.iter().filter_map(|p| p.err()) {
+                    problems.push(("index".into(), location.to_owned(), format!("column {name} does not exist")));
+                }
+                if !index.is_unique() || positions.iter().any(|p| p.is_err()) { continue; }
+                let mut keys = HashMap::new();
+                for id in df.get_indices()? {
+                    let row = match df.read_one(id) {
+                        Ok(Some(row)) => row,
+                        Ok(None) | Err(..) => continue
+                    };
+                    let values = positions.iter().flatten().map(|&p| row[p].to_owned()).collect::<Vec<_>>();
+                    if values.iter().any(|v| matches!(v, Null | Undefined)) { continue; }
+                    let key = values.iter().map(|v| v.to_code()).collect::<Vec<_>>().join(", ");
+                    match keys.get(&key) {
+                        Some(first_id) => problems.push(("index".into(), format!("{location}#{id}"),
+                                                         format!("duplicate key ({key}) of row #{first_id}"))),
+                        None => { keys.insert(key, id); }
+                    }
+                }
+            }
+            Ok(problems)
+        }
+        let result = match table {
+            ErrorValue(err) => return (ms, ErrorValue(err.to_owned())),
+            NamespaceValue(ns) => verify(ns),
+            TableValue(df) => df.verify(),
+            z => return (ms, ErrorValue(TypeMismatch(CollectionExpected(z.to_code()))))
+        };
+        match result {
+            Ok(problems) => {
+                let columns = Column::from_parameters(&Self::get_tools_verify_parameters());
+                let rows = problems.into_iter().map(|(kind, location, problem)| Row::new(0, vec![
+                    StringValue(kind), StringValue(location), StringValue(problem),
+                ])).collect::<Vec<_>>();
+                (ms, TableValue(Model(ModelRowCollection::from_columns_and_rows(&columns, &rows))))
+            }
+            Err(err) => (ms, ErrorValue(Exact(err.to_string())))
+        }
+    }
+
     fn do_util_base64(
         ms: Machine,
         a: &TypedValue,
@@ -1850,6 +2188,24 @@ impl PlatformOps {
         ]
     }
 
+    pub fn get_tools_history_parameters() -> Vec<Parameter> {
+        vec![
+            Parameter::new("event_id", NumberType(U64Kind)),
+            Parameter::new("column_id", NumberType(U16Kind)),
+            Parameter::new("action", StringType(2)),
+            Parameter::new("new_value", StringType(256)),
+            Parameter::new("created_time", NumberType(DateKind)),
+        ]
+    }
+
+    pub fn get_tools_verify_parameters() -> Vec<Parameter> {
+        vec![
+            Parameter::new("kind", StringType(8)),
+            Parameter::new("location", StringType(128)),
+            Parameter::new("problem", StringType(128)),
+        ]
+    }
+
     fn open_namespace(ns: &Namespace) -> TypedValue {
         match FileRowCollection::open(ns) {
             Err(err) => ErrorValue(Exact(err.to_string())),
@@ -2024,17 +2380,26 @@ mod tests {
         assert_eq!(StrSubstring.to_code(), "str::substring(s: String, m: i64, n: i64)");
         assert_eq!(StrToString.to_code(), "str::to_string(x)");
         // tools
+        assert_eq!(ToolsBackup.to_code(), "tools::backup(a, b: String)");
+        assert_eq!(ToolsCheckpoint.to_code(), "tools::checkpoint(t: Table)");
         assert_eq!(ToolsCompact.to_code(), "tools::compact(t: Table)");
+        assert_eq!(ToolsCompactJournal.to_code(), "tools::compact_journal(t: Table, n: i64)");
         assert_eq!(ToolsDescribe.to_code(), "tools::describe(t: Table)");
+        assert_eq!(ToolsDropPartition.to_code(), "tools::drop_partition(a: Table, b)");
         assert_eq!(ToolsFetch.to_code(), "tools::fetch(t: Table, n: u64)");
+        assert_eq!(ToolsHistory.to_code(), "tools::history(t: Table, n: u64)");
         assert_eq!(ToolsPop.to_code(), "tools::pop(t: Table)");
         assert_eq!(ToolsPush.to_code(), "tools::push(a, b)");
+        assert_eq!(ToolsReplay.to_code(), "tools::replay(t: Table)");
+        assert_eq!(ToolsRestore.to_code(), "tools::restore(s: String)");
         assert_eq!(ToolsReverse.to_code(), "tools::reverse(t: Table)");
         assert_eq!(ToolsScan.to_code(), "tools::scan(t: Table)");
         assert_eq!(ToolsToArray.to_code(), "tools::to_array(t: Table)");
         assert_eq!(ToolsToCSV.to_code(), "tools::to_csv(t: Table)");
         assert_eq!(ToolsToJSON.to_code(), "tools::to_json(t: Table)");
         assert_eq!(ToolsToTable.to_code(), "tools::to_table(x)");
+        assert_eq!(ToolsVacuum.to_code(), "tools::vacuum(t: Table)");
+        assert_eq!(ToolsVerify.to_code(), "tools::verify(t: Table)");
         // util
         assert_eq!(UtilBase64.to_code(), "util::base64(x)");
         assert_eq!(UtilBinary.to_code(), "util::to_binary(x)");
@@ -2136,26 +2501,36 @@ mod tests {
     #[cfg(test)]
     mod io_tests {
         use super::*;
+        use crate::machine::Machine;
         use crate::platform::PlatformOps;
         use crate::typed_values::TypedValue::*;
+        use std::fs;
         use PlatformOps::*;
 
+        /// the files of the io tests are created under the (temporary) oxide home
+        fn create_oxide_home() {
+            fs::create_dir_all(Machine::oxide_home()).unwrap();
+        }
+
         #[test]
         fn test_io_create_file_qualified() {
+            create_oxide_home();
             verify_exact(r#"
-                io::create_file("quote.json", { symbol: "TRX", exchange: "NYSE", last_sale: 45.32 })
+                io::create_file(str::format("{}/quote_qualified.json", oxide::home()), { symbol: "TRX", exchange: "NYSE", last_sale: 45.32 })
             "#, Number(U64Value(52)));
 
             verify_exact(r#"
-                io::exists("quote.json")
+                io::exists(str::format("{}/quote_qualified.json", oxide::home()))
             "#, Boolean(true));
         }
 
         #[test]
         fn test_io_create_file_postfix() {
+            create_oxide_home();
             verify_exact(r#"
                 import io
-                "quote.json":::create_file({
+                file := str::format("{}/quote_postfix.json", oxide::home())
+                file:::create_file({
                     symbol: "TRX",
                     exchange: "NYSE",
                     last_sale: 45.32
@@ -2164,7 +2539,8 @@ mod tests {
 
             verify_exact(r#"
                 import io
-                "quote.json":::exists()
+                file := str::format("{}/quote_postfix.json", oxide::home())
+                file:::exists()
             "#, Boolean(true));
         }
 
@@ -2179,9 +2555,10 @@ mod tests {
 
         #[test]
         fn test_io_create_and_read_text_file() {
+            create_oxide_home();
             verify_exact(r#"
                 import io, util
-                file := "temp_secret.txt"
+                file := str::format("{}/secret.txt", oxide::home())
                 file:::create_file(md5("**keep**this**secret**"))
                 file:::read_text_file()
             "#, StringValue("47338bd5f35bbb239092c36e30775b4a".into()))
@@ -2756,12 +3133,72 @@ mod tests {
         use super::*;
         use crate::expression::Expression::{ColonColon, FunctionCall, StructureExpression, Variable};
         use crate::interpreter::Interpreter;
+        use crate::object_config::HashIndexConfig;
         use crate::platform::PlatformOps;
         use crate::structures::HardStructure;
         use crate::testdata::*;
         use crate::typed_values::TypedValue::*;
         use PlatformOps::*;
 
+        #[test]
+        fn test_tools_backup_and_restore() {
+            let mut interpreter = Interpreter::new();
+            interpreter = verify_where(interpreter, r#"
+                [+] stocks := ns("platform.backup_restore.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                     { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 }] ~> stocks
+                [+] tools::backup(stocks, str::format("{}/backups/platform.backup_restore", oxide::home()))
+            "#, Number(RowsAffected(3)));
+
+            interpreter = verify_where(interpreter, r#"
+                [+] delete from stocks where symbol == "ABC"
+                [+] tools::restore(str::format("{}/backups/platform.backup_restore", oxide::home()), "platform.backup_restore.quotes")
+            "#, Number(RowsAffected(3)));
+
+            verify_exact_table_where(interpreter, r#"
+                from ns("platform.backup_restore.quotes")
+            "#, vec![
+                "|------------------------------------|",
+                "| id | symbol | exchange | last_sale |",
+                "|------------------------------------|",
+                "| 0  | ABC    | AMEX     | 11.77     |",
+                "| 1  | BIZ    | NYSE     | 23.66     |",
+                "|------------------------------------|"
+            ]);
+        }
+
+        #[test]
+        fn test_tools_checkpoint_and_replay() {
+            let mut interpreter = Interpreter::new();
+            interpreter = verify_where(interpreter, r#"
+                [+] stocks := ns("platform.checkpoint_replay.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] create table ns("platform.checkpoint_replay.stocks") (
+                        symbol: String(8), exchange: String(8), last_sale: f64
+                    ) with journaling
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                     { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 }] ~> stocks
+                [+] tools::checkpoint(stocks)
+            "#, Number(RowsAffected(2)));
+
+            interpreter = verify_where(interpreter, r#"
+                [+] tools::compact_journal(stocks, 0)
+                [+] tools::replay(stocks)
+            "#, Number(RowsAffected(2)));
+
+            verify_exact_table_where(interpreter, r#"
+                from stocks
+            "#, vec![
+                "|------------------------------------|",
+                "| id | symbol | exchange | last_sale |",
+                "|------------------------------------|",
+                "| 0  | ABC    | AMEX     | 11.77     |",
+                "| 1  | BIZ    | NYSE     | 23.66     |",
+                "|------------------------------------|"
+            ]);
+        }
+
         #[test]
         fn test_tools_compact() {
             let mut interpreter = Interpreter::new();
@@ -2834,6 +3271,29 @@ mod tests {
             ]);
         }
 
+        #[test]
+        fn test_tools_drop_partition() {
+            verify_exact_table_with_ids(r#"
+                [+] stocks := ns("platform.drop_partition.stocks")
+                [+] create table ns("platform.drop_partition.stocks") (
+                        symbol: String(8), exchange: String(8), last_sale: f64
+                    ) with partitioned(exchange)
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                     { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 },
+                     { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                     { symbol: "GOTO", exchange: "OTC", last_sale: 0.1428 }] ~> stocks
+                [+] tools::drop_partition(stocks, "OTC")
+                [+] from stocks
+            "#, vec![
+                "|------------------------------------|",
+                "| id | symbol | exchange | last_sale |",
+                "|------------------------------------|",
+                "| 0  | ABC    | AMEX     | 11.77     |",
+                "| 1  | BIZ    | NYSE     | 23.66     |",
+                "|------------------------------------|"
+            ]);
+        }
+
         #[test]
         fn test_tools_fetch() {
             // fully-qualified
@@ -2870,6 +3330,48 @@ mod tests {
             ]);
         }
 
+        #[test]
+        fn test_tools_history_and_as_of() {
+            let mut interpreter = Interpreter::new();
+            interpreter = verify_exact_table_where(interpreter, r#"
+                [+] stocks := ns("platform.history_as_of.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] table(row_id: u64, column_id: u16, action: String(2), new_value: String(256), created_time: Date)
+                        ~> ns("platform.history_as_of.stocks_events")
+                [+] create table ns("platform.history_as_of.stocks") (
+                        symbol: String(8), exchange: String(8), last_sale: f64
+                    ) from [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                            { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 }] with journaling
+                [+] select event_id, action, new_value from tools::history(stocks, 1)
+            "#, vec![
+                "|-------------------------------------------------|",
+                "| id | event_id | action | new_value              |",
+                "|-------------------------------------------------|",
+                "| 0  | 1        | CR     | [\"BIZ\", \"NYSE\", 23.66] |",
+                "|-------------------------------------------------|"
+            ]);
+
+            interpreter = verify_exact_table_where(interpreter, r#"
+                from stocks as of 0
+            "#, vec![
+                "|------------------------------------|",
+                "| id | symbol | exchange | last_sale |",
+                "|------------------------------------|",
+                "| 0  | ABC    | AMEX     | 11.77     |",
+                "|------------------------------------|"
+            ]);
+
+            verify_exact_table_where(interpreter, r#"
+                from stocks as of cal::now() where last_sale > 20.0
+            "#, vec![
+                "|------------------------------------|",
+                "| id | symbol | exchange | last_sale |",
+                "|------------------------------------|",
+                "| 1  | BIZ    | NYSE     | 23.66     |",
+                "|------------------------------------|"
+            ]);
+        }
+
         #[test]
         fn test_tools_pop() {
             verify_exact_table_with_ids(r#"
@@ -3226,6 +3728,78 @@ mod tests {
                 "|-------------------------------------|"
             ])
         }
+
+        #[test]
+        fn test_tools_vacuum() {
+            let mut interpreter = Interpreter::new();
+            interpreter = verify_where(interpreter, r#"
+                [+] stocks := ns("platform.vacuum.stocks")
+                [+] table(symbol: String(4), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                     { symbol: "BIZARRE", exchange: "NYSE", last_sale: 23.66 },
+                     { symbol: "OUTERSPACE", exchange: "OTC", last_sale: 0.2456 }] ~> stocks
+                [+] update stocks via { symbol: "BIZ" } where symbol == "BIZARRE"
+                [+] tools::vacuum(stocks)
+            "#, Number(RowsAffected(1)));
+
+            verify_exact_table_where(interpreter, r#"
+                from stocks
+            "#, vec![
+                "|----------------------------------------|",
+                "| id | symbol     | exchange | last_sale |",
+                "|----------------------------------------|",
+                "| 0  | ABC        | AMEX     | 11.77     |",
+                "| 1  | BIZ        | NYSE     | 23.66     |",
+                "| 2  | OUTERSPACE | OTC      | 0.2456    |",
+                "|----------------------------------------|"
+            ]);
+        }
+
+        #[test]
+        fn test_tools_verify() {
+            let mut interpreter = Interpreter::new();
+            interpreter = verify_where(interpreter, r#"
+                [+] stocks := ns("platform.verify.stocks")
+                [+] table(symbol: String(4), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                     { symbol: "OUTERSPACE", exchange: "OTC", last_sale: 0.2456 }] ~> stocks
+            "#, Number(RowsAffected(2)));
+
+            verify_exact_table_where(interpreter, r#"
+                tools::verify(stocks)
+            "#, vec![
+                "|--------------------------------|",
+                "| id | kind | location | problem |",
+                "|--------------------------------|",
+                "|--------------------------------|"
+            ]);
+        }
+
+        #[test]
+        fn test_tools_verify_unique_index_entries() {
+            let mut interpreter = Interpreter::new();
+            interpreter = verify_where(interpreter, r#"
+                [+] stocks := ns("platform.verify_index.stocks")
+                [+] table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                [+] [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                     { symbol: "XYZ", exchange: "NYSE", last_sale: 0.2456 },
+                     { symbol: "ABC", exchange: "OTC", last_sale: 11.79 }] ~> stocks
+            "#, Number(RowsAffected(3)));
+
+            // the keys of a unique index (declared over existing rows) must be unique
+            let ns = Namespace::new("platform", "verify_index", "stocks");
+            let config = ObjectConfig::load(&ns).unwrap();
+            config.with_indices(vec![HashIndexConfig::new(vec!["symbol".into()], true)]).save(&ns).unwrap();
+            verify_exact_table_where(interpreter, r#"
+                tools::verify(stocks)
+            "#, vec![
+                "|----------------------------------------------------------------|",
+                "| id | kind  | location        | problem                         |",
+                "|----------------------------------------------------------------|",
+                r#"| 0  | index | index(symbol)#2 | duplicate key ("ABC") of row #0 |"#,
+                "|----------------------------------------------------------------|"
+            ]);
+        }
     }
 
     /// Package "util" tests
@@ -3,10 +3,12 @@
 // QueryEngine classes
 ////////////////////////////////////////////////////////////////////
 
+use crate::change_capture::{ChangeKind, Trigger};
 use crate::columns::Column;
+use crate::constraints::{Constraint, Constraints, ForeignKeys};
 use crate::cursor::Cursor;
 use crate::data_types::DataType;
-use crate::data_types::DataType::{TableType, VaryingType};
+use crate::data_types::DataType::{NumberType, TableType, VaryingType};
 
 use crate::dataframe::Dataframe;
 use crate::dataframe::Dataframe::*;
@@ -14,28 +16,36 @@ use crate::errors::throw;
 use crate::errors::Errors::*;
 use crate::errors::TypeMismatchErrors::{CollectionExpected, FunctionArgsExpected, QueryableExpected, TableExpected, UnsupportedType};
 use crate::expression::Conditions::True;
-use crate::expression::CreationEntity::{IndexEntity, TableEntity, TableFnEntity};
+use crate::expression::CreationEntity::{IndexEntity, SequenceEntity, TableEntity, TableFnEntity, TriggerEntity};
 use crate::expression::DatabaseOps::Mutation;
 use crate::expression::Expression::*;
 use crate::expression::MutateTarget::{IndexTarget, TableTarget};
 use crate::expression::Mutations::Declare;
-use crate::expression::TableOptions::Journaling;
-use crate::expression::{Conditions, DatabaseOps, Expression, Mutations, Queryables, TableOptions};
+use crate::expression::TableOptions::{Encrypted, Journaling, Replicated};
+use crate::expression::{Alterations, Conditions, DatabaseOps, Expression, Mutations, Queryables, TableOptions};
+use crate::hybrid_row_collection::HybridRowCollection;
 use crate::file_row_collection::FileRowCollection;
 use crate::inferences::Inferences;
-use crate::journaling::{JournaledRowCollection, TableFunction};
+use crate::journaling::{JournalMoment, JournaledRowCollection, TableFunction};
 use crate::machine::Machine;
 use crate::model_row_collection::ModelRowCollection;
-use crate::namespaces::Namespace;
+use crate::named_sequences::NamedSequence;
+use crate::namespace_leases::NamespaceLease;
+use crate::namespaces::{Namespace, TEMP_DATABASE};
+use crate::number_kind::NumberKind::{DateKind, I64Kind};
 use crate::numbers::Numbers::Ack;
+use crate::numbers::Numbers::DateValue;
+use crate::numbers::Numbers::I64Value;
 use crate::numbers::Numbers::RowsAffected;
-use crate::object_config::{HashIndexConfig, ObjectConfig};
+use crate::object_config::{HashIndexConfig, ObjectConfig, TriggerConfig};
 use crate::parameter::Parameter;
+use crate::partitioned_row_collection::PartitionedRowCollection;
 use crate::row_collection::RowCollection;
 use crate::sequences::Sequence;
 use crate::structures::Row;
 use crate::structures::Structure;
 use crate::structures::Structures::Soft;
+use crate::temp_tables::TempTables;
 use crate::typed_values::TypedValue;
 use crate::typed_values::TypedValue::*;
 use serde::{Deserialize, Serialize};
@@ -61,6 +71,7 @@ pub fn do_inquiry(
     queryable: &Queryables,
 ) -> std::io::Result<(Machine, TypedValue)> {
     match queryable {
+        Queryables::AsOf { from, moment } => do_table_as_of(ms, from, moment),
         Queryables::Limit { from, limit } => {
             let (ms, limit) = ms.evaluate(limit)?;
             do_table_or_view_query(&ms, from, &True, &limit)
@@ -77,23 +88,35 @@ pub fn do_mutation(
     mutation: &Mutations,
 ) -> std::io::Result<(Machine, TypedValue)> {
     match mutation {
-        Mutations::Append { path, source } =>
-            do_table_row_append(&ms, path, source),
+        Mutations::Alter { path, alteration } =>
+            do_table_alter(&ms, path, alteration),
+        Mutations::Append { path, source, is_bulk } =>
+            do_table_row_append(&ms, path, source, *is_bulk),
         Mutations::Create { path, entity } => match entity {
             IndexEntity { columns } =>
                 do_table_create_index(&ms, path, columns),
-            TableEntity { columns, from, options } =>
-                do_table_create_table(&ms, path, columns, from, options),
+            SequenceEntity { start, increment } =>
+                do_table_create_sequence(&ms, path, start, increment),
+            TableEntity { columns, constraints, from, options } if options.contains(&TableOptions::Temporary) =>
+                do_table_create_temp_table(&ms, path, columns, constraints, from, options),
+            TableEntity { columns, constraints, from, options } =>
+                do_table_create_table(&ms, path, columns, constraints, from, options),
             TableFnEntity { fx } =>
                 do_table_create_table_fn(&ms, fx),
+            TriggerEntity { name, kinds, fx } =>
+                do_table_create_trigger(&ms, path, name, kinds, fx),
         }
         Mutations::Declare(entity) => match entity {
             IndexEntity { columns } =>
                 do_table_declare_index(&ms, columns),
-            TableEntity { columns, from, options } =>
-                do_table_declare_table(&ms, columns, from, options),
+            TableEntity { columns, constraints, from, options } =>
+                do_table_declare_table(&ms, columns, constraints, from, options),
+            SequenceEntity { .. } =>
+                throw(NotImplemented("declare sequence".to_string())),
             TableFnEntity { fx } =>
                 do_table_declare_table_fn(&ms, fx),
+            TriggerEntity { .. } =>
+                throw(NotImplemented("declare trigger".to_string())),
         }
         Mutations::Delete { path, condition, limit } =>
             do_table_row_delete(&ms, path, condition, limit),
@@ -105,6 +128,8 @@ pub fn do_mutation(
             do_table_into(&ms, target, source),
         Mutations::Overwrite { path, source, condition, limit } =>
             do_table_row_overwrite(&ms, path, source, condition, limit),
+        Mutations::Rename { path, target } =>
+            do_table_rename(&ms, path, target),
         Mutations::Truncate { path, limit } =>
             match limit {
                 None => do_table_row_resize(&ms, path, Boolean(false)),
@@ -131,17 +156,23 @@ fn do_table_into(
             do_rows_from_query(&ms, source, table)?,
         Literal(TableValue(rc)) => (machine, rc.get_rows()),
         Literal(NamespaceValue(ns)) => {
-            (machine, FileRowCollection::open(ns)?.read_active_rows()?)
+            (machine, Dataframe::open(ns)?.read_active_rows()?)
         }
-        DatabaseOp(Mutation(Declare(TableEntity { columns, from, options }))) =>
+        DatabaseOp(Mutation(Declare(TableEntity { columns, from, .. }))) =>
             do_rows_from_table_declaration(&machine, table, from, columns)?,
         source =>
             do_rows_from_query(&ms, source, table)?,
     };
 
+    // verify the references of the rows
+    let (_, target) = machine.evaluate(table)?;
+    if let Some(foreign_keys) = ForeignKeys::load_value(&target)? {
+        foreign_keys.verify_rows(&rows)?;
+    }
+
     // write the rows to the target
     let mut inserted = 0;
-    let mut rc = expect_row_collection(&machine, table)?;
+    let mut rc = target.to_table()?;
     match rc.append_rows(rows) {
         ErrorValue(err) => return throw(err),
         Number(oc) => inserted += oc.to_i64(),
@@ -159,7 +190,8 @@ pub fn do_eval_ns(
         ErrorValue(err) => Ok((ms, ErrorValue(err))),
         StringValue(path) =>
             match path.split('.').collect::<Vec<_>>().as_slice() {
-                [d, s, n] => Ok((ms, NamespaceValue(Namespace::new(d, s, n)))),
+                // temporary tables are only reachable (by name) within their own session
+                [d, s, n] if *d != TEMP_DATABASE => Ok((ms, NamespaceValue(Namespace::new(d, s, n)))),
                 _ => Ok((ms, ErrorValue(InvalidNamespace(path))))
             }
         NamespaceValue(ns) => Ok((ms, NamespaceValue(ns))),
@@ -167,6 +199,20 @@ pub fn do_eval_ns(
     }
 }
 
+/// Advances a named sequence
+/// e.g.: nextval(ns("finance.trades.trade_ids"))
+pub fn do_eval_next_val(
+    ms: &Machine,
+    expr: &Expression,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let (ms, result) = do_eval_ns(ms, expr)?;
+    match result {
+        ErrorValue(err) => Ok((ms, ErrorValue(err))),
+        NamespaceValue(ns) => Ok((ms, Number(I64Value(NamedSequence::next_value(&ns)?)))),
+        other => throw(TypeMismatch(UnsupportedType(NumberType(I64Kind), other.get_type()))),
+    }
+}
+
 fn do_table_row_resize(
     ms: &Machine,
     table: &Expression,
@@ -184,6 +230,7 @@ fn do_table_row_append(
     ms: &Machine,
     table_expr: &Expression,
     from_expr: &Expression,
+    is_bulk: bool,
 ) -> std::io::Result<(Machine, TypedValue)> {
     // evaluate the table expression (table_expr)
     let (ms, table) = ms.evaluate(table_expr)?;
@@ -193,8 +240,17 @@ fn do_table_row_append(
             let (ms, result) = ms.evaluate(from_expr)?;
             match result.to_table_value() {
                 TableValue(src) => {
+                    // verify the references of the rows
+                    let rows = src.read_active_rows()?;
+                    if let Some(foreign_keys) = ForeignKeys::load_value(&table)? {
+                        foreign_keys.verify_rows(&rows)?;
+                    }
                     // write the rows to the dataframe
-                    Ok((ms, df.append_rows(src.read_active_rows()?)))
+                    if is_bulk {
+                        Ok((ms, Number(RowsAffected(df.bulk_load(rows)? as i64))))
+                    } else {
+                        Ok((ms, df.append_rows(rows)))
+                    }
                 }
                 _ => throw(TypeMismatch(QueryableExpected(from_expr.to_code())))
             }
@@ -212,7 +268,10 @@ fn do_table_row_delete(
     let (ms, limit) = ms.evaluate_opt(limit)?;
     let (ms, table) = ms.evaluate(from)?;
     match table.to_table_value() {
-        TableValue(mut rc) => Ok((ms.clone(), rc.delete_where(&ms, &condition, limit)?)),
+        TableValue(rc) => {
+            let foreign_keys = ForeignKeys::load_value(&table)?;
+            Ok((ms.clone(), rc.delete_where(&ms, &condition, limit, &foreign_keys)?))
+        }
         other => Ok((ms, ErrorValue(TypeMismatch(UnsupportedType(TableType(vec![], 0), other.get_type())))))
     }
 }
@@ -230,7 +289,8 @@ fn do_table_row_overwrite(
     match tv_table.to_table_value() {
         ErrorValue(err) => Ok((machine, ErrorValue(err))),
         TableValue(rc) => {
-            let (_, overwritten) = Dataframe::overwrite_where(rc, &machine, &fields, &values, condition, limit)?;
+            let foreign_keys = ForeignKeys::load_value(&tv_table)?;
+            let (_, overwritten) = Dataframe::overwrite_where(rc, &machine, &fields, &values, condition, limit, &foreign_keys)?;
             Ok((machine, overwritten))
         }
         other => throw(TypeMismatch(UnsupportedType(TableType(vec![], 0), other.get_type())))
@@ -239,6 +299,29 @@ fn do_table_row_overwrite(
 
 /// Evaluates the queryable [Expression] (e.g. from, limit and where)
 /// e.g.: from ns("interpreter.select.stocks") where last_sale > 1.0 limit 1
+/// Reconstructs a journaled table as of a date or event ID
+/// ex: from ns("journal.as_of.stocks") as of 3
+fn do_table_as_of(
+    ms: &Machine,
+    from: &Expression,
+    moment: &Expression,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let from = match from {
+        Expression::From(src) => src.deref(),
+        other => other
+    };
+    let (ms, table) = ms.evaluate(from)?;
+    let (ms, moment) = ms.evaluate(moment)?;
+    let moment = match moment {
+        ErrorValue(err) => return throw(err),
+        Number(DateValue(millis)) => JournalMoment::AtTime(millis),
+        Number(n) => JournalMoment::AtEvent(n.to_usize()),
+        z => return throw(TypeMismatch(UnsupportedType(NumberType(DateKind), z.get_type())))
+    };
+    let mrc = JournaledRowCollection::open_value(&table)?.as_of(moment)?;
+    Ok((ms, TableValue(Model(mrc))))
+}
+
 pub fn do_table_or_view_query(
     ms: &Machine,
     src: &Expression,
@@ -247,6 +330,7 @@ pub fn do_table_or_view_query(
 ) -> std::io::Result<(Machine, TypedValue)> {
     //println!("do_table_or_view_query: src = {src:?}, condition = {condition:?}, limit = {limit:?}");
     let (machine, df) = ms.evaluate_as_dataframe(src)?;
+    let df = df.prune(&Some(condition.to_owned()));
     let limit = limit.to_usize();
     let columns = df.get_columns().clone();
     let mut cursor = Cursor::filter(Box::new(df), condition.to_owned());
@@ -287,7 +371,7 @@ fn do_table_row_update(
     match tv_table.to_table_value() {
         ErrorValue(err) => Ok((ms, ErrorValue(err))),
         TableValue(rc) =>
-            match Dataframe::update_where(rc, &ms, &fields, &values, &condition, limit) {
+            match Dataframe::update_where(rc, &ms, &fields, &values, &condition, limit, &ForeignKeys::load_value(&tv_table)?) {
                 Ok(modified) => Ok((ms, modified)),
                 Err(err) => throw(Exact(err.to_string())),
             }
@@ -295,6 +379,44 @@ fn do_table_row_update(
     }
 }
 
+fn do_table_alter(
+    ms: &Machine,
+    table: &Expression,
+    alteration: &Alterations,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let (machine, result) = ms.evaluate(table)?;
+    match result {
+        ErrorValue(msg) => throw(msg),
+        Null | Undefined => Ok((machine, result)),
+        TableValue(_rcv) =>
+            throw(Exact("Memory collections do not support the 'alter' keyword".to_string())),
+        NamespaceValue(ns) => {
+            let migrated = Dataframe::alter_table(&ns, alteration)?;
+            Ok((machine, Number(RowsAffected(migrated as i64))))
+        }
+        z => throw(TypeMismatch(CollectionExpected(z.to_code())))
+    }
+}
+
+fn do_table_rename(
+    ms: &Machine,
+    table: &Expression,
+    target: &Expression,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let (machine, result) = ms.evaluate(table)?;
+    let (machine, target) = machine.evaluate(target)?;
+    match (result, target) {
+        (ErrorValue(msg), _) | (_, ErrorValue(msg)) => throw(msg),
+        (NamespaceValue(ns), NamespaceValue(target)) => {
+            Dataframe::rename_table(&ns, &target)?;
+            Ok((machine, Number(Ack)))
+        }
+        (TableValue(_rcv), _) =>
+            throw(Exact("Memory collections do not support the 'rename' keyword".to_string())),
+        (NamespaceValue(..), z) | (z, ..) => throw(TypeMismatch(CollectionExpected(z.to_code())))
+    }
+}
+
 fn do_table_create_index(
     ms: &Machine,
     index: &Expression,
@@ -326,10 +448,35 @@ fn do_table_create_index(
     }
 }
 
+fn do_table_create_sequence(
+    ms: &Machine,
+    sequence: &Expression,
+    start: &Option<Box<Expression>>,
+    increment: &Option<Box<Expression>>,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let (ms, result) = ms.evaluate(sequence)?;
+    match result {
+        ErrorValue(err) => throw(err),
+        NamespaceValue(ns) => {
+            let (ms, start) = ms.evaluate_opt(start)?;
+            let (ms, increment) = ms.evaluate_opt(increment)?;
+            let as_i64 = |value: TypedValue, default: i64| match value {
+                Null | Undefined => Ok(default),
+                Number(n) => Ok(n.to_i64()),
+                z => throw(TypeMismatch(UnsupportedType(NumberType(I64Kind), z.get_type())))
+            };
+            NamedSequence::create(&ns, as_i64(start, 1)?, as_i64(increment, 1)?)?;
+            Ok((ms, Number(Ack)))
+        }
+        z => throw(TypeMismatch(CollectionExpected(z.to_code())))
+    }
+}
+
 fn do_table_create_table(
     ms: &Machine,
     table: &Expression,
     columns: &Vec<Parameter>,
+    constraints: &Vec<Constraint>,
     from: &Option<Box<Expression>>,
     options: &Vec<TableOptions>,
 ) -> std::io::Result<(Machine, TypedValue)> {
@@ -339,13 +486,68 @@ fn do_table_create_table(
         ErrorValue(err) => Ok((ms, ErrorValue(err))),
         TableValue(_rcv) => throw(Exact("Memory collections do not 'create' keyword".to_string())),
         NamespaceValue(ns) => {
+            // register the table with the tables it references
+            ForeignKeys::register(&ns, constraints)?;
             // determine the table kind
+            let partitions = options.iter().find_map(|o| match o {
+                TableOptions::Partitioned(columns) => Some(columns.to_owned()),
+                _ => None
+            });
+            let has_ttl = options.iter().any(|o| matches!(o, TableOptions::Ttl(..)));
+            let is_hybrid = options.iter().any(|o| matches!(o, TableOptions::Hybrid(..)));
             let rc =
-                if options.contains(&Journaling) {
+                if is_hybrid {
+                    if options.contains(&Journaling) || partitions.is_some() {
+                        return throw(NotImplemented("journaled or partitioned hybrid tables".to_string()));
+                    }
+                    if options.contains(&Replicated) || has_ttl {
+                        return throw(NotImplemented("replication or ttl on a hybrid table".to_string()));
+                    }
+                    Hybrid(HybridRowCollection::create_table(&ns, columns, options)?)
+                } else if options.contains(&Journaling) {
+                    if options.contains(&Encrypted) {
+                        return throw(NotImplemented("encrypted journaling".to_string()));
+                    }
+                    if partitions.is_some() {
+                        return throw(NotImplemented("partitioned journaling".to_string()));
+                    }
+                    if has_ttl {
+                        return throw(NotImplemented("ttl on a journaled table".to_string()));
+                    }
                     Journaled(JournaledRowCollection::new(&ns, columns)?)
+                } else if let Some(partitions) = partitions {
+                    if options.contains(&Replicated) {
+                        return throw(NotImplemented("partitioned replication".to_string()));
+                    }
+                    if has_ttl {
+                        return throw(NotImplemented("ttl on a partitioned table".to_string()));
+                    }
+                    let options = options.iter()
+                        .filter(|o| !matches!(o, TableOptions::Partitioned(..)))
+                        .cloned()
+                        .collect();
+                    Partitioned(PartitionedRowCollection::create_table(&ns, columns, &partitions, &options)?)
                 } else {
-                    Disk(FileRowCollection::create_table(&ns, columns)?)
+                    Disk(FileRowCollection::create_table_with_options(&ns, columns, options)?)
                 };
+            // record the constraints of the table (along with the unique indices enforcing them),
+            // and (re)start its identities
+            let config = ObjectConfig::load(&ns)?;
+            let mut indices = config.get_indices();
+            for constraint in constraints {
+                if let Constraint::Unique(names) = constraint {
+                    let index = HashIndexConfig::new(names.to_owned(), true);
+                    if !indices.contains(&index) { indices.push(index) }
+                }
+            }
+            config.with_indices(indices).with_constraints(constraints.to_owned()).save(&ns)?;
+            for constraint in constraints {
+                if let Constraint::Identity(column) = constraint {
+                    NamedSequence::create_identity(&ns, column)?;
+                }
+            }
+            let rc = rc.with_constraints(Constraints::build(
+                ns.get_full_name().as_str(), &Column::from_parameters(columns), constraints.to_owned()))?;
             // append the rows of the "from" clause
             Ok((populate_dataframe_opt(&ms, rc, from)?, Number(Ack)))
         }
@@ -353,6 +555,31 @@ fn do_table_create_table(
     }
 }
 
+/// Creates a temporary table within the session's scratch schema;
+/// binding the table's namespace to the given name.
+/// ex: create temp table scratch (symbol: String(8), last_sale: f64)
+fn do_table_create_temp_table(
+    ms: &Machine,
+    table: &Expression,
+    columns: &Vec<Parameter>,
+    constraints: &Vec<Constraint>,
+    from: &Option<Box<Expression>>,
+    options: &Vec<TableOptions>,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let name = match table {
+        Variable(name) => name,
+        other => return throw(Exact(format!("A temporary table requires a name, found {}", other.to_code())))
+    };
+    let (ms, ns) = TempTables::get_namespace(ms, name);
+    let options = options.iter()
+        .filter(|o| **o != TableOptions::Temporary)
+        .cloned()
+        .collect();
+    let (ms, result) = do_table_create_table(
+        &ms, &Literal(NamespaceValue(ns.to_owned())), columns, constraints, from, &options)?;
+    Ok((ms.with_variable(name, NamespaceValue(ns)), result))
+}
+
 fn do_table_create_table_fn(
     ms: &Machine,
     fx: &Box<Expression>,
@@ -373,6 +600,34 @@ fn do_table_create_table_fn(
     }
 }
 
+/// Creates (or replaces) a trigger of a table
+/// ex: create trigger audit on stocks after update fn(old, new) => ...
+fn do_table_create_trigger(
+    ms: &Machine,
+    table: &Expression,
+    name: &str,
+    kinds: &Vec<ChangeKind>,
+    fx: &Expression,
+) -> std::io::Result<(Machine, TypedValue)> {
+    let (ms, result) = ms.evaluate(table)?;
+    match result {
+        ErrorValue(err) => throw(err),
+        TableValue(_rcv) => throw(Exact("Memory collections do not support triggers".to_string())),
+        NamespaceValue(ns) => {
+            let trigger = TriggerConfig::new(name, kinds.to_owned(), fx.to_code().as_str());
+            Trigger::compile(&trigger)?;
+            let config = ObjectConfig::load(&ns)?;
+            let mut triggers = config.get_triggers().into_iter()
+                .filter(|t| t.get_name() != name)
+                .collect::<Vec<_>>();
+            triggers.push(trigger);
+            config.with_triggers(triggers).save(&ns)?;
+            Ok((ms, Number(Ack)))
+        }
+        z => throw(TypeMismatch(CollectionExpected(z.to_code())))
+    }
+}
+
 fn do_table_declare_index(
     ms: &Machine,
     columns: &Vec<Expression>,
@@ -384,11 +639,14 @@ fn do_table_declare_index(
 fn do_table_declare_table(
     ms: &Machine,
     columns: &Vec<Parameter>,
+    constraints: &Vec<Constraint>,
     from: &Option<Box<Expression>>,
     options: &Vec<TableOptions>,
 ) -> std::io::Result<(Machine, TypedValue)> {
     let columns = Column::from_parameters(columns);
-    Ok((ms.to_owned(), TableValue(Model(ModelRowCollection::with_rows(columns, Vec::new())))))
+    let constraints = Constraints::build("table", &columns, constraints.to_owned());
+    let mrc = ModelRowCollection::with_rows(columns, Vec::new()).with_constraints(constraints);
+    Ok((ms.to_owned(), TableValue(Model(mrc))))
 }
 
 fn do_table_declare_table_fn(
@@ -409,7 +667,9 @@ fn do_table_drop(ms: &Machine, table: &Expression) -> std::io::Result<(Machine,
     match table {
         ErrorValue(err) => Ok((machine, ErrorValue(err))),
         NamespaceValue(ns) => {
+            NamespaceLease::acquire(&ns)?;
             let result = fs::remove_file(ns.get_table_file_path());
+            NamespaceLease::release(&ns);
             Ok((machine, if result.is_ok() { Number(Ack) } else { Boolean(false) }))
         }
         _ => Ok((machine, Boolean(false)))
@@ -431,9 +691,9 @@ pub fn do_select(
             match table_v {
                 ErrorValue(err) => throw(err),
                 NamespaceValue(ns) =>
-                    match FileRowCollection::open(&ns) {
-                        Ok(frc) =>
-                            Ok(do_select_go(ms, Disk(frc), fields, condition, group_by, having, order_by, limit)),
+                    match Dataframe::open(&ns) {
+                        Ok(df) =>
+                            Ok(do_select_go(ms, df, fields, condition, group_by, having, order_by, limit)),
                         Err(err) => throw(Exact(err.to_string()))
                     }
                 TableValue(rc) =>
@@ -456,6 +716,7 @@ fn do_select_go(
 ) -> (Machine, TypedValue) {
     // cache the initial state
     let ms0 = ms.clone();
+    let df0 = df0.prune(condition);
 
     // step 1: determine output layout and limits
     let (_, rc1, new_columns, limit) =
@@ -652,7 +913,7 @@ fn expect_rows(
             }
             Ok(rows)
         }
-        NamespaceValue(ns) => FileRowCollection::open(&ns)?.read_active_rows(),
+        NamespaceValue(ns) => Dataframe::open(&ns)?.read_active_rows(),
         Structured(s) => Ok(vec![Row::from_tuples(0, columns, &s.to_name_values())]),
         TableValue(rcv) => Ok(rcv.get_rows()),
         tv => throw(TypeMismatch(UnsupportedType(TableType(Parameter::from_columns(columns), 0), tv.get_type())))
@@ -781,10 +1042,16 @@ fn column_not_found(name: &str, columns: &Vec<Column>) -> String {
 #[cfg(test)]
 mod sql_tests {
     use crate::columns::Column;
+    use crate::data_types::DataType::{NumberType, StringType};
     use crate::dataframe::Dataframe::Model;
     use crate::interpreter::Interpreter;
     use crate::model_row_collection::ModelRowCollection;
-    use crate::numbers::Numbers::{Ack, RowsAffected};
+    use crate::namespaces::Namespace;
+    use crate::number_kind::NumberKind::{F32Kind, I64Kind};
+    use crate::numbers::Numbers::{Ack, F32Value, I64Value, RowsAffected};
+    use crate::object_config::{HashIndexConfig, ObjectConfig};
+    use crate::parameter::Parameter;
+    use crate::structures::Row;
     use crate::testdata::*;
     use crate::typed_values::TypedValue::*;
 
@@ -807,6 +1074,118 @@ mod sql_tests {
         verify_exact("'Hello' like 'H%ll%'", Boolean(false));
     }
 
+    #[test]
+    fn test_table_alter_in_namespace() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(Number(RowsAffected(2)), interpreter.evaluate(r#"
+                stocks := ns("interpreter.alter.stocks")
+                table(symbol: String(8), exchange: String(8), last_sale: f64) ~> stocks
+                append stocks from [
+                    { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                    { symbol: "GOTO", exchange: "OTC", last_sale: 0.1428 }
+                ]
+            "#).unwrap());
+        assert_eq!(Number(Ack), interpreter.evaluate(r#"
+                create index stocks on [symbol, exchange]
+            "#).unwrap());
+
+        // add, rename, retype and drop columns
+        assert_eq!(Number(RowsAffected(2)), interpreter.evaluate(r#"
+                alter table ns("interpreter.alter.stocks") add column volume: i64
+            "#).unwrap());
+        assert_eq!(Number(RowsAffected(2)), interpreter.evaluate(r#"
+                alter table ns("interpreter.alter.stocks") rename column exchange to market
+            "#).unwrap());
+        assert_eq!(Number(RowsAffected(2)), interpreter.evaluate(r#"
+                alter table ns("interpreter.alter.stocks") alter column last_sale: f32
+            "#).unwrap());
+        assert!(interpreter.evaluate(r#"
+                alter table ns("interpreter.alter.stocks") drop column exchange
+            "#).is_err());
+
+        // verify the rows and the configuration
+        let params = vec![
+            Parameter::new("symbol", StringType(8)),
+            Parameter::new("market", StringType(8)),
+            Parameter::new("last_sale", NumberType(F32Kind)),
+            Parameter::new("volume", NumberType(I64Kind)),
+        ];
+        assert_eq!(
+            interpreter.evaluate("from stocks").unwrap(),
+            TableValue(Model(ModelRowCollection::from_columns_and_rows(&Column::from_parameters(&params), &vec![
+                Row::new(0, vec![StringValue("BIZ".into()), StringValue("NYSE".into()), Number(F32Value(23.66)), Number(I64Value(0))]),
+                Row::new(1, vec![StringValue("GOTO".into()), StringValue("OTC".into()), Number(F32Value(0.1428)), Number(I64Value(0))]),
+            ])))
+        );
+        let config = ObjectConfig::load(&Namespace::parse("interpreter.alter.stocks").unwrap()).unwrap();
+        assert_eq!(config.get_columns(), params);
+        assert_eq!(config.get_indices(), vec![
+            HashIndexConfig::new(vec!["symbol".into(), "market".into()], false)
+        ]);
+
+        // dropping an indexed column also drops the index
+        assert_eq!(Number(RowsAffected(2)), interpreter.evaluate(r#"
+                alter table ns("interpreter.alter.stocks") drop column market
+            "#).unwrap());
+        let config = ObjectConfig::load(&Namespace::parse("interpreter.alter.stocks").unwrap()).unwrap();
+        assert_eq!(config.get_columns().len(), 3);
+        assert_eq!(config.get_indices(), vec![]);
+    }
+
+    #[test]
+    fn test_table_rename() {
+        let mut interpreter = Interpreter::new();
+        let (old_ns, new_ns) = (Namespace::parse("interpreter.rename.stocks").unwrap(),
+                                Namespace::parse("interpreter.archive.stocks_2025").unwrap());
+        interpreter.evaluate(r#"
+            drop table ns("interpreter.archive.stocks_2025")
+            drop table ns("interpreter.rename.trades")
+            create table ns("interpreter.rename.stocks") (symbol: String(8), exchange: String(8), last_sale: f64)
+            create index ns("interpreter.rename.stocks") on [symbol]
+            create table ns("interpreter.rename.trades") (
+                symbol: String(8) references ns("interpreter.rename.stocks")(symbol),
+                quantity: i64
+            )
+            append ns("interpreter.rename.stocks") from [
+                { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                { symbol: "GOTO", exchange: "OTC", last_sale: 0.1428 }
+            ]
+        "#).unwrap();
+
+        // move the table to another schema (under a new name)
+        assert_eq!(Number(Ack), interpreter.evaluate(r#"
+            rename table ns("interpreter.rename.stocks") to ns("interpreter.archive.stocks_2025")
+        "#).unwrap());
+        assert!(!std::path::Path::new(&old_ns.get_root_path()).exists());
+        assert!(std::path::Path::new(&new_ns.get_file_path("table.blob")).exists());
+        assert_eq!(ObjectConfig::load(&new_ns).unwrap().get_indices(),
+                   vec![HashIndexConfig::new(vec!["symbol".into()], false)]);
+        assert_eq!(
+            interpreter.evaluate(r#"from ns("interpreter.archive.stocks_2025")"#).unwrap(),
+            TableValue(Model(ModelRowCollection::from_columns_and_rows(
+                &Column::from_parameters(&make_quote_parameters()), &vec![
+                    make_quote(0, "BIZ", "NYSE", 23.66),
+                    make_quote(1, "GOTO", "OTC", 0.1428),
+                ])))
+        );
+        assert!(interpreter.evaluate(r#"from ns("interpreter.rename.stocks")"#).is_err());
+
+        // the foreign keys referencing the table follow it
+        let constraints = ObjectConfig::load(&Namespace::parse("interpreter.rename.trades").unwrap())
+            .unwrap().get_constraints();
+        assert_eq!(constraints[0].to_code(), r#"references ns("interpreter.archive.stocks_2025")(symbol)"#);
+
+        // an existing table is never replaced
+        interpreter.evaluate(r#"
+            create table ns("interpreter.rename.stocks") (symbol: String(8), exchange: String(8), last_sale: f64)
+        "#).unwrap();
+        let result = interpreter.evaluate(r#"
+            rename table ns("interpreter.rename.stocks") to ns("interpreter.archive.stocks_2025")
+        "#);
+        assert_eq!(result.map_err(|e| e.to_string()),
+                   Err("Table interpreter.archive.stocks_2025 already exists".to_string()));
+    }
+
     #[test]
     fn test_table_create_ephemeral() {
         verify_exact(r#"
@@ -829,6 +1208,68 @@ mod sql_tests {
                 )"#, Number(Ack))
     }
 
+    #[test]
+    fn test_table_create_encrypted() {
+        let mut interpreter = Interpreter::new();
+        let phys_columns = Column::from_parameters(&make_quote_parameters());
+        assert_eq!(Number(Ack), interpreter.evaluate(r#"
+                create table ns("interpreter.encrypted.stocks") (
+                    symbol: String(8),
+                    exchange: String(8),
+                    last_sale: f64
+                ) with encrypted"#).unwrap());
+        assert_eq!(Number(RowsAffected(2)), interpreter.evaluate(r#"
+                [{ symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                 { symbol: "GOTO", exchange: "OTC", last_sale: 0.1428 }] ~> ns("interpreter.encrypted.stocks")
+            "#).unwrap());
+        assert_eq!(
+            interpreter.evaluate(r#"from ns("interpreter.encrypted.stocks")"#).unwrap(),
+            TableValue(Model(ModelRowCollection::from_columns_and_rows(&phys_columns, &vec![
+                make_quote(0, "BIZ", "NYSE", 23.66),
+                make_quote(1, "GOTO", "OTC", 0.1428),
+            ])))
+        );
+    }
+
+    #[test]
+    fn test_table_append_with_bulk() {
+        let mut interpreter = Interpreter::new();
+        let phys_columns = Column::from_parameters(&make_quote_parameters());
+        assert_eq!(Number(Ack), interpreter.evaluate(r#"
+                create table ns("interpreter.bulk.stocks") (
+                    symbol: String(8) unique,
+                    exchange: String(8),
+                    last_sale: f64
+                )"#).unwrap());
+        assert_eq!(Number(RowsAffected(3)), interpreter.evaluate(r#"
+                append ns("interpreter.bulk.stocks") from [
+                    { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                    { symbol: "GOTO", exchange: "OTC", last_sale: 0.1428 },
+                    { symbol: "VERY_LONG_SYMBOL", exchange: "AMEX", last_sale: 5.5 }
+                ] with bulk
+            "#).unwrap());
+        assert_eq!(
+            interpreter.evaluate(r#"from ns("interpreter.bulk.stocks")"#).unwrap(),
+            TableValue(Model(ModelRowCollection::from_columns_and_rows(&phys_columns, &vec![
+                make_quote(0, "BIZ", "NYSE", 23.66),
+                make_quote(1, "GOTO", "OTC", 0.1428),
+                make_quote(2, "VERY_LONG_SYMBOL", "AMEX", 5.5),
+            ])))
+        );
+
+        // the batch is rejected as a whole if any of its rows violates a constraint
+        let result = interpreter.evaluate(r#"
+                append ns("interpreter.bulk.stocks") from [
+                    { symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+                    { symbol: "GOTO", exchange: "NYSE", last_sale: 0.1 }
+                ] with bulk
+            "#);
+        assert_eq!(result.map_err(|e| e.to_string()),
+                   Err(r#"interpreter.bulk.stocks.symbol violates unique: "GOTO""#.to_string()));
+        let df = interpreter.evaluate(r#"ns("interpreter.bulk.stocks")"#).unwrap().to_table().unwrap();
+        assert_eq!(df.len().unwrap(), 3);
+    }
+
     #[test]
     fn test_table_crud_in_namespace() {
         let mut interpreter = Interpreter::new();
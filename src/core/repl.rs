@@ -22,6 +22,7 @@ use crate::structures::Row;
 use crate::structures::Structures::{Hard, Soft};
 use crate::structures::{HardStructure, SoftStructure, Structure};
 use crate::table_renderer::TableRenderer;
+use crate::temp_tables::TempTables;
 use crate::typed_values::TypedValue;
 use crate::typed_values::TypedValue::*;
 use chrono::{DateTime, Local, TimeDelta};
@@ -95,6 +96,9 @@ pub fn do_terminal(
     while state.is_alive {
         (stdout, state) = do_terminal_input(state, stdout, reader)?
     }
+
+    // drop the session's temporary tables
+    TempTables::release(state.interpreter.get_machine());
     Ok(())
 }
 
@@ -322,7 +326,8 @@ async fn start_server(args: Vec<String>) -> std::io::Result<()> {
     // start the server
     info!("Welcome to Oxide Server.\n");
     info!("Starting server on port {}:{}.", host, port);
-    let server = actix_web::HttpServer::new(move || web_routes!(SharedState::new()))
+    let state = SharedState::new();
+    let server = actix_web::HttpServer::new(move || web_routes!(state.to_owned()))
         .bind(format!("{}:{}", host, port))?
         .run();
     server.await?;
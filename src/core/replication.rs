@@ -0,0 +1,575 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// replication module
+////////////////////////////////////////////////////////////////////
+
+use crate::byte_code_compiler::ByteCodeCompiler;
+use crate::encryption::EncryptionKey;
+use crate::errors::throw;
+use crate::errors::Errors::Exact;
+use crate::expression::TableOptions;
+use crate::file_row_collection::FileRowCollection;
+use crate::namespaces::Namespace;
+use crate::object_config::ObjectConfig;
+use crate::row_collection::RowCollection;
+use crate::row_metadata::RowMetadata;
+use crate::structures::Row;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::ErrorValue;
+use actix::{Actor, AsyncContext, StreamHandler};
+use actix_web::http::header::AUTHORIZATION;
+use actix_web::HttpRequest;
+use actix_web_actors::ws;
+use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::{SinkExt, StreamExt};
+use log::{error, info};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use shared_lib::cnv_error;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::runtime::Runtime;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{connect_async, MaybeTlsStream, WebSocketStream};
+
+/// the websocket path of the replication channel
+pub const REPLICATION_PATH: &str = "/replication";
+
+/// the environment variable containing the shared secret that followers must present
+/// to their leader; replication is disabled unless it is set.
+pub const TOKEN_ENV_VAR: &str = "OXIDE_REPLICATION_TOKEN";
+
+/// the maximum number of events transmitted in a single batch
+const BATCH_SIZE: usize = 1000;
+
+/// how often the leader checks the replication logs for new events
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// how long a follower waits before reconnecting to its leader
+const RECONNECT_DELAY: Duration = Duration::from_secs(3);
+
+/// Represents a change to a replicated table
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ReplicationEvent {
+    Resized { size: usize },
+    RowChanged { id: usize, row: Row, metadata: RowMetadata },
+}
+
+/// Represents a message exchanged between a leader and its followers
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum ReplicationMessage {
+    /// (follower -> leader) subscribes to the events of a table starting at a sequence number
+    Subscribe { ns: Namespace, since: u64 },
+    /// (leader -> follower) the (JSON) configuration of a subscribed table; a configuration
+    /// omits its empty fields, so it can't be encoded as bincode (which isn't self-describing).
+    Config { ns: Namespace, cfg: String },
+    /// (leader -> follower) a batch of events; `next` is the sequence number to resume from
+    Events { ns: Namespace, events: Vec<ReplicationEvent>, next: u64 },
+    /// (leader -> follower) a subscription could not be honored
+    Failure { ns: Namespace, message: String },
+}
+
+impl ReplicationMessage {
+    pub fn decode(bytes: &[u8]) -> std::io::Result<Self> {
+        ByteCodeCompiler::unwrap_as_result(bincode::deserialize(bytes))
+    }
+
+    pub fn encode(&self) -> std::io::Result<Vec<u8>> {
+        ByteCodeCompiler::unwrap_as_result(bincode::serialize(self))
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+// ReplicationLog class
+////////////////////////////////////////////////////////////////////
+
+/// Append-only log of the [ReplicationEvent]s of a table. Each entry is
+/// length-prefixed, and its byte offset serves as its sequence number.
+#[derive(Clone)]
+pub struct ReplicationLog {
+    encryption: Option<EncryptionKey>,
+    file: Arc<File>,
+}
+
+impl ReplicationLog {
+    /// Creates (or truncates) the replication log of a table
+    pub fn create(ns: &Namespace) -> std::io::Result<Self> {
+        let path = Self::get_log_file_path(&ns.get_table_file_path());
+        fs::create_dir_all(ns.get_root_path())?;
+        OpenOptions::new().create(true).truncate(true).write(true).open(&path)?;
+        Self::open_file(path.as_str())
+    }
+
+    /// Returns the path of the replication log for the given table file
+    pub fn get_log_file_path(table_path: &str) -> String {
+        format!("{}.replication", table_path)
+    }
+
+    /// Opens the replication log of a table
+    pub fn open(ns: &Namespace) -> std::io::Result<Self> {
+        Self::open_file(Self::get_log_file_path(&ns.get_table_file_path()).as_str())
+    }
+
+    /// Opens (or creates) a replication log by file
+    pub fn open_file(path: &str) -> std::io::Result<Self> {
+        let file = OpenOptions::new().read(true).append(true).create(true).open(path)?;
+        Ok(Self {
+            encryption: None,
+            file: Arc::new(file),
+        })
+    }
+
+    /// Enables encryption at rest for the entries of this log
+    pub fn with_encryption(self, key: EncryptionKey) -> Self {
+        Self {
+            encryption: Some(key),
+            ..self
+        }
+    }
+
+    /// Appends an event to the log; returning its sequence number
+    pub fn append(&self, event: &ReplicationEvent) -> std::io::Result<u64> {
+        self.append_entry(event)
+    }
+
+    /// Appends an entry of any kind to the log; returning its sequence number
+    pub fn append_entry<A: Serialize>(&self, entry: &A) -> std::io::Result<u64> {
+        let payload = ByteCodeCompiler::unwrap_as_result(bincode::serialize(entry))?;
+        let payload = match &self.encryption {
+            Some(key) => key.encrypt(&payload, &[])?,
+            None => payload
+        };
+        // the entry is written in a single (append-mode) write
+        let mut entry = Vec::with_capacity(8 + payload.len());
+        entry.extend((payload.len() as u64).to_be_bytes());
+        entry.extend(payload);
+        let sequence = self.file.metadata()?.len();
+        (&*self.file).write_all(&entry)?;
+        Ok(sequence)
+    }
+
+    /// Reads up to `limit` events starting at the sequence number `since`;
+    /// returning the events and the sequence number to resume from.
+    pub fn read_since(&self, since: u64, limit: usize) -> std::io::Result<(Vec<ReplicationEvent>, u64)> {
+        let (entries, next) = self.read_entries_since(since, limit)?;
+        Ok((entries.into_iter().map(|(_, event)| event).collect(), next))
+    }
+
+    /// Reads up to `limit` entries (along with their sequence numbers) starting at the
+    /// sequence number `since`; returning the entries and the sequence number to resume from.
+    pub fn read_entries_since<A: DeserializeOwned>(
+        &self,
+        since: u64,
+        limit: usize,
+    ) -> std::io::Result<(Vec<(u64, A)>, u64)> {
+        let end = self.file.metadata()?.len();
+        let mut entries = Vec::new();
+        let mut offset = since;
+        while offset + 8 <= end && entries.len() < limit {
+            let mut len_bytes = [0u8; 8];
+            self.file.read_exact_at(&mut len_bytes, offset)?;
+            let len = u64::from_be_bytes(len_bytes);
+            // stop at a partially written entry
+            if offset + 8 + len > end { break; }
+            let mut payload = vec![0u8; len as usize];
+            self.file.read_exact_at(&mut payload, offset + 8)?;
+            let payload = match &self.encryption {
+                Some(key) => key.decrypt(&payload, &[])?,
+                None => payload
+            };
+            entries.push((offset, ByteCodeCompiler::unwrap_as_result(bincode::deserialize(&payload))?));
+            offset += 8 + len;
+        }
+        Ok((entries, offset))
+    }
+}
+
+/// Returns the shared secret authenticating followers (see [TOKEN_ENV_VAR])
+pub fn get_replication_token() -> Option<String> {
+    env::var(TOKEN_ENV_VAR).ok().filter(|token| !token.is_empty())
+}
+
+/// Applies a replicated event to a (follower) table; marking the affected row as replicated
+pub fn apply_event(frc: &mut FileRowCollection, event: &ReplicationEvent) -> TypedValue {
+    match event {
+        ReplicationEvent::Resized { size } => frc.resize(*size),
+        ReplicationEvent::RowChanged { id, row, metadata } =>
+            match frc.overwrite_row(*id, row.to_owned()) {
+                ErrorValue(err) => ErrorValue(err),
+                _ => frc.overwrite_row_metadata(*id, metadata.with_replicated(true)),
+            }
+    }
+}
+
+////////////////////////////////////////////////////////////////////
+// ReplicationClient class (follower)
+////////////////////////////////////////////////////////////////////
+
+/// Oxide Replication Client; applies a leader's events to local tables
+pub struct ReplicationClient {
+    read: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    write: SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>,
+    subscriptions: HashMap<String, Namespace>,
+}
+
+impl ReplicationClient {
+    /// Connects to the replication channel of a leader; authenticating with the shared secret
+    pub async fn connect(host: &str, port: u16, token: &str) -> std::io::Result<Self> {
+        let mut request = format!("ws://{host}:{port}{REPLICATION_PATH}").into_client_request()
+            .map_err(|e| cnv_error!(e))?;
+        request.headers_mut().insert("Authorization", format!("Bearer {token}").parse()
+            .map_err(|e| cnv_error!(e))?);
+        let (ws_stream, _response) = connect_async(request).await
+            .map_err(|e| cnv_error!(e))?;
+        let (write, read) = ws_stream.split();
+        Ok(Self { read, write, subscriptions: HashMap::new() })
+    }
+
+    /// Returns the path of the file containing the replication checkpoint of a local table
+    pub fn get_checkpoint_file_path(local_ns: &Namespace) -> String {
+        local_ns.get_file_path("replica")
+    }
+
+    /// Receives the next message from the leader; returning the number of events applied
+    pub async fn receive(&mut self) -> std::io::Result<usize> {
+        let bytes = match self.read.next().await {
+            None => return throw(Exact("Replication channel closed".into())),
+            Some(Ok(Message::Binary(bytes))) => bytes,
+            Some(Ok(..)) => return Ok(0),
+            Some(Err(err)) => return throw(Exact(err.to_string()))
+        };
+        match ReplicationMessage::decode(&bytes)? {
+            ReplicationMessage::Config { ns, cfg } => {
+                let local_ns = self.get_local_namespace(&ns)?;
+                let cfg = serde_json::from_str::<ObjectConfig>(&cfg).map_err(|e| cnv_error!(e))?;
+                Self::create_replica(&local_ns, &cfg)?;
+                Ok(0)
+            }
+            ReplicationMessage::Events { ns, events, next } => {
+                let local_ns = self.get_local_namespace(&ns)?;
+                let mut frc = FileRowCollection::open(&local_ns)?.with_write_protection(false);
+                for event in events.iter() {
+                    if let ErrorValue(err) = apply_event(&mut frc, event) {
+                        return throw(err);
+                    }
+                }
+                fs::write(Self::get_checkpoint_file_path(&local_ns), next.to_string())?;
+                Ok(events.len())
+            }
+            ReplicationMessage::Failure { ns, message } =>
+                throw(Exact(format!("Replication of {} failed: {}", ns, message))),
+            ReplicationMessage::Subscribe { .. } =>
+                throw(Exact("Unexpected replication message".into())),
+        }
+    }
+
+    /// Subscribes to the events of a leader's table, which are applied to the local table;
+    /// resuming from the last checkpoint when the local table already exists.
+    pub async fn subscribe(&mut self, leader_ns: &Namespace, local_ns: &Namespace) -> std::io::Result<()> {
+        let checkpoint_path = Self::get_checkpoint_file_path(local_ns);
+        let since = if Path::new(&local_ns.get_config_file_path()).exists() {
+            fs::read_to_string(&checkpoint_path)
+                .map(|s| s.trim().parse::<u64>().unwrap_or(0))
+                .unwrap_or(0)
+        } else { 0 };
+        self.subscriptions.insert(leader_ns.id(), local_ns.to_owned());
+        let message = ReplicationMessage::Subscribe { ns: leader_ns.to_owned(), since };
+        self.write.send(Message::Binary(message.encode()?)).await
+            .map_err(|e| cnv_error!(e))
+    }
+
+    /// Creates the local (write-protected) copy of a leader's table if it does not exist
+    fn create_replica(local_ns: &Namespace, cfg: &ObjectConfig) -> std::io::Result<()> {
+        if Path::new(&local_ns.get_config_file_path()).exists() {
+            return Ok(());
+        }
+        let mut options = cfg.get_options().into_iter()
+            .filter(|o| *o != TableOptions::Replicated)
+            .collect::<Vec<_>>();
+        options.push(TableOptions::Replica);
+        let _ = fs::remove_file(Self::get_checkpoint_file_path(local_ns));
+        FileRowCollection::create_table_with_options(local_ns, &cfg.get_columns(), &options)?;
+        Ok(())
+    }
+
+    fn get_local_namespace(&self, ns: &Namespace) -> std::io::Result<Namespace> {
+        match self.subscriptions.get(&ns.id()) {
+            Some(local_ns) => Ok(local_ns.to_owned()),
+            None => throw(Exact(format!("Not subscribed to {}", ns)))
+        }
+    }
+}
+
+/// Starts a follower, which replicates the given namespaces from the leader
+/// found at host:port; reconnecting whenever the connection is lost.
+pub fn start_follower(host: String, port: u16, namespaces: Vec<Namespace>) -> JoinHandle<()> {
+    async fn follow(host: &str, port: u16, namespaces: &Vec<Namespace>) -> std::io::Result<()> {
+        let token = match get_replication_token() {
+            Some(token) => token,
+            None => return throw(Exact(format!("Replication requires a shared secret: set {TOKEN_ENV_VAR}")))
+        };
+        let mut client = ReplicationClient::connect(host, port, token.as_str()).await?;
+        for ns in namespaces {
+            client.subscribe(ns, ns).await?;
+        }
+        loop {
+            client.receive().await?;
+        }
+    }
+
+    thread::spawn(move || {
+        let runtime = Runtime::new().expect("Failed to create a Runtime instance");
+        loop {
+            if let Err(err) = runtime.block_on(follow(host.as_str(), port, &namespaces)) {
+                error!("replication from {host}:{port} interrupted: {err}");
+            }
+            thread::sleep(RECONNECT_DELAY);
+        }
+    })
+}
+
+////////////////////////////////////////////////////////////////////
+// ReplicationServer class (leader)
+////////////////////////////////////////////////////////////////////
+
+/// Oxide Replication Server; streams the events of replicated tables to a follower
+pub struct ReplicationServer {
+    subscriptions: Vec<(Namespace, ReplicationLog, u64)>,
+}
+
+impl ReplicationServer {
+    pub fn new() -> Self {
+        Self { subscriptions: Vec::new() }
+    }
+
+    /// Indicates whether the request presents the shared secret (as a bearer token);
+    /// all requests are refused when no secret has been configured.
+    pub fn is_authorized(req: &HttpRequest) -> bool {
+        let presented = req.headers().get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        match (get_replication_token(), presented) {
+            (Some(expected), Some(presented)) if expected.len() == presented.len() =>
+                // compare in constant time
+                expected.bytes().zip(presented.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0,
+            _ => false
+        }
+    }
+
+    /// opens the replication log of a table created with the `replicated` option;
+    /// encrypted tables are never streamed, as the events would leave the leader in the clear.
+    fn open_log(ns: &Namespace) -> std::io::Result<(ObjectConfig, ReplicationLog)> {
+        let cfg = ObjectConfig::load(ns)?;
+        let options = cfg.get_options();
+        if !options.contains(&TableOptions::Replicated) {
+            return throw(Exact(format!("{} is not replicated", ns)));
+        } else if options.contains(&TableOptions::Encrypted) {
+            return throw(Exact(format!("{} is encrypted, and can't be replicated", ns)));
+        }
+        Ok((cfg, ReplicationLog::open(ns)?))
+    }
+
+    /// transmits any new events to the follower
+    fn publish(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        for (ns, log, since) in self.subscriptions.iter_mut() {
+            match log.read_since(*since, BATCH_SIZE) {
+                Ok((events, _)) if events.is_empty() => {}
+                Ok((events, next)) => {
+                    *since = next;
+                    Self::transmit(ctx, ReplicationMessage::Events { ns: ns.to_owned(), events, next })
+                }
+                Err(err) => error!("replication of {ns} failed: {err}")
+            }
+        }
+    }
+
+    fn subscribe(&mut self, ctx: &mut ws::WebsocketContext<Self>, ns: Namespace, since: u64) {
+        match Self::open_log(&ns).and_then(|(cfg, log)| Ok((serde_json::to_string(&cfg)?, log))) {
+            Ok((cfg, log)) => {
+                info!("replicating {ns} from sequence {since}");
+                self.subscriptions.push((ns.to_owned(), log, since));
+                Self::transmit(ctx, ReplicationMessage::Config { ns, cfg })
+            }
+            Err(err) =>
+                Self::transmit(ctx, ReplicationMessage::Failure { ns, message: err.to_string() })
+        }
+    }
+
+    fn transmit(ctx: &mut ws::WebsocketContext<Self>, message: ReplicationMessage) {
+        match message.encode() {
+            Ok(bytes) => ctx.binary(bytes),
+            Err(err) => error!("{}", err)
+        }
+    }
+}
+
+impl Actor for ReplicationServer {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        ctx.run_interval(POLL_INTERVAL, |server, ctx| server.publish(ctx));
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for ReplicationServer {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Err(err) => error!("{}", err),
+            Ok(ws::Message::Ping(msg)) => ctx.pong(&msg),
+            Ok(ws::Message::Pong(msg)) => ctx.ping(&msg),
+            Ok(ws::Message::Binary(bytes)) =>
+                match ReplicationMessage::decode(&bytes) {
+                    Ok(ReplicationMessage::Subscribe { ns, since }) => self.subscribe(ctx, ns, since),
+                    Ok(other) => error!("Unexpected replication message: {:?}", other),
+                    Err(err) => error!("{}", err)
+                }
+            Ok(ws::Message::Close(reason)) => {
+                info!("replication channel closed [{:?}]", reason);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::Errors::WriteProtected;
+    use crate::interpreter::Interpreter;
+    use crate::numbers::Numbers::{Ack, RowsAffected};
+    use crate::testdata::{make_quote, run_isolated_test, start_test_server};
+    use crate::typed_values::TypedValue::Number;
+
+    #[test]
+    fn test_log_append_then_read() {
+        let ns = Namespace::new("replication", "log", "stocks");
+        let log = ReplicationLog::create(&ns).unwrap();
+        let event0 = ReplicationEvent::RowChanged {
+            id: 0,
+            row: make_quote(0, "ABC", "AMEX", 11.77),
+            metadata: RowMetadata::new(true),
+        };
+        let event1 = ReplicationEvent::Resized { size: 0 };
+        assert_eq!(log.append(&event0).unwrap(), 0);
+        let seq1 = log.append(&event1).unwrap();
+        assert!(seq1 > 0);
+
+        // read all events, then resume from the second one
+        let (events, next) = log.read_since(0, BATCH_SIZE).unwrap();
+        assert_eq!(events, vec![event0, event1.clone()]);
+        assert_eq!(log.append(&event1).unwrap(), next);
+        assert_eq!(log.read_since(seq1, BATCH_SIZE).unwrap().0, vec![event1.clone(), event1.clone()]);
+        assert_eq!(log.read_since(seq1, 1).unwrap(), (vec![event1], next));
+    }
+
+    #[actix::test]
+    async fn test_leader_follower() {
+        let port = 8030;
+        start_test_server(port);
+        let leader_ns = Namespace::new("replication", "leader", "stocks");
+        let follower_ns = Namespace::new("replication", "follower", "stocks");
+        let _ = fs::remove_dir_all(follower_ns.get_root_path());
+
+        // create and populate the leader's table
+        let mut interpreter = Interpreter::new();
+        assert_eq!(Number(Ack), interpreter.evaluate(r#"
+            create table ns("replication.leader.stocks") (
+                symbol: String(8), exchange: String(8), last_sale: f64
+            ) with replicated
+        "#).unwrap());
+        assert_eq!(Number(RowsAffected(3)), interpreter.evaluate(r#"
+            [{ symbol: "ABC", exchange: "AMEX", last_sale: 11.77 },
+             { symbol: "UNO", exchange: "OTC", last_sale: 0.2456 },
+             { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 }] ~> ns("replication.leader.stocks")
+        "#).unwrap());
+
+        // subscribe to the leader and wait for the rows to arrive
+        let token = get_replication_token().unwrap();
+        let mut client = ReplicationClient::connect("0.0.0.0", port, token.as_str()).await.unwrap();
+        client.subscribe(&leader_ns, &follower_ns).await.unwrap();
+        let mut applied = 0;
+        while applied < 3 {
+            applied += client.receive().await.unwrap();
+        }
+        let mut replica = FileRowCollection::open(&follower_ns).unwrap();
+        assert_eq!(replica.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(1, "UNO", "OTC", 0.2456),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+        ]);
+        assert!(replica.read_row_metadata(1).unwrap().is_replicated);
+
+        // the replica must be read-only
+        assert_eq!(replica.append_row(make_quote(0, "XYZ", "NYSE", 1.0)), ErrorValue(WriteProtected));
+
+        // deletes on the leader must be replicated
+        assert_eq!(Number(RowsAffected(1)), interpreter.evaluate(r#"
+            delete from ns("replication.leader.stocks") where symbol is "UNO"
+        "#).unwrap());
+        while applied < 4 {
+            applied += client.receive().await.unwrap();
+        }
+        assert_eq!(replica.read_active_rows().unwrap(), vec![
+            make_quote(0, "ABC", "AMEX", 11.77),
+            make_quote(2, "BIZ", "NYSE", 23.66),
+        ]);
+    }
+
+    #[actix::test]
+    async fn test_unauthorized_followers_are_refused() {
+        let port = 8033;
+        start_test_server(port);
+        assert!(ReplicationClient::connect("0.0.0.0", port, "not-the-secret").await.is_err());
+        assert!(ReplicationClient::connect("0.0.0.0", port, "").await.is_err());
+    }
+
+    #[test]
+    fn test_replication_without_a_token() {
+        assert!(run_isolated_test("replication::tests::replication_without_a_token"));
+    }
+
+    /// (run in a child process by [test_replication_without_a_token])
+    #[actix::test]
+    #[ignore]
+    async fn replication_without_a_token() {
+        env::remove_var(TOKEN_ENV_VAR);
+        assert_eq!(get_replication_token(), None);
+        let port = 8035;
+        start_test_server(port);
+        assert!(ReplicationClient::connect("0.0.0.0", port, "oxide-test-token").await.is_err());
+    }
+
+    #[actix::test]
+    async fn test_encrypted_tables_are_not_replicated() {
+        let port = 8034;
+        start_test_server(port);
+        let leader_ns = Namespace::new("replication", "encrypted", "stocks");
+        let follower_ns = Namespace::new("replication", "encrypted_follower", "stocks");
+        let mut interpreter = Interpreter::new();
+        assert_eq!(Number(Ack), interpreter.evaluate(r#"
+            create table ns("replication.encrypted.stocks") (
+                symbol: String(8), exchange: String(8), last_sale: f64
+            ) with replicated with encrypted
+        "#).unwrap());
+
+        // the subscription is refused, so no rows leave the leader
+        let token = get_replication_token().unwrap();
+        let mut client = ReplicationClient::connect("0.0.0.0", port, token.as_str()).await.unwrap();
+        client.subscribe(&leader_ns, &follower_ns).await.unwrap();
+        let err = client.receive().await.unwrap_err();
+        assert!(err.to_string().contains("is encrypted"));
+        assert!(!Path::new(&follower_ns.get_config_file_path()).exists());
+    }
+}
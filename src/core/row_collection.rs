@@ -37,9 +37,9 @@ pub trait RowCollection: Debug {
     /// Appends the given row to the end of the table
     fn append_row(&mut self, row: Row) -> TypedValue {
         match self.len() {
-            Ok(id) => {
-                let _ = self.overwrite_row(id, row.with_row_id(id));
-                Number(RowId(id as u64))
+            Ok(id) => match self.overwrite_row(id, row.with_row_id(id)) {
+                ErrorValue(err) => ErrorValue(err),
+                _ => Number(RowId(id as u64))
             }
             Err(err) => ErrorValue(Errors::Exact(err.to_string()))
         }
@@ -137,6 +137,11 @@ pub trait RowCollection: Debug {
         Ok(Box::new(ModelRowCollection::with_rows(columns, Vec::new())))
     }
 
+    /// Removes a related structure (e.g. an index) that is no longer in use
+    fn delete_related_structure(&self, _extension: &str) -> std::io::Result<()> {
+        Ok(())
+    }
+
     /// deletes an existing row by ID from the table
     fn delete_row(&mut self, id: usize) -> TypedValue {
         self.overwrite_row_metadata(id, RowMetadata::new(false))
@@ -187,14 +192,15 @@ pub trait RowCollection: Debug {
         columns.push(Column::new("_id", NumberType(U64Kind), Undefined, record_size));
         columns.push(Column::new("_active", BooleanType, Undefined, 8 + record_size));
 
+        // read the rows with their metadata
+        let rows = match self.read_range_with_metadata(range.to_owned()) {
+            Ok(rows) => rows,
+            Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
+        };
+
         // gather the row data
         let mut row_data = Vec::new();
-        for row_id in range {
-            // read the row with its metadata
-            let (row, meta) = match self.read_row(row_id) {
-                Ok(result) => result,
-                Err(err) => return ErrorValue(Errors::Exact(err.to_string()))
-            };
+        for (row_id, (row, meta)) in range.zip(rows) {
             // augment the values with the extras
             let mut values = row.get_values();
             values.push(Number(U64Value(row_id as u64)));
@@ -579,8 +585,7 @@ pub trait RowCollection: Debug {
     /// reads a span/range of rows
     fn read_range(&self, index: std::ops::Range<usize>) -> std::io::Result<Vec<Row>> {
         let mut rows = Vec::with_capacity(index.len());
-        for row_id in index {
-            let (row, metadata) = self.read_row(row_id)?;
+        for (row, metadata) in self.read_range_with_metadata(index)? {
             if metadata.is_allocated {
                 rows.push(row);
             }
@@ -588,6 +593,11 @@ pub trait RowCollection: Debug {
         Ok(rows)
     }
 
+    /// reads the active and inactive rows within the given range along with their metadata
+    fn read_range_with_metadata(&self, index: std::ops::Range<usize>) -> std::io::Result<Vec<(Row, RowMetadata)>> {
+        index.map(|row_id| self.read_row(row_id)).collect()
+    }
+
     /// reads a row by ID
     fn read_row(&self, id: usize) -> std::io::Result<(Row, RowMetadata)>;
 
@@ -24,7 +24,7 @@ impl RowMetadata {
         }
     }
 
-    pub fn from_bytes(buf: &Vec<u8>, offset: usize) -> Self {
+    pub fn from_bytes(buf: &[u8], offset: usize) -> Self {
         if offset >= buf.len() {
             return Self::new(false);
         }
@@ -71,6 +71,24 @@ impl RowMetadata {
             is_replicated: self.is_replicated,
         }
     }
+
+    pub fn with_encrypted(&self, is_encrypted: bool) -> Self {
+        Self {
+            is_allocated: self.is_allocated,
+            is_blob: self.is_blob,
+            is_encrypted,
+            is_replicated: self.is_replicated,
+        }
+    }
+
+    pub fn with_replicated(&self, is_replicated: bool) -> Self {
+        Self {
+            is_allocated: self.is_allocated,
+            is_blob: self.is_blob,
+            is_encrypted: self.is_encrypted,
+            is_replicated,
+        }
+    }
 }
 
 // Unit tests
@@ -0,0 +1,316 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// SystemCatalog class
+////////////////////////////////////////////////////////////////////
+
+use crate::data_types::DataType::{BooleanType, NumberType, StringType};
+use crate::errors::throw;
+use crate::errors::Errors::Exact;
+use crate::machine::Machine;
+use crate::model_row_collection::ModelRowCollection;
+use crate::namespaces::Namespace;
+use crate::number_kind::NumberKind::U16Kind;
+use crate::numbers::Numbers::U16Value;
+use crate::object_config::ObjectConfig;
+use crate::parameter::Parameter;
+use crate::row_collection::RowCollection;
+use crate::structures::Row;
+use crate::typed_values::TypedValue;
+use crate::typed_values::TypedValue::{Boolean, ErrorValue, Number, StringValue};
+use std::fs;
+use std::path::Path;
+
+/// the database and schema of the system catalog
+const CATALOG_DATABASE: &str = "oxide";
+const CATALOG_SCHEMA: &str = "system";
+
+/// the names of the system catalog tables
+const CATALOG_TABLES: [&str; 5] = ["columns", "databases", "indices", "schemas", "tables"];
+
+/// Represents the system catalog; a set of virtual (read-only) tables describing the
+/// databases, schemas, tables, columns and indices found under `$OXIDE_HOME/ns`.
+/// ex: select name, column_count from ns("oxide.system.tables") where database is "securities"
+/// NOTE: each catalog table is built (from the namespace directories and their configurations)
+/// when it is opened; so it is a snapshot, and writes to it are discarded.
+pub struct SystemCatalog;
+
+impl SystemCatalog {
+    ////////////////////////////////////////////////////////////////
+    // static methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Returns the (sorted) namespaces of all tables (i.e. those having a configuration)
+    pub fn find_tables() -> std::io::Result<Vec<Namespace>> {
+        let root = format!("{}/ns", Machine::oxide_home());
+        let mut namespaces = Vec::new();
+        for database in Self::list_directories(&root)? {
+            for schema in Self::list_directories(&format!("{root}/{database}"))? {
+                for name in Self::list_directories(&format!("{root}/{database}/{schema}"))? {
+                    let ns = Namespace::new(database.as_str(), schema.as_str(), name.as_str());
+                    if Path::new(&ns.get_config_file_path()).exists() {
+                        namespaces.push(ns)
+                    }
+                }
+            }
+        }
+        Ok(namespaces)
+    }
+
+    /// Returns true, if the given namespace refers to the system catalog
+    pub fn is_catalog(ns: &Namespace) -> bool {
+        ns.database == CATALOG_DATABASE && ns.schema == CATALOG_SCHEMA
+    }
+
+    /// Builds the catalog table of the given namespace (e.g. `oxide.system.tables`)
+    pub fn open(ns: &Namespace) -> std::io::Result<ModelRowCollection> {
+        let tables = Self::find_tables()?;
+        let (params, rows) = match ns.name.as_str() {
+            "columns" => (Self::get_columns_parameters(), Self::get_column_rows(&tables)),
+            "databases" => (Self::get_databases_parameters(), Self::get_database_rows(&tables)),
+            "indices" => (Self::get_indices_parameters(), Self::get_index_rows(&tables)),
+            "schemas" => (Self::get_schemas_parameters(), Self::get_schema_rows(&tables)),
+            "tables" => (Self::get_tables_parameters(), Self::get_table_rows(&tables)),
+            name => return throw(Exact(format!(
+                "System catalog table '{name}' not found (expected one of: {})", CATALOG_TABLES.join(", "))))
+        };
+        let mut mrc = ModelRowCollection::from_parameters(&params);
+        for values in rows {
+            if let ErrorValue(err) = mrc.append_row(Row::new(0, values)) {
+                return throw(err);
+            }
+        }
+        Ok(mrc)
+    }
+
+    ////////////////////////////////////////////////////////////////
+    // catalog rows
+    ////////////////////////////////////////////////////////////////
+
+    fn get_column_rows(tables: &Vec<Namespace>) -> Vec<Vec<TypedValue>> {
+        let mut rows = Vec::new();
+        for (ns, cfg) in Self::load_configs(tables) {
+            for (position, param) in cfg.get_columns().iter().enumerate() {
+                let mut values = Self::get_table_values(&ns);
+                values.extend(vec![
+                    StringValue(param.get_name().to_string()),
+                    Number(U16Value(position as u16)),
+                    StringValue(param.get_data_type().to_code()),
+                    StringValue(param.get_default_value().unwrap_value()),
+                ]);
+                rows.push(values)
+            }
+        }
+        rows
+    }
+
+    fn get_database_rows(tables: &Vec<Namespace>) -> Vec<Vec<TypedValue>> {
+        let mut databases = tables.iter().map(|ns| ns.database.to_owned()).collect::<Vec<_>>();
+        databases.dedup();
+        databases.into_iter().map(|name| vec![StringValue(name)]).collect()
+    }
+
+    fn get_index_rows(tables: &Vec<Namespace>) -> Vec<Vec<TypedValue>> {
+        let mut rows = Vec::new();
+        for (ns, cfg) in Self::load_configs(tables) {
+            for index in cfg.get_indices() {
+                let mut values = Self::get_table_values(&ns);
+                values.extend(vec![
+                    StringValue(index.get_indexed_column_names().join(", ")),
+                    Boolean(index.is_unique()),
+                ]);
+                rows.push(values)
+            }
+        }
+        rows
+    }
+
+    fn get_schema_rows(tables: &Vec<Namespace>) -> Vec<Vec<TypedValue>> {
+        let mut schemas = tables.iter()
+            .map(|ns| (ns.database.to_owned(), ns.schema.to_owned()))
+            .collect::<Vec<_>>();
+        schemas.dedup();
+        schemas.into_iter()
+            .map(|(database, name)| vec![StringValue(database), StringValue(name)])
+            .collect()
+    }
+
+    fn get_table_rows(tables: &Vec<Namespace>) -> Vec<Vec<TypedValue>> {
+        Self::load_configs(tables).into_iter().map(|(ns, cfg)| {
+            let mut values = Self::get_table_values(&ns);
+            values.extend(vec![
+                Number(U16Value(cfg.get_columns().len() as u16)),
+                Number(U16Value(cfg.get_indices().len() as u16)),
+                StringValue(cfg.get_partitions().map(|p| p.join(", ")).unwrap_or_default()),
+                StringValue(cfg.get_options().iter().map(|o| o.to_code()).collect::<Vec<_>>().join(", ")),
+            ]);
+            values
+        }).collect()
+    }
+
+    /// Returns the values identifying a table (i.e. its database, schema and name)
+    fn get_table_values(ns: &Namespace) -> Vec<TypedValue> {
+        vec![
+            StringValue(ns.database.to_owned()),
+            StringValue(ns.schema.to_owned()),
+            StringValue(ns.name.to_owned()),
+        ]
+    }
+
+    ////////////////////////////////////////////////////////////////
+    // catalog parameters
+    ////////////////////////////////////////////////////////////////
+
+    fn get_columns_parameters() -> Vec<Parameter> {
+        let mut params = Self::get_table_parameters();
+        params.extend(vec![
+            Parameter::new("column_name", StringType(128)),
+            Parameter::new("position", NumberType(U16Kind)),
+            Parameter::new("type", StringType(128)),
+            Parameter::new("default_value", StringType(128)),
+        ]);
+        params
+    }
+
+    fn get_databases_parameters() -> Vec<Parameter> {
+        vec![Parameter::new("name", StringType(128))]
+    }
+
+    fn get_indices_parameters() -> Vec<Parameter> {
+        let mut params = Self::get_table_parameters();
+        params.extend(vec![
+            Parameter::new("columns", StringType(256)),
+            Parameter::new("is_unique", BooleanType),
+        ]);
+        params
+    }
+
+    fn get_schemas_parameters() -> Vec<Parameter> {
+        vec![
+            Parameter::new("database", StringType(128)),
+            Parameter::new("name", StringType(128)),
+        ]
+    }
+
+    fn get_tables_parameters() -> Vec<Parameter> {
+        let mut params = Self::get_table_parameters();
+        params.extend(vec![
+            Parameter::new("column_count", NumberType(U16Kind)),
+            Parameter::new("index_count", NumberType(U16Kind)),
+            Parameter::new("partitions", StringType(256)),
+            Parameter::new("options", StringType(256)),
+        ]);
+        params
+    }
+
+    /// Returns the parameters identifying a table (i.e. its database, schema and name)
+    fn get_table_parameters() -> Vec<Parameter> {
+        vec![
+            Parameter::new("database", StringType(128)),
+            Parameter::new("schema", StringType(128)),
+            Parameter::new("name", StringType(128)),
+        ]
+    }
+
+    ////////////////////////////////////////////////////////////////
+    // utilities
+    ////////////////////////////////////////////////////////////////
+
+    /// Returns the (sorted) names of the subdirectories of the given directory
+    fn list_directories(path: &str) -> std::io::Result<Vec<String>> {
+        if !Path::new(path).is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut names = fs::read_dir(path)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().to_str().map(|s| s.to_string()))
+            .collect::<Vec<_>>();
+        names.sort();
+        Ok(names)
+    }
+
+    /// Loads the configurations of the given tables; skipping those that can't be read
+    /// (e.g. a table being dropped concurrently)
+    fn load_configs(tables: &Vec<Namespace>) -> Vec<(Namespace, ObjectConfig)> {
+        tables.iter()
+            .filter_map(|ns| ObjectConfig::load(ns).ok().map(|cfg| (ns.to_owned(), cfg)))
+            .collect()
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+
+    fn query(interpreter: &mut Interpreter, code: &str) -> Vec<Vec<TypedValue>> {
+        interpreter.evaluate(code).unwrap().to_table().unwrap()
+            .get_rows().iter().map(|row| row.get_values()).collect()
+    }
+
+    fn setup(interpreter: &mut Interpreter) {
+        interpreter.evaluate(r#"
+            drop table ns("catalog.securities.stocks")
+            drop table ns("catalog.securities.quotes")
+            create table ns("catalog.securities.stocks") (
+                symbol: String(8),
+                exchange: String(8),
+                last_sale: f64 = 0.0
+            )
+            create index ns("catalog.securities.stocks") on [symbol]
+            create table ns("catalog.securities.quotes") (symbol: String(8), bid: f64, ask: f64)
+        "#).unwrap();
+    }
+
+    #[test]
+    fn test_is_catalog() {
+        assert!(SystemCatalog::is_catalog(&Namespace::parse("oxide.system.tables").unwrap()));
+        assert!(!SystemCatalog::is_catalog(&Namespace::parse("oxide.public.tables").unwrap()));
+    }
+
+    #[test]
+    fn test_query_tables() {
+        let mut interpreter = Interpreter::new();
+        setup(&mut interpreter);
+        assert_eq!(query(&mut interpreter, r#"
+            select name, column_count, index_count from ns("oxide.system.tables")
+            where database is "catalog"
+        "#), vec![
+            vec![StringValue("quotes".into()), Number(U16Value(3)), Number(U16Value(0))],
+            vec![StringValue("stocks".into()), Number(U16Value(3)), Number(U16Value(1))],
+        ]);
+        assert!(query(&mut interpreter, r#"from ns("oxide.system.databases")"#)
+            .contains(&vec![StringValue("catalog".into())]));
+        assert!(query(&mut interpreter, r#"from ns("oxide.system.schemas")"#)
+            .contains(&vec![StringValue("catalog".into()), StringValue("securities".into())]));
+    }
+
+    #[test]
+    fn test_query_columns_and_indices() {
+        let mut interpreter = Interpreter::new();
+        setup(&mut interpreter);
+        assert_eq!(query(&mut interpreter, r#"
+            select column_name, position, type, default_value from ns("oxide.system.columns")
+            where (database is "catalog") && (name is "stocks")
+        "#), vec![
+            vec![StringValue("symbol".into()), Number(U16Value(0)), StringValue("String(8)".into()), StringValue("null".into())],
+            vec![StringValue("exchange".into()), Number(U16Value(1)), StringValue("String(8)".into()), StringValue("null".into())],
+            vec![StringValue("last_sale".into()), Number(U16Value(2)), StringValue("f64".into()), StringValue("0.0".into())],
+        ]);
+        assert_eq!(query(&mut interpreter, r#"
+            select name, columns, is_unique from ns("oxide.system.indices")
+            where database is "catalog"
+        "#), vec![
+            vec![StringValue("stocks".into()), StringValue("symbol".into()), Boolean(false)],
+        ]);
+    }
+
+    #[test]
+    fn test_unknown_catalog_table() {
+        let mut interpreter = Interpreter::new();
+        let result = interpreter.evaluate(r#"from ns("oxide.system.views")"#);
+        assert_eq!(result.map_err(|e| e.to_string()), Err(
+            "System catalog table 'views' not found (expected one of: columns, databases, indices, schemas, tables)".into()));
+    }
+}
@@ -0,0 +1,168 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// TableHeader class
+////////////////////////////////////////////////////////////////////
+
+use crate::checksums::CHECKSUM_LEN;
+use crate::columns::Column;
+use crate::errors::Errors::Exact;
+use crate::errors::throw;
+use crate::structures::Row;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::os::unix::fs::FileExt;
+
+/// the length of the header block at the start of each table file
+pub const TABLE_HEADER_LEN: usize = 32;
+
+/// the current table file format version; increment it whenever the record
+/// encoding (e.g. `ByteCodeCompiler::encode_row` or the `NumberKind` discriminants) changes.
+pub const TABLE_FORMAT_VERSION: u16 = CHECKSUMMED_BLOBS_VERSION;
+
+/// the format version in which a checksum was appended to each record
+pub const CHECKSUMMED_RECORDS_VERSION: u16 = 2;
+
+/// the format version in which a checksum was appended to each BLOB; and the records
+/// never written within the table were marked as empty (rather than left zeroed).
+pub const CHECKSUMMED_BLOBS_VERSION: u16 = 3;
+
+/// the magic number identifying a table file. NOTE: the low nibble of a row's
+/// metadata byte is always zero, so a headerless (legacy) file never starts with 'O' (0x4F).
+const TABLE_MAGIC: [u8; 4] = *b"OXTB";
+
+/// Represents the header block of a table file:
+/// magic (4) | format version (2) | reserved (2) | record size (8) | column fingerprint (8) | table ID (8)
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub struct TableHeader {
+    pub(crate) version: u16,
+    pub(crate) record_size: u64,
+    pub(crate) fingerprint: u64,
+    pub(crate) table_id: u64,
+}
+
+impl TableHeader {
+    /// Creates a header (of the current format version) describing the given columns;
+    /// each table receives a random ID, which binds its encrypted contents to the table.
+    pub fn new(columns: &Vec<Column>) -> Self {
+        Self {
+            version: TABLE_FORMAT_VERSION,
+            record_size: Self::compute_record_size(columns, TABLE_FORMAT_VERSION),
+            fingerprint: Self::compute_fingerprint(columns),
+            table_id: rand::random(),
+        }
+    }
+
+    /// Computes the (unencrypted) size of the records of the given columns within the given format version
+    pub fn compute_record_size(columns: &Vec<Column>, version: u16) -> u64 {
+        let record_size = Row::compute_record_size(columns);
+        (if version < CHECKSUMMED_RECORDS_VERSION { record_size } else { record_size + CHECKSUM_LEN }) as u64
+    }
+
+    /// Computes a stable fingerprint of the names, types and sizes of the given columns
+    pub fn compute_fingerprint(columns: &Vec<Column>) -> u64 {
+        let schema = columns.iter()
+            .map(|c| format!("{}: {} [{}]", c.get_name(), c.get_data_type().to_code(), c.get_fixed_size()))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let md5::Digest(bytes) = md5::compute(schema.as_bytes());
+        u64::from_be_bytes(bytes[0..8].try_into().unwrap())
+    }
+
+    /// Decodes a header from the given bytes; or [None] if they do not start with the magic number
+    pub fn decode(buffer: &[u8]) -> Option<Self> {
+        if buffer.len() < TABLE_HEADER_LEN || buffer[0..4] != TABLE_MAGIC {
+            return None;
+        }
+        Some(Self {
+            version: u16::from_be_bytes(buffer[4..6].try_into().ok()?),
+            record_size: u64::from_be_bytes(buffer[8..16].try_into().ok()?),
+            fingerprint: u64::from_be_bytes(buffer[16..24].try_into().ok()?),
+            table_id: u64::from_be_bytes(buffer[24..32].try_into().ok()?),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::with_capacity(TABLE_HEADER_LEN);
+        buffer.extend(TABLE_MAGIC);
+        buffer.extend(self.version.to_be_bytes());
+        buffer.extend([0u8; 2]);
+        buffer.extend(self.record_size.to_be_bytes());
+        buffer.extend(self.fingerprint.to_be_bytes());
+        buffer.extend(self.table_id.to_be_bytes());
+        buffer
+    }
+
+    /// Reads the header of the given table file; or [None] if the file has no header
+    pub fn read(file: &File) -> std::io::Result<Option<Self>> {
+        if file.metadata()?.len() < TABLE_HEADER_LEN as u64 {
+            return Ok(None);
+        }
+        let mut buffer = vec![0u8; TABLE_HEADER_LEN];
+        file.read_exact_at(&mut buffer, 0)?;
+        Ok(Self::decode(&buffer))
+    }
+
+    /// Ensures the table file described by this header can be read using the given columns
+    pub fn validate(&self, columns: &Vec<Column>, path: &str) -> std::io::Result<()> {
+        let expected = Self {
+            record_size: Self::compute_record_size(columns, self.version),
+            ..Self::new(columns)
+        };
+        if self.version > TABLE_FORMAT_VERSION {
+            throw(Exact(format!("{path} uses table format version {}, but only versions up to {} are supported",
+                                self.version, TABLE_FORMAT_VERSION)))
+        } else if self.record_size != expected.record_size {
+            throw(Exact(format!("{path} contains records of {} bytes, but its columns require {} bytes",
+                                self.record_size, expected.record_size)))
+        } else if self.fingerprint != expected.fingerprint {
+            throw(Exact(format!("{path} was written with a different set of columns than its configuration")))
+        } else { Ok(()) }
+    }
+
+    /// Writes this header at the start of the given table file
+    pub fn write(&self, file: &File) -> std::io::Result<()> {
+        file.write_all_at(&self.encode(), 0)
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::DataType::StringType;
+    use crate::parameter::Parameter;
+    use crate::testdata::make_quote_parameters;
+
+    #[test]
+    fn test_encode_and_decode() {
+        let header = TableHeader::new(&Column::from_parameters(&make_quote_parameters()));
+        let bytes = header.encode();
+        assert_eq!(bytes.len(), TABLE_HEADER_LEN);
+        assert_eq!(TableHeader::decode(&bytes), Some(header));
+
+        // legacy (headerless) files start with a row's metadata byte
+        let mut legacy = bytes.clone();
+        legacy[0] = 0b1000_0000;
+        assert_eq!(TableHeader::decode(&legacy), None);
+    }
+
+    #[test]
+    fn test_validate() {
+        let columns = Column::from_parameters(&make_quote_parameters());
+        let header = TableHeader::new(&columns);
+        assert!(header.validate(&columns, "stocks.table").is_ok());
+
+        // a different column layout is rejected
+        let mut params = make_quote_parameters();
+        params[1] = Parameter::new("market", StringType(8));
+        assert!(header.validate(&Column::from_parameters(&params), "stocks.table").is_err());
+
+        // an older format version is accepted (so that it can be upgraded)
+        let legacy = TableHeader { version: 1, record_size: Row::compute_record_size(&columns) as u64, ..header };
+        assert!(legacy.validate(&columns, "stocks.table").is_ok());
+
+        // a newer format version is rejected
+        let header = TableHeader { version: TABLE_FORMAT_VERSION + 1, ..header };
+        assert!(header.validate(&columns, "stocks.table").is_err());
+    }
+}
@@ -0,0 +1,248 @@
+#![warn(dead_code)]
+////////////////////////////////////////////////////////////////////
+// TempTables class
+////////////////////////////////////////////////////////////////////
+
+use crate::hybrid_row_collection::HybridRowCollection;
+use crate::machine::Machine;
+use crate::namespace_leases::NamespaceLease;
+use crate::namespaces::{Namespace, TEMP_DATABASE};
+use crate::numbers::Numbers::I64Value;
+use crate::typed_values::TypedValue::Number;
+use chrono::Local;
+use log::{error, info};
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::OnceLock;
+
+/// the variable holding the ID of a session
+const SESSION_ID: &str = "__SESSION_ID__";
+
+/// distinguishes the sessions started (by this process) within the same millisecond
+static SESSION_SEQUENCE: AtomicI64 = AtomicI64::new(0);
+
+/// the start time of this process (see [TempTables::get_start_time])
+static START_TIME: OnceLock<u64> = OnceLock::new();
+
+/// Represents the temporary tables of sessions; each session writes its temporary tables
+/// (`create temp table stocks (..)`) to its own scratch schema (`p<pid>_t<start time>_s<session>`)
+/// of the reserved [TEMP_DATABASE], which is dropped when the session ends, or (if the process
+/// died) when Oxide restarts. The start time of the process tells a process apart from a later
+/// one that reuses its ID.
+pub struct TempTables;
+
+impl TempTables {
+    ////////////////////////////////////////////////////////////////
+    // static methods
+    ////////////////////////////////////////////////////////////////
+
+    /// Drops the temporary tables of a session (e.g. once the session has ended);
+    /// returning the number of tables dropped.
+    pub fn drop_session(ms: &Machine) -> std::io::Result<usize> {
+        match Self::get_session_id(ms) {
+            Some(session_id) => Self::drop_schema(&Self::get_own_schema(session_id)),
+            None => Ok(0)
+        }
+    }
+
+    /// Returns the namespace of a temporary table of the current session;
+    /// assigning the session an ID, if it doesn't already have one.
+    pub fn get_namespace(ms: &Machine, name: &str) -> (Machine, Namespace) {
+        let (ms, session_id) = match Self::get_session_id(ms) {
+            Some(session_id) => (ms.to_owned(), session_id),
+            None => {
+                let sequence = SESSION_SEQUENCE.fetch_add(1, Ordering::Relaxed) % 1000;
+                let session_id = Local::now().timestamp_millis() * 1000 + sequence;
+                (ms.with_variable(SESSION_ID, Number(I64Value(session_id))), session_id)
+            }
+        };
+        let schema = Self::get_own_schema(session_id);
+        (ms, Namespace::new(TEMP_DATABASE, schema.as_str(), name))
+    }
+
+    /// Drops the temporary tables of the sessions of processes that are no longer
+    /// running (e.g. sessions abandoned by a crash); returning the number of tables dropped.
+    pub fn purge_abandoned() -> std::io::Result<usize> {
+        let root = Namespace::get_database_path(TEMP_DATABASE);
+        if !Path::new(&root).is_dir() {
+            return Ok(0);
+        }
+        let mut dropped = 0;
+        for entry in fs::read_dir(&root)? {
+            let schema = entry?.file_name().to_string_lossy().to_string();
+            match Self::parse_schema(&schema) {
+                Some((pid, start_time, _)) if !Self::is_running(pid, start_time) =>
+                    dropped += Self::drop_schema(&schema)?,
+                _ => {}
+            }
+        }
+        if dropped > 0 {
+            info!("Dropped {dropped} abandoned temporary table(s)");
+        }
+        Ok(dropped)
+    }
+
+    /// Drops the temporary tables of a session; logging (rather than failing on) any errors
+    pub fn release(ms: &Machine) {
+        if let Err(err) = Self::drop_session(ms) {
+            error!("Failed to drop the temporary tables of the session: {err}");
+        }
+    }
+
+    /// Drops a scratch schema, and the temporary tables within it
+    fn drop_schema(schema: &str) -> std::io::Result<usize> {
+        let path = format!("{}/{schema}", Namespace::get_database_path(TEMP_DATABASE));
+        if !Path::new(&path).is_dir() {
+            return Ok(0);
+        }
+        let mut dropped = 0;
+        for entry in fs::read_dir(&path)? {
+            let name = entry?.file_name().to_string_lossy().to_string();
+            let ns = Namespace::new(TEMP_DATABASE, schema, name.as_str());
+            HybridRowCollection::discard(&ns);
+            NamespaceLease::release(&ns);
+            dropped += 1;
+        }
+        fs::remove_dir_all(&path)?;
+        Ok(dropped)
+    }
+
+    /// Returns the scratch schema of a session of this process
+    fn get_own_schema(session_id: i64) -> String {
+        Self::get_schema(process::id(), Self::get_own_start_time(), session_id)
+    }
+
+    /// Returns the start time of this process (or 0, if unknown)
+    fn get_own_start_time() -> u64 {
+        *START_TIME.get_or_init(|| Self::get_start_time(process::id()).unwrap_or(0))
+    }
+
+    /// Returns the scratch schema of a session (e.g. "p4123_t982733_s1735707600000000")
+    fn get_schema(pid: u32, start_time: u64, session_id: i64) -> String {
+        format!("p{pid}_t{start_time}_s{session_id}")
+    }
+
+    /// Returns the ID of the current session (if assigned)
+    fn get_session_id(ms: &Machine) -> Option<i64> {
+        ms.get(SESSION_ID).map(|value| value.to_i64())
+    }
+
+    /// Returns the start time of a process (in clock ticks since boot; see proc(5)),
+    /// if known; e.g. "/proc/4123/stat" => "4123 (oxide) S 1 ... 982733 ..."
+    fn get_start_time(pid: u32) -> Option<u64> {
+        let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+        // the fields following the command (which may contain spaces or parentheses)
+        let (_, fields) = stat.rsplit_once(')')?;
+        fields.split_whitespace().nth(19)?.parse().ok()
+    }
+
+    /// Returns true, if the given process (started at the given time) is still running;
+    /// a process whose start time is unknown is presumed to be the one that's running.
+    fn is_running(pid: u32, start_time: u64) -> bool {
+        if pid == process::id() {
+            return start_time == Self::get_own_start_time();
+        }
+        let is_alive = unsafe { libc::kill(pid as libc::pid_t, 0) } == 0
+            || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM);
+        is_alive && match (start_time, Self::get_start_time(pid)) {
+            (0, _) | (_, None) => true,
+            (start_time, Some(actual)) => start_time == actual
+        }
+    }
+
+    /// Parses a scratch schema into its process ID, process start time and session ID
+    fn parse_schema(schema: &str) -> Option<(u32, u64, i64)> {
+        let (pid, rest) = schema.strip_prefix('p')?.split_once("_t")?;
+        let (start_time, session_id) = rest.split_once("_s")?;
+        Some((pid.parse().ok()?, start_time.parse().ok()?, session_id.parse().ok()?))
+    }
+}
+
+/// Unit tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::Interpreter;
+    use crate::typed_values::TypedValue::{ErrorValue, NamespaceValue};
+
+    #[test]
+    fn test_get_namespace() {
+        let ms = Machine::new_platform();
+        let (ms, ns) = TempTables::get_namespace(&ms, "stocks");
+        let session_id = TempTables::get_session_id(&ms).unwrap();
+        let start_time = TempTables::get_own_start_time();
+        assert_ne!(start_time, 0);
+        assert_eq!(ns, Namespace::new(TEMP_DATABASE, format!("p{}_t{start_time}_s{session_id}", process::id()).as_str(), "stocks"));
+
+        // the session keeps its ID
+        let (_, ns) = TempTables::get_namespace(&ms, "quotes");
+        assert_eq!(ns.schema, format!("p{}_t{start_time}_s{session_id}", process::id()));
+        assert_eq!(TempTables::parse_schema(&ns.schema), Some((process::id(), start_time, session_id)));
+    }
+
+    #[test]
+    fn test_create_temp_table() {
+        let mut interpreter = Interpreter::new();
+        interpreter.evaluate(r#"
+            create temp table scratch (symbol: String(8), exchange: String(8), last_sale: f64)
+            append scratch from [
+                { symbol: "BIZ", exchange: "NYSE", last_sale: 23.66 },
+                { symbol: "GOTO", exchange: "OTC", last_sale: 0.1428 }
+            ]
+        "#).unwrap();
+        let df = interpreter.evaluate(r#"
+            select symbol from scratch where last_sale > 1.0
+        "#).unwrap().to_table().unwrap();
+        assert_eq!(df.get_rows().len(), 1);
+
+        // the table is written to the session's scratch schema
+        let ns = match interpreter.evaluate("scratch").unwrap() {
+            NamespaceValue(ns) => ns,
+            other => panic!("Expected a namespace, got {other}")
+        };
+        assert_eq!(ns.database, TEMP_DATABASE);
+        assert!(Path::new(&ns.get_table_file_path()).exists());
+
+        // the table is outside of the public namespaces
+        assert!(!ns.get_root_path().starts_with(&format!("{}/ns/", Machine::oxide_home())));
+        let result = interpreter.evaluate(format!(r#"ns("{}")"#, ns.get_full_name()).as_str()).unwrap();
+        assert!(matches!(result, ErrorValue(..)));
+
+        // other sessions have scratch schemas of their own
+        let mut other = Interpreter::new();
+        other.evaluate(r#"create temp table scratch (symbol: String(8))"#).unwrap();
+        assert_ne!(other.evaluate("scratch").unwrap(), NamespaceValue(ns.to_owned()));
+
+        // the tables are dropped when the session ends
+        assert_eq!(TempTables::drop_session(interpreter.get_machine()).unwrap(), 1);
+        assert!(!Path::new(&ns.get_root_path()).exists());
+        TempTables::release(other.get_machine());
+    }
+
+    #[test]
+    fn test_purge_abandoned() {
+        // a (presumably) defunct process, and an earlier process having the ID of this one
+        let schemas = [
+            TempTables::get_schema(u32::MAX >> 1, 1, 1),
+            TempTables::get_schema(process::id(), TempTables::get_own_start_time() + 1, 1),
+        ];
+        for schema in schemas.iter() {
+            let ns = Namespace::new(TEMP_DATABASE, schema.as_str(), "stocks");
+            fs::create_dir_all(ns.get_root_path()).unwrap();
+            fs::write(ns.get_table_file_path(), []).unwrap();
+        }
+        // the sessions of this process are kept
+        let (ms, ns) = TempTables::get_namespace(&Machine::new_platform(), "stocks");
+        fs::create_dir_all(ns.get_root_path()).unwrap();
+
+        assert!(TempTables::purge_abandoned().unwrap() >= 2);
+        for schema in schemas.iter() {
+            let ns = Namespace::new(TEMP_DATABASE, schema.as_str(), "stocks");
+            assert!(!Path::new(&ns.get_root_path()).exists());
+        }
+        assert!(Path::new(&ns.get_root_path()).exists());
+        TempTables::release(&ms);
+    }
+}
@@ -30,11 +30,35 @@ use rand::prelude::ThreadRng;
 use rand::{thread_rng, Rng, RngCore};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::env;
 use std::fs::File;
+use std::process::Command;
 use std::thread;
 use std::time::Duration;
 use actix::fut::result;
 
+/// Configures the environment of the unit tests: tables are kept in a temporary
+/// oxide home, and encrypted tables and replication use throwaway secrets (unless configured otherwise).
+/// It runs before `main`; i.e. before the tests (which read the environment) are started.
+#[cfg(test)]
+#[used]
+#[link_section = ".init_array"]
+static INIT_TEST_ENV: extern "C" fn() = init_test_env;
+
+#[cfg(test)]
+extern "C" fn init_test_env() {
+    let defaults = [
+        ("OXIDE_HOME", env::temp_dir().join("oxide_db").to_string_lossy().to_string()),
+        (crate::encryption::KEY_ENV_VAR, hex::encode(rand::random::<[u8; crate::encryption::KEY_LEN]>())),
+        (crate::replication::TOKEN_ENV_VAR, hex::encode(rand::random::<[u8; 16]>())),
+    ];
+    for (name, value) in defaults {
+        if env::var_os(name).is_none() {
+            env::set_var(name, value);
+        }
+    }
+}
+
 pub fn make_dataframe(database: &str, schema: &str, name: &str, columns: Vec<Parameter>) -> std::io::Result<Dataframe> {
     make_dataframe_ns(Namespace::new(database, schema, name), columns)
 }
@@ -99,6 +123,18 @@ pub fn make_table_file(
     (ns.get_table_file_path(), file, table_columns, record_size)
 }
 
+/// Runs an (ignored) test by itself in a child process; e.g. a test modifying
+/// the environment, which would race with the other tests of this process.
+pub fn run_isolated_test(name: &str) -> bool {
+    match env::current_exe().and_then(|exe| Command::new(exe)
+        .args([name, "--exact", "--ignored", "--test-threads=1"])
+        .output()) {
+        Ok(output) => output.status.success()
+            && String::from_utf8_lossy(&output.stdout).contains("1 passed"),
+        Err(..) => false
+    }
+}
+
 pub fn start_test_server(port: u16) {
     start_http_server(port);
     thread::sleep(Duration::from_millis(100));
@@ -25,14 +25,13 @@ use crate::data_types::DataType::*;
 
 use crate::data_types::*;
 use crate::dataframe::Dataframe;
-use crate::dataframe::Dataframe::{Disk, Model};
+use crate::dataframe::Dataframe::Model;
 
 use crate::errors::Errors::{CannotSubtract, Exact, Multiple, Syntax, TypeMismatch};
 use crate::errors::TypeMismatchErrors::{ArgumentsMismatched, CannotBeNegated, StructsOneOrMoreExpected, UnsupportedType};
 use crate::errors::{throw, Errors};
 use crate::expression::Expression;
 use crate::field::FieldMetadata;
-use crate::file_row_collection::FileRowCollection;
 use crate::inferences::Inferences;
 use crate::machine::Machine;
 use crate::model_row_collection::ModelRowCollection;
@@ -41,7 +40,6 @@ use crate::number_kind::NumberKind;
 use crate::number_kind::NumberKind::*;
 use crate::numbers::Numbers;
 use crate::numbers::Numbers::*;
-use crate::object_config::ObjectConfig;
 use crate::parameter::Parameter;
 use crate::platform::PlatformOps;
 use crate::row_collection::RowCollection;
@@ -430,7 +428,7 @@ impl TypedValue {
         match self {
             ArrayValue(items) => self.convert_array_to_table(&items.get_values()),
             ErrorValue(err) => throw(err.to_owned()),
-            NamespaceValue(ns) => Ok(Disk(FileRowCollection::open(ns)?)),
+            NamespaceValue(ns) => Ok(Dataframe::open(ns)?),
             Structured(s) => Ok(Model(s.to_table())),
             TableValue(df) => Ok(df.to_owned()),
             z => throw(TypeMismatch(UnsupportedType(TableType(vec![], 0), z.get_type())))
@@ -476,7 +474,7 @@ impl TypedValue {
     pub fn to_sequence(&self) -> std::io::Result<Sequences> {
         match self {
             ArrayValue(array) => Ok(Sequences::TheArray(array.clone())),
-            NamespaceValue(ns) => Ok(Sequences::TheDataframe(Disk(FileRowCollection::open(ns)?))),
+            NamespaceValue(ns) => Ok(Sequences::TheDataframe(Dataframe::open(ns)?)),
             TableValue(df) => Ok(Sequences::TheDataframe(df.clone())),
             TupleValue(t) => Ok(Sequences::TheTuple(t.to_vec())),
             z => throw(TypeMismatch(UnsupportedType(TableType(vec![], 0), z.get_type())))
@@ -500,13 +498,9 @@ impl TypedValue {
                 }
             ErrorValue(err) => ErrorValue(err.to_owned()),
             NamespaceValue(ns) =>
-                match ObjectConfig::load(ns) {
-                    Ok(ObjectConfig::TableConfig { .. }) =>
-                        match FileRowCollection::open(ns) {
-                            Ok(frc) => TableValue(Disk(frc)),
-                            Err(err) => ErrorValue(Exact(err.to_string())),
-                        },
-                    Err(err) => ErrorValue(Exact(err.to_string()))
+                match Dataframe::open(ns) {
+                    Ok(df) => TableValue(df),
+                    Err(err) => ErrorValue(Exact(err.to_string())),
                 }
             Structured(s) => TableValue(s.to_dataframe()),
             TableValue(df) => TableValue(df.to_owned()),
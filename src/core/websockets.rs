@@ -8,9 +8,10 @@ use crate::errors::throw;
 use crate::errors::Errors::{Exact, TypeMismatch};
 use crate::expression::Expression;
 use crate::interpreter::Interpreter;
+use crate::temp_tables::TempTables;
 use crate::typed_values::TypedValue;
 use crate::typed_values::TypedValue::{ErrorValue, StringValue, Undefined};
-use actix::{Actor, StreamHandler};
+use actix::{Actor, ActorContext, StreamHandler};
 use actix_web_actors::ws;
 use actix_web_actors::ws::WebsocketContext;
 use futures_util::stream::{SplitSink, SplitStream};
@@ -37,6 +38,12 @@ impl OxideWebSocketClient {
         Ok(Self { read, write })
     }
 
+    /// Closes the connection; which ends the session (e.g. dropping its temporary tables)
+    pub async fn close(&mut self) -> std::io::Result<()> {
+        self.write.send(Message::Close(None)).await
+            .map_err(|e| cnv_error!(e))
+    }
+
     pub async fn evaluate(&mut self, script: &str) -> std::io::Result<TypedValue> {
         self.send_text_message(script).await?;
         self.read_next().await
@@ -89,6 +96,11 @@ impl OxideWebSocketServer {
 
 impl Actor for OxideWebSocketServer {
     type Context = ws::WebsocketContext<Self>;
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        // drop the session's temporary tables
+        TempTables::release(self.interpreter.get_machine());
+    }
 }
 
 impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for OxideWebSocketServer {
@@ -119,7 +131,9 @@ impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for OxideWebSocketSer
                 transmit(ctx, &value)
             }
             Ok(ws::Message::Close(reason)) => {
-                println!("Close! [{:?}]", reason);
+                // end the session (see [OxideWebSocketServer::stopped])
+                ctx.close(reason);
+                ctx.stop();
             }
             _ => {}
         }
@@ -135,7 +149,9 @@ mod tests {
     use crate::repl;
     use crate::testdata::{make_quote, make_quote_columns, start_test_server};
     use crate::typed_values::TypedValue;
-    use crate::typed_values::TypedValue::{Number, TableValue};
+    use crate::typed_values::TypedValue::{NamespaceValue, Number, TableValue};
+    use std::path::Path;
+    use std::time::Duration;
     use crate::websockets::OxideWebSocketClient;
 
     #[actix::test]
@@ -150,6 +166,28 @@ mod tests {
         assert_eq!(value, Number(I64Value(3)))
     }
 
+    #[actix::test]
+    async fn test_websockets_close_drops_temp_tables() {
+        let port = 8012;
+        start_test_server(port);
+
+        let mut wsc = OxideWebSocketClient::connect("0.0.0.0", port, "/ws").await.unwrap();
+        let ns = match wsc.evaluate(r#"
+            create temp table scratch (symbol: String(8))
+            scratch
+        "#).await.unwrap() {
+            NamespaceValue(ns) => ns,
+            other => panic!("Expected a namespace, got {other}")
+        };
+        assert!(Path::new(&ns.get_table_file_path()).exists());
+        wsc.close().await.unwrap();
+        for _ in 0..50 {
+            if !Path::new(&ns.get_root_path()).exists() { break; }
+            actix::clock::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(!Path::new(&ns.get_root_path()).exists());
+    }
+
     #[actix::test]
     async fn test_websockets_script() {
         let port = 8011;